@@ -0,0 +1,40 @@
+// 把组件预览需要的第三方库从 node_modules 复制到 src-tauri/preview-runtime，随应用一起打包，
+// 预览时不需要访问网络或者启动额外的服务
+import { copyFileSync, existsSync, mkdirSync } from "node:fs";
+import { dirname, join } from "node:path";
+import { fileURLToPath } from "node:url";
+
+const root = join(dirname(fileURLToPath(import.meta.url)), "..");
+const target = join(root, "src-tauri", "preview-runtime");
+
+const files = {
+    "tailwind.js": "@tailwindcss/browser/dist/index.global.js",
+    "react.js": "react/umd/react.production.min.js",
+    "react-dom.js": "react-dom/umd/react-dom.production.min.js",
+    "prop-types.js": "prop-types/prop-types.min.js",
+    "recharts.js": "recharts/umd/Recharts.js",
+    "lucide-react.js": "lucide-react/dist/umd/lucide-react.min.js",
+    "babel.js": "@babel/standalone/babel.min.js",
+    "vue.js": "vue/dist/vue.global.prod.js",
+    "vue3-sfc-loader.js": "vue3-sfc-loader/dist/vue3-sfc-loader.js",
+    "mermaid.js": "mermaid/dist/mermaid.min.js",
+    "marked.js": "marked/marked.min.js",
+    "viz.js": "@viz-js/viz/lib/viz-standalone.js",
+    "vega.js": "vega/build/vega.min.js",
+    "vega-lite.js": "vega-lite/build/vega-lite.min.js",
+};
+
+mkdirSync(target, { recursive: true });
+let missing = 0;
+for (const [name, source] of Object.entries(files)) {
+    const sourcePath = join(root, "node_modules", source);
+    if (!existsSync(sourcePath)) {
+        console.error(`预览运行时文件不存在: ${source}，请先执行 npm install`);
+        missing++;
+        continue;
+    }
+    copyFileSync(sourcePath, join(target, name));
+}
+if (missing > 0) {
+    process.exit(1);
+}
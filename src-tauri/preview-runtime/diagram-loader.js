@@ -0,0 +1,112 @@
+// 渲染 mermaid、markdown、dot、vega-lite 和 svg，渲染完成后把 SVG 和 2 倍大小的 PNG 回传，用于导出
+(function () {
+    const root = document.getElementById("root");
+    const kind = root.dataset.kind;
+
+    const showError = (error) => {
+        const pre = document.createElement("pre");
+        pre.style.cssText = "color: #dc2626; white-space: pre-wrap; padding: 16px; margin: 0;";
+        pre.textContent = String((error && error.stack) || error);
+        document.body.replaceChildren(pre);
+    };
+
+    const post = (file, body) => fetch(file, { method: "POST", body });
+
+    // 渲染结果统一转换成带尺寸的 SVG 文本
+    const serialize = (svg) => {
+        const box = svg.getBoundingClientRect();
+        svg.setAttribute("xmlns", "http://www.w3.org/2000/svg");
+        svg.setAttribute("width", Math.ceil(box.width));
+        svg.setAttribute("height", Math.ceil(box.height));
+        return { text: new XMLSerializer().serializeToString(svg), width: box.width, height: box.height };
+    };
+
+    // markdown 没有 SVG，用 foreignObject 包装渲染后的 HTML
+    const wrapHtml = (element) => {
+        const box = element.getBoundingClientRect();
+        const width = Math.ceil(box.width);
+        const height = Math.ceil(box.height);
+        const style = [...document.querySelectorAll("style")].map((s) => s.textContent).join("\n");
+        const html = new XMLSerializer().serializeToString(element);
+        const text =
+            `<svg xmlns="http://www.w3.org/2000/svg" width="${width}" height="${height}">` +
+            `<style>${style}</style><foreignObject width="100%" height="100%">${html}</foreignObject></svg>`;
+        return { text, width, height };
+    };
+
+    const rasterize = ({ text, width, height }) =>
+        new Promise((resolve, reject) => {
+            const image = new Image();
+            image.onload = () => {
+                const canvas = document.createElement("canvas");
+                canvas.width = Math.ceil(width * 2);
+                canvas.height = Math.ceil(height * 2);
+                const context = canvas.getContext("2d");
+                context.fillStyle = "#ffffff";
+                context.fillRect(0, 0, canvas.width, canvas.height);
+                context.scale(2, 2);
+                context.drawImage(image, 0, 0, width, height);
+                try {
+                    canvas.toBlob((blob) => (blob ? resolve(blob) : reject(new Error("生成 PNG 失败"))), "image/png");
+                } catch (e) {
+                    reject(e);
+                }
+            };
+            image.onerror = () => reject(new Error("生成 PNG 失败"));
+            image.src = "data:image/svg+xml;charset=utf-8," + encodeURIComponent(text);
+        });
+
+    const report = async (rendered) => {
+        await post("rendered.svg", rendered.text);
+        // 部分平台不允许把 foreignObject 绘制到画布上，这时只能导出 SVG
+        try {
+            await post("rendered.png", await rasterize(rendered));
+        } catch (e) {
+            console.warn("生成 PNG 失败", e);
+        }
+    };
+
+    const renderers = {
+        mermaid: async (code) => {
+            // 不使用 HTML 标签，保证导出的 SVG 可以转换成图片
+            mermaid.initialize({ startOnLoad: false, htmlLabels: false, flowchart: { htmlLabels: false } });
+            const { svg } = await mermaid.render("aipp-mermaid", code);
+            root.innerHTML = svg;
+            return serialize(root.querySelector("svg"));
+        },
+        markdown: async (code) => {
+            const style = document.createElement("style");
+            style.textContent =
+                "#root { padding: 24px; max-width: 860px; font-family: sans-serif; line-height: 1.6; background: #fff; }" +
+                " #root pre { background: #f5f5f5; padding: 12px; overflow: auto; }" +
+                " #root table { border-collapse: collapse; } #root td, #root th { border: 1px solid #ddd; padding: 4px 8px; }";
+            document.head.appendChild(style);
+            root.innerHTML = marked.parse(code);
+            return wrapHtml(root);
+        },
+        dot: async (code) => {
+            const viz = await Viz.instance();
+            root.replaceChildren(viz.renderSVGElement(code));
+            return serialize(root.querySelector("svg"));
+        },
+        "vega-lite": async (code) => {
+            const spec = vegaLite.compile(JSON.parse(code)).spec;
+            const view = new vega.View(vega.parse(spec), { renderer: "none" });
+            root.innerHTML = await view.toSVG();
+            view.finalize();
+            return serialize(root.querySelector("svg"));
+        },
+        svg: async (code) => {
+            root.innerHTML = code;
+            const svg = root.querySelector("svg");
+            if (!svg) throw new Error("没有找到 svg 元素");
+            return serialize(svg);
+        },
+    };
+
+    fetch(root.dataset.component)
+        .then((response) => response.text())
+        .then((code) => renderers[kind](code))
+        .then(report)
+        .catch(showError);
+})();
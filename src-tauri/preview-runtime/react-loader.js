@@ -0,0 +1,58 @@
+// 读取组件代码，用 Babel 转换成 CommonJS 后执行，import 的库映射到运行时提供的全局变量
+(function () {
+    const root = document.getElementById("root");
+
+    const showError = (error) => {
+        const pre = document.createElement("pre");
+        pre.style.cssText = "color: #dc2626; white-space: pre-wrap; padding: 16px; margin: 0;";
+        pre.textContent = String((error && error.stack) || error);
+        document.body.replaceChildren(pre);
+    };
+    window.addEventListener("error", (event) => showError(event.error || event.message));
+
+    const modules = {
+        react: window.React,
+        "react-dom": window.ReactDOM,
+        "react-dom/client": window.ReactDOM,
+        recharts: window.Recharts,
+        "lucide-react": window.LucideReact,
+    };
+    const requireModule = (name) => {
+        if (modules[name]) return modules[name];
+        // 样式文件由 tailwind 处理，直接忽略
+        if (/\.css$/.test(name)) return {};
+        throw new Error("预览不支持引入 " + name);
+    };
+
+    // 没有 export default 时，把最后声明的大写开头的组件作为默认导出
+    const withDefaultExport = (code) => {
+        if (/export\s+default\s/.test(code)) return code;
+        const names = [...code.matchAll(/(?:function|const|let|class)\s+([A-Z]\w*)/g)].map((m) => m[1]);
+        if (names.length === 0) return code;
+        const name = names[names.length - 1];
+        return code + "\nexport default " + name + ";\n";
+    };
+
+    fetch(root.dataset.component)
+        .then((response) => response.text())
+        .then((code) => {
+            const compiled = Babel.transform(withDefaultExport(code), {
+                filename: "component.tsx",
+                presets: [["typescript", { isTSX: true, allExtensions: true }], "react"],
+                plugins: ["transform-modules-commonjs"],
+            }).code;
+            const module = { exports: {} };
+            new Function("require", "module", "exports", "React", compiled)(
+                requireModule,
+                module,
+                module.exports,
+                window.React,
+            );
+            const Component = module.exports.default;
+            if (typeof Component !== "function" && typeof Component !== "object") {
+                throw new Error("没有找到可以渲染的组件");
+            }
+            ReactDOM.createRoot(root).render(React.createElement(Component));
+        })
+        .catch(showError);
+})();
@@ -0,0 +1,35 @@
+// 用 vue3-sfc-loader 在页面中编译单文件组件，样式插入到 head 中
+(function () {
+    const app = document.getElementById("app");
+
+    const showError = (error) => {
+        const pre = document.createElement("pre");
+        pre.style.cssText = "color: #dc2626; white-space: pre-wrap; padding: 16px; margin: 0;";
+        pre.textContent = String((error && error.stack) || error);
+        document.body.replaceChildren(pre);
+    };
+    window.addEventListener("error", (event) => showError(event.error || event.message));
+
+    const { loadModule } = window["vue3-sfc-loader"];
+    const options = {
+        moduleCache: { vue: Vue },
+        getFile: (url) =>
+            fetch(url).then((response) =>
+                response.ok ? response.text() : Promise.reject(new Error("无法加载 " + url)),
+            ),
+        addStyle: (text) => {
+            const style = document.createElement("style");
+            style.textContent = text;
+            document.head.appendChild(style);
+        },
+        log: (type, ...args) => console[type](...args),
+    };
+
+    loadModule(app.dataset.component, options)
+        .then((component) => {
+            const instance = Vue.createApp(component);
+            instance.config.errorHandler = showError;
+            instance.mount(app);
+        })
+        .catch(showError);
+})();
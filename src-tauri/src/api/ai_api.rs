@@ -1,14 +1,17 @@
-use crate::api::assistant_api::get_assistant;
+use crate::api::artifact_workspace_api::update_conversation_artifact;
+use crate::api::assistant_api::{get_assistant, record_prompt_version, resolve_prompt_params};
+use crate::api::assistant_pipeline::{parse_compare_models, AssistantType};
 use crate::api::llm::get_provider;
-use crate::db::assistant_db::AssistantModelConfig;
+use crate::api::workflow_api::run_workflow_for_message;
+use crate::db::assistant_db::{AssistantDatabase, AssistantModelConfig};
 use crate::db::conversation_db::{AttachmentType, Repository};
 use crate::db::conversation_db::{Conversation, ConversationDatabase, Message, MessageAttachment};
-use crate::db::llm_db::LLMDatabase;
+use crate::db::llm_db::{LLMDatabase, ModelDetail};
 use crate::db::system_db::FeatureConfig;
 use crate::errors::AppError;
 use crate::state::message_token::MessageTokenManager;
-use crate::template_engine::TemplateEngine;
-use crate::{AppState, FeatureConfigState};
+use crate::template_engine::{BangResult, TemplateEngine};
+use crate::{AppState, FeatureConfigState, TemplateEngineState};
 use anyhow::Context;
 use anyhow::Error;
 use serde::{Deserialize, Serialize};
@@ -16,7 +19,9 @@ use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 use tauri::Emitter;
 use tauri::Listener;
+use tauri::Manager;
 use tauri::State;
+use tauri_plugin_clipboard_manager::ClipboardExt;
 use tokio::sync::mpsc;
 use tokio::time::timeout;
 use tokio_util::sync::CancellationToken;
@@ -34,6 +39,8 @@ pub struct AiRequest {
     max_tokens: Option<u32>,
     stream: Option<bool>,
     attachment_list: Option<Vec<i64>>,
+    // 助手提示词参数的值，key 为参数名
+    prompt_params: Option<HashMap<String, String>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -41,15 +48,18 @@ pub struct AiResponse {
     conversation_id: i64,
     add_message_id: i64,
     request_prompt_result_with_context: String,
+    // 多模型对比助手其他模型回答的消息 id，作为 add_message_id 的其他版本
+    compare_message_ids: Vec<i64>,
 }
 #[tauri::command]
 pub async fn ask_ai(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     feature_config_state: State<'_, FeatureConfigState>,
+    template_engine_state: State<'_, TemplateEngineState>,
     message_token_manager: State<'_, MessageTokenManager>,
     window: tauri::Window,
-    request: AiRequest,
+    mut request: AiRequest,
     override_model_config: Option<Vec<(String, serde_json::Value)>>,
     override_prompt: Option<String>,
 ) -> Result<AiResponse, AppError> {
@@ -57,7 +67,7 @@ pub async fn ask_ai(
         "ask_ai: {:?}, override_model_config: {:?}, override_prompt: {:?}",
         request, override_model_config, override_prompt
     );
-    let template_engine = TemplateEngine::new();
+    let template_engine = template_engine_state.engine.lock().await.clone();
     let mut template_context = HashMap::new();
     let (tx, mut rx) = mpsc::channel(100);
 
@@ -66,20 +76,57 @@ pub async fn ask_ai(
 
     let app_handle_clone = app_handle.clone();
     let assistant_detail = get_assistant(app_handle_clone, request.assistant_id).unwrap();
+    let assistant_type = AssistantType::from_i64(assistant_detail.assistant.assistant_type);
+    if assistant_type == AssistantType::Transform {
+        // 选中文本处理助手的请求都记录在该助手的同一个对话中，每次都是一次性的请求，不带历史消息
+        request.conversation_id = get_conversation_db(&app_handle)?
+            .conversation_repo()
+            .unwrap()
+            .find_latest_by_assistant_id(assistant_detail.assistant.id)?
+            .map(|c| c.id.to_string())
+            .unwrap_or_default();
+    }
+    // 提示词参数只替换到助手的提示词中，用户自己输入的内容不做替换
+    let prompt_params = resolve_prompt_params(
+        &assistant_detail.prompt_params,
+        &request.prompt_params.clone().unwrap_or_default(),
+    )
+    .map_err(AppError::PromptParamError)?;
+    let mut prompt_context = template_context.clone();
+    prompt_context.extend(prompt_params);
     let assistant_prompt_origin = &assistant_detail.prompts[0].prompt;
-    let assistant_prompt_result = template_engine
-        .parse(&assistant_prompt_origin, &template_context)
+    let assistant_prompt_render = template_engine
+        .render(&assistant_prompt_origin, &prompt_context)
         .await;
+    let assistant_prompt_result = assistant_prompt_render.text;
     println!("assistant_prompt_result: {}", assistant_prompt_result);
 
     if assistant_detail.model.is_empty() {
         return Err(AppError::NoModelFound);
     }
 
-    let need_generate_title = request.conversation_id.is_empty();
-    let request_prompt_result = template_engine
-        .parse(&request.prompt, &template_context)
+    let need_new_conversation = request.conversation_id.is_empty();
+    // 选中文本处理助手的对话使用助手名称作为标题
+    let need_generate_title = need_new_conversation && assistant_type != AssistantType::Transform;
+    // 提示词是普通文本，其中类似 bang 的内容有语法错误时按原文发送
+    let request_prompt_render = template_engine
+        .render(&request.prompt, &template_context)
         .await;
+    let request_prompt_result = request_prompt_render.text;
+
+    // 图片、音频类bang的结果作为附件随用户消息一起发送
+    let bang_attachments = [
+        assistant_prompt_render.attachments,
+        request_prompt_render.attachments,
+    ]
+    .concat();
+    if !bang_attachments.is_empty() {
+        let bang_attachment_ids = save_bang_attachments(&app_handle, &bang_attachments)?;
+        request
+            .attachment_list
+            .get_or_insert_with(Vec::new)
+            .extend(bang_attachment_ids);
+    }
 
     let app_handle_clone = app_handle.clone();
     let (conversation_id, new_message_id, request_prompt_result_with_context, init_message_list) =
@@ -90,9 +137,23 @@ pub async fn ask_ai(
             assistant_prompt_result,
             request_prompt_result.clone(),
             override_prompt.clone(),
+            assistant_type != AssistantType::Transform,
         )
         .await?;
+    if assistant_type == AssistantType::Transform && need_new_conversation {
+        let conversation = Conversation {
+            id: conversation_id,
+            name: assistant_detail.assistant.name.clone(),
+            assistant_id: Some(assistant_detail.assistant.id),
+            created_time: chrono::Utc::now(),
+        };
+        get_conversation_db(&app_handle)?
+            .conversation_repo()
+            .unwrap()
+            .update_name(&conversation)?;
+    }
 
+    let mut compare_message_ids = Vec::new();
     if new_message_id.is_some() {
         let config_feature_map = feature_config_state.config_feature_map.lock().await.clone();
 
@@ -105,6 +166,37 @@ pub async fn ask_ai(
             .store_token(new_message_id.unwrap(), cancel_token.clone())
             .await;
 
+        if assistant_type == AssistantType::Compare {
+            let compare_targets = create_compare_messages(
+                &app_handle,
+                &assistant_detail,
+                conversation_id,
+                message_id,
+            )?;
+            compare_message_ids = compare_targets.iter().map(|(id, _)| *id).collect();
+            spawn_compare_answers(
+                &app_handle,
+                &window,
+                &assistant_detail,
+                compare_targets,
+                init_message_list.clone(),
+                cancel_token.clone(),
+            );
+        }
+
+        let answer_message_ids = [vec![message_id], compare_message_ids.clone()].concat();
+        record_message_prompt_version(
+            &app_handle,
+            assistant_detail.assistant.id,
+            &answer_message_ids,
+        );
+
+        // 工作流步骤属于助手的提示词，可以引用提示词参数
+        let workflow_context = prompt_context.clone();
+        let copy_to_clipboard = assistant_detail
+            .model_configs
+            .iter()
+            .any(|c| c.name == "copy_to_clipboard" && c.value.as_deref() == Some("true"));
         let tokens = message_token_manager.get_tokens();
         tokio::spawn(async move {
             let db = LLMDatabase::new(&app_handle_clone)
@@ -180,7 +272,24 @@ pub async fn ask_ai(
 
             println!("prompt: {}", request_prompt_result_clone);
 
-            if stream {
+            if assistant_type == AssistantType::Workflow {
+                // !question 和 !input 为用户的问题
+                let mut inputs = workflow_context;
+                inputs.insert("question".to_string(), request_prompt_result_clone.clone());
+                inputs.insert("input".to_string(), request_prompt_result_clone);
+                let result = run_workflow_for_message(
+                    &app_handle_clone,
+                    &template_engine,
+                    config_map.get("workflow"),
+                    inputs,
+                    message_id,
+                    &tx,
+                    cancel_token,
+                )
+                .await;
+                let content = result.unwrap_or_else(|e| format!("Workflow error: {}", e));
+                tx.send((message_id, content, true)).await.unwrap();
+            } else if stream {
                 let tx_clone = tx.clone();
                 if let Err(e) = provider
                     .chat_stream(
@@ -260,6 +369,20 @@ pub async fn ask_ai(
                                 .unwrap()
                                 .update(&message)
                                 .unwrap();
+                            if let Err(e) =
+                                update_conversation_artifact(&app_handle_clone, &message)
+                            {
+                                eprintln!("Failed to update artifact: {}", e);
+                            }
+
+                            // 选中文本处理助手的结果直接复制到剪贴板
+                            if assistant_type == AssistantType::Transform && copy_to_clipboard {
+                                if let Err(e) =
+                                    app_handle_clone.clipboard().write_text(content.clone())
+                                {
+                                    eprintln!("Failed to copy to clipboard: {}", e);
+                                }
+                            }
 
                             println!("Message finish: id={}", id);
                             window_clone
@@ -307,6 +430,7 @@ pub async fn ask_ai(
         conversation_id,
         add_message_id: new_message_id.unwrap(),
         request_prompt_result_with_context,
+        compare_message_ids,
     })
 }
 
@@ -358,6 +482,8 @@ fn init_conversation(
                 start_time: None,
                 finish_time: None,
                 token_count: 0,
+                prompt_version_id: None,
+                compare_to_id: None,
             })
             .map_err(AppError::from)?;
         for attachment in attachment_list {
@@ -374,6 +500,112 @@ fn init_conversation(
     Ok((conversation_clone, message_result_array))
 }
 
+// 不经过窗口直接执行一次助手，结果保存为新对话，用于定时任务等后台场景
+pub(crate) async fn run_assistant_in_new_conversation(
+    app_handle: &tauri::AppHandle,
+    template_engine: &TemplateEngine,
+    assistant_id: i64,
+    prompt: &str,
+    template_context: &HashMap<String, String>,
+    conversation_name: String,
+) -> Result<Conversation, AppError> {
+    let assistant_detail =
+        get_assistant(app_handle.clone(), assistant_id).map_err(AppError::UnknownError)?;
+    if assistant_detail.model.is_empty() {
+        return Err(AppError::NoModelFound);
+    }
+    let mut prompt_context = template_context.clone();
+    let prompt_params = resolve_prompt_params(&assistant_detail.prompt_params, &HashMap::new())
+        .map_err(AppError::PromptParamError)?;
+    prompt_context.extend(prompt_params);
+
+    let mut messages = vec![];
+    let mut bang_attachments = vec![];
+    if let Some(assistant_prompt) = assistant_detail.prompts.first() {
+        let render = template_engine
+            .render(&assistant_prompt.prompt, &prompt_context)
+            .await;
+        bang_attachments.extend(render.attachments);
+        messages.push(("system".to_string(), render.text, vec![]));
+    }
+    let prompt_render = template_engine.render(prompt, &template_context).await;
+    bang_attachments.extend(prompt_render.attachments);
+    let attachment_ids = save_bang_attachments(app_handle, &bang_attachments)?;
+    let attachments = get_conversation_db(app_handle)?
+        .attachment_repo()
+        .unwrap()
+        .list_by_id(&attachment_ids)?;
+    messages.push(("user".to_string(), prompt_render.text, attachments));
+
+    let model_detail = get_llm_db(app_handle)?
+        .get_llm_model_detail(
+            &assistant_detail.model[0].provider_id,
+            &assistant_detail.model[0].model_code,
+        )
+        .map_err(AppError::from)?;
+    let (conversation, _) = init_conversation(
+        app_handle,
+        assistant_id,
+        model_detail.model.id,
+        model_detail.model.code.clone(),
+        &messages,
+    )?;
+    let conversation = Conversation {
+        name: conversation_name,
+        ..conversation
+    };
+    get_conversation_db(app_handle)?
+        .conversation_repo()
+        .unwrap()
+        .update_name(&conversation)?;
+
+    let mut model_configs = assistant_detail.model_configs.clone();
+    model_configs.push(AssistantModelConfig {
+        id: 0,
+        assistant_id,
+        assistant_model_id: model_detail.model.id,
+        name: "model".to_string(),
+        value: Some(model_detail.model.code.clone()),
+        value_type: "string".to_string(),
+    });
+    let provider = get_provider(model_detail.provider, model_detail.configs);
+    let message = add_message(
+        app_handle,
+        None,
+        conversation.id,
+        "assistant".to_string(),
+        String::new(),
+        Some(model_detail.model.id),
+        Some(model_detail.model.code),
+        Some(chrono::Utc::now()),
+        None,
+        0,
+    )?;
+    // 和窗口中的回答一样通过消息 id 登记取消令牌，打开对话后可以停止生成
+    let message_token_manager = app_handle.state::<MessageTokenManager>();
+    let cancel_token = CancellationToken::new();
+    message_token_manager
+        .store_token(message.id, cancel_token.clone())
+        .await;
+    let result = provider
+        .chat(message.id, messages, model_configs, cancel_token)
+        .await;
+    message_token_manager.remove_token(message.id).await;
+
+    let message_repo = get_conversation_db(app_handle)?.message_repo().unwrap();
+    match result {
+        Ok(content) => {
+            message_repo.update(&Message { content, ..message })?;
+            message_repo.update_finish_time(message.id)?;
+            Ok(conversation)
+        }
+        Err(e) => {
+            message_repo.delete(message.id)?;
+            Err(AppError::ProviderError(e.to_string()))
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn regenerate_ai(
     app_handle: tauri::AppHandle,
@@ -394,10 +626,14 @@ pub async fn regenerate_ai(
         .unwrap()
         .read(conversation_id)?
         .ok_or(AppError::DatabaseError("未找到对话".to_string()))?;
-    let messages = db
+    // 对比的回答不参与对话历史
+    let messages: Vec<_> = db
         .message_repo()
         .unwrap()
-        .list_by_conversation_id(conversation_id)?;
+        .list_by_conversation_id(conversation_id)?
+        .into_iter()
+        .filter(|m| m.0.compare_to_id.is_none())
+        .collect();
 
     let parent_ids: HashSet<i64> = messages.iter().filter_map(|m| m.0.parent_id).collect();
     println!("parent_ids: {:?}", parent_ids);
@@ -470,6 +706,7 @@ pub async fn regenerate_ai(
         0,
     )?;
     let new_message_id = new_message.id;
+    record_message_prompt_version(&app_handle, assistant_id, &[new_message_id]);
 
     let cancel_token = CancellationToken::new();
     message_token_manager
@@ -599,6 +836,9 @@ pub async fn regenerate_ai(
                             .unwrap()
                             .update(&message)
                             .unwrap();
+                        if let Err(e) = update_conversation_artifact(&app_handle_clone, &message) {
+                            eprintln!("Failed to update artifact: {}", e);
+                        }
 
                         println!("Message finish: id={}", id);
                         window_clone
@@ -634,9 +874,126 @@ pub async fn regenerate_ai(
         conversation_id,
         add_message_id: new_message_id,
         request_prompt_result_with_context: String::new(),
+        compare_message_ids: Vec::new(),
     })
 }
 
+// 记录回答消息是由哪个提示词版本生成的，没有版本记录的旧助手会补记当前版本
+fn record_message_prompt_version(
+    app_handle: &tauri::AppHandle,
+    assistant_id: i64,
+    message_ids: &[i64],
+) {
+    let prompt_version_id = match AssistantDatabase::new(app_handle)
+        .map_err(|e| e.to_string())
+        .and_then(|db| record_prompt_version(&db, assistant_id))
+    {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("Failed to record prompt version: {}", e);
+            return;
+        }
+    };
+    if let Ok(conversation_db) = ConversationDatabase::new(app_handle) {
+        let repo = conversation_db.message_repo().unwrap();
+        for message_id in message_ids {
+            let _ = repo.update_prompt_version_id(*message_id, prompt_version_id);
+        }
+    }
+}
+
+// 为多模型对比助手的其他模型创建回答消息，作为主回答消息的其他版本
+// 其他模型的回答通过 compare_to_id 关联到主模型的回答，和重新生成的回答区分开
+fn create_compare_messages(
+    app_handle: &tauri::AppHandle,
+    assistant_detail: &AssistantDetail,
+    conversation_id: i64,
+    compare_to_id: i64,
+) -> Result<Vec<(i64, ModelDetail)>, AppError> {
+    let compare_models = assistant_detail
+        .model_configs
+        .iter()
+        .find(|c| c.name == "compare_models")
+        .and_then(|c| c.value.as_deref())
+        .map(parse_compare_models)
+        .unwrap_or_default();
+    let db = LLMDatabase::new(app_handle).map_err(AppError::from)?;
+    let conversation_db = get_conversation_db(app_handle)?;
+    let mut targets = Vec::new();
+    for (provider_id, model_code) in compare_models {
+        let model_detail = match db.get_llm_model_detail(&provider_id, &model_code) {
+            Ok(model_detail) => model_detail,
+            Err(e) => {
+                eprintln!("Compare model not found: {} {}", model_code, e);
+                continue;
+            }
+        };
+        let message = conversation_db.message_repo().unwrap().create(&Message {
+            id: 0,
+            parent_id: None,
+            conversation_id,
+            message_type: "assistant".to_string(),
+            content: String::new(),
+            llm_model_id: Some(model_detail.model.id),
+            llm_model_name: Some(model_detail.model.code.clone()),
+            created_time: chrono::Utc::now(),
+            start_time: Some(chrono::Utc::now()),
+            finish_time: None,
+            token_count: 0,
+            prompt_version_id: None,
+            compare_to_id: Some(compare_to_id),
+        })?;
+        targets.push((message.id, model_detail));
+    }
+    Ok(targets)
+}
+
+// 对比的模型不使用流式输出，回答完成后一次性推送
+fn spawn_compare_answers(
+    app_handle: &tauri::AppHandle,
+    window: &tauri::Window,
+    assistant_detail: &AssistantDetail,
+    targets: Vec<(i64, ModelDetail)>,
+    messages: Vec<(String, String, Vec<MessageAttachment>)>,
+    cancel_token: CancellationToken,
+) {
+    for (message_id, model_detail) in targets {
+        let provider = get_provider(model_detail.provider, model_detail.configs);
+        let mut model_config = assistant_detail.model_configs.clone();
+        model_config.push(AssistantModelConfig {
+            id: 0,
+            assistant_id: assistant_detail.assistant.id,
+            assistant_model_id: model_detail.model.id,
+            name: "model".to_string(),
+            value: Some(model_detail.model.code),
+            value_type: "string".to_string(),
+        });
+        let app_handle = app_handle.clone();
+        let window = window.clone();
+        let messages = messages.clone();
+        // 取消主回答时一起取消
+        let cancel_token = cancel_token.child_token();
+        tokio::spawn(async move {
+            let content = provider
+                .chat(message_id, messages, model_config, cancel_token)
+                .await
+                .unwrap_or_else(|e| format!("Chat error: {}", e));
+
+            if let Ok(conversation_db) = ConversationDatabase::new(&app_handle) {
+                let repo = conversation_db.message_repo().unwrap();
+                if let Ok(Some(mut message)) = repo.read(message_id) {
+                    message.content = content.clone();
+                    let _ = repo.update(&message);
+                }
+                let _ = repo.update_finish_time(message_id);
+            }
+            let event = format!("message_{}", message_id);
+            let _ = window.emit(event.as_str(), content);
+            let _ = window.emit(event.as_str(), "Tea::Event::MessageFinish");
+        });
+    }
+}
+
 fn add_message(
     app_handle: &tauri::AppHandle,
     parent_id: Option<i64>,
@@ -665,11 +1022,41 @@ fn add_message(
             finish_time,
             created_time: chrono::Utc::now(),
             token_count,
+            prompt_version_id: None,
+            compare_to_id: None,
         })
         .map_err(AppError::from)?;
     Ok(message.clone())
 }
 
+// 保存模板中图片、音频类bang产生的附件，返回附件id
+fn save_bang_attachments(
+    app_handle: &tauri::AppHandle,
+    attachments: &[BangResult],
+) -> Result<Vec<i64>, AppError> {
+    let db = get_conversation_db(app_handle)?;
+    let mut attachment_ids = vec![];
+    for attachment in attachments {
+        let (attachment_type, content) = match attachment {
+            BangResult::Image(content) => (AttachmentType::Image, content),
+            BangResult::Audio(content) => (AttachmentType::Audio, content),
+            BangResult::Text(_) | BangResult::Error(_) => continue,
+        };
+        let message_attachment = db.attachment_repo().unwrap().create(&MessageAttachment {
+            id: 0,
+            message_id: -1,
+            attachment_type,
+            attachment_url: None,
+            attachment_content: Some(content.clone()),
+            attachment_hash: None,
+            use_vector: false,
+            token_count: Some(0),
+        })?;
+        attachment_ids.push(message_attachment.id);
+    }
+    Ok(attachment_ids)
+}
+
 async fn initialize_conversation(
     app_handle: &tauri::AppHandle,
     request: &AiRequest,
@@ -677,6 +1064,7 @@ async fn initialize_conversation(
     assistant_prompt_result: String,
     request_prompt_result: String,
     override_prompt: Option<String>,
+    with_history: bool,
 ) -> Result<
     (
         i64,
@@ -754,10 +1142,14 @@ async fn initialize_conversation(
         } else {
             // 已存在对话逻辑
             let conversation_id = request.conversation_id.parse::<i64>()?;
-            let all_messages = db
+            // 对比的回答不参与对话历史
+            let all_messages: Vec<_> = db
                 .message_repo()
                 .unwrap()
-                .list_by_conversation_id(conversation_id)?;
+                .list_by_conversation_id(conversation_id)?
+                .into_iter()
+                .filter(|(message, _)| message.compare_to_id.is_none())
+                .collect();
 
             // 创建一个 HashMap 来存储每个消息的最新子消息
             let mut latest_children: HashMap<i64, (Message, Option<MessageAttachment>)> =
@@ -793,6 +1185,16 @@ async fn initialize_conversation(
                     )
                 })
                 .collect();
+            // 不带历史消息时只发送当前的助手提示词
+            let message_list = if with_history {
+                message_list
+            } else {
+                vec![(
+                    String::from("system"),
+                    override_prompt.unwrap_or(assistant_prompt_result),
+                    vec![],
+                )]
+            };
 
             // 获取到消息的附件列表
             let message_attachment_list = db
@@ -816,7 +1218,7 @@ async fn initialize_conversation(
             let request_prompt_result_with_context =
                 format!("{}\n{}", request_prompt_result, context);
             // 添加用户消息
-            let _ = add_message(
+            let user_message = add_message(
                 app_handle,
                 None,
                 conversation_id,
@@ -828,6 +1230,11 @@ async fn initialize_conversation(
                 None,
                 0,
             )?;
+            for attachment in message_attachment_list.iter() {
+                let mut updated_attachment = attachment.clone();
+                updated_attachment.message_id = user_message.id;
+                db.attachment_repo().unwrap().update(&updated_attachment)?;
+            }
             let mut updated_message_list = message_list;
             updated_message_list.push((
                 String::from("user"),
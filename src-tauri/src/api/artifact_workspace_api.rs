@@ -0,0 +1,187 @@
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::artifacts::workspace::{
+    build_zip, diff_files, extract_files, merge_files, write_files_to_folder, ArtifactFileDiff,
+};
+use crate::db::conversation_db::{
+    Artifact, ArtifactVersion, ConversationDatabase, Message, Repository,
+};
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ArtifactVersionSummary {
+    pub version: i64,
+    pub message_id: Option<i64>,
+    pub paths: Vec<String>,
+    pub created_time: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArtifactDetail {
+    pub artifact: Artifact,
+    pub versions: Vec<ArtifactVersionSummary>,
+    pub latest: Option<ArtifactVersion>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ArtifactUpdatedEvent {
+    pub conversation_id: i64,
+    pub artifact_id: i64,
+    pub version: i64,
+}
+
+// 回答完成后调用，回答中有带文件路径的代码块时生成对话产物的新版本，文件内容没有变化时不生成
+pub(crate) fn update_conversation_artifact(
+    app_handle: &tauri::AppHandle,
+    message: &Message,
+) -> Result<Option<ArtifactVersion>, String> {
+    let updates = extract_files(&message.content);
+    if updates.is_empty() {
+        return Ok(None);
+    }
+    let db = ConversationDatabase::new(app_handle).map_err(|e| e.to_string())?;
+    let repo = db.artifact_repo().map_err(|e| e.to_string())?;
+    let artifact = match repo
+        .read_by_conversation_id(message.conversation_id)
+        .map_err(|e| e.to_string())?
+    {
+        Some(artifact) => artifact,
+        None => {
+            let name = db
+                .conversation_repo()
+                .map_err(|e| e.to_string())?
+                .read(message.conversation_id)
+                .map_err(|e| e.to_string())?
+                .map(|c| c.name)
+                .unwrap_or_else(|| "artifact".to_string());
+            repo.create(message.conversation_id, &name)
+                .map_err(|e| e.to_string())?
+        }
+    };
+    let previous = repo
+        .read_version_by_number(artifact.id, None)
+        .map_err(|e| e.to_string())?
+        .map(|v| v.files)
+        .unwrap_or_default();
+    let files = merge_files(&previous, updates);
+    if files == previous {
+        return Ok(None);
+    }
+    let version = repo
+        .add_version(artifact.id, Some(message.id), &files)
+        .map_err(|e| e.to_string())?;
+    let _ = app_handle.emit(
+        "artifact_updated",
+        ArtifactUpdatedEvent {
+            conversation_id: message.conversation_id,
+            artifact_id: artifact.id,
+            version: version.version,
+        },
+    );
+    Ok(Some(version))
+}
+
+#[tauri::command]
+pub fn get_conversation_artifact(
+    app_handle: tauri::AppHandle,
+    conversation_id: i64,
+) -> Result<Option<ArtifactDetail>, String> {
+    let db = ConversationDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let repo = db.artifact_repo().map_err(|e| e.to_string())?;
+    let Some(artifact) = repo
+        .read_by_conversation_id(conversation_id)
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+    let versions = repo.list_versions(artifact.id).map_err(|e| e.to_string())?;
+    let summaries = versions
+        .iter()
+        .map(|v| ArtifactVersionSummary {
+            version: v.version,
+            message_id: v.message_id,
+            paths: v.files.iter().map(|f| f.path.clone()).collect(),
+            created_time: v.created_time,
+        })
+        .collect();
+    Ok(Some(ArtifactDetail {
+        artifact,
+        versions: summaries,
+        latest: versions.into_iter().last(),
+    }))
+}
+
+fn read_version(
+    app_handle: &tauri::AppHandle,
+    artifact_id: i64,
+    version: Option<i64>,
+) -> Result<ArtifactVersion, String> {
+    let db = ConversationDatabase::new(app_handle).map_err(|e| e.to_string())?;
+    db.artifact_repo()
+        .map_err(|e| e.to_string())?
+        .read_version_by_number(artifact_id, version)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "未找到产物版本".to_string())
+}
+
+#[tauri::command]
+pub fn get_artifact_version(
+    app_handle: tauri::AppHandle,
+    artifact_id: i64,
+    version: i64,
+) -> Result<ArtifactVersion, String> {
+    read_version(&app_handle, artifact_id, Some(version))
+}
+
+// 按文件比较两个版本，from_version 为 0 时与空版本比较
+#[tauri::command]
+pub fn diff_artifact_versions(
+    app_handle: tauri::AppHandle,
+    artifact_id: i64,
+    from_version: i64,
+    to_version: i64,
+) -> Result<Vec<ArtifactFileDiff>, String> {
+    let old_files = if from_version > 0 {
+        read_version(&app_handle, artifact_id, Some(from_version))?.files
+    } else {
+        vec![]
+    };
+    let new_version = read_version(&app_handle, artifact_id, Some(to_version))?;
+    Ok(diff_files(&old_files, &new_version.files))
+}
+
+// 导出为目录或者 zip，folder 时 path 为目标目录，zip 时 path 为 zip 文件；version 为空时导出最新版本
+#[tauri::command]
+pub fn export_artifact(
+    app_handle: tauri::AppHandle,
+    artifact_id: i64,
+    version: Option<i64>,
+    format: String,
+    path: String,
+) -> Result<(), String> {
+    let db = ConversationDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let artifact = db
+        .artifact_repo()
+        .map_err(|e| e.to_string())?
+        .read(artifact_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "未找到产物".to_string())?;
+    let version = read_version(&app_handle, artifact_id, version)?;
+    match format.as_str() {
+        "folder" => write_files_to_folder(&version.files, Path::new(&path)),
+        "zip" => {
+            // zip 中的根目录使用产物名称，去掉不能作为目录名的字符
+            let root: String = artifact
+                .name
+                .chars()
+                .filter(|c| !"/\\:*?\"<>|".contains(*c))
+                .collect();
+            let root = format!("{}-v{}", root.trim(), version.version);
+            let zip = build_zip(&version.files, &root)?;
+            std::fs::write(&path, zip).map_err(|e| format!("写入文件失败: {}", e))
+        }
+        _ => Err(format!("不支持的导出格式: {}", format)),
+    }
+}
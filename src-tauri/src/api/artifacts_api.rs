@@ -1,58 +1,89 @@
 use std::collections::HashMap;
 
+use serde::Serialize;
+use tauri::{Emitter, Manager, State};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::db::conversation_db::{ArtifactRunRecord, ConversationDatabase};
+use crate::db::system_db::FeatureConfig;
+use crate::state::artifact_run::{ArtifactRunManager, ArtifactRunSnapshot};
+use crate::state::preview_store::PreviewStore;
+use crate::utils::diff::{diff_lines, DiffLine};
 use crate::FeatureConfigState;
 
 use crate::{
-    artifacts::{applescript::run_applescript, powershell::run_powershell},
+    artifacts::history::{build_fix_error_prompt, code_hash},
+    artifacts::preview::{png_to_pdf, ExportFormat, PreviewKind},
+    artifacts::sandbox::{
+        run_sandboxed, OutputLine, SandboxOptions, SandboxResult, ScriptLanguage,
+    },
     errors::AppError,
-    window::{open_preview_html_window, open_preview_react_window, open_preview_vue_window},
+    window::{open_preview_component_window, open_preview_html_window},
 };
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArtifactStarted {
+    // 在后台运行的脚本
+    Process {
+        run_id: i64,
+    },
+    // 在预览窗口中渲染，可以导出的类型通过 hash 导出渲染结果
+    Preview {
+        hash: String,
+        kind: String,
+        exportable: bool,
+    },
+}
+
+// 脚本类的语言在后台运行并返回执行 id，每行输出通过 artifact_output_{id} 推送，
+// 结束时通过 artifact_finished_{id} 推送退出状态和耗时；组件和图表打开预览窗口并返回预览的 hash；
+// html 打开网页预览，返回 None
+// 脚本的每次运行都会记录到 artifact_run 表，message_id 为代码所在的消息
 #[tauri::command]
 pub async fn run_artifacts(
     app_handle: tauri::AppHandle,
     state: tauri::State<'_, FeatureConfigState>,
+    artifact_run_manager: State<'_, ArtifactRunManager>,
     lang: &str,
     input_str: &str,
-) -> Result<String, AppError> {
+    message_id: Option<i64>,
+) -> Result<Option<ArtifactStarted>, AppError> {
     // Anthropic artifacts : code, markdown, html, svg, mermaid, react(引入了 lucid3-react, recharts, tailwind, shadcn/ui )
-    // 加上 vue, nextjs 引入更多的前端库( echarts, antd, element-ui )
+    // react 和 vue 组件在本地的预览协议中渲染，不需要额外启动服务
 
-    let config_map = state.config_feature_map.lock().await;
-    let preview_config = config_map
-        .get("preview")
-        .map(|c| c.to_owned())
-        .unwrap_or_else(HashMap::new);
+    let preview_config = get_preview_config(&state).await;
 
-    let nextjs_port = preview_config
-        .get("nextjs_port")
-        .and_then(|config| config.value.parse::<u16>().ok())
-        .unwrap_or(3001); // 默认端口如果解析失败
+    if let Some(language) = ScriptLanguage::from_lang(lang) {
+        let run_id = start_artifact_run(
+            app_handle,
+            &artifact_run_manager,
+            &preview_config,
+            message_id,
+            lang,
+            language,
+            input_str.to_string(),
+        )
+        .await?;
+        return Ok(Some(ArtifactStarted::Process { run_id }));
+    }
 
-    let nuxtjs_port = preview_config
-        .get("nuxtjs_port")
-        .and_then(|config| config.value.parse::<u16>().ok())
-        .unwrap_or(3002); // 默认端口如果解析失败
+    if let Some(kind) = PreviewKind::from_lang(lang) {
+        let hash = open_preview_component_window(app_handle, kind, input_str.to_string())
+            .await
+            .map_err(AppError::RunCodeError)?;
+        return Ok(Some(ArtifactStarted::Preview {
+            hash,
+            kind: kind.name().to_string(),
+            exportable: kind.exportable(),
+        }));
+    }
 
     match lang {
-        "powershell" => {
-            return Ok(run_powershell(input_str).map_err(|e| {
-                AppError::RunCodeError("PowerShell 脚本执行失败:".to_owned() + &e.to_string())
-            })?);
-        }
-        "applescript" => {
-            return Ok(run_applescript(input_str).map_err(|e| {
-                AppError::RunCodeError("AppleScript 脚本执行失败:".to_owned() + &e.to_string())
-            })?);
-        }
-        "xml" | "svg" | "html" => {
+        "xml" | "html" => {
             let _ = open_preview_html_window(app_handle, input_str.to_string()).await;
         }
-        "react" | "jsx" => {
-            let _ = open_preview_react_window(app_handle, input_str.to_string(), nextjs_port).await;
-        }
-        "vue" => {
-            let _ = open_preview_vue_window(app_handle, input_str.to_string(), nuxtjs_port).await;
-        }
         _ => {
             // Handle other languages here
             return Err(AppError::RunCodeError(
@@ -60,5 +91,255 @@ pub async fn run_artifacts(
             ));
         }
     }
-    Ok("".to_string())
+    Ok(None)
+}
+
+async fn get_preview_config(state: &FeatureConfigState) -> HashMap<String, FeatureConfig> {
+    let config_map = state.config_feature_map.lock().await;
+    config_map
+        .get("preview")
+        .map(|c| c.to_owned())
+        .unwrap_or_else(HashMap::new)
+}
+
+// 先写入运行记录，用记录的 id 作为执行 id
+async fn start_artifact_run(
+    app_handle: tauri::AppHandle,
+    artifact_run_manager: &ArtifactRunManager,
+    preview_config: &HashMap<String, FeatureConfig>,
+    message_id: Option<i64>,
+    lang: &str,
+    language: ScriptLanguage,
+    code: String,
+) -> Result<i64, AppError> {
+    let options = SandboxOptions::from_config(language, preview_config);
+    let db = ConversationDatabase::new(&app_handle)?;
+    let run_id = db
+        .artifact_run_repo()?
+        .create(message_id, lang, &code, &code_hash(&code))?;
+    let (stdin_tx, stdin_rx) = mpsc::channel::<String>(16);
+    let cancel_token = CancellationToken::new();
+    artifact_run_manager
+        .register(run_id, stdin_tx, cancel_token.clone())
+        .await;
+    spawn_artifact_run(
+        app_handle,
+        run_id,
+        language,
+        code,
+        options,
+        stdin_rx,
+        cancel_token,
+    );
+    Ok(run_id)
+}
+
+fn spawn_artifact_run(
+    app_handle: tauri::AppHandle,
+    run_id: i64,
+    language: ScriptLanguage,
+    code: String,
+    options: SandboxOptions,
+    stdin: mpsc::Receiver<String>,
+    cancel_token: CancellationToken,
+) {
+    tauri::async_runtime::spawn(async move {
+        let manager = app_handle.state::<ArtifactRunManager>();
+        let (tx, mut rx) = mpsc::channel::<OutputLine>(100);
+        let output_event = format!("artifact_output_{}", run_id);
+        let forward = async {
+            while let Some(line) = rx.recv().await {
+                let event = manager.push_output(run_id, line).await;
+                let _ = app_handle.emit(output_event.as_str(), &event);
+            }
+        };
+        let run = run_sandboxed(
+            language,
+            &code,
+            &options,
+            Some(tx),
+            Some(stdin),
+            cancel_token,
+        );
+        let (result, _) = tokio::join!(run, forward);
+        // 没有启动成功时把错误作为 stderr 返回
+        let result = result.unwrap_or_else(|e| SandboxResult {
+            exit_code: None,
+            stdout: String::new(),
+            stderr: e,
+            timed_out: false,
+            truncated: false,
+            killed: false,
+            duration_ms: 0,
+        });
+        let saved = ConversationDatabase::new(&app_handle)
+            .map_err(AppError::from)
+            .and_then(|db| db.artifact_run_repo())
+            .and_then(|repo| repo.finish(run_id, &result).map_err(AppError::from));
+        if let Err(e) = saved {
+            println!("保存代码运行记录失败: {}", e);
+        }
+        manager.finish(run_id, result.clone()).await;
+        let _ = app_handle.emit(format!("artifact_finished_{}", run_id).as_str(), &result);
+    });
+}
+
+// 向运行中的进程写入输入，close 为 true 时写入后关闭 stdin
+#[tauri::command]
+pub async fn write_artifact_stdin(
+    artifact_run_manager: State<'_, ArtifactRunManager>,
+    run_id: i64,
+    input: String,
+    close: Option<bool>,
+) -> Result<(), String> {
+    if !input.is_empty() {
+        artifact_run_manager.write_stdin(run_id, input).await?;
+    }
+    if close.unwrap_or(false) {
+        artifact_run_manager.close_stdin(run_id).await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn kill_artifact_run(
+    artifact_run_manager: State<'_, ArtifactRunManager>,
+    run_id: i64,
+) -> Result<(), String> {
+    artifact_run_manager.kill(run_id).await;
+    Ok(())
+}
+
+// 窗口开始监听前已经产生的输出和结束状态
+#[tauri::command]
+pub async fn get_artifact_run(
+    artifact_run_manager: State<'_, ArtifactRunManager>,
+    run_id: i64,
+) -> Result<Option<ArtifactRunSnapshot>, String> {
+    Ok(artifact_run_manager.snapshot(run_id).await)
+}
+
+#[tauri::command]
+pub fn list_artifact_runs(
+    app_handle: tauri::AppHandle,
+    message_id: Option<i64>,
+    code_hash: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<ArtifactRunRecord>, String> {
+    let db = ConversationDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.artifact_run_repo()
+        .map_err(|e| e.to_string())?
+        .list(message_id, code_hash.as_deref(), limit.unwrap_or(50))
+        .map_err(|e| e.to_string())
+}
+
+fn read_artifact_run(
+    app_handle: &tauri::AppHandle,
+    run_id: i64,
+) -> Result<ArtifactRunRecord, String> {
+    let db = ConversationDatabase::new(app_handle).map_err(|e| e.to_string())?;
+    db.artifact_run_repo()
+        .map_err(|e| e.to_string())?
+        .read(run_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("未找到代码运行记录 {}", run_id))
+}
+
+// 用记录中的代码重新运行，新的记录关联到同一条消息，返回新的执行 id
+#[tauri::command]
+pub async fn rerun_artifact(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, FeatureConfigState>,
+    artifact_run_manager: State<'_, ArtifactRunManager>,
+    run_id: i64,
+) -> Result<i64, String> {
+    let run = read_artifact_run(&app_handle, run_id)?;
+    let language = ScriptLanguage::from_lang(&run.language)
+        .ok_or_else(|| format!("暂不支持该语言的代码执行: {}", run.language))?;
+    let preview_config = get_preview_config(&state).await;
+    start_artifact_run(
+        app_handle,
+        &artifact_run_manager,
+        &preview_config,
+        run.message_id,
+        &run.language,
+        language,
+        run.code,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArtifactRunComparison {
+    pub stdout: Vec<DiffLine>,
+    pub stderr: Vec<DiffLine>,
+    pub same_code: bool,
+}
+
+// 按行比较两次运行的输出
+#[tauri::command]
+pub fn compare_artifact_runs(
+    app_handle: tauri::AppHandle,
+    old_run_id: i64,
+    new_run_id: i64,
+) -> Result<ArtifactRunComparison, String> {
+    let old_run = read_artifact_run(&app_handle, old_run_id)?;
+    let new_run = read_artifact_run(&app_handle, new_run_id)?;
+    Ok(ArtifactRunComparison {
+        stdout: diff_lines(&old_run.stdout, &new_run.stdout),
+        stderr: diff_lines(&old_run.stderr, &new_run.stderr),
+        same_code: old_run.code_hash == new_run.code_hash,
+    })
+}
+
+// 根据运行记录的错误输出生成追问，由窗口作为新消息发送
+#[tauri::command]
+pub fn get_artifact_fix_prompt(
+    app_handle: tauri::AppHandle,
+    run_id: i64,
+) -> Result<String, String> {
+    let run = read_artifact_run(&app_handle, run_id)?;
+    if run.finished_time.is_none() {
+        return Err("代码还在运行中".to_string());
+    }
+    Ok(build_fix_error_prompt(&run))
+}
+
+// 把预览窗口渲染的结果导出到文件，format 为 png、svg 或 pdf
+#[tauri::command]
+pub fn export_artifact_preview(
+    preview_store: State<'_, PreviewStore>,
+    hash: String,
+    format: String,
+    path: String,
+) -> Result<(), String> {
+    let format = ExportFormat::parse(&format).ok_or(format!("不支持的导出格式: {}", format))?;
+    let preview = preview_store
+        .get(&hash)
+        .ok_or("预览已过期，请重新运行代码".to_string())?;
+    if !preview.kind.exportable() {
+        return Err(format!("{} 预览不支持导出", preview.kind.name()));
+    }
+    let content = match format {
+        ExportFormat::Svg => preview.rendered_svg.map(String::into_bytes),
+        ExportFormat::Png => preview.rendered_png,
+        ExportFormat::Pdf => preview
+            .rendered_png
+            .map(|png| png_to_pdf(&png))
+            .transpose()?,
+    }
+    .ok_or("预览还没有渲染完成，请稍后再试".to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))
+}
+
+// HTML 预览窗口加载后读取自己要显示的内容
+#[tauri::command]
+pub fn get_preview_html(
+    window: tauri::WebviewWindow,
+    preview_store: State<'_, PreviewStore>,
+) -> Result<String, String> {
+    preview_store
+        .get_html(window.label())
+        .ok_or("预览内容不存在".to_string())
 }
@@ -1,12 +1,20 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
 use crate::{
+    api::assistant_pipeline::{config_schema, AssistantType},
     db::{
         assistant_db::{
             Assistant, AssistantDatabase, AssistantModel, AssistantModelConfig, AssistantPrompt,
-            AssistantPromptParam,
+            AssistantPromptParam, AssistantPromptVersion,
         },
         conversation_db::ConversationDatabase,
+        llm_db::LLMDatabase,
     },
-    NameCacheState,
+    template_engine::TemplateEngine,
+    utils::diff::{diff_lines, DiffLine},
+    NameCacheState, TemplateEngineState,
 };
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
@@ -80,8 +88,10 @@ pub fn get_assistant(
 pub async fn save_assistant(
     app_handle: tauri::AppHandle,
     name_cache_state: tauri::State<'_, NameCacheState>,
+    template_engine_state: tauri::State<'_, TemplateEngineState>,
     assistant_detail: AssistantDetail,
 ) -> Result<(), String> {
+    let template_engine = template_engine_state.engine.lock().await.clone();
     let assistant_db = AssistantDatabase::new(&app_handle).map_err(|e| e.to_string())?;
 
     println!(
@@ -185,30 +195,25 @@ pub async fn save_assistant(
         }
     }
 
-    // Save or update the AssistantPromptParams
+    // Save the AssistantPromptParams, 参数列表整体替换，删除的参数也要从数据库中去掉
+    validate_prompt_param_names(&assistant_detail.prompt_params, &template_engine)?;
+    assistant_db
+        .delete_assistant_prompt_param_by_assistant_id(assistant_detail.assistant.id)
+        .map_err(|e| e.to_string())?;
     for param in assistant_detail.prompt_params {
-        if param.id == 0 {
-            assistant_db
-                .add_assistant_prompt_param(
-                    param.assistant_id,
-                    param.assistant_prompt_id,
-                    &param.param_name,
-                    param.param_type.as_deref().unwrap_or(""),
-                    param.param_value.as_deref().unwrap_or(""),
-                )
-                .map_err(|e| e.to_string())?;
-        } else {
-            assistant_db
-                .update_assistant_prompt_param(
-                    param.id,
-                    &param.param_name,
-                    param.param_type.as_deref().unwrap_or(""),
-                    param.param_value.as_deref().unwrap_or(""),
-                )
-                .map_err(|e| e.to_string())?;
-        }
+        assistant_db
+            .add_assistant_prompt_param(
+                assistant_detail.assistant.id,
+                param.assistant_prompt_id,
+                &param.param_name,
+                param.param_type.as_deref().unwrap_or(PARAM_TYPE_TEXT),
+                param.param_value.as_deref().unwrap_or(""),
+            )
+            .map_err(|e| e.to_string())?;
     }
 
+    record_prompt_version(&assistant_db, assistant_detail.assistant.id)?;
+
     Ok(())
 }
 
@@ -249,41 +254,18 @@ pub fn add_assistant(
         .map_err(|e| e.to_string())?;
     println!("model_id: {:?}", model_id);
 
-    // Add default model configs
-    let default_model_configs = vec![
-        AssistantModelConfig {
+    // 按助手类型添加默认的模型配置
+    let default_model_configs = config_schema(AssistantType::from_i64(Some(assistant_type)))
+        .into_iter()
+        .map(|field| AssistantModelConfig {
             id: 0,
             assistant_id,
-            assistant_model_id: model_id, // Assuming 0 is a default model ID
-            name: "max_tokens".to_string(),
-            value: Some("2000".to_string()),
-            value_type: "number".to_string(),
-        },
-        AssistantModelConfig {
-            id: 0,
-            assistant_id,
-            assistant_model_id: model_id, // Assuming 0 is a default model ID
-            name: "temperature".to_string(),
-            value: Some("0.7".to_string()),
-            value_type: "float".to_string(),
-        },
-        AssistantModelConfig {
-            id: 0,
-            assistant_id,
-            assistant_model_id: model_id, // Assuming 0 is a default model ID
-            name: "top_p".to_string(),
-            value: Some("1.0".to_string()),
-            value_type: "float".to_string(),
-        },
-        AssistantModelConfig {
-            id: 0,
-            assistant_id,
-            assistant_model_id: model_id, // Assuming 0 is a default model ID
-            name: "stream".to_string(),
-            value: Some("true".to_string()),
-            value_type: "boolean".to_string(),
-        },
-    ];
+            assistant_model_id: model_id,
+            name: field.name,
+            value: Some(field.default_value),
+            value_type: field.value_type,
+        })
+        .collect::<Vec<_>>();
     let mut model_configs = Vec::new();
     for config in default_model_configs {
         let config_id = assistant_db
@@ -316,6 +298,8 @@ pub fn add_assistant(
     }];
     let prompt_params = Vec::new();
 
+    record_prompt_version(&assistant_db, assistant_id)?;
+
     // Build AssistantDetail object
     let assistant_detail = AssistantDetail {
         assistant,
@@ -421,6 +405,8 @@ pub fn copy_assistant(
         .get_assistant(new_assistant_id)
         .map_err(|e| e.to_string())?;
 
+    record_prompt_version(&assistant_db, new_assistant_id)?;
+
     // Build AssistantDetail object
     let assistant_detail = AssistantDetail {
         assistant: new_assistant,
@@ -449,6 +435,9 @@ pub fn delete_assistant(app_handle: tauri::AppHandle, assistant_id: i64) -> Resu
     let _ = assistant_db
         .delete_assistant_prompt_param_by_assistant_id(assistant_id)
         .map_err(|e| e.to_string());
+    let _ = assistant_db
+        .delete_prompt_version_by_assistant_id(assistant_id)
+        .map_err(|e| e.to_string());
 
     let conversation_db = ConversationDatabase::new(&app_handle).map_err(|e| e.to_string())?;
     let _ = conversation_db
@@ -499,3 +488,654 @@ pub fn get_assistant_field_value(
         .and_then(|config| config.value.clone())
         .ok_or_else(|| format!("Field '{}' not found", field_name))
 }
+
+const PARAM_TYPE_TEXT: &str = "text";
+
+// 参数名会作为 !参数名 在提示词中引用，只能包含字母、数字和下划线，且不能与上下文中的 selected_text 冲突，
+// 与 bang 同名时会执行 bang 而不是替换参数，所以也不允许
+pub(crate) fn validate_prompt_param_names(
+    params: &[AssistantPromptParam],
+    template_engine: &TemplateEngine,
+) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for param in params {
+        let name = param.param_name.as_str();
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(format!("参数名 {} 只能包含字母、数字和下划线", name));
+        }
+        if name == "selected_text" {
+            return Err("参数名 selected_text 为保留名称".to_string());
+        }
+        if template_engine.has_command(name) {
+            return Err(format!("参数名 {} 与 bang !{} 重名", name, name));
+        }
+        if !names.insert(name) {
+            return Err(format!("参数名 {} 重复", name));
+        }
+    }
+    Ok(())
+}
+
+// 根据参数类型校验提问时填写的值，没有填写时使用默认值
+// param_type: text 单行文本，multiline 多行文本，number 数字，select 选项
+// param_value: select 为逗号分隔的选项，默认第一项，其他类型为默认值
+pub fn resolve_prompt_params(
+    params: &[AssistantPromptParam],
+    values: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    let mut result = HashMap::new();
+    for param in params {
+        let name = &param.param_name;
+        let param_value = param.param_value.as_deref().unwrap_or("");
+        let input = values.get(name).filter(|v| !v.trim().is_empty());
+
+        let value = match param.param_type.as_deref().unwrap_or(PARAM_TYPE_TEXT) {
+            "number" => {
+                let value = input.map(|v| v.trim()).unwrap_or(param_value.trim());
+                if value.parse::<f64>().is_err() {
+                    return Err(format!("参数 {} 需要填写数字", name));
+                }
+                value.to_string()
+            }
+            "select" => {
+                let options: Vec<&str> = param_value
+                    .split(',')
+                    .map(|o| o.trim())
+                    .filter(|o| !o.is_empty())
+                    .collect();
+                match input {
+                    Some(value) if options.contains(&value.trim()) => value.trim().to_string(),
+                    Some(value) => {
+                        return Err(format!(
+                            "参数 {} 的值 {} 不在可选项 {} 中",
+                            name,
+                            value,
+                            options.join("、")
+                        ))
+                    }
+                    None => options.first().unwrap_or(&"").to_string(),
+                }
+            }
+            // text 与 multiline 只是输入方式不同
+            _ => input.cloned().unwrap_or_else(|| param_value.to_string()),
+        };
+        result.insert(name.clone(), value);
+    }
+    Ok(result)
+}
+
+// 助手导出文件的格式标识和版本，格式有不兼容的修改时增加版本号
+const EXPORT_FORMAT: &str = "aipp-assistant";
+const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssistantExport {
+    pub format: String,
+    pub version: u32,
+    pub assistant: AssistantExportInfo,
+    pub prompts: Vec<String>,
+    #[serde(default)]
+    pub prompt_params: Vec<AssistantExportParam>,
+    #[serde(default)]
+    pub models: Vec<AssistantExportModel>,
+    #[serde(default)]
+    pub model_configs: Vec<AssistantExportModelConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssistantExportInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub assistant_type: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssistantExportParam {
+    pub name: String,
+    pub param_type: Option<String>,
+    pub value: Option<String>,
+}
+
+// 模型通过提供商的 api_type 和模型 code 引用，导入时映射到本地的提供商
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssistantExportModel {
+    pub provider_api_type: String,
+    pub provider_name: String,
+    pub model_code: String,
+    pub alias: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssistantExportModelConfig {
+    // 对应 models 中的下标，不属于任何模型时为空
+    pub model_index: Option<usize>,
+    pub name: String,
+    pub value: Option<String>,
+    pub value_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssistantImportResult {
+    pub assistant: AssistantDetail,
+    // 模型无法映射等需要用户确认的问题
+    pub warnings: Vec<String>,
+}
+
+#[tauri::command]
+pub fn export_assistant(
+    app_handle: tauri::AppHandle,
+    assistant_id: i64,
+    path: String,
+) -> Result<(), String> {
+    let detail = get_assistant(app_handle.clone(), assistant_id)?;
+    let llm_db = LLMDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+
+    let models: Vec<AssistantExportModel> = detail
+        .model
+        .iter()
+        .map(|model| {
+            let provider = llm_db.get_llm_provider(model.provider_id).ok();
+            AssistantExportModel {
+                provider_api_type: provider
+                    .as_ref()
+                    .map(|p| p.api_type.clone())
+                    .unwrap_or_default(),
+                provider_name: provider.map(|p| p.name).unwrap_or_default(),
+                model_code: model.model_code.clone(),
+                alias: model.alias.clone(),
+            }
+        })
+        .collect();
+
+    let export = AssistantExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        assistant: AssistantExportInfo {
+            name: detail.assistant.name.clone(),
+            description: detail.assistant.description.clone(),
+            assistant_type: detail.assistant.assistant_type,
+        },
+        prompts: detail.prompts.iter().map(|p| p.prompt.clone()).collect(),
+        prompt_params: detail
+            .prompt_params
+            .iter()
+            .map(|p| AssistantExportParam {
+                name: p.param_name.clone(),
+                param_type: p.param_type.clone(),
+                value: p.param_value.clone(),
+            })
+            .collect(),
+        models,
+        model_configs: detail
+            .model_configs
+            .iter()
+            .map(|c| AssistantExportModelConfig {
+                model_index: detail
+                    .model
+                    .iter()
+                    .position(|m| m.id == c.assistant_model_id),
+                name: c.name.clone(),
+                value: c.value.clone(),
+                value_type: c.value_type.clone(),
+            })
+            .collect(),
+    };
+
+    let content = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))
+}
+
+// conflict_strategy: rename 存在同名助手时重命名导入，overwrite 覆盖同名助手的配置
+#[tauri::command]
+pub async fn import_assistant(
+    app_handle: tauri::AppHandle,
+    name_cache_state: tauri::State<'_, NameCacheState>,
+    template_engine_state: tauri::State<'_, TemplateEngineState>,
+    path: String,
+    conflict_strategy: Option<String>,
+) -> Result<AssistantImportResult, String> {
+    let template_engine = template_engine_state.engine.lock().await.clone();
+    let result = {
+        let content = std::fs::read_to_string(&path).map_err(|e| format!("读取文件失败: {}", e))?;
+        let export: AssistantExport =
+            serde_json::from_str(&content).map_err(|e| format!("文件格式不正确: {}", e))?;
+        let assistant_db = AssistantDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+        let llm_db = LLMDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+        import_assistant_export(
+            &assistant_db,
+            &llm_db,
+            &template_engine,
+            export,
+            conflict_strategy.as_deref().unwrap_or("rename"),
+        )?
+    };
+
+    name_cache_state.assistant_names.lock().await.insert(
+        result.assistant.assistant.id,
+        result.assistant.assistant.name.clone(),
+    );
+    Ok(result)
+}
+
+fn import_assistant_export(
+    assistant_db: &AssistantDatabase,
+    llm_db: &LLMDatabase,
+    template_engine: &TemplateEngine,
+    export: AssistantExport,
+    conflict_strategy: &str,
+) -> Result<AssistantImportResult, String> {
+    if export.format != EXPORT_FORMAT {
+        return Err("不是助手导出文件".to_string());
+    }
+    if export.version > EXPORT_VERSION {
+        return Err(format!(
+            "导出文件版本 {} 高于当前支持的版本 {}，请升级后再导入",
+            export.version, EXPORT_VERSION
+        ));
+    }
+    let params: Vec<AssistantPromptParam> = export
+        .prompt_params
+        .iter()
+        .map(|p| AssistantPromptParam {
+            id: 0,
+            assistant_id: 0,
+            assistant_prompt_id: 0,
+            param_name: p.name.clone(),
+            param_type: p.param_type.clone(),
+            param_value: p.value.clone(),
+        })
+        .collect();
+    validate_prompt_param_names(&params, template_engine)?;
+
+    let mut warnings = Vec::new();
+    let used_bangs = local_access_bangs(
+        export
+            .prompts
+            .iter()
+            .chain(export.model_configs.iter().filter_map(|c| c.value.as_ref())),
+    );
+    if !used_bangs.is_empty() {
+        warnings.push(format!(
+            "导入的助手使用了 {}，会执行命令或读取本地文件、环境变量，请确认提示词内容可信",
+            used_bangs
+                .iter()
+                .map(|name| format!("!{}", name))
+                .collect::<Vec<_>>()
+                .join("、")
+        ));
+    }
+
+    // 覆盖时先删除再重新添加，中途失败需要整体回滚，避免留下配置不完整的助手
+    let transaction = assistant_db
+        .conn
+        .unchecked_transaction()
+        .map_err(|e| e.to_string())?;
+
+    let info = &export.assistant;
+    let description = info.description.as_deref().unwrap_or("");
+    let assistants = assistant_db.get_assistants().map_err(|e| e.to_string())?;
+    let existing = assistants.iter().find(|a| a.name == info.name);
+
+    let assistant_id = match (existing, conflict_strategy) {
+        // 内置的快速使用助手不允许被覆盖
+        (Some(existing), "overwrite") if existing.id != 1 => {
+            assistant_db
+                .update_assistant(existing.id, &info.name, description)
+                .map_err(|e| e.to_string())?;
+            assistant_db
+                .delete_assistant_prompt_by_assistant_id(existing.id)
+                .map_err(|e| e.to_string())?;
+            assistant_db
+                .delete_assistant_prompt_param_by_assistant_id(existing.id)
+                .map_err(|e| e.to_string())?;
+            assistant_db
+                .delete_assistant_model_config_by_assistant_id(existing.id)
+                .map_err(|e| e.to_string())?;
+            assistant_db
+                .delete_assistant_model_by_assistant_id(existing.id)
+                .map_err(|e| e.to_string())?;
+            existing.id
+        }
+        (Some(_), _) => {
+            let names: HashSet<&str> = assistants.iter().map(|a| a.name.as_str()).collect();
+            let mut name = format!("{} (导入)", info.name);
+            let mut index = 2;
+            while names.contains(name.as_str()) {
+                name = format!("{} (导入{})", info.name, index);
+                index += 1;
+            }
+            assistant_db
+                .add_assistant(&name, description, info.assistant_type, true)
+                .map_err(|e| e.to_string())?
+        }
+        (None, _) => assistant_db
+            .add_assistant(&info.name, description, info.assistant_type, true)
+            .map_err(|e| e.to_string())?,
+    };
+
+    let mut prompt_ids = Vec::new();
+    for prompt in &export.prompts {
+        prompt_ids.push(
+            assistant_db
+                .add_assistant_prompt(assistant_id, prompt)
+                .map_err(|e| e.to_string())?,
+        );
+    }
+    for param in &params {
+        assistant_db
+            .add_assistant_prompt_param(
+                assistant_id,
+                prompt_ids.first().copied().unwrap_or(0),
+                &param.param_name,
+                param.param_type.as_deref().unwrap_or(PARAM_TYPE_TEXT),
+                param.param_value.as_deref().unwrap_or(""),
+            )
+            .map_err(|e| e.to_string())?;
+    }
+
+    let providers = llm_db.get_llm_providers().map_err(|e| e.to_string())?;
+    let local_models = llm_db.get_all_llm_models().map_err(|e| e.to_string())?;
+    let mut model_ids = Vec::new();
+    for model in &export.models {
+        let (provider_id, model_code) = map_model(&providers, &local_models, model, &mut warnings);
+        model_ids.push(
+            assistant_db
+                .add_assistant_model(assistant_id, provider_id, &model_code, &model.alias)
+                .map_err(|e| e.to_string())?,
+        );
+    }
+    for config in &export.model_configs {
+        let model_id = config
+            .model_index
+            .and_then(|i| model_ids.get(i).copied())
+            .unwrap_or(0);
+        assistant_db
+            .add_assistant_model_config(
+                assistant_id,
+                model_id,
+                &config.name,
+                config.value.as_deref().unwrap_or(""),
+                &config.value_type,
+            )
+            .map_err(|e| e.to_string())?;
+    }
+
+    record_prompt_version(&assistant_db, assistant_id)?;
+    transaction.commit().map_err(|e| e.to_string())?;
+
+    let assistant = AssistantDetail {
+        assistant: assistant_db
+            .get_assistant(assistant_id)
+            .map_err(|e| e.to_string())?,
+        prompts: assistant_db
+            .get_assistant_prompt(assistant_id)
+            .map_err(|e| e.to_string())?,
+        model: assistant_db
+            .get_assistant_model(assistant_id)
+            .map_err(|e| e.to_string())?,
+        model_configs: assistant_db
+            .get_assistant_model_configs(assistant_id)
+            .map_err(|e| e.to_string())?,
+        prompt_params: assistant_db
+            .get_assistant_prompt_params(assistant_id)
+            .map_err(|e| e.to_string())?,
+    };
+    Ok(AssistantImportResult {
+        assistant,
+        warnings,
+    })
+}
+
+// 会执行命令或读取本地数据的 bang，导入他人的助手时需要提醒用户确认
+const LOCAL_ACCESS_BANGS: [&str; 4] = ["shell", "file", "dir", "env"];
+
+// 找出文本中用到的 LOCAL_ACCESS_BANGS，!shell_xxx 这类更长的名称不算
+pub(crate) fn local_access_bangs<'a>(texts: impl Iterator<Item = &'a String>) -> Vec<&'static str> {
+    let mut used = Vec::new();
+    for text in texts {
+        for name in LOCAL_ACCESS_BANGS {
+            let pattern = format!("!{}", name);
+            let found = text.match_indices(&pattern).any(|(index, _)| {
+                !text[index + pattern.len()..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_alphanumeric() || c == '_')
+            });
+            if found && !used.contains(&name) {
+                used.push(name);
+            }
+        }
+    }
+    used
+}
+
+// 按 api_type 找到本地的提供商，优先同名且有该模型的，其次是启用的、有该模型的
+pub(crate) fn map_model(
+    providers: &[(i64, String, String, String, bool, bool)],
+    local_models: &[(i64, String, i64, String, String, bool, bool, bool)],
+    model: &AssistantExportModel,
+    warnings: &mut Vec<String>,
+) -> (i64, String) {
+    let has_model = |provider_id: i64| {
+        local_models
+            .iter()
+            .any(|m| m.2 == provider_id && m.3 == model.model_code)
+    };
+    let candidates: Vec<_> = providers
+        .iter()
+        .filter(|p| p.2 == model.provider_api_type)
+        .collect();
+
+    let matched = candidates
+        .iter()
+        .find(|p| p.1 == model.provider_name && has_model(p.0))
+        .or_else(|| candidates.iter().find(|p| p.5 && has_model(p.0)))
+        .or_else(|| candidates.iter().find(|p| has_model(p.0)));
+    if let Some(provider) = matched {
+        return (provider.0, model.model_code.clone());
+    }
+
+    match candidates.iter().find(|p| p.5).or(candidates.first()) {
+        Some(provider) => {
+            warnings.push(format!(
+                "提供商 {} 中没有模型 {}，请确认模型是否可用",
+                provider.1, model.model_code
+            ));
+            (provider.0, model.model_code.clone())
+        }
+        None => {
+            warnings.push(format!(
+                "本地没有类型为 {} 的提供商，模型 {} 需要重新选择",
+                model.provider_api_type, model.model_code
+            ));
+            (0, String::new())
+        }
+    }
+}
+
+// 比较配置时忽略 id，只看名称、值和类型
+fn same_model_configs(a: &[AssistantModelConfig], b: &[AssistantModelConfig]) -> bool {
+    let key = |configs: &[AssistantModelConfig]| {
+        let mut keys = configs
+            .iter()
+            .map(|c| (c.name.clone(), c.value.clone(), c.value_type.clone()))
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    };
+    key(a) == key(b)
+}
+
+// 当前的提示词和模型配置与最新版本不同时记录一个新版本，返回当前内容对应的版本 id
+pub fn record_prompt_version(
+    assistant_db: &AssistantDatabase,
+    assistant_id: i64,
+) -> Result<i64, String> {
+    let prompt = assistant_db
+        .get_assistant_prompt(assistant_id)
+        .map_err(|e| e.to_string())?
+        .first()
+        .map(|p| p.prompt.clone())
+        .unwrap_or_default();
+    let model_configs = assistant_db
+        .get_assistant_model_configs(assistant_id)
+        .map_err(|e| e.to_string())?;
+
+    let versions = assistant_db
+        .get_prompt_versions(assistant_id)
+        .map_err(|e| e.to_string())?;
+    if let Some(latest) = versions.first() {
+        if latest.prompt == prompt && same_model_configs(&latest.model_configs, &model_configs) {
+            return Ok(latest.id);
+        }
+    }
+    assistant_db
+        .add_prompt_version(assistant_id, &prompt, &model_configs)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_assistant_prompt_versions(
+    app_handle: tauri::AppHandle,
+    assistant_id: i64,
+) -> Result<Vec<AssistantPromptVersion>, String> {
+    let assistant_db = AssistantDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    assistant_db
+        .get_prompt_versions(assistant_id)
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModelConfigChange {
+    pub name: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PromptVersionDiff {
+    pub from_version: i64,
+    pub to_version: i64,
+    pub prompt: Vec<DiffLine>,
+    // 只包含有变化的配置
+    pub model_configs: Vec<ModelConfigChange>,
+}
+
+pub fn diff_prompt_versions(
+    from: &AssistantPromptVersion,
+    to: &AssistantPromptVersion,
+) -> PromptVersionDiff {
+    let value_of = |configs: &[AssistantModelConfig], name: &str| {
+        configs
+            .iter()
+            .find(|c| c.name == name)
+            .and_then(|c| c.value.clone())
+    };
+    let mut names = from
+        .model_configs
+        .iter()
+        .chain(to.model_configs.iter())
+        .map(|c| c.name.clone())
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+
+    let model_configs = names
+        .into_iter()
+        .filter_map(|name| {
+            let old_value = value_of(&from.model_configs, &name);
+            let new_value = value_of(&to.model_configs, &name);
+            (old_value != new_value).then_some(ModelConfigChange {
+                name,
+                old_value,
+                new_value,
+            })
+        })
+        .collect();
+
+    PromptVersionDiff {
+        from_version: from.version,
+        to_version: to.version,
+        prompt: diff_lines(&from.prompt, &to.prompt),
+        model_configs,
+    }
+}
+
+#[tauri::command]
+pub fn diff_assistant_prompt_versions(
+    app_handle: tauri::AppHandle,
+    from_version_id: i64,
+    to_version_id: i64,
+) -> Result<PromptVersionDiff, String> {
+    let assistant_db = AssistantDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let from = assistant_db
+        .get_prompt_version(from_version_id)
+        .map_err(|e| e.to_string())?;
+    let to = assistant_db
+        .get_prompt_version(to_version_id)
+        .map_err(|e| e.to_string())?;
+    Ok(diff_prompt_versions(&from, &to))
+}
+
+// 回滚到指定版本，回滚后的内容记录为一个新版本，历史版本不会被删除
+#[tauri::command]
+pub fn rollback_assistant_prompt_version(
+    app_handle: tauri::AppHandle,
+    version_id: i64,
+) -> Result<AssistantDetail, String> {
+    let assistant_db = AssistantDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let version = assistant_db
+        .get_prompt_version(version_id)
+        .map_err(|e| e.to_string())?;
+    let assistant_id = version.assistant_id;
+
+    // 提示词、模型配置和新的版本记录要么全部写入，要么全部回滚
+    let transaction = assistant_db
+        .conn
+        .unchecked_transaction()
+        .map_err(|e| e.to_string())?;
+
+    match assistant_db
+        .get_assistant_prompt(assistant_id)
+        .map_err(|e| e.to_string())?
+        .first()
+    {
+        Some(prompt) => assistant_db.update_assistant_prompt(prompt.id, &version.prompt),
+        None => assistant_db
+            .add_assistant_prompt(assistant_id, &version.prompt)
+            .map(|_| ()),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let current_configs = assistant_db
+        .get_assistant_model_configs(assistant_id)
+        .map_err(|e| e.to_string())?;
+    let model_id = assistant_db
+        .get_assistant_model(assistant_id)
+        .map_err(|e| e.to_string())?
+        .first()
+        .map(|m| m.id)
+        .unwrap_or(0);
+    for config in &version.model_configs {
+        let value = config.value.as_deref().unwrap_or("");
+        match current_configs.iter().find(|c| c.name == config.name) {
+            Some(current) => {
+                assistant_db.update_assistant_model_config(current.id, &config.name, value)
+            }
+            None => assistant_db
+                .add_assistant_model_config(
+                    assistant_id,
+                    model_id,
+                    &config.name,
+                    value,
+                    &config.value_type,
+                )
+                .map(|_| ()),
+        }
+        .map_err(|e| e.to_string())?;
+    }
+
+    record_prompt_version(&assistant_db, assistant_id)?;
+    transaction.commit().map_err(|e| e.to_string())?;
+    get_assistant(app_handle, assistant_id)
+}
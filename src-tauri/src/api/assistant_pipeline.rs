@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+// 助手类型，决定提问时走哪一条执行流程
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssistantType {
+    // 普通对话，带上历史消息
+    Chat,
+    // 多个模型同时回答同一个问题，结果作为同一条消息的不同版本
+    Compare,
+    // 执行工作流页面中配置的工作流，用户的问题作为工作流的输入
+    Workflow,
+    // 展示助手，由前端插件处理
+    Display,
+    // 一次性处理选中文本，不带历史消息，结果复制到剪贴板
+    Transform,
+}
+
+impl AssistantType {
+    pub fn from_i64(value: Option<i64>) -> Self {
+        match value.unwrap_or(0) {
+            1 => AssistantType::Compare,
+            2 => AssistantType::Workflow,
+            3 => AssistantType::Display,
+            4 => AssistantType::Transform,
+            _ => AssistantType::Chat,
+        }
+    }
+}
+
+// 助手类型在 assistant_model_config 中需要的配置项
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AssistantConfigField {
+    pub name: String,
+    pub label: String,
+    pub value_type: String,
+    pub default_value: String,
+}
+
+fn field(name: &str, label: &str, value_type: &str, default_value: &str) -> AssistantConfigField {
+    AssistantConfigField {
+        name: name.to_string(),
+        label: label.to_string(),
+        value_type: value_type.to_string(),
+        default_value: default_value.to_string(),
+    }
+}
+
+pub fn config_schema(assistant_type: AssistantType) -> Vec<AssistantConfigField> {
+    let mut fields = vec![
+        field("max_tokens", "max_tokens", "number", "2000"),
+        field("temperature", "temperature", "float", "0.7"),
+        field("top_p", "top_p", "float", "1.0"),
+        field("stream", "stream", "boolean", "true"),
+    ];
+    match assistant_type {
+        AssistantType::Transform => {
+            fields.push(field(
+                "copy_to_clipboard",
+                "结果复制到剪贴板",
+                "boolean",
+                "true",
+            ));
+        }
+        AssistantType::Compare => {
+            // 格式与模型选择一致：model_code%%provider_id，多个用逗号分隔
+            fields.push(field("compare_models", "对比模型", "string", ""));
+        }
+        AssistantType::Workflow => {
+            fields.push(field("workflow", "工作流（名称或 id）", "string", ""));
+        }
+        AssistantType::Chat | AssistantType::Display => {}
+    }
+    fields
+}
+
+#[tauri::command]
+pub fn get_assistant_type_config_schema(assistant_type: i64) -> Vec<AssistantConfigField> {
+    config_schema(AssistantType::from_i64(Some(assistant_type)))
+}
+
+// 解析 compare_models 配置，返回 (provider_id, model_code)
+pub fn parse_compare_models(value: &str) -> Vec<(i64, String)> {
+    value
+        .split(',')
+        .filter_map(|item| {
+            let (model_code, provider_id) = item.trim().split_once("%%")?;
+            let provider_id = provider_id.trim().parse().ok()?;
+            Some((provider_id, model_code.trim().to_string()))
+        })
+        .filter(|(_, model_code)| !model_code.is_empty())
+        .collect()
+}
@@ -9,6 +9,9 @@ use crate::{
         ConversationDatabase, Message, MessageAttachment, MessageDetail, Repository,
     },
     errors::AppError,
+    export::conversation::{
+        build_conversation_export, render_conversation, ConversationExportFormat, ExportScope,
+    },
     NameCacheState,
 };
 
@@ -73,25 +76,50 @@ pub async fn get_conversation_with_messages(
         .list_by_conversation_id(conversation_id)
         .map_err(|e| e.to_string())?;
 
+    let message_details = build_message_details(messages);
+
+    let assistant_name_cache = name_cache_state.assistant_names.lock().await;
+    let assistant_name = assistant_name_cache
+        .get(&conversation.assistant_id.unwrap_or(0))
+        .cloned()
+        .unwrap_or_else(|| "未知".to_string());
+
+    Ok((
+        ConversationResult {
+            id: conversation.id,
+            name: conversation.name,
+            assistant_id: conversation.assistant_id.unwrap_or(0),
+            assistant_name,
+            created_time: conversation.created_time,
+        },
+        message_details,
+    ))
+}
+
+// 按 parent_id 和 compare_to_id 把重新生成的回答和对比的回答放到对应的消息中
+pub(crate) fn build_message_details(
+    messages: Vec<(Message, Option<MessageAttachment>)>,
+) -> Vec<MessageDetail> {
     let mut message_details: Vec<MessageDetail> = Vec::new();
     let mut attachment_map: HashMap<i64, Vec<MessageAttachment>> = HashMap::new();
 
-    for (message, attachment) in messages.clone() {
+    for (message, attachment) in messages.iter() {
         if let Some(attachment) = attachment {
             attachment_map
                 .entry(message.id)
                 .or_default()
-                .push(attachment);
+                .push(attachment.clone());
         }
     }
 
     // Convert messages to a HashMap to preserve it for the second pass
     let message_map: HashMap<i64, Message> = messages
-        .clone()
         .into_iter()
         .map(|(message, _)| (message.id, message))
         .collect();
 
+    // 对比的回答 id 到主回答 id
+    let mut compare_ids: HashMap<i64, i64> = HashMap::new();
     // Second pass: Create MessageDetail with the collected attachments
     for (message_id, message) in message_map {
         let attachment_list = attachment_map.get(&message_id).cloned().unwrap_or_default();
@@ -103,10 +131,16 @@ pub async fn get_conversation_with_messages(
             llm_model_id: message.llm_model_id,
             created_time: message.created_time,
             token_count: message.token_count,
+            prompt_version_id: message.prompt_version_id,
+            llm_model_name: message.llm_model_name,
             attachment_list,
             regenerate: Vec::new(),
+            compare: Vec::new(),
             parent_id: message.parent_id,
         });
+        if let Some(compare_to_id) = message.compare_to_id {
+            compare_ids.insert(message.id, compare_to_id);
+        }
     }
 
     // 处理 regenerate 关系
@@ -119,35 +153,34 @@ pub async fn get_conversation_with_messages(
             acc
         });
 
+    // 多模型对比助手其他模型的回答
+    let mut compare_map: HashMap<i64, Vec<MessageDetail>> = HashMap::new();
+    for message in &message_details {
+        if let Some(compare_to_id) = compare_ids.get(&message.id) {
+            compare_map
+                .entry(*compare_to_id)
+                .or_default()
+                .push(message.clone());
+        }
+    }
+
     for message in &mut message_details {
         if let Some(regenerated) = regenerate_map.get(&message.id) {
             message.regenerate = regenerated.clone();
         }
+        if let Some(compare) = compare_map.get_mut(&message.id) {
+            compare.sort_by_key(|m| m.id);
+            message.compare = compare.clone();
+        }
     }
 
-    // 过滤掉有 parent_id 的消息，并按 ID 排序
+    // 过滤掉有 parent_id 的消息和对比的回答，并按 ID 排序
     message_details = message_details
         .into_iter()
-        .filter(|m| m.parent_id.is_none())
+        .filter(|m| m.parent_id.is_none() && !compare_ids.contains_key(&m.id))
         .collect();
     message_details.sort_by_key(|m| m.id);
-
-    let assistant_name_cache = name_cache_state.assistant_names.lock().await;
-    let assistant_name = assistant_name_cache
-        .get(&conversation.assistant_id.unwrap_or(0))
-        .cloned()
-        .unwrap_or_else(|| "未知".to_string());
-
-    Ok((
-        ConversationResult {
-            id: conversation.id,
-            name: conversation.name,
-            assistant_id: conversation.assistant_id.unwrap_or(0),
-            assistant_name,
-            created_time: conversation.created_time,
-        },
-        message_details,
-    ))
+    message_details
 }
 
 #[tauri::command]
@@ -156,6 +189,14 @@ pub fn delete_conversation(
     conversation_id: i64,
 ) -> Result<(), String> {
     let db = ConversationDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.artifact_repo()
+        .map_err(|e| e.to_string())?
+        .delete_by_conversation_id(conversation_id)
+        .map_err(|e| e.to_string())?;
+    db.artifact_run_repo()
+        .map_err(|e| e.to_string())?
+        .delete_by_conversation_id(conversation_id)
+        .map_err(|e| e.to_string())?;
     db.conversation_repo()
         .unwrap()
         .delete(conversation_id)
@@ -184,3 +225,42 @@ pub fn update_conversation(
     let _ = app_handle.emit("title_change", [conversation_id.to_string(), name]);
     Ok(())
 }
+
+// 导出对话到用户选择的文件，format 为 markdown、html、json 或 pdf，scope 为 branch（当前分支）或 tree（包含所有重新生成的回答）
+#[tauri::command]
+pub async fn export_conversation(
+    app_handle: tauri::AppHandle,
+    name_cache_state: tauri::State<'_, NameCacheState>,
+    conversation_id: i64,
+    format: String,
+    scope: String,
+    path: String,
+) -> Result<(), String> {
+    let format =
+        ConversationExportFormat::parse(&format).ok_or(format!("不支持的导出格式: {}", format))?;
+    let scope = ExportScope::parse(&scope).ok_or(format!("不支持的导出范围: {}", scope))?;
+    let db = ConversationDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let conversation = db
+        .conversation_repo()
+        .unwrap()
+        .read(conversation_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Conversation not found".to_string())?;
+    let messages = db
+        .message_repo()
+        .unwrap()
+        .list_by_conversation_id(conversation_id)
+        .map_err(|e| e.to_string())?;
+
+    let assistant_name = name_cache_state
+        .assistant_names
+        .lock()
+        .await
+        .get(&conversation.assistant_id.unwrap_or(0))
+        .cloned()
+        .unwrap_or_else(|| "未知".to_string());
+
+    let export = build_conversation_export(&conversation, &assistant_name, messages, scope);
+    let content = render_conversation(&export, format)?;
+    std::fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))
+}
@@ -0,0 +1,282 @@
+use tauri::{Emitter, State};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::api::llm::get_provider;
+use crate::db::conversation_db::ConversationDatabase;
+use crate::db::eval_db::{
+    EvalDatabase, EvalModel, EvalModelSummary, EvalQuestion, EvalQuestionSet, EvalResult, EvalRun,
+};
+use crate::db::llm_db::LLMDatabase;
+use crate::eval::{run_eval, score_regex, EvalCase, EvalOptions, EvalOutcome, EvalTarget, Scorer};
+use crate::EvalRunState;
+
+const DEFAULT_CONCURRENCY: usize = 4;
+const DEFAULT_REQUESTS_PER_MINUTE: u32 = 60;
+
+#[tauri::command]
+pub fn list_eval_question_sets(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<EvalQuestionSet>, String> {
+    let db = EvalDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.get_question_sets().map_err(|e| e.to_string())
+}
+
+// id 为空时新建题集，返回题集 id
+#[tauri::command]
+pub fn save_eval_question_set(
+    app_handle: tauri::AppHandle,
+    id: Option<i64>,
+    name: String,
+    description: Option<String>,
+) -> Result<i64, String> {
+    if name.trim().is_empty() {
+        return Err("题集名称不能为空".to_string());
+    }
+    let db = EvalDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    match id {
+        Some(id) => db
+            .update_question_set(id, &name, description.as_deref())
+            .map(|_| id),
+        None => db.add_question_set(&name, description.as_deref()),
+    }
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_eval_question_set(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db = EvalDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.delete_question_set(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_eval_questions(
+    app_handle: tauri::AppHandle,
+    set_id: i64,
+) -> Result<Vec<EvalQuestion>, String> {
+    let db = EvalDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.get_questions(set_id).map_err(|e| e.to_string())
+}
+
+// id 为空时新建题目，保存前检查评分方式和正则表达式，返回题目 id
+#[tauri::command]
+pub fn save_eval_question(
+    app_handle: tauri::AppHandle,
+    id: Option<i64>,
+    set_id: i64,
+    prompt: String,
+    attachment_ids: Option<Vec<i64>>,
+    scorer: String,
+    expected: String,
+) -> Result<i64, String> {
+    if prompt.trim().is_empty() {
+        return Err("题目不能为空".to_string());
+    }
+    if Scorer::parse(&scorer).map_err(|e| e.to_string())? == Scorer::Regex {
+        score_regex(&expected, "").map_err(|e| e.to_string())?;
+    }
+    let attachment_ids = attachment_ids.unwrap_or_default();
+    let db = EvalDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    match id {
+        Some(id) => db
+            .update_question(id, &prompt, &attachment_ids, &scorer, &expected)
+            .map(|_| id),
+        None => db.add_question(set_id, &prompt, &attachment_ids, &scorer, &expected),
+    }
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_eval_question(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db = EvalDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.delete_question(id).map_err(|e| e.to_string())
+}
+
+fn load_target(llm_db: &LLMDatabase, model: &EvalModel) -> Result<EvalTarget, String> {
+    let detail = llm_db
+        .get_llm_model_detail(&model.provider_id, &model.model_code)
+        .map_err(|e| format!("找不到模型 {}: {}", model.model_code, e))?;
+    Ok(EvalTarget {
+        provider_id: model.provider_id,
+        model_code: detail.model.code.clone(),
+        provider: get_provider(detail.provider, detail.configs),
+    })
+}
+
+fn load_cases(
+    app_handle: &tauri::AppHandle,
+    questions: &[EvalQuestion],
+) -> Result<Vec<EvalCase>, String> {
+    let conversation_db = ConversationDatabase::new(app_handle).map_err(|e| e.to_string())?;
+    questions
+        .iter()
+        .map(|question| {
+            let attachments = if question.attachment_ids.is_empty() {
+                vec![]
+            } else {
+                conversation_db
+                    .attachment_repo()
+                    .map_err(|e| e.to_string())?
+                    .list_by_id(&question.attachment_ids)
+                    .map_err(|e| e.to_string())?
+            };
+            Ok(EvalCase {
+                question_id: question.id,
+                prompt: question.prompt.clone(),
+                attachments,
+                scorer: Scorer::parse(&question.scorer).map_err(|e| e.to_string())?,
+                expected: question.expected.clone(),
+            })
+        })
+        .collect()
+}
+
+// 在后台执行评测并立即返回评测 id，每道题的结果通过 eval_result_{run_id} 推送，
+// 结束时通过 eval_run_{run_id} 推送评测状态
+#[tauri::command]
+pub async fn start_eval_run(
+    app_handle: tauri::AppHandle,
+    eval_run_state: State<'_, EvalRunState>,
+    set_id: i64,
+    models: Vec<EvalModel>,
+    judge_model: Option<EvalModel>,
+    concurrency: Option<usize>,
+    requests_per_minute: Option<u32>,
+) -> Result<i64, String> {
+    if models.is_empty() {
+        return Err("请选择需要评测的模型".to_string());
+    }
+    let eval_db = EvalDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let questions = eval_db.get_questions(set_id).map_err(|e| e.to_string())?;
+    if questions.is_empty() {
+        return Err("题集中还没有题目".to_string());
+    }
+    let cases = load_cases(&app_handle, &questions)?;
+    if judge_model.is_none() && cases.iter().any(|c| c.scorer == Scorer::LlmJudge) {
+        return Err("题集中有使用模型评分的题目，请选择评分模型".to_string());
+    }
+
+    let llm_db = LLMDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let targets = models
+        .iter()
+        .map(|model| load_target(&llm_db, model))
+        .collect::<Result<Vec<_>, String>>()?;
+    let judge = judge_model
+        .as_ref()
+        .map(|model| load_target(&llm_db, model))
+        .transpose()?;
+    let options = EvalOptions {
+        concurrency: concurrency.unwrap_or(DEFAULT_CONCURRENCY),
+        requests_per_minute: requests_per_minute.unwrap_or(DEFAULT_REQUESTS_PER_MINUTE),
+    };
+
+    let run_id = eval_db
+        .add_run(
+            set_id,
+            &models,
+            judge_model.as_ref(),
+            (cases.len() * targets.len()) as i64,
+        )
+        .map_err(|e| e.to_string())?;
+    let cancel_token = CancellationToken::new();
+    eval_run_state
+        .tokens
+        .lock()
+        .await
+        .insert(run_id, cancel_token.clone());
+    let tokens = eval_run_state.tokens.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let (tx, mut rx) = mpsc::channel::<EvalOutcome>(32);
+        let save_results = async {
+            let db = match EvalDatabase::new(&app_handle) {
+                Ok(db) => db,
+                Err(e) => return Err(e.to_string()),
+            };
+            while let Some(outcome) = rx.recv().await {
+                let mut result = EvalResult {
+                    id: 0,
+                    run_id,
+                    question_id: outcome.question_id,
+                    provider_id: outcome.provider_id,
+                    model_code: outcome.model_code,
+                    answer: outcome.answer,
+                    score: outcome.score,
+                    passed: outcome.passed,
+                    reason: outcome.reason,
+                    error: outcome.error,
+                    duration_ms: outcome.duration_ms,
+                    created_time: String::new(),
+                };
+                result.id = db.add_result(&result).map_err(|e| e.to_string())?;
+                let _ = app_handle.emit(format!("eval_result_{}", run_id).as_str(), &result);
+            }
+            Ok(())
+        };
+        let (_, saved) = tokio::join!(
+            run_eval(
+                &cases,
+                &targets,
+                judge.as_ref(),
+                &options,
+                Some(tx),
+                cancel_token.clone(),
+            ),
+            save_results
+        );
+
+        tokens.lock().await.remove(&run_id);
+        let status = match saved {
+            Ok(_) if cancel_token.is_cancelled() => "cancelled",
+            Ok(_) => "finished",
+            Err(e) => {
+                println!("save eval result error: {}", e);
+                "failed"
+            }
+        };
+        if let Ok(db) = EvalDatabase::new(&app_handle) {
+            let _ = db.finish_run(run_id, status);
+            if let Ok(Some(run)) = db.get_run(run_id) {
+                let _ = app_handle.emit(format!("eval_run_{}", run_id).as_str(), &run);
+            }
+        }
+    });
+
+    Ok(run_id)
+}
+
+#[tauri::command]
+pub async fn cancel_eval_run(
+    eval_run_state: State<'_, EvalRunState>,
+    run_id: i64,
+) -> Result<(), String> {
+    if let Some(token) = eval_run_state.tokens.lock().await.get(&run_id) {
+        token.cancel();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_eval_runs(app_handle: tauri::AppHandle, set_id: i64) -> Result<Vec<EvalRun>, String> {
+    let db = EvalDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.get_runs(set_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_eval_run_results(
+    app_handle: tauri::AppHandle,
+    run_id: i64,
+) -> Result<Vec<EvalResult>, String> {
+    let db = EvalDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.get_results(run_id).map_err(|e| e.to_string())
+}
+
+// 题集每次评测中各个模型的得分，用于对比模型随时间的变化
+#[tauri::command]
+pub fn get_eval_model_summaries(
+    app_handle: tauri::AppHandle,
+    set_id: i64,
+) -> Result<Vec<EvalModelSummary>, String> {
+    let db = EvalDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.get_model_summaries(set_id).map_err(|e| e.to_string())
+}
@@ -0,0 +1,139 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::conversation_db::{
+    ConversationDatabase, FeedbackFilter, FeedbackRecord, MessageFeedback,
+};
+
+// rating 为 1 表示好，-1 表示不好，0 表示取消反馈
+#[tauri::command]
+pub fn set_message_feedback(
+    app_handle: tauri::AppHandle,
+    message_id: i64,
+    rating: i64,
+    tags: Option<Vec<String>>,
+    comment: Option<String>,
+) -> Result<(), String> {
+    let db = ConversationDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let repo = db.feedback_repo().map_err(|e| e.to_string())?;
+    let tags = normalize_tags(tags.unwrap_or_default());
+    match rating {
+        0 => repo.delete_by_message_id(message_id),
+        1 | -1 => repo.upsert(
+            message_id,
+            rating,
+            &tags,
+            comment.as_deref().filter(|c| !c.trim().is_empty()),
+        ),
+        _ => return Err(format!("不支持的评价: {}", rating)),
+    }
+    .map_err(|e| e.to_string())
+}
+
+// 去掉空白和重复的标签，保留第一次出现的顺序
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty() && seen.insert(t.clone()))
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_message_feedback(
+    app_handle: tauri::AppHandle,
+    message_id: i64,
+) -> Result<Option<MessageFeedback>, String> {
+    let db = ConversationDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.feedback_repo()
+        .map_err(|e| e.to_string())?
+        .read_by_message_id(message_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_message_feedback(
+    app_handle: tauri::AppHandle,
+    filter: Option<FeedbackFilter>,
+    limit: Option<usize>,
+) -> Result<Vec<FeedbackRecord>, String> {
+    let db = ConversationDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.feedback_repo()
+        .map_err(|e| e.to_string())?
+        .list(&filter.unwrap_or_default(), limit)
+        .map_err(|e| e.to_string())
+}
+
+// 某个助手下某个模型的反馈汇总，用来挑选助手的默认模型
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FeedbackStats {
+    pub assistant_id: Option<i64>,
+    pub llm_model_name: Option<String>,
+    pub total: i64,
+    pub positive: i64,
+    pub negative: i64,
+    pub positive_rate: f64,
+    pub tag_counts: BTreeMap<String, i64>,
+}
+
+pub fn aggregate_feedback(records: &[FeedbackRecord]) -> Vec<FeedbackStats> {
+    let mut groups: BTreeMap<(Option<i64>, Option<String>), FeedbackStats> = BTreeMap::new();
+    for record in records {
+        let key = (record.assistant_id, record.llm_model_name.clone());
+        let stats = groups.entry(key).or_insert_with(|| FeedbackStats {
+            assistant_id: record.assistant_id,
+            llm_model_name: record.llm_model_name.clone(),
+            total: 0,
+            positive: 0,
+            negative: 0,
+            positive_rate: 0.0,
+            tag_counts: BTreeMap::new(),
+        });
+        stats.total += 1;
+        if record.feedback.rating > 0 {
+            stats.positive += 1;
+        } else {
+            stats.negative += 1;
+        }
+        for tag in &record.feedback.tags {
+            *stats.tag_counts.entry(tag.clone()).or_insert(0) += 1;
+        }
+    }
+
+    let mut stats = groups
+        .into_values()
+        .map(|mut s| {
+            s.positive_rate = s.positive as f64 / s.total as f64;
+            s
+        })
+        .collect::<Vec<_>>();
+    // 好评率高、样本多的排在前面
+    stats.sort_by(|a, b| {
+        b.positive_rate
+            .partial_cmp(&a.positive_rate)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.total.cmp(&a.total))
+    });
+    stats
+}
+
+#[tauri::command]
+pub fn get_feedback_stats(
+    app_handle: tauri::AppHandle,
+    assistant_id: Option<i64>,
+) -> Result<Vec<FeedbackStats>, String> {
+    let db = ConversationDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let records = db
+        .feedback_repo()
+        .map_err(|e| e.to_string())?
+        .list(
+            &FeedbackFilter {
+                assistant_id,
+                ..Default::default()
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(aggregate_feedback(&records))
+}
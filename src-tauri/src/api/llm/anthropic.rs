@@ -1,4 +1,4 @@
-use super::ModelProvider;
+use super::{with_audio_fallback, ModelProvider};
 use crate::{
     api::llm_api::LlmModel,
     db::{
@@ -160,7 +160,7 @@ impl ModelProvider for AnthropicProvider {
                     if attachment_list.len() > 0 {
                         let content_array = vec![json!({
                             "type": "text",
-                            "text": content
+                            "text": with_audio_fallback(content, attachment_list)
                         })];
 
                         let mut images = attachment_list
@@ -294,7 +294,7 @@ impl ModelProvider for AnthropicProvider {
                     if attachment_list.len() > 0 {
                         let content_array = vec![json!({
                             "type": "text",
-                            "text": content
+                            "text": with_audio_fallback(content, attachment_list)
                         })];
 
                         let mut images = attachment_list
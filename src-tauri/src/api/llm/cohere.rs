@@ -14,7 +14,7 @@ use crate::{
     db::{conversation_db::MessageAttachment, llm_db::LLMProviderConfig},
 };
 
-use super::ModelProvider;
+use super::{with_audio_fallback, ModelProvider};
 use futures::StreamExt;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -76,14 +76,14 @@ impl ModelProvider for CohereProvider {
 
             let json_messages = messages
                 .iter()
-                .map(|(message_type, content, _)| {
+                .map(|(message_type, content, attachment_list)| {
                     let role = match message_type.as_str() {
                         "assistant" => "chatbot",
                         _ => message_type,
                     };
                     json!({
                         "role": role.to_uppercase(),
-                        "message": content
+                        "message": with_audio_fallback(content, attachment_list)
                     })
                 })
                 .collect::<Vec<serde_json::Value>>();
@@ -177,14 +177,14 @@ impl ModelProvider for CohereProvider {
             }
             let json_messages = messages
                 .iter()
-                .map(|(message_type, content, _)| {
+                .map(|(message_type, content, attachment_list)| {
                     let role = match message_type.as_str() {
                         "assistant" => "chatbot",
                         _ => message_type,
                     };
                     json!({
                         "role": role.to_uppercase(),
-                        "message": content
+                        "message": with_audio_fallback(content, attachment_list)
                     })
                 })
                 .collect::<Vec<serde_json::Value>>();
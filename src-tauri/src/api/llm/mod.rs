@@ -12,7 +12,7 @@ use tokio_util::sync::CancellationToken;
 
 use crate::db::{
     assistant_db::AssistantModelConfig,
-    conversation_db::MessageAttachment,
+    conversation_db::{AttachmentType, MessageAttachment},
     llm_db::{LLMProvider, LLMProviderConfig},
 };
 
@@ -48,6 +48,21 @@ pub trait ModelProvider: Send + Sync {
     fn models(&self) -> BoxFuture<'static, Result<Vec<LlmModel>>>;
 }
 
+// 提供商不支持音频输入时，bang 产生的音频附件不能静默丢弃，在文本末尾说明音频没有发送
+pub fn with_audio_fallback(content: &str, attachments: &[MessageAttachment]) -> String {
+    let count = attachments
+        .iter()
+        .filter(|a| a.attachment_type == AttachmentType::Audio)
+        .count();
+    if count == 0 {
+        return content.to_string();
+    }
+    format!(
+        "{}\n\n[附带的 {} 个音频附件未发送，当前模型提供商不支持音频输入]",
+        content, count
+    )
+}
+
 pub fn get_provider(
     provider: LLMProvider,
     llm_provider_config: Vec<LLMProviderConfig>,
@@ -16,7 +16,7 @@ use std::collections::HashMap;
 use tokio::{select, sync::mpsc};
 use tokio_util::sync::CancellationToken;
 
-use super::ModelProvider;
+use super::{with_audio_fallback, ModelProvider};
 
 #[derive(Serialize, Deserialize, Debug)]
 struct ModelsResponse {
@@ -98,7 +98,7 @@ impl ModelProvider for OllamaProvider {
                             .collect::<Vec<String>>();
                         json!({
                             "role": message_type,
-                            "content": content,
+                            "content": with_audio_fallback(content, attachment_list),
                             "images": images,
                         })
                     } else {
@@ -211,7 +211,7 @@ impl ModelProvider for OllamaProvider {
                             .collect::<Vec<String>>();
                         json!({
                             "role": message_type,
-                            "content": content,
+                            "content": with_audio_fallback(content, attachment_list),
                             "images": images,
                         })
                     } else {
@@ -81,19 +81,11 @@ impl ModelProvider for OpenAIProvider {
                             "type": "text",
                             "text": content
                         })];
-                        let images = attachment_list
+                        let media = attachment_list
                             .iter()
-                            .filter(|a| a.attachment_type == AttachmentType::Image)
-                            .map(|a| {
-                                json!({
-                                    "type": "image_url",
-                                    "image_url": {
-                                        "url": a.attachment_content.clone().unwrap()
-                                    }
-                                })
-                            })
+                            .filter_map(attachment_to_content)
                             .collect::<Vec<Value>>();
-                        content_array.extend(images);
+                        content_array.extend(media);
 
                         json!({
                             "role": message_type,
@@ -198,19 +190,11 @@ impl ModelProvider for OpenAIProvider {
                             "type": "text",
                             "text": content
                         })];
-                        let images = attachment_list
+                        let media = attachment_list
                             .iter()
-                            .filter(|a| a.attachment_type == AttachmentType::Image)
-                            .map(|a| {
-                                json!({
-                                    "type": "image_url",
-                                    "image_url": {
-                                        "url": a.attachment_content.clone().unwrap()
-                                    }
-                                })
-                            })
+                            .filter_map(attachment_to_content)
                             .collect::<Vec<Value>>();
-                        content_array.extend(images);
+                        content_array.extend(media);
 
                         json!({
                             "role": message_type,
@@ -389,3 +373,32 @@ impl ModelProvider for OpenAIProvider {
         })
     }
 }
+
+// 将附件转换为 content 数组中的元素，音频使用 input_audio 格式
+fn attachment_to_content(attachment: &MessageAttachment) -> Option<Value> {
+    let content = attachment.attachment_content.clone()?;
+    match attachment.attachment_type {
+        AttachmentType::Image => Some(json!({
+            "type": "image_url",
+            "image_url": {
+                "url": content
+            }
+        })),
+        AttachmentType::Audio => {
+            // data:audio/mpeg;base64,xxx
+            let (header, data) = content.split_once(";base64,")?;
+            let format = match header.trim_start_matches("data:audio/") {
+                "mpeg" | "mp3" => "mp3",
+                other => other,
+            };
+            Some(json!({
+                "type": "input_audio",
+                "input_audio": {
+                    "data": data,
+                    "format": format
+                }
+            }))
+        }
+        _ => None,
+    }
+}
@@ -1,8 +1,18 @@
 pub mod ai_api;
+pub mod artifact_workspace_api;
 pub mod artifacts_api;
 pub mod assistant_api;
+pub mod assistant_pipeline;
 pub mod attachment_api;
 pub mod conversation_api;
-mod llm;
+pub mod eval_api;
+pub mod feedback_api;
+pub(crate) mod llm;
 pub mod llm_api;
+pub mod prompt_optimize_api;
+pub mod schedule_api;
 pub mod system_api;
+pub mod workflow_api;
+
+#[cfg(test)]
+mod tests;
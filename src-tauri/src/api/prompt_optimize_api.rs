@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tokio_util::sync::CancellationToken;
+
+use crate::api::assistant_api::{get_assistant, record_prompt_version, AssistantDetail};
+use crate::api::llm::get_provider;
+use crate::db::assistant_db::{AssistantDatabase, AssistantModelConfig};
+use crate::db::conversation_db::{ConversationDatabase, FeedbackExample};
+use crate::db::llm_db::LLMDatabase;
+use crate::utils::diff::{diff_lines, DiffLine};
+use crate::FeatureConfigState;
+
+const DEFAULT_OPTIMIZE_PROMPT: &str = "你是一名提示词优化专家。用户会提供一个AI助手当前的系统提示词，以及该助手被用户评价为不好的若干问答示例和用户意见。
+请分析这些回答不好的原因，在保留原提示词意图的前提下改进系统提示词，使助手以后能更好地回答这类问题。
+只输出改进后的完整系统提示词，不要输出任何解释。";
+// 参与优化的反馈示例数量上限
+const MAX_EXAMPLES: u32 = 10;
+// 每条回答放入优化请求的最大字符数
+const MAX_ANSWER_CHARS: usize = 2000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PromptOptimization {
+    pub assistant_id: i64,
+    pub current_prompt: String,
+    pub proposed_prompt: String,
+    pub diff: Vec<DiffLine>,
+    pub example_count: usize,
+}
+
+fn build_optimize_request(prompt: &str, examples: &[FeedbackExample]) -> String {
+    let mut content = format!("# 当前系统提示词\n{}\n\n# 评价不好的回答\n", prompt);
+    for (index, example) in examples.iter().enumerate() {
+        content.push_str(&format!(
+            "\n## 示例 {}\n### 问题\n{}\n### 回答\n{}\n",
+            index + 1,
+            example.question,
+            example
+                .answer
+                .chars()
+                .take(MAX_ANSWER_CHARS)
+                .collect::<String>()
+        ));
+        if let Some(comment) = &example.comment {
+            content.push_str(&format!("### 用户意见\n{}\n", comment));
+        }
+    }
+    content
+}
+
+// 模型有时会用代码块包住提示词，去掉外层的代码块标记
+fn strip_code_fence(text: &str) -> String {
+    let text = text.trim();
+    match text.strip_prefix("```") {
+        Some(rest) if text.ends_with("```") && rest.len() >= 3 => {
+            let rest = &rest[..rest.len() - 3];
+            // 去掉代码块的语言标记行
+            rest.split_once('\n')
+                .map(|(_, body)| body)
+                .unwrap_or(rest)
+                .trim()
+                .to_string()
+        }
+        _ => text.to_string(),
+    }
+}
+
+// 根据用户评价不好的回答，让配置的模型给出改进后的提示词，确认后再保存为新版本
+#[tauri::command]
+pub async fn optimize_assistant_prompt(
+    app_handle: tauri::AppHandle,
+    feature_config_state: State<'_, FeatureConfigState>,
+    assistant_id: i64,
+) -> Result<PromptOptimization, String> {
+    let config = feature_config_state
+        .config_feature_map
+        .lock()
+        .await
+        .get("prompt_optimize")
+        .cloned()
+        .unwrap_or_default();
+    let provider_id = config
+        .get("provider_id")
+        .and_then(|c| c.value.parse::<i64>().ok())
+        .ok_or("没有配置提示词优化使用的模型".to_string())?;
+    let model_code = config
+        .get("model_code")
+        .map(|c| c.value.clone())
+        .filter(|v| !v.is_empty())
+        .ok_or("没有配置提示词优化使用的模型".to_string())?;
+    let optimize_prompt = config
+        .get("prompt")
+        .map(|c| c.value.clone())
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_OPTIMIZE_PROMPT.to_string());
+
+    let current_prompt = get_assistant(app_handle.clone(), assistant_id)?
+        .prompts
+        .first()
+        .map(|p| p.prompt.clone())
+        .unwrap_or_default();
+    let examples = ConversationDatabase::new(&app_handle)
+        .map_err(|e| e.to_string())?
+        .feedback_repo()
+        .map_err(|e| e.to_string())?
+        .list_negative_examples(assistant_id, MAX_EXAMPLES)
+        .map_err(|e| e.to_string())?;
+    if examples.is_empty() {
+        return Err("该助手还没有评价不好的回答，无法优化".to_string());
+    }
+
+    let model_detail = LLMDatabase::new(&app_handle)
+        .map_err(|e| e.to_string())?
+        .get_llm_model_detail(&provider_id, &model_code)
+        .map_err(|e| e.to_string())?;
+    let provider = get_provider(model_detail.provider, model_detail.configs);
+    let response = provider
+        .chat(
+            -1,
+            vec![
+                ("system".to_string(), optimize_prompt, vec![]),
+                (
+                    "user".to_string(),
+                    build_optimize_request(&current_prompt, &examples),
+                    vec![],
+                ),
+            ],
+            vec![AssistantModelConfig {
+                id: 0,
+                assistant_id: 0,
+                assistant_model_id: 0,
+                name: "model".to_string(),
+                value: Some(model_detail.model.code),
+                value_type: "string".to_string(),
+            }],
+            CancellationToken::new(),
+        )
+        .await
+        .map_err(|e| format!("优化提示词失败: {}", e))?;
+
+    let proposed_prompt = strip_code_fence(&response);
+    if proposed_prompt.is_empty() {
+        return Err("模型没有返回优化后的提示词".to_string());
+    }
+    Ok(PromptOptimization {
+        assistant_id,
+        diff: diff_lines(&current_prompt, &proposed_prompt),
+        current_prompt,
+        proposed_prompt,
+        example_count: examples.len(),
+    })
+}
+
+// 保存优化后的提示词，作为助手提示词的一个新版本
+#[tauri::command]
+pub fn save_optimized_prompt(
+    app_handle: tauri::AppHandle,
+    assistant_id: i64,
+    prompt: String,
+) -> Result<AssistantDetail, String> {
+    let assistant_db = AssistantDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    match assistant_db
+        .get_assistant_prompt(assistant_id)
+        .map_err(|e| e.to_string())?
+        .first()
+    {
+        Some(current) => assistant_db.update_assistant_prompt(current.id, &prompt),
+        None => assistant_db
+            .add_assistant_prompt(assistant_id, &prompt)
+            .map(|_| ()),
+    }
+    .map_err(|e| e.to_string())?;
+    record_prompt_version(&assistant_db, assistant_id)?;
+    get_assistant(app_handle, assistant_id)
+}
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Utc};
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::api::ai_api::run_assistant_in_new_conversation;
+use crate::db::schedule_db::{ScheduleDatabase, ScheduledTask, ScheduledTaskRun};
+use crate::scheduler::{
+    decide, CronExpression, MissedRunPolicy, ScheduleDecision, ScheduleNotice, NOTICE_MENU_PREFIX,
+};
+use crate::window::{open_chat_ui_window, open_config_window};
+use crate::{refresh_tray_menu, SchedulerState, TemplateEngineState};
+
+// 检查到期任务的间隔
+const CHECK_INTERVAL_SECONDS: u64 = 30;
+// 托盘菜单中最多保留的执行结果
+const MAX_NOTICES: usize = 5;
+
+fn compute_next_run_time(
+    cron: &str,
+    after: DateTime<Local>,
+) -> Result<Option<DateTime<Utc>>, String> {
+    let expression = CronExpression::parse(cron)?;
+    Ok(expression
+        .next_after(after)
+        .map(|time| time.with_timezone(&Utc)))
+}
+
+#[tauri::command]
+pub fn list_scheduled_tasks(app_handle: tauri::AppHandle) -> Result<Vec<ScheduledTask>, String> {
+    let db = ScheduleDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.get_tasks().map_err(|e| e.to_string())
+}
+
+// id 为 0 时新建，保存时按当前时间重新计算下一次执行时间
+#[tauri::command]
+pub fn save_scheduled_task(
+    app_handle: tauri::AppHandle,
+    task: ScheduledTask,
+) -> Result<ScheduledTask, String> {
+    if task.name.trim().is_empty() {
+        return Err("任务名称不能为空".to_string());
+    }
+    let next_run_time = compute_next_run_time(&task.cron, Local::now())?;
+    if next_run_time.is_none() {
+        return Err("cron 表达式不会触发执行".to_string());
+    }
+    let task = ScheduledTask {
+        next_run_time: if task.enabled { next_run_time } else { None },
+        ..task
+    };
+    let db = ScheduleDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let id = if task.id == 0 {
+        db.add_task(&task)
+    } else {
+        db.update_task(&task).map(|_| task.id)
+    }
+    .map_err(|e| e.to_string())?;
+    db.get_task(id)
+        .map_err(|e| e.to_string())?
+        .ok_or("定时任务不存在".to_string())
+}
+
+#[tauri::command]
+pub fn delete_scheduled_task(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db = ScheduleDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.delete_task(id).map_err(|e| e.to_string())
+}
+
+// 预览 cron 表达式接下来的几次执行时间
+#[tauri::command]
+pub fn preview_cron_schedule(cron: String, count: Option<usize>) -> Result<Vec<String>, String> {
+    let expression = CronExpression::parse(&cron)?;
+    let mut times = vec![];
+    let mut after = Local::now();
+    for _ in 0..count.unwrap_or(5) {
+        match expression.next_after(after) {
+            Some(time) => {
+                times.push(time.format("%Y-%m-%d %H:%M").to_string());
+                after = time;
+            }
+            None => break,
+        }
+    }
+    Ok(times)
+}
+
+// 立即执行一次，不影响下一次计划执行的时间
+#[tauri::command]
+pub fn run_scheduled_task_now(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db = ScheduleDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let task = db
+        .get_task(id)
+        .map_err(|e| e.to_string())?
+        .ok_or("定时任务不存在".to_string())?;
+    tauri::async_runtime::spawn(async move {
+        execute_task(&app_handle, task, Utc::now(), false).await;
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_scheduled_task_runs(
+    app_handle: tauri::AppHandle,
+    task_id: i64,
+) -> Result<Vec<ScheduledTaskRun>, String> {
+    let db = ScheduleDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.get_runs(task_id).map_err(|e| e.to_string())
+}
+
+// 在对话窗口中打开定时任务生成的对话
+#[tauri::command]
+pub async fn open_scheduled_conversation(
+    app_handle: tauri::AppHandle,
+    scheduler_state: State<'_, SchedulerState>,
+    conversation_id: i64,
+) -> Result<(), String> {
+    match app_handle.get_webview_window("chat_ui") {
+        Some(window) => window
+            .emit("open_conversation", conversation_id)
+            .map_err(|e| e.to_string())?,
+        // 窗口还没创建时监听不到事件，由窗口加载完成后主动获取
+        None => *scheduler_state.pending_conversation.lock().await = Some(conversation_id),
+    }
+    open_chat_ui_window(app_handle).await
+}
+
+#[tauri::command]
+pub async fn take_pending_conversation(
+    scheduler_state: State<'_, SchedulerState>,
+) -> Result<Option<i64>, String> {
+    Ok(scheduler_state.pending_conversation.lock().await.take())
+}
+
+// 点击托盘菜单中的执行结果：成功的打开对话，失败的打开设置窗口查看执行记录
+pub fn open_schedule_notice(app_handle: &tauri::AppHandle, menu_id: &str) {
+    let Some(run_id) = menu_id
+        .strip_prefix(NOTICE_MENU_PREFIX)
+        .and_then(|id| id.parse::<i64>().ok())
+    else {
+        return;
+    };
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let scheduler_state = app_handle.state::<SchedulerState>();
+        let notice = {
+            let mut notices = scheduler_state.notices.lock().await;
+            let notice = notices
+                .iter()
+                .position(|n| n.run_id == run_id)
+                .map(|index| notices.remove(index));
+            let _ = refresh_tray_menu(&app_handle, &notices);
+            notice
+        };
+        let result = match notice.and_then(|n| n.conversation_id) {
+            Some(conversation_id) => {
+                open_scheduled_conversation(app_handle.clone(), scheduler_state, conversation_id)
+                    .await
+            }
+            None => open_config_window(app_handle.clone()).await,
+        };
+        if let Err(e) = result {
+            println!("打开定时任务结果失败: {}", e);
+        }
+    });
+}
+
+// 后台定时检查到期的任务，启动后立即检查一次，处理程序未运行期间错过的任务
+pub fn start_scheduler(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(CHECK_INTERVAL_SECONDS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = check_due_tasks(&app_handle) {
+                println!("检查定时任务失败: {}", e);
+            }
+        }
+    });
+}
+
+fn check_due_tasks(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let db = ScheduleDatabase::new(app_handle).map_err(|e| e.to_string())?;
+    let now = Local::now();
+    for task in db.get_tasks().map_err(|e| e.to_string())? {
+        let Some(next_run_time) = task.next_run_time.filter(|_| task.enabled) else {
+            continue;
+        };
+        let policy = MissedRunPolicy::parse(&task.missed_policy);
+        let decision = decide(next_run_time.with_timezone(&Local), now, policy);
+        if decision == ScheduleDecision::Wait {
+            continue;
+        }
+        // 先更新下一次执行时间，避免执行时间超过检查间隔时重复执行
+        let next = compute_next_run_time(&task.cron, now).unwrap_or(None);
+        db.update_next_run_time(task.id, next)
+            .map_err(|e| e.to_string())?;
+        match decision {
+            ScheduleDecision::Run { missed } => {
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    execute_task(&app_handle, task, next_run_time, missed).await;
+                });
+            }
+            ScheduleDecision::Skip => {
+                let run_id = db
+                    .add_run(task.id, next_run_time, true)
+                    .map_err(|e| e.to_string())?;
+                db.finish_run(run_id, "skipped", None, None)
+                    .map_err(|e| e.to_string())?;
+            }
+            ScheduleDecision::Wait => {}
+        }
+    }
+    Ok(())
+}
+
+// 执行任务并把结果保存为新对话，执行结束后在托盘菜单中提示
+async fn execute_task(
+    app_handle: &tauri::AppHandle,
+    task: ScheduledTask,
+    scheduled_time: DateTime<Utc>,
+    missed: bool,
+) {
+    let run_id = match ScheduleDatabase::new(app_handle).and_then(|db| {
+        db.update_last_run_time(task.id, Utc::now())?;
+        db.add_run(task.id, scheduled_time, missed)
+    }) {
+        Ok(run_id) => run_id,
+        Err(e) => {
+            println!("记录定时任务执行失败: {}", e);
+            return;
+        }
+    };
+
+    let template_engine = app_handle
+        .state::<TemplateEngineState>()
+        .engine
+        .lock()
+        .await
+        .clone();
+    let scheduled_time = scheduled_time.with_timezone(&Local);
+    // 提示词中可以通过 !task_name 和 !scheduled_time 引用任务信息
+    let mut template_context = HashMap::new();
+    template_context.insert("task_name".to_string(), task.name.clone());
+    template_context.insert(
+        "scheduled_time".to_string(),
+        scheduled_time.format("%Y-%m-%d %H:%M").to_string(),
+    );
+    let result = run_assistant_in_new_conversation(
+        app_handle,
+        &template_engine,
+        task.assistant_id,
+        &task.prompt,
+        &template_context,
+        format!("{} {}", task.name, scheduled_time.format("%m-%d %H:%M")),
+    )
+    .await;
+
+    let (status, conversation_id, error) = match result {
+        Ok(conversation) => ("success", Some(conversation.id), None),
+        Err(e) => ("failed", None, Some(e.to_string())),
+    };
+    if let Ok(db) = ScheduleDatabase::new(app_handle) {
+        let _ = db.finish_run(run_id, status, conversation_id, error.as_deref());
+    }
+    let _ = app_handle.emit("scheduled_task_run_finished", task.id);
+
+    let notice = ScheduleNotice {
+        run_id,
+        conversation_id,
+        text: match error {
+            None => format!("定时任务「{}」已完成", task.name),
+            Some(_) => format!("定时任务「{}」执行失败", task.name),
+        },
+    };
+    // 系统通知提醒执行结果，托盘菜单中保留最近的结果供查看
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title("Aipp")
+        .body(&notice.text)
+        .show()
+    {
+        println!("发送系统通知失败: {}", e);
+    }
+    let scheduler_state = app_handle.state::<SchedulerState>();
+    let mut notices = scheduler_state.notices.lock().await;
+    notices.insert(0, notice);
+    notices.truncate(MAX_NOTICES);
+    let _ = refresh_tray_menu(app_handle, &notices);
+}
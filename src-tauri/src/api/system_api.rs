@@ -5,8 +5,9 @@ use tauri::{Manager, State};
 use crate::template_engine::{BangType, TemplateEngine};
 use crate::AppState;
 use crate::FeatureConfigState;
+use crate::TemplateEngineState;
 
-use crate::db::system_db::{FeatureConfig, SystemDatabase};
+use crate::db::system_db::{CustomBang, FeatureConfig, SystemDatabase};
 
 #[tauri::command]
 pub async fn get_all_feature_config(
@@ -20,21 +21,32 @@ pub async fn get_all_feature_config(
 pub async fn save_feature_config(
     app_handle: tauri::AppHandle,
     state: State<'_, FeatureConfigState>,
+    template_engine_state: State<'_, TemplateEngineState>,
     feature_code: String,
     config: HashMap<String, String>,
 ) -> Result<(), String> {
-    let db = SystemDatabase::new(&app_handle).map_err(|e| e.to_string())?;
-    let _ = db.delete_feature_config_by_feature_code(feature_code.as_str());
-    for (key, value) in config.iter() {
-        db.add_feature_config(&FeatureConfig {
-            id: None,
-            feature_code: feature_code.clone(),
-            key: key.clone(),
-            value: value.clone(),
-            data_type: "string".to_string(),
-            description: Some("".to_string()),
-        })
-        .map_err(|e| e.to_string())?;
+    let engine = {
+        let db = SystemDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+        let _ = db.delete_feature_config_by_feature_code(feature_code.as_str());
+        for (key, value) in config.iter() {
+            db.add_feature_config(&FeatureConfig {
+                id: None,
+                feature_code: feature_code.clone(),
+                key: key.clone(),
+                value: value.clone(),
+                data_type: "string".to_string(),
+                description: Some("".to_string()),
+            })
+            .map_err(|e| e.to_string())?;
+        }
+        if feature_code == "bang" {
+            Some(build_template_engine(&app_handle, &db)?)
+        } else {
+            None
+        }
+    };
+    if let Some(engine) = engine {
+        *template_engine_state.engine.lock().await = engine;
     }
 
     // 更新内存状态
@@ -75,8 +87,10 @@ pub async fn open_data_folder(app: tauri::AppHandle) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn get_bang_list() -> Result<Vec<(String, String, String, BangType)>, String> {
-    let engine = TemplateEngine::new();
+pub async fn get_bang_list(
+    template_engine_state: State<'_, TemplateEngineState>,
+) -> Result<Vec<(String, String, String, BangType)>, String> {
+    let engine = template_engine_state.engine.lock().await.clone();
     let mut list = vec![];
     for bang in engine.get_commands().iter() {
         list.push((
@@ -94,3 +108,79 @@ pub async fn get_selected_text_api(state: tauri::State<'_, AppState>) -> Result<
     let selected_text = state.selected_text.lock().await;
     Ok(selected_text.clone())
 }
+
+#[tauri::command]
+pub async fn list_custom_bangs(app_handle: tauri::AppHandle) -> Result<Vec<CustomBang>, String> {
+    let db = SystemDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.get_custom_bangs().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_custom_bang(
+    app_handle: tauri::AppHandle,
+    template_engine_state: State<'_, TemplateEngineState>,
+    bang: CustomBang,
+) -> Result<i64, String> {
+    if !matches!(
+        bang.bang_kind.as_str(),
+        "text" | "command" | "http" | "file"
+    ) {
+        return Err(format!("不支持的bang类型: {}", bang.bang_kind));
+    }
+    if bang.name.is_empty() || !bang.name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err("bang名称只能包含字母、数字和下划线".to_string());
+    }
+
+    let (id, engine) = {
+        let db = SystemDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+        let id = if bang.id == 0 {
+            db.add_custom_bang(&bang).map_err(|e| e.to_string())?
+        } else {
+            db.update_custom_bang(&bang).map_err(|e| e.to_string())?;
+            bang.id
+        };
+        (id, build_template_engine(&app_handle, &db)?)
+    };
+
+    *template_engine_state.engine.lock().await = engine;
+    Ok(id)
+}
+
+#[tauri::command]
+pub async fn delete_custom_bang(
+    app_handle: tauri::AppHandle,
+    template_engine_state: State<'_, TemplateEngineState>,
+    id: i64,
+) -> Result<(), String> {
+    let engine = {
+        let db = SystemDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+        db.delete_custom_bang(id).map_err(|e| e.to_string())?;
+        build_template_engine(&app_handle, &db)?
+    };
+
+    *template_engine_state.engine.lock().await = engine;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn clear_bang_cache(
+    template_engine_state: State<'_, TemplateEngineState>,
+) -> Result<(), String> {
+    template_engine_state.engine.lock().await.clear_cache();
+    Ok(())
+}
+
+// 根据自定义bang和bang相关配置构建模板引擎，自定义bang或配置变化后需要重新构建
+pub fn build_template_engine(
+    app_handle: &tauri::AppHandle,
+    db: &SystemDatabase,
+) -> Result<TemplateEngine, String> {
+    let mut engine = TemplateEngine::new();
+    engine.set_app_handle(app_handle.clone());
+    engine.register_custom_bangs(&db.get_custom_bangs().map_err(|e| e.to_string())?);
+    engine.apply_feature_config(
+        &db.get_feature_config_by_module("bang")
+            .map_err(|e| e.to_string())?,
+    )?;
+    Ok(engine)
+}
@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use super::assistant_api::{
+    local_access_bangs, map_model, resolve_prompt_params, validate_prompt_param_names,
+    AssistantExportModel,
+};
+use super::assistant_pipeline::{config_schema, parse_compare_models, AssistantType};
+use super::conversation_api::build_message_details;
+use super::feedback_api::{aggregate_feedback, normalize_tags};
+use crate::db::assistant_db::AssistantPromptParam;
+use crate::db::conversation_db::{FeedbackRecord, Message, MessageFeedback};
+use crate::template_engine::TemplateEngine;
+
+fn param(name: &str, param_type: &str, value: &str) -> AssistantPromptParam {
+    AssistantPromptParam {
+        id: 0,
+        assistant_id: 0,
+        assistant_prompt_id: 0,
+        param_name: name.to_string(),
+        param_type: Some(param_type.to_string()),
+        param_value: Some(value.to_string()),
+    }
+}
+
+#[test]
+fn test_validate_prompt_param_names() {
+    let engine = TemplateEngine::new();
+    assert!(validate_prompt_param_names(
+        &[param("language", "text", ""), param("tone_2", "text", "")],
+        &engine
+    )
+    .is_ok());
+    assert!(validate_prompt_param_names(&[param("my-lang", "text", "")], &engine).is_err());
+    assert!(validate_prompt_param_names(&[param("", "text", "")], &engine).is_err());
+    assert!(validate_prompt_param_names(&[param("selected_text", "text", "")], &engine).is_err());
+    assert!(validate_prompt_param_names(
+        &[param("language", "text", ""), param("language", "text", "")],
+        &engine
+    )
+    .is_err());
+}
+
+#[test]
+fn test_validate_prompt_param_names_rejects_bang_names() {
+    let engine = TemplateEngine::new();
+    for name in ["s", "cd", "web"] {
+        let result = validate_prompt_param_names(&[param(name, "text", "")], &engine);
+        assert!(result.unwrap_err().contains("重名"), "{}", name);
+    }
+}
+
+#[test]
+fn test_resolve_prompt_params() {
+    let params = [
+        param("language", "text", "英文"),
+        param("count", "number", "3"),
+        param("tone", "select", "正式, 随意"),
+    ];
+
+    let result = resolve_prompt_params(&params, &HashMap::new()).unwrap();
+    assert_eq!(result["language"], "英文");
+    assert_eq!(result["count"], "3");
+    assert_eq!(result["tone"], "正式");
+
+    let values = HashMap::from([
+        ("language".to_string(), "日文".to_string()),
+        ("count".to_string(), " 5 ".to_string()),
+        ("tone".to_string(), "随意".to_string()),
+    ]);
+    let result = resolve_prompt_params(&params, &values).unwrap();
+    assert_eq!(result["language"], "日文");
+    assert_eq!(result["count"], "5");
+    assert_eq!(result["tone"], "随意");
+
+    let values = HashMap::from([("count".to_string(), "三".to_string())]);
+    assert!(resolve_prompt_params(&params, &values).is_err());
+    let values = HashMap::from([("tone".to_string(), "幽默".to_string())]);
+    assert!(resolve_prompt_params(&params, &values).is_err());
+}
+
+fn export_model(api_type: &str, provider_name: &str, model_code: &str) -> AssistantExportModel {
+    AssistantExportModel {
+        provider_api_type: api_type.to_string(),
+        provider_name: provider_name.to_string(),
+        model_code: model_code.to_string(),
+        alias: String::new(),
+    }
+}
+
+fn provider(
+    id: i64,
+    name: &str,
+    api_type: &str,
+    enabled: bool,
+) -> (i64, String, String, String, bool, bool) {
+    (
+        id,
+        name.to_string(),
+        api_type.to_string(),
+        String::new(),
+        false,
+        enabled,
+    )
+}
+
+fn local_model(
+    provider_id: i64,
+    code: &str,
+) -> (i64, String, i64, String, String, bool, bool, bool) {
+    (
+        0,
+        code.to_string(),
+        provider_id,
+        code.to_string(),
+        String::new(),
+        false,
+        false,
+        false,
+    )
+}
+
+#[test]
+fn test_map_model_prefers_same_provider_name() {
+    let providers = [
+        provider(1, "OpenAI", "openai_api", true),
+        provider(2, "中转", "openai_api", true),
+    ];
+    let models = [local_model(1, "gpt-4o"), local_model(2, "gpt-4o")];
+    let mut warnings = vec![];
+
+    let result = map_model(
+        &providers,
+        &models,
+        &export_model("openai_api", "中转", "gpt-4o"),
+        &mut warnings,
+    );
+    assert_eq!(result, (2, "gpt-4o".to_string()));
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_map_model_prefers_enabled_provider_with_model() {
+    let providers = [
+        provider(1, "禁用的", "openai_api", false),
+        provider(2, "没有模型的", "openai_api", true),
+        provider(3, "启用的", "openai_api", true),
+        provider(4, "Claude", "anthropic", true),
+    ];
+    let models = [
+        local_model(1, "gpt-4o"),
+        local_model(3, "gpt-4o"),
+        local_model(4, "gpt-4o"),
+    ];
+    let mut warnings = vec![];
+
+    let result = map_model(
+        &providers,
+        &models,
+        &export_model("openai_api", "其他电脑上的提供商", "gpt-4o"),
+        &mut warnings,
+    );
+    assert_eq!(result, (3, "gpt-4o".to_string()));
+    assert!(warnings.is_empty());
+
+    // 只有禁用的提供商有这个模型时也使用它
+    let result = map_model(
+        &providers[..2],
+        &models,
+        &export_model("openai_api", "", "gpt-4o"),
+        &mut warnings,
+    );
+    assert_eq!(result, (1, "gpt-4o".to_string()));
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_map_model_warnings() {
+    let providers = [
+        provider(1, "禁用的", "openai_api", false),
+        provider(2, "启用的", "openai_api", true),
+    ];
+    let mut warnings = vec![];
+
+    // 没有提供商有这个模型时使用启用的提供商，并提醒用户确认
+    let result = map_model(
+        &providers,
+        &[],
+        &export_model("openai_api", "", "gpt-4o"),
+        &mut warnings,
+    );
+    assert_eq!(result, (2, "gpt-4o".to_string()));
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("启用的"));
+
+    let result = map_model(
+        &providers,
+        &[],
+        &export_model("ollama", "", "qwen2"),
+        &mut warnings,
+    );
+    assert_eq!(result, (0, String::new()));
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[1].contains("ollama"));
+}
+
+#[test]
+fn test_local_access_bangs() {
+    let texts = [
+        "总结这个文件: !file(/tmp/a.txt) 当前目录 !cd".to_string(),
+        "!shell(git log) !env(HOME) !shell(ls)".to_string(),
+        "!shell_tool 和 !filename 不是内置的 bang".to_string(),
+    ];
+    assert_eq!(
+        local_access_bangs(texts.iter()),
+        vec!["file", "shell", "env"]
+    );
+    assert!(local_access_bangs(["!web(https://example.com)".to_string()].iter()).is_empty());
+}
+
+fn message(id: i64, message_type: &str, content: &str) -> Message {
+    Message {
+        id,
+        parent_id: None,
+        conversation_id: 1,
+        message_type: message_type.to_string(),
+        content: content.to_string(),
+        llm_model_id: Some(1),
+        llm_model_name: Some("gpt-4o".to_string()),
+        created_time: Utc::now(),
+        start_time: None,
+        finish_time: None,
+        token_count: 0,
+        prompt_version_id: None,
+        compare_to_id: None,
+    }
+}
+
+#[test]
+fn test_parse_compare_models() {
+    assert_eq!(
+        parse_compare_models("gpt-4o%%1, claude-3-5-sonnet %% 2,bad,%%3,qwen%%x"),
+        vec![
+            (1, "gpt-4o".to_string()),
+            (2, "claude-3-5-sonnet".to_string())
+        ]
+    );
+    assert!(parse_compare_models("").is_empty());
+}
+
+#[test]
+fn test_workflow_assistant_config_schema() {
+    let fields = config_schema(AssistantType::from_i64(Some(2)));
+    assert!(fields.iter().any(|f| f.name == "workflow"));
+    assert!(!fields.iter().any(|f| f.name == "workflow_steps"));
+    let fields = config_schema(AssistantType::from_i64(Some(1)));
+    assert!(fields.iter().any(|f| f.name == "compare_models"));
+}
+
+#[test]
+fn test_build_message_details_separates_compare_answers() {
+    let mut regenerated = message(4, "assistant", "重新生成的回答");
+    regenerated.parent_id = Some(3);
+    let mut compare_b = message(6, "assistant", "模型 B 的回答");
+    compare_b.llm_model_name = Some("model-b".to_string());
+    compare_b.compare_to_id = Some(3);
+    let mut compare_a = message(5, "assistant", "模型 A 的回答");
+    compare_a.llm_model_name = Some("model-a".to_string());
+    compare_a.compare_to_id = Some(3);
+    let rows = vec![
+        (message(1, "system", "你是一个助手"), None),
+        (message(2, "user", "你好"), None),
+        (message(3, "assistant", "主模型的回答"), None),
+        (compare_b, None),
+        (regenerated, None),
+        (compare_a, None),
+    ];
+
+    let details = build_message_details(rows);
+    assert_eq!(
+        details.iter().map(|m| m.id).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    let answer = &details[2];
+    assert_eq!(
+        answer.regenerate.iter().map(|m| m.id).collect::<Vec<_>>(),
+        vec![4]
+    );
+    assert_eq!(
+        answer
+            .compare
+            .iter()
+            .map(|m| m.llm_model_name.clone().unwrap())
+            .collect::<Vec<_>>(),
+        vec!["model-a", "model-b"]
+    );
+}
+
+#[test]
+fn test_normalize_tags() {
+    assert_eq!(
+        normalize_tags(vec![
+            "准确".to_string(),
+            " 啰嗦 ".to_string(),
+            "".to_string(),
+            "准确".to_string(),
+            "啰嗦".to_string(),
+        ]),
+        vec!["准确", "啰嗦"]
+    );
+}
+
+fn feedback_record(assistant_id: i64, model: &str, rating: i64, tags: &[&str]) -> FeedbackRecord {
+    FeedbackRecord {
+        feedback: MessageFeedback {
+            id: 0,
+            message_id: 0,
+            rating,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            comment: None,
+            created_time: Utc::now(),
+        },
+        conversation_id: 1,
+        assistant_id: Some(assistant_id),
+        llm_model_id: None,
+        llm_model_name: Some(model.to_string()),
+        prompt_version_id: None,
+    }
+}
+
+#[test]
+fn test_aggregate_feedback() {
+    let records = [
+        feedback_record(1, "gpt-4o", 1, &[]),
+        feedback_record(1, "gpt-4o", -1, &["啰嗦"]),
+        feedback_record(1, "gpt-4o-mini", 1, &[]),
+        feedback_record(1, "gpt-4o-mini", 1, &["准确"]),
+        feedback_record(1, "claude", 1, &[]),
+    ];
+    let stats = aggregate_feedback(&records);
+    let summary = stats
+        .iter()
+        .map(|s| (s.llm_model_name.clone().unwrap(), s.total, s.positive))
+        .collect::<Vec<_>>();
+    // 好评率相同时样本多的排在前面
+    assert_eq!(
+        summary,
+        vec![
+            ("gpt-4o-mini".to_string(), 2, 2),
+            ("claude".to_string(), 1, 1),
+            ("gpt-4o".to_string(), 2, 1),
+        ]
+    );
+    assert_eq!(stats[2].positive_rate, 0.5);
+    assert_eq!(stats[2].tag_counts.get("啰嗦"), Some(&1));
+}
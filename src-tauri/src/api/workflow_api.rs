@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tauri::{Emitter, Manager, State};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::api::assistant_api::{get_assistant, resolve_prompt_params};
+use crate::api::llm::get_provider;
+use crate::artifacts::sandbox::{run_sandboxed, SandboxOptions, ScriptLanguage};
+use crate::db::assistant_db::AssistantModelConfig;
+use crate::db::llm_db::LLMDatabase;
+use crate::db::workflow_db::{Workflow, WorkflowDatabase, WorkflowRun, WorkflowStepRun};
+use crate::state::message_token::MessageTokenManager;
+use crate::template_engine::TemplateEngine;
+use crate::workflow::{
+    parse_definition, StepExecutor, WorkflowDefinition, WorkflowEvent, WorkflowRunner,
+};
+use crate::{FeatureConfigState, TemplateEngineState};
+
+// 工作流运行在 MessageTokenManager 中使用负数 id，避免与消息 id 冲突
+fn run_token_key(run_id: i64) -> i64 {
+    -run_id
+}
+
+// 工作流中需要访问模型和本机环境的步骤
+struct AppStepExecutor {
+    app_handle: tauri::AppHandle,
+    template_engine: TemplateEngine,
+    // 工作流的输入，渲染助手提示词时作为上下文
+    inputs: HashMap<String, String>,
+    run_id: i64,
+}
+
+impl StepExecutor for AppStepExecutor {
+    fn llm(
+        &self,
+        assistant_id: i64,
+        prompt: String,
+        cancel_token: CancellationToken,
+    ) -> BoxFuture<'static, anyhow::Result<String>> {
+        let prepare = || -> Result<_, String> {
+            let assistant = get_assistant(self.app_handle.clone(), assistant_id)?;
+            let assistant_model = assistant
+                .model
+                .first()
+                .ok_or(format!("助手 {} 没有配置模型", assistant_id))?;
+            let model_detail = LLMDatabase::new(&self.app_handle)
+                .map_err(|e| e.to_string())?
+                .get_llm_model_detail(&assistant_model.provider_id, &assistant_model.model_code)
+                .map_err(|e| e.to_string())?;
+            let mut model_configs = assistant.model_configs.clone();
+            model_configs.push(AssistantModelConfig {
+                id: 0,
+                assistant_id,
+                assistant_model_id: model_detail.model.id,
+                name: "model".to_string(),
+                value: Some(model_detail.model.code.clone()),
+                value_type: "string".to_string(),
+            });
+            let mut prompt_context = self.inputs.clone();
+            prompt_context.extend(resolve_prompt_params(
+                &assistant.prompt_params,
+                &HashMap::new(),
+            )?);
+            Ok((
+                get_provider(model_detail.provider, model_detail.configs),
+                assistant.prompts.first().map(|p| p.prompt.clone()),
+                prompt_context,
+                model_configs,
+            ))
+        };
+        let (provider, system_prompt, prompt_context, model_configs) = match prepare() {
+            Ok(prepared) => prepared,
+            Err(e) => return async move { Err(anyhow!(e)) }.boxed(),
+        };
+        let template_engine = self.template_engine.clone();
+        let run_id = self.run_id;
+        async move {
+            // 助手提示词和对话中一样经过模板引擎渲染
+            let mut messages = vec![];
+            if let Some(system_prompt) = system_prompt {
+                let system_prompt = template_engine.parse(&system_prompt, &prompt_context).await;
+                messages.push(("system".to_string(), system_prompt, vec![]));
+            }
+            messages.push(("user".to_string(), prompt, vec![]));
+            provider
+                .chat(run_id, messages, model_configs, cancel_token)
+                .await
+        }
+        .boxed()
+    }
+
+    fn script(
+        &self,
+        language: String,
+        code: String,
+        cancel_token: CancellationToken,
+    ) -> BoxFuture<'static, anyhow::Result<String>> {
+        let app_handle = self.app_handle.clone();
+        async move {
+            // 使用 preview 配置中的解释器和限制，非 0 退出码视为步骤失败
+            let language = ScriptLanguage::from_lang(&language)
+                .ok_or(anyhow!("暂不支持该语言的代码执行: {}", language))?;
+            let preview_config = app_handle
+                .state::<FeatureConfigState>()
+                .config_feature_map
+                .lock()
+                .await
+                .get("preview")
+                .cloned()
+                .unwrap_or_default();
+            let options = SandboxOptions::from_config(language, &preview_config);
+            let result = run_sandboxed(language, &code, &options, None, None, cancel_token.clone())
+                .await
+                .map_err(|e| anyhow!(e))?;
+            if result.killed {
+                Err(anyhow!("代码执行已取消"))
+            } else if result.success() {
+                Ok(result.stdout.trim_end().to_string())
+            } else {
+                Err(anyhow!(result.summary()))
+            }
+        }
+        .boxed()
+    }
+}
+
+// 执行工作流并记录每个步骤的运行结果，步骤事件通过 workflow_run_{run_id} 推送，
+// 结束时通过 workflow_run_finished_{run_id} 推送运行结果，on_event 在每个事件保存后调用
+async fn execute_run(
+    app_handle: &tauri::AppHandle,
+    template_engine: &TemplateEngine,
+    definition: &WorkflowDefinition,
+    inputs: &HashMap<String, String>,
+    run_id: i64,
+    cancel_token: CancellationToken,
+    mut on_event: impl FnMut(&WorkflowEvent),
+) -> anyhow::Result<String> {
+    let executor = AppStepExecutor {
+        app_handle: app_handle.clone(),
+        template_engine: template_engine.clone(),
+        inputs: inputs.clone(),
+        run_id,
+    };
+    let (tx, mut rx) = mpsc::channel::<WorkflowEvent>(100);
+    let runner = WorkflowRunner {
+        template_engine,
+        executor: &executor,
+        events: tx,
+        cancel_token: cancel_token.clone(),
+    };
+    let event_name = format!("workflow_run_{}", run_id);
+    let save_events = async {
+        while let Some(event) = rx.recv().await {
+            if let Ok(db) = WorkflowDatabase::new(app_handle) {
+                let _ = match &event {
+                    WorkflowEvent::StepStarted { step_id } => db.start_step(run_id, step_id),
+                    WorkflowEvent::StepFinished {
+                        step_id,
+                        status,
+                        output,
+                        error,
+                    } => db.finish_step(run_id, step_id, status.as_str(), output, error.as_deref()),
+                    WorkflowEvent::LoopProgress { .. } => Ok(()),
+                };
+            }
+            let _ = app_handle.emit(event_name.as_str(), &event);
+            on_event(&event);
+        }
+    };
+    let run = async {
+        let result = runner.run(definition, inputs).await;
+        // 释放发送端，事件接收循环才会结束
+        drop(runner);
+        result
+    };
+    let (result, _) = tokio::join!(run, save_events);
+
+    let (status, output, error) = match &result {
+        Ok(output) => ("success", Some(output.as_str()), None),
+        Err(_) if cancel_token.is_cancelled() => ("cancelled", None, None),
+        Err(e) => ("failed", None, Some(e.to_string())),
+    };
+    if let Ok(db) = WorkflowDatabase::new(app_handle) {
+        let _ = db.finish_run(run_id, status, output, error.as_deref());
+        if let Ok(Some(run)) = db.get_run(run_id) {
+            let _ = app_handle.emit(format!("workflow_run_finished_{}", run_id).as_str(), &run);
+        }
+    }
+    result
+}
+
+// 工作流助手回答消息时执行的工作流，workflow 配置为工作流的 id 或名称，
+// 执行进度作为消息内容推送，返回工作流的最终输出
+pub(crate) async fn run_workflow_for_message(
+    app_handle: &tauri::AppHandle,
+    template_engine: &TemplateEngine,
+    workflow: Option<&String>,
+    inputs: HashMap<String, String>,
+    message_id: i64,
+    tx: &mpsc::Sender<(i64, String, bool)>,
+    cancel_token: CancellationToken,
+) -> anyhow::Result<String> {
+    let workflow = workflow
+        .map(|w| w.trim())
+        .filter(|w| !w.is_empty())
+        .ok_or(anyhow!("工作流助手没有配置工作流"))?;
+    let db = WorkflowDatabase::new(app_handle)?;
+    let workflow = db
+        .get_workflows()?
+        .into_iter()
+        .find(|w| w.id.to_string() == workflow || w.name == workflow)
+        .ok_or(anyhow!("工作流 {} 不存在", workflow))?;
+    let definition = parse_definition(&workflow.definition, template_engine)?;
+
+    let run_id = db.add_run(workflow.id, &serde_json::to_string(&inputs)?)?;
+
+    let step_names: HashMap<&str, &str> = definition
+        .steps
+        .iter()
+        .map(|s| (s.id.as_str(), s.name.as_deref().unwrap_or(s.id.as_str())))
+        .collect();
+    let total = definition.steps.len();
+    let mut started = 0;
+    let mut progress = String::new();
+    execute_run(
+        app_handle,
+        template_engine,
+        &definition,
+        &inputs,
+        run_id,
+        cancel_token,
+        |event| {
+            if let WorkflowEvent::StepStarted { step_id } = event {
+                started += 1;
+                let name = step_names.get(step_id.as_str()).unwrap_or(&"");
+                progress.push_str(&format!("> [{}/{}] {}\n", started, total, name));
+                // 每次推送的都是完整的进度，通道满时丢弃一次不影响显示
+                let _ = tx.try_send((message_id, progress.clone(), false));
+            }
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub fn list_workflows(app_handle: tauri::AppHandle) -> Result<Vec<Workflow>, String> {
+    let db = WorkflowDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.get_workflows().map_err(|e| e.to_string())
+}
+
+// 保存前校验定义，工作流名称取自定义中的 name，id 为空时新建
+#[tauri::command]
+pub async fn save_workflow(
+    app_handle: tauri::AppHandle,
+    template_engine_state: State<'_, TemplateEngineState>,
+    id: Option<i64>,
+    definition: String,
+) -> Result<Workflow, String> {
+    let template_engine = template_engine_state.engine.lock().await.clone();
+    let parsed = parse_definition(&definition, &template_engine).map_err(|e| e.to_string())?;
+    let db = WorkflowDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let id = match id {
+        Some(id) => db
+            .update_workflow(id, &parsed.name, &definition)
+            .map(|_| id),
+        None => db.add_workflow(&parsed.name, &definition),
+    }
+    .map_err(|e| e.to_string())?;
+    db.get_workflow(id)
+        .map_err(|e| e.to_string())?
+        .ok_or("工作流不存在".to_string())
+}
+
+// 从 YAML 或 JSON 文件导入工作流
+#[tauri::command]
+pub async fn import_workflow_file(
+    app_handle: tauri::AppHandle,
+    template_engine_state: State<'_, TemplateEngineState>,
+    file_path: String,
+) -> Result<Workflow, String> {
+    let definition = std::fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
+    save_workflow(app_handle, template_engine_state, None, definition).await
+}
+
+#[tauri::command]
+pub fn delete_workflow(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db = WorkflowDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.delete_workflow(id).map_err(|e| e.to_string())
+}
+
+// 在后台执行工作流并立即返回运行 id，步骤事件通过 workflow_run_{run_id} 推送，
+// 结束时通过 workflow_run_finished_{run_id} 推送运行结果
+#[tauri::command]
+pub async fn run_workflow(
+    app_handle: tauri::AppHandle,
+    template_engine_state: State<'_, TemplateEngineState>,
+    message_token_manager: State<'_, MessageTokenManager>,
+    workflow_id: i64,
+    inputs: Option<HashMap<String, String>>,
+) -> Result<i64, String> {
+    let db = WorkflowDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    let workflow = db
+        .get_workflow(workflow_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("工作流不存在".to_string())?;
+    let template_engine = template_engine_state.engine.lock().await.clone();
+    let definition =
+        parse_definition(&workflow.definition, &template_engine).map_err(|e| e.to_string())?;
+    let inputs = inputs.unwrap_or_default();
+    let run_id = db
+        .add_run(
+            workflow_id,
+            &serde_json::to_string(&inputs).map_err(|e| e.to_string())?,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let cancel_token = CancellationToken::new();
+    message_token_manager
+        .store_token(run_token_key(run_id), cancel_token.clone())
+        .await;
+    let tokens = message_token_manager.get_tokens();
+
+    tauri::async_runtime::spawn(async move {
+        let _ = execute_run(
+            &app_handle,
+            &template_engine,
+            &definition,
+            &inputs,
+            run_id,
+            cancel_token,
+            |_| {},
+        )
+        .await;
+        tokens.lock().await.remove(&run_token_key(run_id));
+    });
+
+    Ok(run_id)
+}
+
+#[tauri::command]
+pub async fn cancel_workflow_run(
+    message_token_manager: State<'_, MessageTokenManager>,
+    run_id: i64,
+) -> Result<(), String> {
+    message_token_manager
+        .cancel_request(run_token_key(run_id))
+        .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_workflow_runs(
+    app_handle: tauri::AppHandle,
+    workflow_id: i64,
+) -> Result<Vec<WorkflowRun>, String> {
+    let db = WorkflowDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.get_runs(workflow_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_workflow_step_runs(
+    app_handle: tauri::AppHandle,
+    run_id: i64,
+) -> Result<Vec<WorkflowStepRun>, String> {
+    let db = WorkflowDatabase::new(&app_handle).map_err(|e| e.to_string())?;
+    db.get_step_runs(run_id).map_err(|e| e.to_string())
+}
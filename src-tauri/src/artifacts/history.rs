@@ -0,0 +1,56 @@
+use sha2::{Digest, Sha256};
+
+use crate::db::conversation_db::ArtifactRunRecord;
+
+// 放进提示词的 stderr 最大字符数，报错信息一般在末尾，超出时保留末尾部分
+const FIX_PROMPT_MAX_STDERR_CHARS: usize = 4000;
+
+// 同样的代码多次运行得到同样的 hash，用于查找同一段代码的运行记录
+pub fn code_hash(code: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(code.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn tail_chars(text: &str, max_chars: usize) -> &str {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text;
+    }
+    let start = text
+        .char_indices()
+        .nth(count - max_chars)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    &text[start..]
+}
+
+// 根据运行记录中的代码和错误输出生成让模型修复错误的追问
+pub fn build_fix_error_prompt(run: &ArtifactRunRecord) -> String {
+    let stderr = run.stderr.trim();
+    let mut error = tail_chars(stderr, FIX_PROMPT_MAX_STDERR_CHARS).to_string();
+    if error.len() < stderr.len() {
+        error = format!("...(前面的输出已省略)\n{}", error);
+    }
+    let status = if run.timed_out {
+        "执行超时".to_string()
+    } else if run.killed {
+        "进程被结束".to_string()
+    } else {
+        match run.exit_code {
+            Some(code) => format!("退出码 {}", code),
+            None => "进程没有正常退出".to_string(),
+        }
+    };
+    if error.is_empty() {
+        error = "(没有错误输出)".to_string();
+    }
+    format!(
+        "运行下面的 {} 代码时出错了（{}），请找出原因并给出修复后的完整代码。\n\n```{}\n{}\n```\n\n错误输出：\n```\n{}\n```",
+        run.language,
+        status,
+        run.language,
+        run.code.trim_end(),
+        error
+    )
+}
@@ -1,2 +1,7 @@
-pub mod applescript;
-pub mod powershell;
+pub mod history;
+pub mod preview;
+pub mod sandbox;
+pub mod workspace;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,359 @@
+use image::codecs::jpeg::JpegEncoder;
+use image::{ImageFormat, RgbImage};
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, Url};
+
+use crate::state::preview_store::PreviewStore;
+use crate::utils::pdf::{stream_object, write_pdf};
+
+// React、Vue 组件和图表类的预览不依赖外部服务：代码和运行时都通过这个自定义协议提供，
+// 页面中用打包好的 Babel、vue3-sfc-loader、mermaid 等库渲染
+pub const PREVIEW_PROTOCOL: &str = "aipp-preview";
+
+// 运行时文件在资源目录中的位置，第三方库由 scripts/copy-preview-runtime.mjs 从 node_modules 复制
+const RUNTIME_DIR: &str = "preview-runtime";
+
+const REACT_RUNTIME: &[&str] = &[
+    "tailwind.js",
+    "react.js",
+    "react-dom.js",
+    "prop-types.js",
+    "recharts.js",
+    "lucide-react.js",
+    "babel.js",
+    "react-loader.js",
+];
+
+const VUE_RUNTIME: &[&str] = &[
+    "tailwind.js",
+    "vue.js",
+    "vue3-sfc-loader.js",
+    "vue-loader.js",
+];
+
+const MERMAID_RUNTIME: &[&str] = &["mermaid.js", "diagram-loader.js"];
+const MARKDOWN_RUNTIME: &[&str] = &["marked.js", "diagram-loader.js"];
+const DOT_RUNTIME: &[&str] = &["viz.js", "diagram-loader.js"];
+const VEGA_LITE_RUNTIME: &[&str] = &["vega.js", "vega-lite.js", "diagram-loader.js"];
+const SVG_RUNTIME: &[&str] = &["diagram-loader.js"];
+
+const ALL_RUNTIME: &[&[&str]] = &[
+    REACT_RUNTIME,
+    VUE_RUNTIME,
+    MERMAID_RUNTIME,
+    MARKDOWN_RUNTIME,
+    DOT_RUNTIME,
+    VEGA_LITE_RUNTIME,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreviewKind {
+    React,
+    Vue,
+    Mermaid,
+    Markdown,
+    Dot,
+    VegaLite,
+    Svg,
+}
+
+impl PreviewKind {
+    pub fn from_lang(lang: &str) -> Option<Self> {
+        match lang {
+            "react" | "jsx" | "tsx" => Some(PreviewKind::React),
+            "vue" => Some(PreviewKind::Vue),
+            "mermaid" | "mmd" => Some(PreviewKind::Mermaid),
+            "markdown" | "md" => Some(PreviewKind::Markdown),
+            "dot" | "graphviz" | "gv" => Some(PreviewKind::Dot),
+            "vega-lite" | "vegalite" | "vega" => Some(PreviewKind::VegaLite),
+            "svg" => Some(PreviewKind::Svg),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PreviewKind::React => "react",
+            PreviewKind::Vue => "vue",
+            PreviewKind::Mermaid => "mermaid",
+            PreviewKind::Markdown => "markdown",
+            PreviewKind::Dot => "dot",
+            PreviewKind::VegaLite => "vega-lite",
+            PreviewKind::Svg => "svg",
+        }
+    }
+
+    pub fn window_label(&self, hash: &str) -> String {
+        preview_window_label(&self.name().replace('-', "_"), hash)
+    }
+
+    // 渲染结果可以导出成图片和 PDF 的类型
+    pub fn exportable(&self) -> bool {
+        !matches!(self, PreviewKind::React | PreviewKind::Vue)
+    }
+
+    // 页面中加载代码使用的相对路径
+    fn component_file(&self) -> &'static str {
+        match self {
+            PreviewKind::React => "component.jsx",
+            PreviewKind::Vue => "component.vue",
+            PreviewKind::Mermaid => "component.mmd",
+            PreviewKind::Markdown => "component.md",
+            PreviewKind::Dot => "component.dot",
+            PreviewKind::VegaLite => "component.json",
+            PreviewKind::Svg => "component.svg",
+        }
+    }
+
+    fn runtime(&self) -> &'static [&'static str] {
+        match self {
+            PreviewKind::React => REACT_RUNTIME,
+            PreviewKind::Vue => VUE_RUNTIME,
+            PreviewKind::Mermaid => MERMAID_RUNTIME,
+            PreviewKind::Markdown => MARKDOWN_RUNTIME,
+            PreviewKind::Dot => DOT_RUNTIME,
+            PreviewKind::VegaLite => VEGA_LITE_RUNTIME,
+            PreviewKind::Svg => SVG_RUNTIME,
+        }
+    }
+
+    fn mount_id(&self) -> &'static str {
+        match self {
+            PreviewKind::Vue => "app",
+            _ => "root",
+        }
+    }
+}
+
+// 页面渲染完成后回传的结果，用于导出
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderedFormat {
+    Svg,
+    Png,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Png,
+    Svg,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_lowercase().as_str() {
+            "png" => Some(ExportFormat::Png),
+            "svg" => Some(ExportFormat::Svg),
+            "pdf" => Some(ExportFormat::Pdf),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PreviewRequest<'a> {
+    // /runtime/{file}
+    Runtime(&'a str),
+    // /{hash}/index.html
+    Page(&'a str),
+    // /{hash}/component.*
+    Component(&'a str),
+    // POST /{hash}/rendered.svg 或 /{hash}/rendered.png
+    Rendered(&'a str, RenderedFormat),
+}
+
+fn is_hash(text: &str) -> bool {
+    text.len() == 64 && text.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// 只接受固定格式的路径，运行时文件必须在白名单中，避免读取资源目录以外的文件
+pub fn parse_request_path(path: &str) -> Option<PreviewRequest<'_>> {
+    let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    match parts.as_slice() {
+        ["runtime", file] if ALL_RUNTIME.iter().any(|files| files.contains(file)) => {
+            Some(PreviewRequest::Runtime(file))
+        }
+        [hash, "" | "index.html"] if is_hash(hash) => Some(PreviewRequest::Page(hash)),
+        [hash, file] if is_hash(hash) && file.starts_with("component.") => {
+            Some(PreviewRequest::Component(hash))
+        }
+        [hash, "rendered.svg"] if is_hash(hash) => {
+            Some(PreviewRequest::Rendered(hash, RenderedFormat::Svg))
+        }
+        [hash, "rendered.png"] if is_hash(hash) => {
+            Some(PreviewRequest::Rendered(hash, RenderedFormat::Png))
+        }
+        _ => None,
+    }
+}
+
+// 预览页面只负责按顺序加载运行时，转换和渲染在 react-loader.js / vue-loader.js / diagram-loader.js 中完成
+pub fn render_preview_page(kind: PreviewKind) -> String {
+    let scripts = kind
+        .runtime()
+        .iter()
+        .map(|file| format!("    <script src=\"/runtime/{}\"></script>", file))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n    <meta charset=\"utf-8\">\n    <title>Aipp</title>\n</head>\n<body>\n    <div id=\"{}\" data-kind=\"{}\" data-component=\"{}\"></div>\n{}\n</body>\n</html>\n",
+        kind.mount_id(),
+        kind.name(),
+        kind.component_file(),
+        scripts
+    )
+}
+
+// 预览窗口按内容 hash 区分，同样的内容复用同一个窗口，不同的内容可以同时打开多个窗口
+pub fn preview_window_label(kind: &str, hash: &str) -> String {
+    format!("preview_{}_{}", kind, &hash[..hash.len().min(16)])
+}
+
+// 不同平台上自定义协议的地址格式不同
+pub fn preview_url(hash: &str) -> Result<Url, String> {
+    #[cfg(any(target_os = "windows", target_os = "android"))]
+    let url = format!("http://{}.localhost/{}/index.html", PREVIEW_PROTOCOL, hash);
+    #[cfg(not(any(target_os = "windows", target_os = "android")))]
+    let url = format!("{}://localhost/{}/index.html", PREVIEW_PROTOCOL, hash);
+    Url::parse(&url).map_err(|e| e.to_string())
+}
+
+fn content_type(file: &str) -> &'static str {
+    if file.ends_with(".html") {
+        "text/html; charset=utf-8"
+    } else if file.ends_with(".js") || file.ends_with(".jsx") {
+        "text/javascript; charset=utf-8"
+    } else {
+        "text/plain; charset=utf-8"
+    }
+}
+
+fn respond(status: StatusCode, content_type: &str, body: Vec<u8>) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(body)
+        .unwrap()
+}
+
+fn not_found(message: String) -> Response<Vec<u8>> {
+    respond(
+        StatusCode::NOT_FOUND,
+        "text/plain; charset=utf-8",
+        message.into_bytes(),
+    )
+}
+
+// 自定义协议的处理函数，在 main.rs 中注册
+pub fn handle_preview_request(
+    app_handle: &AppHandle,
+    request: &Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let path = request.uri().path();
+    let store = app_handle.state::<PreviewStore>();
+    match parse_request_path(path) {
+        Some(PreviewRequest::Rendered(hash, format)) if request.method() == Method::POST => {
+            let body = request.body().clone();
+            let saved = match format {
+                RenderedFormat::Svg => {
+                    store.set_rendered_svg(hash, String::from_utf8_lossy(&body).to_string())
+                }
+                RenderedFormat::Png => store.set_rendered_png(hash, body),
+            };
+            if saved {
+                respond(StatusCode::OK, "text/plain; charset=utf-8", vec![])
+            } else {
+                not_found("预览已过期，请重新运行代码".to_string())
+            }
+        }
+        Some(PreviewRequest::Runtime(file)) => {
+            let runtime_path = app_handle
+                .path()
+                .resource_dir()
+                .map(|dir| dir.join(RUNTIME_DIR).join(file));
+            match runtime_path.and_then(|p| std::fs::read(p).map_err(Into::into)) {
+                Ok(content) => respond(StatusCode::OK, content_type(file), content),
+                Err(e) => not_found(format!("预览运行时文件 {} 不存在: {}", file, e)),
+            }
+        }
+        Some(PreviewRequest::Page(hash)) => match store.get(hash) {
+            Some(component) => respond(
+                StatusCode::OK,
+                content_type("index.html"),
+                render_preview_page(component.kind).into_bytes(),
+            ),
+            None => not_found("预览已过期，请重新运行代码".to_string()),
+        },
+        Some(PreviewRequest::Component(hash)) => match store.get(hash) {
+            Some(component) => respond(
+                StatusCode::OK,
+                content_type(component.kind.component_file()),
+                component.code.into_bytes(),
+            ),
+            None => not_found("预览已过期，请重新运行代码".to_string()),
+        },
+        Some(PreviewRequest::Rendered(..)) | None => not_found(format!("未知的预览地址: {}", path)),
+    }
+}
+
+// 把透明背景铺成白色后编码成 JPEG，PDF 可以直接嵌入 JPEG 数据
+fn png_to_jpeg(png: &[u8]) -> Result<(Vec<u8>, u32, u32), String> {
+    let image = image::load_from_memory_with_format(png, ImageFormat::Png)
+        .map_err(|e| format!("读取渲染结果失败: {}", e))?
+        .to_rgba8();
+    let (width, height) = image.dimensions();
+    let rgb = RgbImage::from_fn(width, height, |x, y| {
+        let pixel = image.get_pixel(x, y);
+        let alpha = pixel[3] as u32;
+        let blend = |c: u8| ((c as u32 * alpha + 255 * (255 - alpha)) / 255) as u8;
+        image::Rgb([blend(pixel[0]), blend(pixel[1]), blend(pixel[2])])
+    });
+    let mut jpeg = vec![];
+    JpegEncoder::new_with_quality(&mut jpeg, 92)
+        .encode_image(&rgb)
+        .map_err(|e| format!("生成图片失败: {}", e))?;
+    Ok((jpeg, width, height))
+}
+
+// 只有一页的 PDF，页面大小与图片一致，单位为点
+pub fn build_image_pdf(
+    jpeg: &[u8],
+    width: u32,
+    height: u32,
+    page_width: f64,
+    page_height: f64,
+) -> Vec<u8> {
+    let content = format!(
+        "q {:.2} 0 0 {:.2} 0 0 cm /Im0 Do Q",
+        page_width, page_height
+    );
+    write_pdf(&[
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /XObject << /Im0 4 0 R >> >> /Contents 5 0 R >>",
+            page_width, page_height
+        )
+        .into_bytes(),
+        stream_object(
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode",
+                width, height
+            ),
+            jpeg,
+        ),
+        stream_object("", content.as_bytes()),
+    ])
+}
+
+// 页面中的图片按 2 倍大小渲染，PDF 页面按原始大小
+pub fn png_to_pdf(png: &[u8]) -> Result<Vec<u8>, String> {
+    let (jpeg, width, height) = png_to_jpeg(png)?;
+    Ok(build_image_pdf(
+        &jpeg,
+        width,
+        height,
+        width as f64 / 2.0,
+        height as f64 / 2.0,
+    ))
+}
@@ -0,0 +1,489 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::db::system_db::FeatureConfig;
+
+// 在临时目录中运行的脚本语言，解释器路径可以在 preview 配置中修改
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptLanguage {
+    Python,
+    Node,
+    Bash,
+    Deno,
+    PowerShell,
+    AppleScript,
+}
+
+impl ScriptLanguage {
+    pub fn from_lang(lang: &str) -> Option<Self> {
+        match lang {
+            "python" | "py" | "python3" => Some(ScriptLanguage::Python),
+            "javascript" | "js" | "node" => Some(ScriptLanguage::Node),
+            "bash" | "sh" | "shell" => Some(ScriptLanguage::Bash),
+            "deno" | "typescript" | "ts" => Some(ScriptLanguage::Deno),
+            "powershell" | "pwsh" | "ps1" => Some(ScriptLanguage::PowerShell),
+            "applescript" => Some(ScriptLanguage::AppleScript),
+            _ => None,
+        }
+    }
+
+    // preview 配置中解释器路径的 key
+    pub fn config_key(&self) -> &'static str {
+        match self {
+            ScriptLanguage::Python => "python_path",
+            ScriptLanguage::Node => "node_path",
+            ScriptLanguage::Bash => "bash_path",
+            ScriptLanguage::Deno => "deno_path",
+            ScriptLanguage::PowerShell => "powershell_path",
+            ScriptLanguage::AppleScript => "osascript_path",
+        }
+    }
+
+    fn default_interpreter(&self) -> &'static str {
+        match self {
+            ScriptLanguage::Python => "python3",
+            ScriptLanguage::Node => "node",
+            ScriptLanguage::Bash => "bash",
+            ScriptLanguage::Deno => "deno",
+            ScriptLanguage::PowerShell => "powershell",
+            ScriptLanguage::AppleScript => "osascript",
+        }
+    }
+
+    fn file_name(&self) -> &'static str {
+        match self {
+            ScriptLanguage::Python => "main.py",
+            ScriptLanguage::Node => "main.js",
+            ScriptLanguage::Bash => "main.sh",
+            ScriptLanguage::Deno => "main.ts",
+            ScriptLanguage::PowerShell => "main.ps1",
+            ScriptLanguage::AppleScript => "main.applescript",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SandboxOptions {
+    // 为空时使用 PATH 中的默认解释器
+    pub interpreter: Option<String>,
+    pub timeout: Duration,
+    // stdout 和 stderr 合计的最大字节数，超出后结束进程
+    pub max_output_bytes: usize,
+    pub network_isolation: bool,
+}
+
+impl SandboxOptions {
+    pub fn from_config(language: ScriptLanguage, config: &HashMap<String, FeatureConfig>) -> Self {
+        let value = |key: &str| {
+            config
+                .get(key)
+                .map(|c| c.value.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        SandboxOptions {
+            interpreter: value(language.config_key()),
+            timeout: Duration::from_secs(
+                value("run_timeout_seconds")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(30),
+            ),
+            max_output_bytes: value("run_max_output_kb")
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(256)
+                * 1024,
+            network_isolation: value("run_network_isolation").as_deref() == Some("true"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "stream", content = "line", rename_all = "snake_case")]
+pub enum OutputLine {
+    Stdout(String),
+    Stderr(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SandboxResult {
+    // 被信号结束或没有启动成功时没有退出码
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+    pub truncated: bool,
+    // 通过取消令牌结束的进程
+    pub killed: bool,
+    pub duration_ms: u64,
+}
+
+impl SandboxResult {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0) && !self.timed_out && !self.truncated && !self.killed
+    }
+
+    // 合并输出和退出状态，作为一次执行的文本结果
+    pub fn summary(&self) -> String {
+        let mut text = self.stdout.clone();
+        if !self.stderr.is_empty() {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(&self.stderr);
+        }
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        if self.killed {
+            text.push_str("进程已被结束");
+        } else if self.timed_out {
+            text.push_str("执行超时，进程已结束");
+        } else if self.truncated {
+            text.push_str("输出超过限制，进程已结束");
+        } else {
+            match self.exit_code {
+                Some(code) => text.push_str(&format!("执行完成，退出码: {}", code)),
+                None => text.push_str("进程没有正常退出"),
+            }
+        }
+        text
+    }
+}
+
+fn build_command(
+    language: ScriptLanguage,
+    options: &SandboxOptions,
+    script_name: &str,
+) -> Result<Command, String> {
+    let interpreter = options
+        .interpreter
+        .clone()
+        .unwrap_or(language.default_interpreter().to_string());
+    let mut args: Vec<String> = vec![];
+    if language == ScriptLanguage::PowerShell {
+        // 以脚本文件运行，执行完就退出
+        args.extend(["-NoProfile", "-ExecutionPolicy", "Bypass", "-File"].map(String::from));
+    }
+    if language == ScriptLanguage::Deno {
+        // deno 默认没有任何权限，只放开工作目录的读写，需要时再放开网络
+        args.extend(
+            [
+                "run",
+                "--quiet",
+                "--no-prompt",
+                "--allow-read=.",
+                "--allow-write=.",
+            ]
+            .map(String::from),
+        );
+        if !options.network_isolation {
+            args.push("--allow-net".to_string());
+        }
+    }
+    args.push(script_name.to_string());
+
+    if !options.network_isolation || language == ScriptLanguage::Deno {
+        let mut command = Command::new(interpreter);
+        command.args(args);
+        return Ok(command);
+    }
+    isolated_command(interpreter, args)
+}
+
+// 在独立的网络命名空间中运行，只有回环网卡可用
+#[cfg(target_os = "linux")]
+fn isolated_command(interpreter: String, args: Vec<String>) -> Result<Command, String> {
+    let mut command = Command::new("unshare");
+    command
+        .args(["--user", "--map-root-user", "--net", "--"])
+        .arg(interpreter)
+        .args(args);
+    Ok(command)
+}
+
+#[cfg(target_os = "macos")]
+fn isolated_command(interpreter: String, args: Vec<String>) -> Result<Command, String> {
+    let mut command = Command::new("sandbox-exec");
+    command
+        .args(["-p", "(version 1)(allow default)(deny network*)"])
+        .arg(interpreter)
+        .args(args);
+    Ok(command)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn isolated_command(_interpreter: String, _args: Vec<String>) -> Result<Command, String> {
+    Err("当前系统不支持网络隔离，请在配置中关闭后再运行".to_string())
+}
+
+// 按块读取输出并逐行发送，非 UTF-8 的内容按有损方式转换，
+// 读取超过 max_bytes 后停止读取，没有换行的输出也不会一直占用内存
+fn forward_lines<R: AsyncRead + Unpin + Send + 'static>(
+    mut reader: R,
+    tx: mpsc::Sender<OutputLine>,
+    make_line: fn(String) -> OutputLine,
+    max_bytes: usize,
+) {
+    let to_line = move |bytes: &[u8]| {
+        make_line(
+            String::from_utf8_lossy(bytes)
+                .trim_end_matches('\r')
+                .to_string(),
+        )
+    };
+    tokio::spawn(async move {
+        let mut chunk = [0u8; 8192];
+        let mut pending = Vec::new();
+        let mut total = 0;
+        while total <= max_bytes {
+            let n = match reader.read(&mut chunk).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            total += n;
+            let mut rest = &chunk[..n];
+            while let Some(pos) = rest.iter().position(|b| *b == b'\n') {
+                pending.extend_from_slice(&rest[..pos]);
+                if tx.send(to_line(&pending)).await.is_err() {
+                    return;
+                }
+                pending.clear();
+                rest = &rest[pos + 1..];
+            }
+            pending.extend_from_slice(rest);
+        }
+        if !pending.is_empty() {
+            let _ = tx.send(to_line(&pending)).await;
+        }
+    });
+}
+
+// 解释器在单独的进程组中运行，结束时连同脚本放到后台的子进程一起结束，
+// 必须在回收解释器进程之前调用，回收后进程组 id 可能已经被其他进程复用
+#[cfg(unix)]
+async fn kill_process_group(pid: Option<u32>) {
+    if let Some(pid) = pid {
+        // SAFETY: killpg 只读取传入的整数参数，不涉及内存访问；pid 是 process_group(0)
+        // 启动的解释器进程，尚未回收，所以它仍是这个进程组的 id
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+}
+
+// Windows 没有进程组，用 taskkill /T 结束整个进程树，同样要在进程退出前调用
+#[cfg(windows)]
+async fn kill_process_group(pid: Option<u32>) {
+    if let Some(pid) = pid {
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await;
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+async fn kill_process_group(_pid: Option<u32>) {}
+
+// 等待解释器退出后先结束进程组，再回收解释器进程。
+// unix 下用 waitid(WNOWAIT) 等待退出但不回收，保证结束进程组时 id 没有被复用；
+// Windows 下解释器退出后无法再找到它的进程树，放到后台的子进程不会被结束
+#[cfg(unix)]
+async fn wait_and_kill_group(child: &mut Child, pid: Option<u32>) -> std::io::Result<ExitStatus> {
+    if let Some(pid) = pid {
+        let _ = tokio::task::spawn_blocking(move || loop {
+            // SAFETY: siginfo_t 是普通的 C 结构体，全零是合法的初始值，指针在调用期间有效；
+            // WNOWAIT 只等待不回收，解释器进程仍由 child.wait() 回收
+            let ret = unsafe {
+                let mut info: libc::siginfo_t = std::mem::zeroed();
+                libc::waitid(
+                    libc::P_PID,
+                    pid as libc::id_t,
+                    &mut info,
+                    libc::WEXITED | libc::WNOWAIT,
+                )
+            };
+            if ret == 0 || std::io::Error::last_os_error().kind() != std::io::ErrorKind::Interrupted
+            {
+                break;
+            }
+        })
+        .await;
+        kill_process_group(Some(pid)).await;
+    }
+    child.wait().await
+}
+
+#[cfg(not(unix))]
+async fn wait_and_kill_group(child: &mut Child, _pid: Option<u32>) -> std::io::Result<ExitStatus> {
+    child.wait().await
+}
+
+async fn run_in_dir(
+    language: ScriptLanguage,
+    options: &SandboxOptions,
+    work_dir: &Path,
+    lines: Option<mpsc::Sender<OutputLine>>,
+    mut stdin: Option<mpsc::Receiver<String>>,
+    cancel_token: CancellationToken,
+) -> Result<SandboxResult, String> {
+    let mut command = build_command(language, options, language.file_name())?;
+    command
+        .current_dir(work_dir)
+        .env("PYTHONUNBUFFERED", "1")
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+    let started = Instant::now();
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("启动解释器失败，请检查运行环境配置: {}", e))?;
+    let pid = child.id();
+
+    let (tx, mut rx) = mpsc::channel::<OutputLine>(100);
+    forward_lines(
+        child.stdout.take().unwrap(),
+        tx.clone(),
+        OutputLine::Stdout,
+        options.max_output_bytes,
+    );
+    forward_lines(
+        child.stderr.take().unwrap(),
+        tx,
+        OutputLine::Stderr,
+        options.max_output_bytes,
+    );
+    let mut child_stdin = child.stdin.take();
+
+    let deadline = tokio::time::Instant::now() + options.timeout;
+    let mut result = SandboxResult {
+        exit_code: None,
+        stdout: String::new(),
+        stderr: String::new(),
+        timed_out: false,
+        truncated: false,
+        killed: false,
+        duration_ms: 0,
+    };
+    let mut output_bytes = 0;
+    loop {
+        tokio::select! {
+            line = rx.recv() => {
+                let Some(line) = line else { break };
+                let (text, buffer) = match &line {
+                    OutputLine::Stdout(text) => (text, &mut result.stdout),
+                    OutputLine::Stderr(text) => (text, &mut result.stderr),
+                };
+                output_bytes += text.len() + 1;
+                if output_bytes > options.max_output_bytes {
+                    result.truncated = true;
+                    break;
+                }
+                buffer.push_str(text);
+                buffer.push('\n');
+                if let Some(lines) = &lines {
+                    let _ = lines.send(line).await;
+                }
+            }
+            input = async {
+                match stdin.as_mut() {
+                    Some(stdin) => stdin.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                match (input, child_stdin.as_mut()) {
+                    (Some(input), Some(writer)) => {
+                        if writer.write_all(input.as_bytes()).await.is_err()
+                            || writer.flush().await.is_err()
+                        {
+                            child_stdin = None;
+                        }
+                    }
+                    // 发送端关闭后关闭进程的 stdin，读取输入的程序会收到 EOF
+                    _ => {
+                        stdin = None;
+                        child_stdin = None;
+                    }
+                }
+            }
+            _ = tokio::time::sleep_until(deadline) => {
+                result.timed_out = true;
+                break;
+            }
+            _ = cancel_token.cancelled() => {
+                result.killed = true;
+                break;
+            }
+        }
+    }
+
+    // 输出已经关闭但进程还没退出时，继续等到超时
+    let status = if result.timed_out || result.truncated || result.killed {
+        kill_process_group(pid).await;
+        let _ = child.kill().await;
+        child.wait().await
+    } else {
+        drop(child_stdin);
+        tokio::select! {
+            // 解释器正常退出后，仍在后台运行的子进程也一起结束
+            status = wait_and_kill_group(&mut child, pid) => status,
+            _ = tokio::time::sleep_until(deadline) => {
+                result.timed_out = true;
+                kill_process_group(pid).await;
+                let _ = child.kill().await;
+                child.wait().await
+            }
+            _ = cancel_token.cancelled() => {
+                result.killed = true;
+                kill_process_group(pid).await;
+                let _ = child.kill().await;
+                child.wait().await
+            }
+        }
+    };
+    result.exit_code = status.ok().and_then(|s| s.code());
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    Ok(result)
+}
+
+// 把代码写入临时工作目录后运行，每行输出通过 lines 实时发送，stdin 收到的内容写入进程，
+// 取消令牌用于结束进程，结束后删除工作目录
+pub async fn run_sandboxed(
+    language: ScriptLanguage,
+    code: &str,
+    options: &SandboxOptions,
+    lines: Option<mpsc::Sender<OutputLine>>,
+    stdin: Option<mpsc::Receiver<String>>,
+    cancel_token: CancellationToken,
+) -> Result<SandboxResult, String> {
+    let work_dir = std::env::temp_dir().join(format!(
+        "aipp_run_{}_{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    tokio::fs::create_dir_all(&work_dir)
+        .await
+        .map_err(|e| format!("创建工作目录失败: {}", e))?;
+    let result = match tokio::fs::write(work_dir.join(language.file_name()), code).await {
+        Ok(_) => run_in_dir(language, options, &work_dir, lines, stdin, cancel_token).await,
+        Err(e) => Err(format!("写入代码文件失败: {}", e)),
+    };
+    let _ = tokio::fs::remove_dir_all(&work_dir).await;
+    result
+}
@@ -0,0 +1,452 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use super::history::{build_fix_error_prompt, code_hash};
+use super::sandbox::{run_sandboxed, OutputLine, SandboxOptions, ScriptLanguage};
+
+fn options() -> SandboxOptions {
+    SandboxOptions::from_config(ScriptLanguage::Bash, &HashMap::new())
+}
+
+#[tokio::test]
+async fn test_run_bash_streams_lines() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let result = run_sandboxed(
+        ScriptLanguage::Bash,
+        "echo hello\necho oops >&2\npwd\nexit 3",
+        &options(),
+        Some(tx),
+        None,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.exit_code, Some(3));
+    assert!(result.stdout.starts_with("hello\n"));
+    // 在临时工作目录中运行
+    assert!(result.stdout.contains("aipp_run_"));
+    assert_eq!(result.stderr, "oops\n");
+    assert!(!result.success());
+
+    let mut lines = vec![];
+    while let Some(line) = rx.recv().await {
+        lines.push(line);
+    }
+    assert_eq!(lines[0], OutputLine::Stdout("hello".to_string()));
+    assert!(lines.contains(&OutputLine::Stderr("oops".to_string())));
+}
+
+#[tokio::test]
+async fn test_run_bash_limits() {
+    let mut timeout_options = options();
+    timeout_options.timeout = Duration::from_millis(300);
+    let result = run_sandboxed(
+        ScriptLanguage::Bash,
+        "echo start\nsleep 5",
+        &timeout_options,
+        None,
+        None,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert!(result.timed_out);
+    assert_eq!(result.stdout, "start\n");
+    assert!(result.duration_ms < 5000);
+
+    let mut output_options = options();
+    output_options.max_output_bytes = 100;
+    let result = run_sandboxed(
+        ScriptLanguage::Bash,
+        "while true; do echo 0123456789; done",
+        &output_options,
+        None,
+        None,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert!(result.truncated);
+    assert!(result.stdout.len() <= 100);
+
+    // 取消令牌结束进程，仍然返回退出状态
+    let cancel_token = CancellationToken::new();
+    cancel_token.cancel();
+    let result = run_sandboxed(
+        ScriptLanguage::Bash,
+        "sleep 5",
+        &options(),
+        None,
+        None,
+        cancel_token,
+    )
+    .await
+    .unwrap();
+    assert!(result.killed);
+    assert!(!result.success());
+}
+
+#[tokio::test]
+async fn test_run_bash_with_stdin() {
+    let (stdin_tx, stdin_rx) = mpsc::channel(4);
+    stdin_tx.send("world\n".to_string()).await.unwrap();
+    stdin_tx.send("a\nb\n".to_string()).await.unwrap();
+    // 关闭发送端后进程收到 EOF
+    drop(stdin_tx);
+    let result = run_sandboxed(
+        ScriptLanguage::Bash,
+        "read name\necho \"hello $name\"\nwc -l",
+        &options(),
+        None,
+        Some(stdin_rx),
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert!(result.success());
+    assert_eq!(
+        result.stdout.split_whitespace().collect::<Vec<_>>(),
+        ["hello", "world", "2"]
+    );
+}
+
+#[tokio::test]
+async fn test_run_bash_limits_output_without_newlines() {
+    let mut output_options = options();
+    output_options.max_output_bytes = 1024;
+    let result = run_sandboxed(
+        ScriptLanguage::Bash,
+        "yes | tr -d '\\n'",
+        &output_options,
+        None,
+        None,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert!(result.truncated);
+    assert!(result.stdout.len() <= 1024);
+}
+
+// 进程已经退出或只剩僵尸进程
+#[cfg(target_os = "linux")]
+async fn wait_process_exit(pid: &str) -> bool {
+    for _ in 0..50 {
+        match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            Err(_) => return true,
+            Ok(stat) if stat.contains(") Z ") => return true,
+            Ok(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+    false
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_run_bash_kills_background_processes() {
+    // 解释器正常退出时结束放到后台的子进程
+    let result = run_sandboxed(
+        ScriptLanguage::Bash,
+        "sleep 30 > /dev/null 2>&1 &\necho $!",
+        &options(),
+        None,
+        None,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.exit_code, Some(0));
+    assert!(wait_process_exit(result.stdout.trim()).await);
+
+    // 后台子进程占用输出时等到超时，再连同子进程一起结束
+    let mut timeout_options = options();
+    timeout_options.timeout = Duration::from_millis(500);
+    let result = run_sandboxed(
+        ScriptLanguage::Bash,
+        "sleep 30 &\necho $!",
+        &timeout_options,
+        None,
+        None,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    assert!(result.timed_out);
+    assert!(wait_process_exit(result.stdout.trim()).await);
+}
+
+#[test]
+fn test_output_event_format() {
+    let event = crate::state::artifact_run::ArtifactOutput {
+        index: 2,
+        line: OutputLine::Stderr("oops".to_string()),
+    };
+    assert_eq!(
+        serde_json::to_value(&event).unwrap(),
+        serde_json::json!({"index": 2, "stream": "stderr", "line": "oops"})
+    );
+}
+
+fn failed_run(stderr: &str) -> crate::db::conversation_db::ArtifactRunRecord {
+    crate::db::conversation_db::ArtifactRunRecord {
+        id: 1,
+        message_id: Some(3),
+        language: "python".to_string(),
+        code: "print(1/0)\n".to_string(),
+        code_hash: code_hash("print(1/0)\n"),
+        stdout: String::new(),
+        stderr: stderr.to_string(),
+        exit_code: Some(1),
+        timed_out: false,
+        truncated: false,
+        killed: false,
+        duration_ms: 20,
+        created_time: chrono::Utc::now(),
+        finished_time: Some(chrono::Utc::now()),
+    }
+}
+
+#[test]
+fn test_code_hash() {
+    assert_eq!(code_hash("echo 1"), code_hash("echo 1"));
+    assert_ne!(code_hash("echo 1"), code_hash("echo 2"));
+    assert_eq!(code_hash("").len(), 64);
+}
+
+#[test]
+fn test_build_fix_error_prompt() {
+    let prompt = build_fix_error_prompt(&failed_run(
+        "Traceback (most recent call last):\nZeroDivisionError: division by zero\n",
+    ));
+    assert!(prompt.contains("退出码 1"));
+    assert!(prompt.contains("```python\nprint(1/0)\n```"));
+    assert!(prompt.contains("ZeroDivisionError: division by zero"));
+
+    // 过长的错误输出只保留末尾
+    let long = format!("{}最后一行错误", "前".repeat(5000));
+    let prompt = build_fix_error_prompt(&failed_run(&long));
+    assert!(prompt.contains("前面的输出已省略"));
+    assert!(prompt.ends_with("最后一行错误\n```"));
+    assert!(prompt.chars().count() < 4200);
+}
+
+#[test]
+fn test_parse_preview_request_path() {
+    use super::preview::{parse_request_path, PreviewRequest, RenderedFormat};
+
+    let hash = code_hash("export default function App() {}");
+    assert_eq!(
+        parse_request_path(&format!("/{}/index.html", hash)),
+        Some(PreviewRequest::Page(&hash))
+    );
+    assert_eq!(
+        parse_request_path(&format!("/{}/component.vue", hash)),
+        Some(PreviewRequest::Component(&hash))
+    );
+    assert_eq!(
+        parse_request_path("/runtime/react.js"),
+        Some(PreviewRequest::Runtime("react.js"))
+    );
+    // 只能读取白名单中的运行时文件
+    assert_eq!(parse_request_path("/runtime/../../secret.txt"), None);
+    assert_eq!(parse_request_path("/runtime/other.js"), None);
+    assert_eq!(parse_request_path("/abc/index.html"), None);
+    assert_eq!(
+        parse_request_path(&format!("/{}/rendered.png", hash)),
+        Some(PreviewRequest::Rendered(&hash, RenderedFormat::Png))
+    );
+}
+
+#[test]
+fn test_render_preview_page() {
+    use super::preview::{render_preview_page, PreviewKind};
+
+    let page = render_preview_page(PreviewKind::Vue);
+    assert!(
+        page.contains("<div id=\"app\" data-kind=\"vue\" data-component=\"component.vue\"></div>")
+    );
+    let vue = page.find("/runtime/vue.js").unwrap();
+    let loader = page.find("/runtime/vue-loader.js").unwrap();
+    assert!(vue < loader);
+    assert!(!page.contains("http"));
+
+    let page = render_preview_page(PreviewKind::React);
+    assert!(page.contains("data-component=\"component.jsx\""));
+    assert!(page.contains("/runtime/babel.js"));
+}
+
+#[test]
+fn test_png_to_pdf() {
+    use super::preview::png_to_pdf;
+
+    let mut png = std::io::Cursor::new(vec![]);
+    image::RgbaImage::from_pixel(40, 20, image::Rgba([255, 0, 0, 128]))
+        .write_to(&mut png, image::ImageFormat::Png)
+        .unwrap();
+    let pdf = png_to_pdf(png.get_ref()).unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.starts_with("%PDF-1.4"));
+    assert!(text.ends_with("%%EOF\n"));
+    // 按 2 倍渲染的图片，页面大小为一半
+    assert!(text.contains("/MediaBox [0 0 20.00 10.00]"));
+    assert!(text.contains("/Width 40 /Height 20"));
+
+    // xref 中记录的偏移量指向对应的对象，JPEG 数据不是文本，按字节比较
+    let startxref = text.rfind("startxref\n").unwrap();
+    let xref: usize = text[startxref + 10..]
+        .lines()
+        .next()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let table = std::str::from_utf8(&pdf[xref..]).unwrap();
+    assert!(table.starts_with("xref"));
+    let offset: usize = table.lines().nth(7).unwrap()[..10].parse().unwrap();
+    assert!(pdf[offset..].starts_with(b"5 0 obj"));
+
+    assert!(png_to_pdf(b"not a png").is_err());
+}
+
+#[test]
+fn test_extract_artifact_files() {
+    use super::workspace::extract_files;
+
+    let content = r#"下面是修改后的代码：
+
+```java title="src/main/java/UserController.java"
+class UserController {}
+```
+
+**src/main/java/UserService.java**
+
+```java
+class UserService {}
+```
+
+```python
+# file: scripts/init.py
+print("init")
+```
+
+```bash
+echo "没有文件名的代码块不会保存"
+```
+
+```java:../secret.java
+class Secret {}
+```
+
+```java src/main/java/UserController.java
+class UserController { int v2; }
+```
+"#;
+    let files = extract_files(content);
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "src/main/java/UserService.java",
+            "scripts/init.py",
+            "src/main/java/UserController.java"
+        ]
+    );
+    // 同一路径以最后一次出现为准
+    assert_eq!(files[2].content, "class UserController { int v2; }\n");
+    assert!(files[1].content.starts_with("# file: scripts/init.py\n"));
+}
+
+#[test]
+fn test_merge_and_diff_artifact_files() {
+    use super::workspace::{diff_files, merge_files, ArtifactFile, FileChange};
+
+    let file = |path: &str, content: &str| ArtifactFile {
+        path: path.to_string(),
+        content: content.to_string(),
+    };
+    let v1 = vec![file("a.rs", "fn a() {}\n"), file("b.rs", "fn b() {}\n")];
+    let v2 = merge_files(
+        &v1,
+        vec![file("b.rs", "fn b() { 1 }\n"), file("c.rs", "fn c() {}\n")],
+    );
+    assert_eq!(
+        v2.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(),
+        ["a.rs", "b.rs", "c.rs"]
+    );
+
+    let diffs = diff_files(&v1, &v2);
+    let changes: Vec<(&str, FileChange)> =
+        diffs.iter().map(|d| (d.path.as_str(), d.change)).collect();
+    assert_eq!(
+        changes,
+        [
+            ("a.rs", FileChange::Unchanged),
+            ("b.rs", FileChange::Modified),
+            ("c.rs", FileChange::Added)
+        ]
+    );
+    assert!(diffs[0].lines.is_empty());
+    assert_eq!(diffs[1].lines.len(), 2);
+
+    let diffs = diff_files(&v2, &v1[..1]);
+    assert_eq!(diffs[1].change, FileChange::Removed);
+}
+
+#[test]
+fn test_build_zip() {
+    use super::workspace::{build_zip, crc32, ArtifactFile};
+
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+    let files = vec![
+        ArtifactFile {
+            path: "src/main.rs".to_string(),
+            content: "fn main() {}\n".to_string(),
+        },
+        ArtifactFile {
+            path: "说明.md".to_string(),
+            content: "# 你好\n".to_string(),
+        },
+    ];
+    let zip = build_zip(&files, "demo").unwrap();
+    assert!(zip.starts_with(&0x0403_4b50u32.to_le_bytes()));
+    let end = zip.len() - 22;
+    assert_eq!(zip[end..end + 4], 0x0605_4b50u32.to_le_bytes());
+    // 条目数量和中央目录的位置
+    assert_eq!(u16::from_le_bytes([zip[end + 10], zip[end + 11]]), 2);
+    let central = u32::from_le_bytes(zip[end + 16..end + 20].try_into().unwrap()) as usize;
+    assert_eq!(zip[central..central + 4], 0x0201_4b50u32.to_le_bytes());
+    let name = "demo/src/main.rs".as_bytes();
+    assert_eq!(&zip[30..30 + name.len()], name);
+    assert_eq!(
+        &zip[30 + name.len()..30 + name.len() + 13],
+        b"fn main() {}\n"
+    );
+
+    // 条目数量超出 zip 格式的限制
+    let files = (0..=u16::MAX as usize)
+        .map(|i| ArtifactFile {
+            path: format!("{}.txt", i),
+            content: String::new(),
+        })
+        .collect::<Vec<_>>();
+    assert!(build_zip(&files, "").is_err());
+}
+
+#[test]
+fn test_preview_window_label() {
+    use super::history::code_hash;
+    use super::preview::{preview_window_label, PreviewKind};
+
+    let hash = code_hash("graph TD; A-->B");
+    let label = PreviewKind::VegaLite.window_label(&hash);
+    assert_eq!(label, format!("preview_vega_lite_{}", &hash[..16]));
+    assert_eq!(
+        label,
+        PreviewKind::VegaLite.window_label(&code_hash("graph TD; A-->B"))
+    );
+    assert_ne!(label, PreviewKind::VegaLite.window_label(&code_hash("{}")));
+    assert_eq!(preview_window_label("html", "abc"), "preview_html_abc");
+}
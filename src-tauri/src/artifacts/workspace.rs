@@ -0,0 +1,294 @@
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::diff::{diff_lines, DiffLine};
+
+// 对话中的多文件产物，模型在回答中给出带文件路径的代码块时更新
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArtifactFile {
+    pub path: String,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChange {
+    Added,
+    Removed,
+    Modified,
+    Unchanged,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArtifactFileDiff {
+    pub path: String,
+    pub change: FileChange,
+    // 没有变化的文件为空
+    pub lines: Vec<DiffLine>,
+}
+
+// 没有扩展名也可以作为文件名的常见文件
+const BARE_FILE_NAMES: &[&str] = &["Dockerfile", "Makefile", "Procfile", "LICENSE"];
+
+static FENCE_START: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(`{3,}|~{3,})\s*(.*)$").unwrap());
+static INFO_PATH_ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?:title|filename|file|path)\s*=\s*"([^"]+)""#).unwrap());
+static FIRST_LINE_PATH_COMMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?://|#|--|<!--|/\*)\s*(?:file|filename|文件|文件名)\s*[:：]\s*([^\s*]+?)\s*(?:-->|\*/)?\s*$")
+        .unwrap()
+});
+static HEADING_PREFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?:#+|\d+\.|[-*])?\s*").unwrap());
+static HEADING_LABEL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?i:file|filename|path|文件名|文件|路径)\s*[:：]\s*").unwrap());
+
+// 规范化模型给出的路径，拒绝绝对路径和跳出工作区的路径
+pub fn normalize_path(path: &str) -> Option<String> {
+    let path = path.trim().trim_matches('`').replace('\\', "/");
+    let path = path.trim_start_matches("./");
+    if path.is_empty()
+        || path.len() > 260
+        || path.starts_with('/')
+        || path.contains(':')
+        || path
+            .chars()
+            .any(|c| c.is_whitespace() || "\"'<>|*?".contains(c))
+    {
+        return None;
+    }
+    let segments: Vec<&str> = path.split('/').collect();
+    if segments
+        .iter()
+        .any(|s| s.is_empty() || *s == "." || *s == "..")
+    {
+        return None;
+    }
+    let file_name = segments.last()?;
+    if !file_name.contains('.') && !BARE_FILE_NAMES.contains(file_name) {
+        return None;
+    }
+    Some(segments.join("/"))
+}
+
+// 代码块的信息字符串中的路径，如 ```java src/Main.java、```java:src/Main.java、```java title="src/Main.java"
+fn path_from_info(info: &str) -> Option<String> {
+    if let Some(captures) = INFO_PATH_ATTRIBUTE.captures(info) {
+        return normalize_path(&captures[1]);
+    }
+    let mut tokens = info.split_whitespace();
+    let first = tokens.next()?;
+    if let Some((_, path)) = first.split_once(':') {
+        return normalize_path(path);
+    }
+    tokens.next().and_then(normalize_path)
+}
+
+// 代码第一行的注释，如 // file: src/Main.java
+fn path_from_first_line(code: &str) -> Option<String> {
+    let first_line = code.lines().next()?;
+    FIRST_LINE_PATH_COMMENT
+        .captures(first_line)
+        .and_then(|captures| normalize_path(&captures[1]))
+}
+
+// 代码块前一行单独写出的文件名，如 **src/Main.java**、### `pom.xml`、文件：src/Main.java
+fn path_from_heading(line: &str) -> Option<String> {
+    let line = HEADING_PREFIX.replace(line, "");
+    let line = line
+        .trim()
+        .trim_matches(|c| c == '*' || c == '`' || c == '_');
+    let line = HEADING_LABEL.replace(line, "");
+    let line = line
+        .trim()
+        .trim_matches(|c| c == '*' || c == '`' || c == '_')
+        .trim_end_matches([':', '：']);
+    normalize_path(line)
+}
+
+// 结束的围栏由同样的字符组成，长度不少于开始的围栏
+fn is_fence_end(line: &str, fence: &str) -> bool {
+    let line = line.trim();
+    let fence_char = fence.chars().next().unwrap_or('`');
+    line.len() >= fence.len() && line.chars().all(|c| c == fence_char)
+}
+
+// 提取回答中带文件路径的代码块，同一路径出现多次时使用最后一次的内容
+pub fn extract_files(content: &str) -> Vec<ArtifactFile> {
+    let mut files: Vec<ArtifactFile> = vec![];
+    let mut previous_line = "";
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let Some(captures) = FENCE_START.captures(line) else {
+            if !line.trim().is_empty() {
+                previous_line = line;
+            }
+            continue;
+        };
+        let fence = captures[1].to_string();
+        let info = captures[2].to_string();
+        let mut code = vec![];
+        for code_line in lines.by_ref() {
+            if is_fence_end(code_line, &fence) {
+                break;
+            }
+            code.push(code_line);
+        }
+        let code = code.join("\n") + "\n";
+        let path = path_from_info(&info)
+            .or_else(|| path_from_first_line(&code))
+            .or_else(|| path_from_heading(previous_line));
+        previous_line = "";
+        if let Some(path) = path {
+            files.retain(|f| f.path != path);
+            files.push(ArtifactFile {
+                path,
+                content: code,
+            });
+        }
+    }
+    files
+}
+
+// 用新版本的文件覆盖上一个版本，没有提到的文件保持不变
+pub fn merge_files(previous: &[ArtifactFile], updates: Vec<ArtifactFile>) -> Vec<ArtifactFile> {
+    let mut files = previous.to_vec();
+    for update in updates {
+        match files.iter_mut().find(|f| f.path == update.path) {
+            Some(file) => file.content = update.content,
+            None => files.push(update),
+        }
+    }
+    files
+}
+
+// 按文件比较两个版本，新增和删除的文件也给出逐行内容
+pub fn diff_files(old: &[ArtifactFile], new: &[ArtifactFile]) -> Vec<ArtifactFileDiff> {
+    let mut diffs: Vec<ArtifactFileDiff> = new
+        .iter()
+        .map(|file| match old.iter().find(|f| f.path == file.path) {
+            Some(old_file) if old_file.content == file.content => ArtifactFileDiff {
+                path: file.path.clone(),
+                change: FileChange::Unchanged,
+                lines: vec![],
+            },
+            Some(old_file) => ArtifactFileDiff {
+                path: file.path.clone(),
+                change: FileChange::Modified,
+                lines: diff_lines(&old_file.content, &file.content),
+            },
+            None => ArtifactFileDiff {
+                path: file.path.clone(),
+                change: FileChange::Added,
+                lines: diff_lines("", &file.content),
+            },
+        })
+        .collect();
+    diffs.extend(
+        old.iter()
+            .filter(|f| !new.iter().any(|n| n.path == f.path))
+            .map(|file| ArtifactFileDiff {
+                path: file.path.clone(),
+                change: FileChange::Removed,
+                lines: diff_lines(&file.content, ""),
+            }),
+    );
+    diffs
+}
+
+// 导出到目录，目录中已有的同名文件会被覆盖
+pub fn write_files_to_folder(files: &[ArtifactFile], folder: &Path) -> Result<(), String> {
+    for file in files {
+        let path = normalize_path(&file.path).ok_or(format!("文件路径不合法: {}", file.path))?;
+        let target = folder.join(path);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        std::fs::write(&target, &file.content)
+            .map_err(|e| format!("写入文件 {} 失败: {}", file.path, e))?;
+    }
+    Ok(())
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+// 不压缩的 zip 文件，文件都放在 root 目录下，文件名使用 UTF-8，
+// 不支持 ZIP64，文件数量或大小超出 zip 格式的限制时返回错误
+pub fn build_zip(files: &[ArtifactFile], root: &str) -> Result<Vec<u8>, String> {
+    // 1980-01-01 00:00
+    const DOS_TIME: u16 = 0;
+    const DOS_DATE: u16 = 0x0021;
+    const UTF8_FLAG: u16 = 0x0800;
+    let too_large = |_| "文件数量或大小超出 zip 格式的限制，请导出到文件夹".to_string();
+    let count = u16::try_from(files.len()).map_err(too_large)?;
+
+    let mut zip: Vec<u8> = vec![];
+    let mut central: Vec<u8> = vec![];
+    for file in files {
+        let name = if root.is_empty() {
+            file.path.clone()
+        } else {
+            format!("{}/{}", root, file.path)
+        };
+        let data = file.content.as_bytes();
+        let crc = crc32(data);
+        let size = u32::try_from(data.len()).map_err(too_large)?;
+        let name_len = u16::try_from(name.len()).map_err(too_large)?;
+        let offset = u32::try_from(zip.len()).map_err(too_large)?;
+
+        zip.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        zip.extend_from_slice(&20u16.to_le_bytes());
+        zip.extend_from_slice(&UTF8_FLAG.to_le_bytes());
+        zip.extend_from_slice(&0u16.to_le_bytes());
+        zip.extend_from_slice(&DOS_TIME.to_le_bytes());
+        zip.extend_from_slice(&DOS_DATE.to_le_bytes());
+        zip.extend_from_slice(&crc.to_le_bytes());
+        zip.extend_from_slice(&size.to_le_bytes());
+        zip.extend_from_slice(&size.to_le_bytes());
+        zip.extend_from_slice(&name_len.to_le_bytes());
+        zip.extend_from_slice(&0u16.to_le_bytes());
+        zip.extend_from_slice(name.as_bytes());
+        zip.extend_from_slice(data);
+
+        central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes());
+        central.extend_from_slice(&UTF8_FLAG.to_le_bytes());
+        central.extend_from_slice(&0u16.to_le_bytes());
+        central.extend_from_slice(&DOS_TIME.to_le_bytes());
+        central.extend_from_slice(&DOS_DATE.to_le_bytes());
+        central.extend_from_slice(&crc.to_le_bytes());
+        central.extend_from_slice(&size.to_le_bytes());
+        central.extend_from_slice(&size.to_le_bytes());
+        central.extend_from_slice(&name_len.to_le_bytes());
+        // extra、comment 长度，磁盘号，内部、外部属性
+        central.extend_from_slice(&[0u8; 12]);
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let central_offset = u32::try_from(zip.len()).map_err(too_large)?;
+    let central_len = u32::try_from(central.len()).map_err(too_large)?;
+    zip.extend_from_slice(&central);
+    zip.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    zip.extend_from_slice(&[0u8; 4]);
+    zip.extend_from_slice(&count.to_le_bytes());
+    zip.extend_from_slice(&count.to_le_bytes());
+    zip.extend_from_slice(&central_len.to_le_bytes());
+    zip.extend_from_slice(&central_offset.to_le_bytes());
+    zip.extend_from_slice(&0u16.to_le_bytes());
+    Ok(zip)
+}
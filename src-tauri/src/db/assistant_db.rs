@@ -7,7 +7,7 @@ pub struct Assistant {
     pub id: i64,
     pub name: String,
     pub description: Option<String>,
-    pub assistant_type: Option<i64>, // 0: 普通对话助手, 1: 多模型对比助手，2: 工作流助手，3: 展示助手
+    pub assistant_type: Option<i64>, // 0: 普通对话助手, 1: 多模型对比助手，2: 工作流助手，3: 展示助手，4: 选中文本处理助手
     pub is_addition: bool,
     pub created_time: String,
 }
@@ -49,6 +49,17 @@ pub struct AssistantPromptParam {
     pub param_value: Option<String>,
 }
 
+// 助手系统提示词和模型配置的历史版本，每次保存有变化时记录一个新版本
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AssistantPromptVersion {
+    pub id: i64,
+    pub assistant_id: i64,
+    pub version: i64,
+    pub prompt: String,
+    pub model_configs: Vec<AssistantModelConfig>,
+    pub created_time: String,
+}
+
 pub struct AssistantDatabase {
     pub conn: Connection,
 }
@@ -118,6 +129,19 @@ impl AssistantDatabase {
             [],
         )?;
 
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS assistant_prompt_version (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                assistant_id INTEGER NOT NULL,
+                version INTEGER NOT NULL,
+                prompt TEXT NOT NULL,
+                model_configs TEXT NOT NULL,
+                created_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (assistant_id) REFERENCES assistant(id)
+            );",
+            [],
+        )?;
+
         if let Err(err) = self.init_assistant() {
             println!("init_assistant error: {:?}", err);
         }
@@ -207,6 +231,14 @@ impl AssistantDatabase {
         Ok(())
     }
 
+    pub fn delete_assistant_model_by_assistant_id(&self, assistant_id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM assistant_model WHERE assistant_id = ?",
+            params![assistant_id],
+        )?;
+        Ok(())
+    }
+
     pub fn add_assistant_model_config(
         &self,
         assistant_id: i64,
@@ -254,23 +286,62 @@ impl AssistantDatabase {
         Ok(())
     }
 
-    pub fn update_assistant_prompt_param(
-        &self,
-        id: i64,
-        param_name: &str,
-        param_type: &str,
-        param_value: &str,
-    ) -> Result<()> {
+    pub fn delete_assistant_prompt_param_by_assistant_id(&self, assistant_id: i64) -> Result<()> {
         self.conn.execute(
-            "UPDATE assistant_prompt_param SET param_name = ?, param_type = ?, param_value = ? WHERE id = ?",
-            params![param_name, param_type, param_value, id],
+            "DELETE FROM assistant_prompt_param WHERE assistant_id = ?",
+            params![assistant_id],
         )?;
         Ok(())
     }
 
-    pub fn delete_assistant_prompt_param_by_assistant_id(&self, assistant_id: i64) -> Result<()> {
+    pub fn add_prompt_version(
+        &self,
+        assistant_id: i64,
+        prompt: &str,
+        model_configs: &[AssistantModelConfig],
+    ) -> Result<i64> {
+        let model_configs = serde_json::to_string(model_configs)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
         self.conn.execute(
-            "DELETE FROM assistant_prompt_param WHERE assistant_id = ?",
+            "INSERT INTO assistant_prompt_version (assistant_id, version, prompt, model_configs)
+             SELECT ?1, COALESCE(MAX(version), 0) + 1, ?2, ?3 FROM assistant_prompt_version WHERE assistant_id = ?1",
+            params![assistant_id, prompt, model_configs],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn read_prompt_version(row: &rusqlite::Row) -> Result<AssistantPromptVersion> {
+        let model_configs: String = row.get(4)?;
+        Ok(AssistantPromptVersion {
+            id: row.get(0)?,
+            assistant_id: row.get(1)?,
+            version: row.get(2)?,
+            prompt: row.get(3)?,
+            model_configs: serde_json::from_str(&model_configs).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    4,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?,
+            created_time: row.get(5)?,
+        })
+    }
+
+    // 按版本号倒序返回
+    pub fn get_prompt_versions(&self, assistant_id: i64) -> Result<Vec<AssistantPromptVersion>> {
+        let mut stmt = self.conn.prepare("SELECT id, assistant_id, version, prompt, model_configs, created_time FROM assistant_prompt_version WHERE assistant_id = ? ORDER BY version DESC")?;
+        let version_iter = stmt.query_map(params![assistant_id], Self::read_prompt_version)?;
+        version_iter.collect()
+    }
+
+    pub fn get_prompt_version(&self, id: i64) -> Result<AssistantPromptVersion> {
+        self.conn.query_row("SELECT id, assistant_id, version, prompt, model_configs, created_time FROM assistant_prompt_version WHERE id = ?", params![id], Self::read_prompt_version)
+    }
+
+    pub fn delete_prompt_version_by_assistant_id(&self, assistant_id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM assistant_prompt_version WHERE assistant_id = ?",
             params![assistant_id],
         )?;
         Ok(())
@@ -4,6 +4,8 @@ use chrono::prelude::*;
 use rusqlite::{Connection, OptionalExtension, Result};
 use serde::{Deserialize, Serialize};
 
+use crate::artifacts::sandbox::SandboxResult;
+use crate::artifacts::workspace::ArtifactFile;
 use crate::errors::AppError;
 
 use super::get_db_path;
@@ -16,6 +18,7 @@ pub enum AttachmentType {
     Word = 4,
     PowerPoint = 5,
     Excel = 6,
+    Audio = 7,
 }
 
 impl TryFrom<i64> for AttachmentType {
@@ -29,6 +32,7 @@ impl TryFrom<i64> for AttachmentType {
             4 => Ok(AttachmentType::Word),
             5 => Ok(AttachmentType::PowerPoint),
             6 => Ok(AttachmentType::Excel),
+            7 => Ok(AttachmentType::Audio),
             _ => Err(rusqlite::Error::FromSqlConversionFailure(
                 2,
                 rusqlite::types::Type::Integer,
@@ -61,6 +65,10 @@ pub struct Message {
     pub start_time: Option<DateTime<Utc>>,
     pub finish_time: Option<DateTime<Utc>>,
     pub token_count: i32,
+    // 生成这条消息时助手提示词的版本
+    pub prompt_version_id: Option<i64>,
+    // 多模型对比助手中其他模型的回答，指向主模型的回答，不参与后续的对话历史
+    pub compare_to_id: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -73,8 +81,11 @@ pub struct MessageDetail {
     pub llm_model_id: Option<i64>,
     pub created_time: DateTime<Utc>,
     pub token_count: i32,
+    pub prompt_version_id: Option<i64>,
+    pub llm_model_name: Option<String>,
     pub attachment_list: Vec<MessageAttachment>,
     pub regenerate: Vec<MessageDetail>,
+    pub compare: Vec<MessageDetail>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -89,6 +100,85 @@ pub struct MessageAttachment {
     pub token_count: Option<i32>,
 }
 
+// 用户对回答的反馈，rating 为 1 表示好，-1 表示不好
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageFeedback {
+    pub id: i64,
+    pub message_id: i64,
+    pub rating: i64,
+    pub tags: Vec<String>,
+    pub comment: Option<String>,
+    pub created_time: DateTime<Utc>,
+}
+
+// 带上消息所属助手和模型的反馈，用于查询和统计
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FeedbackRecord {
+    pub feedback: MessageFeedback,
+    pub conversation_id: i64,
+    pub assistant_id: Option<i64>,
+    pub llm_model_id: Option<i64>,
+    pub llm_model_name: Option<String>,
+    pub prompt_version_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FeedbackFilter {
+    pub assistant_id: Option<i64>,
+    pub llm_model_name: Option<String>,
+    pub rating: Option<i64>,
+    pub tag: Option<String>,
+}
+
+// 带上问题和回答的反馈，用于优化提示词
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FeedbackExample {
+    pub message_id: i64,
+    pub question: String,
+    pub answer: String,
+    pub comment: Option<String>,
+}
+
+// 一次代码运行的记录，还在运行时 finished_time 为空
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArtifactRunRecord {
+    pub id: i64,
+    pub message_id: Option<i64>,
+    pub language: String,
+    pub code: String,
+    pub code_hash: String,
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub truncated: bool,
+    pub killed: bool,
+    pub duration_ms: u64,
+    pub created_time: DateTime<Utc>,
+    pub finished_time: Option<DateTime<Utc>>,
+}
+
+// 对话的多文件产物，每个对话一个
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Artifact {
+    pub id: i64,
+    pub conversation_id: i64,
+    pub name: String,
+    pub created_time: DateTime<Utc>,
+    pub updated_time: DateTime<Utc>,
+}
+
+// 产物的一个版本保存全部文件，message_id 为产生这个版本的回答
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArtifactVersion {
+    pub id: i64,
+    pub artifact_id: i64,
+    pub version: i64,
+    pub message_id: Option<i64>,
+    pub files: Vec<ArtifactFile>,
+    pub created_time: DateTime<Utc>,
+}
+
 pub trait Repository<T> {
     fn create(&self, item: &T) -> Result<T>;
     fn read(&self, id: i64) -> Result<Option<T>>;
@@ -136,6 +226,24 @@ impl ConversationRepository {
         Ok(())
     }
 
+    // 助手最近的一个对话
+    pub fn find_latest_by_assistant_id(&self, assistant_id: i64) -> Result<Option<Conversation>> {
+        self.conn
+            .query_row(
+                "SELECT id, name, assistant_id, created_time FROM conversation WHERE assistant_id = ?1 ORDER BY id DESC LIMIT 1",
+                [assistant_id],
+                |row| {
+                    Ok(Conversation {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        assistant_id: row.get(2)?,
+                        created_time: row.get(3)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
     pub fn update_name(&self, conversation: &Conversation) -> Result<()> {
         self.conn.execute(
             "UPDATE conversation SET name = ?1 WHERE id = ?2",
@@ -213,7 +321,7 @@ impl MessageRepository {
         &self,
         conversation_id: i64,
     ) -> Result<Vec<(Message, Option<MessageAttachment>)>> {
-        let mut stmt = self.conn.prepare("SELECT message.id, message.parent_id, message.conversation_id, message.message_type, message.content, message.llm_model_id, message.llm_model_name, message.created_time, message.start_time, message.finish_time, message.token_count, ma.attachment_type, ma.attachment_url, ma.attachment_content, ma.use_vector as attachment_use_vector, ma.token_count as attachment_token_count
+        let mut stmt = self.conn.prepare("SELECT message.id, message.parent_id, message.conversation_id, message.message_type, message.content, message.llm_model_id, message.llm_model_name, message.created_time, message.start_time, message.finish_time, message.token_count, ma.attachment_type, ma.attachment_url, ma.attachment_content, ma.use_vector as attachment_use_vector, ma.token_count as attachment_token_count, message.prompt_version_id, message.compare_to_id
                                           FROM message
                                           LEFT JOIN message_attachment ma on message.id = ma.message_id
                                           WHERE conversation_id = ?1")?;
@@ -234,6 +342,8 @@ impl MessageRepository {
                 start_time: row.get(8)?,
                 finish_time: row.get(9)?,
                 token_count: row.get(10)?,
+                prompt_version_id: row.get(16)?,
+                compare_to_id: row.get(17)?,
             };
             let attachment = if attachment_type.is_some() {
                 Some(MessageAttachment {
@@ -262,6 +372,14 @@ impl MessageRepository {
         Ok(())
     }
 
+    pub fn update_prompt_version_id(&self, id: i64, prompt_version_id: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE message SET prompt_version_id = ?1 WHERE id = ?2",
+            (&prompt_version_id, &id),
+        )?;
+        Ok(())
+    }
+
     pub fn update_finish_time(&self, id: i64) -> Result<()> {
         self.conn.execute(
             "UPDATE message SET finish_time = CURRENT_TIMESTAMP WHERE id = ?1",
@@ -274,7 +392,7 @@ impl MessageRepository {
 impl Repository<Message> for MessageRepository {
     fn create(&self, message: &Message) -> Result<Message> {
         self.conn.execute(
-            "INSERT INTO message (parent_id, conversation_id, message_type, content, llm_model_id, llm_model_name, created_time, start_time, finish_time, token_count) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            "INSERT INTO message (parent_id, conversation_id, message_type, content, llm_model_id, llm_model_name, created_time, start_time, finish_time, token_count, prompt_version_id, compare_to_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             (
                 &message.parent_id,
                 &message.conversation_id,
@@ -286,6 +404,8 @@ impl Repository<Message> for MessageRepository {
                 &message.start_time,
                 &message.finish_time,
                 &message.token_count,
+                &message.prompt_version_id,
+                &message.compare_to_id,
             ),
         )?;
         let id = self.conn.last_insert_rowid();
@@ -301,12 +421,14 @@ impl Repository<Message> for MessageRepository {
             start_time: message.start_time,
             finish_time: message.finish_time,
             token_count: message.token_count,
+            prompt_version_id: message.prompt_version_id,
+            compare_to_id: message.compare_to_id,
         })
     }
 
     fn read(&self, id: i64) -> Result<Option<Message>> {
         self.conn
-            .query_row("SELECT id, parent_id, conversation_id, message_type, content, llm_model_id, llm_model_name, created_time, start_time, finish_time, token_count FROM message WHERE id = ?", &[&id], |row| {
+            .query_row("SELECT id, parent_id, conversation_id, message_type, content, llm_model_id, llm_model_name, created_time, start_time, finish_time, token_count, prompt_version_id, compare_to_id FROM message WHERE id = ?", &[&id], |row| {
                 Ok(Message {
                     id: row.get(0)?,
                     parent_id: row.get(1)?,
@@ -319,6 +441,8 @@ impl Repository<Message> for MessageRepository {
                     start_time: row.get(8)?,
                     finish_time: row.get(9)?,
                     token_count: row.get(10)?,
+                    prompt_version_id: row.get(11)?,
+                    compare_to_id: row.get(12)?,
                 })
             })
             .optional()
@@ -340,7 +464,10 @@ impl Repository<Message> for MessageRepository {
         Ok(())
     }
 
+    // 消息的代码运行记录一起删除
     fn delete(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM artifact_run WHERE message_id = ?", &[&id])?;
         self.conn
             .execute("DELETE FROM message WHERE id = ?", &[&id])?;
         Ok(())
@@ -404,6 +531,390 @@ impl MessageAttachmentRepository {
     }
 }
 
+pub struct MessageFeedbackRepository {
+    conn: Connection,
+}
+
+impl MessageFeedbackRepository {
+    pub fn new(conn: Connection) -> Self {
+        MessageFeedbackRepository { conn }
+    }
+
+    // 每条消息只保留一条反馈，重复反馈时覆盖
+    pub fn upsert(
+        &self,
+        message_id: i64,
+        rating: i64,
+        tags: &[String],
+        comment: Option<&str>,
+    ) -> Result<()> {
+        let tags = serde_json::to_string(tags)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.conn.execute(
+            "INSERT INTO message_feedback (message_id, rating, tags, comment, created_time) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(message_id) DO UPDATE SET rating = excluded.rating, tags = excluded.tags, comment = excluded.comment, created_time = excluded.created_time",
+            (&message_id, &rating, &tags, &comment, &Utc::now()),
+        )?;
+        Ok(())
+    }
+
+    fn read_feedback(row: &rusqlite::Row) -> Result<MessageFeedback> {
+        let tags: Option<String> = row.get(3)?;
+        Ok(MessageFeedback {
+            id: row.get(0)?,
+            message_id: row.get(1)?,
+            rating: row.get(2)?,
+            tags: tags
+                .and_then(|t| serde_json::from_str(&t).ok())
+                .unwrap_or_default(),
+            comment: row.get(4)?,
+            created_time: row.get(5)?,
+        })
+    }
+
+    pub fn read_by_message_id(&self, message_id: i64) -> Result<Option<MessageFeedback>> {
+        self.conn
+            .query_row(
+                "SELECT id, message_id, rating, tags, comment, created_time FROM message_feedback WHERE message_id = ?",
+                &[&message_id],
+                Self::read_feedback,
+            )
+            .optional()
+    }
+
+    // 按助手、模型、评价和标签筛选反馈，按时间倒序，limit 为空时不限制条数
+    pub fn list(
+        &self,
+        filter: &FeedbackFilter,
+        limit: Option<usize>,
+    ) -> Result<Vec<FeedbackRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT f.id, f.message_id, f.rating, f.tags, f.comment, f.created_time,
+                    m.conversation_id, c.assistant_id, m.llm_model_id, m.llm_model_name, m.prompt_version_id
+             FROM message_feedback f
+             JOIN message m ON m.id = f.message_id
+             LEFT JOIN conversation c ON c.id = m.conversation_id
+             WHERE (?1 IS NULL OR c.assistant_id = ?1)
+               AND (?2 IS NULL OR m.llm_model_name = ?2)
+               AND (?3 IS NULL OR f.rating = ?3)
+               AND (?4 IS NULL OR EXISTS (SELECT 1 FROM json_each(f.tags) WHERE json_each.value = ?4))
+             ORDER BY f.created_time DESC
+             LIMIT COALESCE(?5, -1)",
+        )?;
+        let rows = stmt.query_map(
+            (
+                &filter.assistant_id,
+                &filter.llm_model_name,
+                &filter.rating,
+                &filter.tag,
+                &limit.map(|l| l as i64),
+            ),
+            |row| {
+                Ok(FeedbackRecord {
+                    feedback: Self::read_feedback(row)?,
+                    conversation_id: row.get(6)?,
+                    assistant_id: row.get(7)?,
+                    llm_model_id: row.get(8)?,
+                    llm_model_name: row.get(9)?,
+                    prompt_version_id: row.get(10)?,
+                })
+            },
+        )?;
+        rows.collect()
+    }
+
+    pub fn delete_by_message_id(&self, message_id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM message_feedback WHERE message_id = ?",
+            &[&message_id],
+        )?;
+        Ok(())
+    }
+
+    // 助手评价不好的回答，问题取同一对话中回答之前最近的一条用户消息
+    pub fn list_negative_examples(
+        &self,
+        assistant_id: i64,
+        limit: u32,
+    ) -> Result<Vec<FeedbackExample>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT m.id,
+                    (SELECT u.content FROM message u
+                     WHERE u.conversation_id = m.conversation_id AND u.id < m.id AND u.message_type = 'user'
+                     ORDER BY u.id DESC LIMIT 1),
+                    m.content, f.comment
+             FROM message_feedback f
+             JOIN message m ON m.id = f.message_id
+             JOIN conversation c ON c.id = m.conversation_id
+             WHERE c.assistant_id = ?1 AND f.rating < 0
+             ORDER BY f.created_time DESC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map((&assistant_id, &limit), |row| {
+            Ok(FeedbackExample {
+                message_id: row.get(0)?,
+                question: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                answer: row.get(2)?,
+                comment: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+pub struct ArtifactRunRepository {
+    conn: Connection,
+}
+
+impl ArtifactRunRepository {
+    pub fn new(conn: Connection) -> Self {
+        ArtifactRunRepository { conn }
+    }
+
+    // 开始运行时插入记录，返回的 id 同时作为执行 id
+    pub fn create(
+        &self,
+        message_id: Option<i64>,
+        language: &str,
+        code: &str,
+        code_hash: &str,
+    ) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO artifact_run (message_id, language, code, code_hash, created_time) VALUES (?1, ?2, ?3, ?4, ?5)",
+            (&message_id, &language, &code, &code_hash, &Utc::now()),
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn finish(&self, id: i64, result: &SandboxResult) -> Result<()> {
+        self.conn.execute(
+            "UPDATE artifact_run SET stdout = ?1, stderr = ?2, exit_code = ?3, timed_out = ?4, truncated = ?5, killed = ?6, duration_ms = ?7, finished_time = ?8 WHERE id = ?9",
+            (
+                &result.stdout,
+                &result.stderr,
+                &result.exit_code,
+                &result.timed_out,
+                &result.truncated,
+                &result.killed,
+                &(result.duration_ms as i64),
+                &Utc::now(),
+                &id,
+            ),
+        )?;
+        Ok(())
+    }
+
+    fn read_run(row: &rusqlite::Row) -> Result<ArtifactRunRecord> {
+        Ok(ArtifactRunRecord {
+            id: row.get(0)?,
+            message_id: row.get(1)?,
+            language: row.get(2)?,
+            code: row.get(3)?,
+            code_hash: row.get(4)?,
+            stdout: row.get(5)?,
+            stderr: row.get(6)?,
+            exit_code: row.get(7)?,
+            timed_out: row.get(8)?,
+            truncated: row.get(9)?,
+            killed: row.get(10)?,
+            duration_ms: row.get::<_, i64>(11)? as u64,
+            created_time: row.get(12)?,
+            finished_time: row.get(13)?,
+        })
+    }
+
+    pub fn read(&self, id: i64) -> Result<Option<ArtifactRunRecord>> {
+        self.conn
+            .query_row(
+                "SELECT id, message_id, language, code, code_hash, stdout, stderr, exit_code, timed_out, truncated, killed, duration_ms, created_time, finished_time
+                 FROM artifact_run WHERE id = ?",
+                &[&id],
+                Self::read_run,
+            )
+            .optional()
+    }
+
+    // 按消息或者代码筛选，最新的在前
+    pub fn list(
+        &self,
+        message_id: Option<i64>,
+        code_hash: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<ArtifactRunRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, message_id, language, code, code_hash, stdout, stderr, exit_code, timed_out, truncated, killed, duration_ms, created_time, finished_time
+             FROM artifact_run
+             WHERE (?1 IS NULL OR message_id = ?1)
+               AND (?2 IS NULL OR code_hash = ?2)
+             ORDER BY id DESC
+             LIMIT ?3",
+        )?;
+        let rows = stmt.query_map((&message_id, &code_hash, &limit), Self::read_run)?;
+        rows.collect()
+    }
+
+    pub fn delete_by_conversation_id(&self, conversation_id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM artifact_run WHERE message_id IN (SELECT id FROM message WHERE conversation_id = ?)",
+            &[&conversation_id],
+        )?;
+        Ok(())
+    }
+
+    // 应用退出时还在运行的记录不会再结束
+    pub fn fail_unfinished(&self) -> Result<()> {
+        self.conn.execute(
+            "UPDATE artifact_run SET killed = 1, finished_time = ?1 WHERE finished_time IS NULL",
+            [&Utc::now()],
+        )?;
+        Ok(())
+    }
+}
+
+pub struct ArtifactRepository {
+    conn: Connection,
+}
+
+impl ArtifactRepository {
+    pub fn new(conn: Connection) -> Self {
+        ArtifactRepository { conn }
+    }
+
+    fn read_artifact(row: &rusqlite::Row) -> Result<Artifact> {
+        Ok(Artifact {
+            id: row.get(0)?,
+            conversation_id: row.get(1)?,
+            name: row.get(2)?,
+            created_time: row.get(3)?,
+            updated_time: row.get(4)?,
+        })
+    }
+
+    pub fn read(&self, id: i64) -> Result<Option<Artifact>> {
+        self.conn
+            .query_row(
+                "SELECT id, conversation_id, name, created_time, updated_time FROM artifact WHERE id = ?",
+                &[&id],
+                Self::read_artifact,
+            )
+            .optional()
+    }
+
+    pub fn read_by_conversation_id(&self, conversation_id: i64) -> Result<Option<Artifact>> {
+        self.conn
+            .query_row(
+                "SELECT id, conversation_id, name, created_time, updated_time FROM artifact WHERE conversation_id = ?",
+                &[&conversation_id],
+                Self::read_artifact,
+            )
+            .optional()
+    }
+
+    pub fn create(&self, conversation_id: i64, name: &str) -> Result<Artifact> {
+        let now = Utc::now();
+        self.conn.execute(
+            "INSERT INTO artifact (conversation_id, name, created_time, updated_time) VALUES (?1, ?2, ?3, ?3)",
+            (&conversation_id, &name, &now),
+        )?;
+        Ok(Artifact {
+            id: self.conn.last_insert_rowid(),
+            conversation_id,
+            name: name.to_string(),
+            created_time: now,
+            updated_time: now,
+        })
+    }
+
+    fn read_version(row: &rusqlite::Row) -> Result<ArtifactVersion> {
+        let files: String = row.get(4)?;
+        Ok(ArtifactVersion {
+            id: row.get(0)?,
+            artifact_id: row.get(1)?,
+            version: row.get(2)?,
+            message_id: row.get(3)?,
+            files: serde_json::from_str(&files).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    4,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?,
+            created_time: row.get(5)?,
+        })
+    }
+
+    // 版本号从 1 开始递增
+    pub fn add_version(
+        &self,
+        artifact_id: i64,
+        message_id: Option<i64>,
+        files: &[ArtifactFile],
+    ) -> Result<ArtifactVersion> {
+        let now = Utc::now();
+        let content = serde_json::to_string(files)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let version: i64 = self.conn.query_row(
+            "SELECT COALESCE(MAX(version), 0) + 1 FROM artifact_version WHERE artifact_id = ?",
+            &[&artifact_id],
+            |row| row.get(0),
+        )?;
+        self.conn.execute(
+            "INSERT INTO artifact_version (artifact_id, version, message_id, files, created_time) VALUES (?1, ?2, ?3, ?4, ?5)",
+            (&artifact_id, &version, &message_id, &content, &now),
+        )?;
+        let id = self.conn.last_insert_rowid();
+        self.conn.execute(
+            "UPDATE artifact SET updated_time = ?1 WHERE id = ?2",
+            (&now, &artifact_id),
+        )?;
+        Ok(ArtifactVersion {
+            id,
+            artifact_id,
+            version,
+            message_id,
+            files: files.to_vec(),
+            created_time: now,
+        })
+    }
+
+    pub fn list_versions(&self, artifact_id: i64) -> Result<Vec<ArtifactVersion>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, artifact_id, version, message_id, files, created_time FROM artifact_version WHERE artifact_id = ? ORDER BY version",
+        )?;
+        let rows = stmt.query_map(&[&artifact_id], Self::read_version)?;
+        rows.collect()
+    }
+
+    // version 为空时返回最新版本
+    pub fn read_version_by_number(
+        &self,
+        artifact_id: i64,
+        version: Option<i64>,
+    ) -> Result<Option<ArtifactVersion>> {
+        self.conn
+            .query_row(
+                "SELECT id, artifact_id, version, message_id, files, created_time FROM artifact_version
+                 WHERE artifact_id = ?1 AND (?2 IS NULL OR version = ?2)
+                 ORDER BY version DESC LIMIT 1",
+                (&artifact_id, &version),
+                Self::read_version,
+            )
+            .optional()
+    }
+
+    pub fn delete_by_conversation_id(&self, conversation_id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM artifact_version WHERE artifact_id IN (SELECT id FROM artifact WHERE conversation_id = ?)",
+            &[&conversation_id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM artifact WHERE conversation_id = ?",
+            &[&conversation_id],
+        )?;
+        Ok(())
+    }
+}
+
 impl Repository<MessageAttachment> for MessageAttachmentRepository {
     fn create(&self, attachment: &MessageAttachment) -> Result<MessageAttachment> {
         self.conn.execute(
@@ -489,49 +1000,129 @@ impl ConversationDatabase {
         Ok(MessageAttachmentRepository::new(conn))
     }
 
+    pub fn get_connection(&self) -> rusqlite::Result<Connection> {
+        Connection::open(self.db_path.clone())
+    }
+
+    pub fn feedback_repo(&self) -> Result<MessageFeedbackRepository, AppError> {
+        let conn = Connection::open(self.db_path.clone()).map_err(AppError::from)?;
+        Ok(MessageFeedbackRepository::new(conn))
+    }
+
+    pub fn artifact_run_repo(&self) -> Result<ArtifactRunRepository, AppError> {
+        let conn = Connection::open(self.db_path.clone()).map_err(AppError::from)?;
+        Ok(ArtifactRunRepository::new(conn))
+    }
+
+    pub fn artifact_repo(&self) -> Result<ArtifactRepository, AppError> {
+        let conn = Connection::open(self.db_path.clone()).map_err(AppError::from)?;
+        Ok(ArtifactRepository::new(conn))
+    }
+
     pub fn create_tables(&self) -> rusqlite::Result<()> {
         let conn = Connection::open(self.db_path.clone()).unwrap();
+        create_tables(&conn)
+    }
+}
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS conversation (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                assistant_id INTEGER,
-                created_time DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-            [],
-        )?;
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS message (
-                id              INTEGER
-                primary key autoincrement,
-                conversation_id INTEGER not null,
-                message_type    TEXT    not null,
-                content         TEXT    not null,
-                llm_model_id    INTEGER,
-                created_time    DATETIME default CURRENT_TIMESTAMP,
-                token_count     INTEGER,
-                parent_id       integer,
-                start_time      DATETIME,
-                finish_time     DATETIME,
-                llm_model_name  TEXT
-            )",
-            [],
-        )?;
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS message_attachment (
-                id                 INTEGER
-                primary key autoincrement,
-                message_id         INTEGER,
-                attachment_type    INTEGER           not null,
-                attachment_url     TEXT,
-                attachment_content TEXT,
-                use_vector         BOOLEAN default 0 not null,
-                token_count        INTEGER
-            )",
-            [],
-        )?;
+pub(crate) fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS conversation (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            assistant_id INTEGER,
+            created_time DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS message (
+            id              INTEGER
+            primary key autoincrement,
+            conversation_id INTEGER not null,
+            message_type    TEXT    not null,
+            content         TEXT    not null,
+            llm_model_id    INTEGER,
+            created_time    DATETIME default CURRENT_TIMESTAMP,
+            token_count     INTEGER,
+            parent_id       integer,
+            start_time      DATETIME,
+            finish_time     DATETIME,
+            llm_model_name  TEXT,
+            prompt_version_id INTEGER,
+            compare_to_id   INTEGER
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS message_attachment (
+            id                 INTEGER
+            primary key autoincrement,
+            message_id         INTEGER,
+            attachment_type    INTEGER           not null,
+            attachment_url     TEXT,
+            attachment_content TEXT,
+            use_vector         BOOLEAN default 0 not null,
+            token_count        INTEGER
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS message_feedback (
+            id           INTEGER
+            primary key autoincrement,
+            message_id   INTEGER not null unique,
+            rating       INTEGER not null,
+            tags         TEXT,
+            comment      TEXT,
+            created_time DATETIME default CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS artifact_run (
+            id            INTEGER
+            primary key autoincrement,
+            message_id    INTEGER,
+            language      TEXT    not null,
+            code          TEXT    not null,
+            code_hash     TEXT    not null,
+            stdout        TEXT    default '' not null,
+            stderr        TEXT    default '' not null,
+            exit_code     INTEGER,
+            timed_out     BOOLEAN default 0 not null,
+            truncated     BOOLEAN default 0 not null,
+            killed        BOOLEAN default 0 not null,
+            duration_ms   INTEGER default 0 not null,
+            created_time  DATETIME default CURRENT_TIMESTAMP,
+            finished_time DATETIME
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS artifact (
+            id              INTEGER
+            primary key autoincrement,
+            conversation_id INTEGER not null unique,
+            name            TEXT    not null,
+            created_time    DATETIME default CURRENT_TIMESTAMP,
+            updated_time    DATETIME default CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS artifact_version (
+            id           INTEGER
+            primary key autoincrement,
+            artifact_id  INTEGER not null,
+            version      INTEGER not null,
+            message_id   INTEGER,
+            files        TEXT    not null,
+            created_time DATETIME default CURRENT_TIMESTAMP,
+            unique (artifact_id, version)
+        )",
+        [],
+    )?;
 
-        Ok(())
-    }
+    Ok(())
 }
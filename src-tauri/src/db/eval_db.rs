@@ -0,0 +1,409 @@
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+
+use super::get_db_path;
+
+// 评测题集
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EvalQuestionSet {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub question_count: i64,
+    pub created_time: String,
+}
+
+// 评测题目，scorer 为 exact、regex、llm_judge，expected 分别对应期望答案、正则表达式、评分标准
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EvalQuestion {
+    pub id: i64,
+    pub set_id: i64,
+    pub prompt: String,
+    // message_attachment 中的附件 id，通过 add_attachment 上传
+    pub attachment_ids: Vec<i64>,
+    pub scorer: String,
+    pub expected: String,
+    pub created_time: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EvalModel {
+    pub provider_id: i64,
+    pub model_code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EvalRun {
+    pub id: i64,
+    pub set_id: i64,
+    pub models: Vec<EvalModel>,
+    pub judge_model: Option<EvalModel>,
+    // running、finished、cancelled、failed
+    pub status: String,
+    pub total: i64,
+    pub finished: i64,
+    pub created_time: String,
+    pub finished_time: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EvalResult {
+    pub id: i64,
+    pub run_id: i64,
+    pub question_id: i64,
+    pub provider_id: i64,
+    pub model_code: String,
+    pub answer: String,
+    pub score: f64,
+    pub passed: bool,
+    pub reason: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+    pub created_time: String,
+}
+
+// 一次评测中某个模型的汇总结果，按时间排列可以对比模型的变化
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EvalModelSummary {
+    pub run_id: i64,
+    pub run_time: String,
+    pub provider_id: i64,
+    pub model_code: String,
+    pub count: i64,
+    pub passed: i64,
+    pub errors: i64,
+    pub avg_score: f64,
+    pub avg_duration_ms: f64,
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(index: usize, value: &str) -> Result<T> {
+    serde_json::from_str(value).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+pub struct EvalDatabase {
+    pub conn: Connection,
+}
+
+impl EvalDatabase {
+    pub fn new(app_handle: &tauri::AppHandle) -> rusqlite::Result<Self> {
+        let db_path = get_db_path(app_handle, "eval.db");
+        let conn = Connection::open(db_path.unwrap())?;
+        Ok(EvalDatabase { conn })
+    }
+
+    pub fn create_tables(&self) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS eval_question_set (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                created_time DATETIME DEFAULT CURRENT_TIMESTAMP
+            );",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS eval_question (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                set_id INTEGER NOT NULL,
+                prompt TEXT NOT NULL,
+                attachment_ids TEXT NOT NULL DEFAULT '[]',
+                scorer TEXT NOT NULL,
+                expected TEXT NOT NULL DEFAULT '',
+                created_time DATETIME DEFAULT CURRENT_TIMESTAMP
+            );",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS eval_run (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                set_id INTEGER NOT NULL,
+                models TEXT NOT NULL,
+                judge_model TEXT,
+                status TEXT NOT NULL,
+                total INTEGER NOT NULL DEFAULT 0,
+                created_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+                finished_time DATETIME
+            );",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS eval_result (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                run_id INTEGER NOT NULL,
+                question_id INTEGER NOT NULL,
+                provider_id INTEGER NOT NULL,
+                model_code TEXT NOT NULL,
+                answer TEXT NOT NULL,
+                score REAL NOT NULL,
+                passed BOOLEAN NOT NULL,
+                reason TEXT,
+                error TEXT,
+                duration_ms INTEGER NOT NULL,
+                created_time DATETIME DEFAULT CURRENT_TIMESTAMP
+            );",
+            [],
+        )?;
+        Ok(())
+    }
+
+    pub fn add_question_set(&self, name: &str, description: Option<&str>) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO eval_question_set (name, description) VALUES (?1, ?2)",
+            params![name, description],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_question_set(
+        &self,
+        id: i64,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<()> {
+        self.conn.execute(
+            "UPDATE eval_question_set SET name = ?1, description = ?2 WHERE id = ?3",
+            params![name, description, id],
+        )?;
+        Ok(())
+    }
+
+    // 删除题集时一并删除题目和评测记录
+    pub fn delete_question_set(&self, id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM eval_result WHERE run_id IN (SELECT id FROM eval_run WHERE set_id = ?1)",
+            [id],
+        )?;
+        self.conn
+            .execute("DELETE FROM eval_run WHERE set_id = ?1", [id])?;
+        self.conn
+            .execute("DELETE FROM eval_question WHERE set_id = ?1", [id])?;
+        self.conn
+            .execute("DELETE FROM eval_question_set WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn get_question_sets(&self) -> Result<Vec<EvalQuestionSet>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.name, s.description,
+                    (SELECT COUNT(*) FROM eval_question q WHERE q.set_id = s.id),
+                    s.created_time
+             FROM eval_question_set s ORDER BY s.id DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(EvalQuestionSet {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                description: row.get(2)?,
+                question_count: row.get(3)?,
+                created_time: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn add_question(
+        &self,
+        set_id: i64,
+        prompt: &str,
+        attachment_ids: &[i64],
+        scorer: &str,
+        expected: &str,
+    ) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO eval_question (set_id, prompt, attachment_ids, scorer, expected) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![set_id, prompt, to_json(&attachment_ids)?, scorer, expected],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_question(
+        &self,
+        id: i64,
+        prompt: &str,
+        attachment_ids: &[i64],
+        scorer: &str,
+        expected: &str,
+    ) -> Result<()> {
+        self.conn.execute(
+            "UPDATE eval_question SET prompt = ?1, attachment_ids = ?2, scorer = ?3, expected = ?4 WHERE id = ?5",
+            params![prompt, to_json(&attachment_ids)?, scorer, expected, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_question(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM eval_question WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn get_questions(&self, set_id: i64) -> Result<Vec<EvalQuestion>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, set_id, prompt, attachment_ids, scorer, expected, created_time
+             FROM eval_question WHERE set_id = ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map([set_id], |row| {
+            let attachment_ids: String = row.get(3)?;
+            Ok(EvalQuestion {
+                id: row.get(0)?,
+                set_id: row.get(1)?,
+                prompt: row.get(2)?,
+                attachment_ids: from_json(3, &attachment_ids)?,
+                scorer: row.get(4)?,
+                expected: row.get(5)?,
+                created_time: row.get(6)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn add_run(
+        &self,
+        set_id: i64,
+        models: &[EvalModel],
+        judge_model: Option<&EvalModel>,
+        total: i64,
+    ) -> Result<i64> {
+        let judge_model = judge_model.map(to_json).transpose()?;
+        self.conn.execute(
+            "INSERT INTO eval_run (set_id, models, judge_model, status, total) VALUES (?1, ?2, ?3, 'running', ?4)",
+            params![set_id, to_json(&models)?, judge_model, total],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn finish_run(&self, id: i64, status: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE eval_run SET status = ?1, finished_time = CURRENT_TIMESTAMP WHERE id = ?2",
+            params![status, id],
+        )?;
+        Ok(())
+    }
+
+    // 程序退出时还在运行的评测不会继续执行，启动时标记为失败
+    pub fn fail_unfinished_runs(&self) -> Result<()> {
+        self.conn.execute(
+            "UPDATE eval_run SET status = 'failed', finished_time = CURRENT_TIMESTAMP WHERE status = 'running'",
+            [],
+        )?;
+        Ok(())
+    }
+
+    fn read_run(row: &rusqlite::Row) -> Result<EvalRun> {
+        let models: String = row.get(2)?;
+        let judge_model: Option<String> = row.get(3)?;
+        Ok(EvalRun {
+            id: row.get(0)?,
+            set_id: row.get(1)?,
+            models: from_json(2, &models)?,
+            judge_model: judge_model.map(|j| from_json(3, &j)).transpose()?,
+            status: row.get(4)?,
+            total: row.get(5)?,
+            finished: row.get(6)?,
+            created_time: row.get(7)?,
+            finished_time: row.get(8)?,
+        })
+    }
+
+    const RUN_COLUMNS: &'static str = "r.id, r.set_id, r.models, r.judge_model, r.status, r.total,
+        (SELECT COUNT(*) FROM eval_result e WHERE e.run_id = r.id), r.created_time, r.finished_time";
+
+    pub fn get_runs(&self, set_id: i64) -> Result<Vec<EvalRun>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM eval_run r WHERE r.set_id = ?1 ORDER BY r.id DESC",
+            Self::RUN_COLUMNS
+        ))?;
+        let rows = stmt.query_map([set_id], Self::read_run)?;
+        rows.collect()
+    }
+
+    pub fn get_run(&self, id: i64) -> Result<Option<EvalRun>> {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM eval_run r WHERE r.id = ?1",
+                    Self::RUN_COLUMNS
+                ),
+                [id],
+                Self::read_run,
+            )
+            .optional()
+    }
+
+    pub fn add_result(&self, result: &EvalResult) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO eval_result (run_id, question_id, provider_id, model_code, answer, score, passed, reason, error, duration_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                result.run_id,
+                result.question_id,
+                result.provider_id,
+                result.model_code,
+                result.answer,
+                result.score,
+                result.passed,
+                result.reason,
+                result.error,
+                result.duration_ms
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_results(&self, run_id: i64) -> Result<Vec<EvalResult>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, run_id, question_id, provider_id, model_code, answer, score, passed, reason, error, duration_ms, created_time
+             FROM eval_result WHERE run_id = ?1 ORDER BY question_id, provider_id, model_code",
+        )?;
+        let rows = stmt.query_map([run_id], |row| {
+            Ok(EvalResult {
+                id: row.get(0)?,
+                run_id: row.get(1)?,
+                question_id: row.get(2)?,
+                provider_id: row.get(3)?,
+                model_code: row.get(4)?,
+                answer: row.get(5)?,
+                score: row.get(6)?,
+                passed: row.get(7)?,
+                reason: row.get(8)?,
+                error: row.get(9)?,
+                duration_ms: row.get(10)?,
+                created_time: row.get(11)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn get_model_summaries(&self, set_id: i64) -> Result<Vec<EvalModelSummary>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT r.id, r.created_time, e.provider_id, e.model_code, COUNT(*),
+                    SUM(CASE WHEN e.passed THEN 1 ELSE 0 END),
+                    SUM(CASE WHEN e.error IS NULL THEN 0 ELSE 1 END),
+                    AVG(e.score), AVG(e.duration_ms)
+             FROM eval_result e JOIN eval_run r ON r.id = e.run_id
+             WHERE r.set_id = ?1
+             GROUP BY r.id, e.provider_id, e.model_code
+             ORDER BY r.id, e.model_code",
+        )?;
+        let rows = stmt.query_map([set_id], |row| {
+            Ok(EvalModelSummary {
+                run_id: row.get(0)?,
+                run_time: row.get(1)?,
+                provider_id: row.get(2)?,
+                model_code: row.get(3)?,
+                count: row.get(4)?,
+                passed: row.get(5)?,
+                errors: row.get(6)?,
+                avg_score: row.get(7)?,
+                avg_duration_ms: row.get(8)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
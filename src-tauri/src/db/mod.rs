@@ -10,11 +10,14 @@ use tauri::Manager;
 
 pub mod assistant_db;
 pub mod conversation_db;
+pub mod eval_db;
 pub mod llm_db;
 pub mod plugin_db;
+pub mod schedule_db;
 pub mod system_db;
+pub mod workflow_db;
 
-const CURRENT_VERSION: &str = "0.0.2";
+const CURRENT_VERSION: &str = "0.0.4";
 
 fn get_db_path(app_handle: &tauri::AppHandle, db_name: &str) -> Result<PathBuf, String> {
     let app_dir = app_handle.path().app_data_dir().unwrap();
@@ -63,7 +66,11 @@ pub fn database_upgrade(
                         &ConversationDatabase,
                         &tauri::AppHandle,
                     ) -> Result<(), String>,
-                )> = vec![("0.0.2", special_logic_0_0_2)];
+                )> = vec![
+                    ("0.0.2", special_logic_0_0_2),
+                    ("0.0.3", special_logic_0_0_3),
+                    ("0.0.4", special_logic_0_0_4),
+                ];
 
                 for (version_str, logic) in special_versions.iter() {
                     let version = Version::parse(version_str).unwrap();
@@ -192,3 +199,45 @@ fn special_logic_0_0_2(
     println!("special_logic_0_0_2 done");
     Ok(())
 }
+
+fn special_logic_0_0_3(
+    _system_db: &SystemDatabase,
+    _llm_db: &LLMDatabase,
+    _assistant_db: &AssistantDatabase,
+    conversation_db: &ConversationDatabase,
+    _app_handle: &tauri::AppHandle,
+) -> Result<(), String> {
+    println!("special_logic_0_0_3");
+    // 消息记录生成时使用的助手提示词版本
+    conversation_db
+        .get_connection()
+        .map_err(|e| format!("打开对话数据库失败: {}", e))?
+        .execute(
+            "ALTER TABLE message ADD COLUMN prompt_version_id INTEGER;",
+            [],
+        )
+        .map_err(|e| format!("添加字段prompt_version_id失败: {}", e))?;
+    println!("special_logic_0_0_3 done");
+    Ok(())
+}
+
+fn special_logic_0_0_4(
+    _system_db: &SystemDatabase,
+    _llm_db: &LLMDatabase,
+    _assistant_db: &AssistantDatabase,
+    conversation_db: &ConversationDatabase,
+    _app_handle: &tauri::AppHandle,
+) -> Result<(), String> {
+    println!("special_logic_0_0_4");
+    // 多模型对比助手其他模型的回答对应的主回答，之前版本中这类回答的 parent_id 指向主回答
+    conversation_db
+        .get_connection()
+        .map_err(|e| format!("打开对话数据库失败: {}", e))?
+        .execute("ALTER TABLE message ADD COLUMN compare_to_id INTEGER;", [])
+        .map_err(|e| format!("添加字段compare_to_id失败: {}", e))?;
+    println!("special_logic_0_0_4 done");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
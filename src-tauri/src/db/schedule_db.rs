@@ -0,0 +1,236 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+
+use super::get_db_path;
+
+// 定时任务，按 cron 表达式用指定助手执行 prompt，prompt 支持模板语法
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduledTask {
+    pub id: i64,
+    pub name: String,
+    pub cron: String,
+    pub assistant_id: i64,
+    pub prompt: String,
+    // run_once：启动后补执行一次错过的任务，skip：跳过错过的任务
+    pub missed_policy: String,
+    pub enabled: bool,
+    pub last_run_time: Option<DateTime<Utc>>,
+    pub next_run_time: Option<DateTime<Utc>>,
+    pub created_time: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduledTaskRun {
+    pub id: i64,
+    pub task_id: i64,
+    // 计划执行的时间，补执行时早于实际执行时间
+    pub scheduled_time: DateTime<Utc>,
+    // running、success、failed
+    pub status: String,
+    pub missed: bool,
+    pub conversation_id: Option<i64>,
+    pub error: Option<String>,
+    pub created_time: String,
+    pub finished_time: Option<String>,
+}
+
+pub struct ScheduleDatabase {
+    pub conn: Connection,
+}
+
+impl ScheduleDatabase {
+    pub fn new(app_handle: &tauri::AppHandle) -> rusqlite::Result<Self> {
+        let db_path = get_db_path(app_handle, "schedule.db");
+        let conn = Connection::open(db_path.unwrap())?;
+        Ok(ScheduleDatabase { conn })
+    }
+
+    pub fn create_tables(&self) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS scheduled_task (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                cron TEXT NOT NULL,
+                assistant_id INTEGER NOT NULL,
+                prompt TEXT NOT NULL,
+                missed_policy TEXT NOT NULL DEFAULT 'run_once',
+                enabled BOOLEAN NOT NULL DEFAULT 1,
+                last_run_time DATETIME,
+                next_run_time DATETIME,
+                created_time DATETIME DEFAULT CURRENT_TIMESTAMP
+            );",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS scheduled_task_run (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id INTEGER NOT NULL,
+                scheduled_time DATETIME NOT NULL,
+                status TEXT NOT NULL,
+                missed BOOLEAN NOT NULL DEFAULT 0,
+                conversation_id INTEGER,
+                error TEXT,
+                created_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+                finished_time DATETIME
+            );",
+            [],
+        )?;
+        Ok(())
+    }
+
+    pub fn add_task(&self, task: &ScheduledTask) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO scheduled_task (name, cron, assistant_id, prompt, missed_policy, enabled, next_run_time)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                task.name,
+                task.cron,
+                task.assistant_id,
+                task.prompt,
+                task.missed_policy,
+                task.enabled,
+                task.next_run_time
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_task(&self, task: &ScheduledTask) -> Result<()> {
+        self.conn.execute(
+            "UPDATE scheduled_task SET name = ?1, cron = ?2, assistant_id = ?3, prompt = ?4,
+                missed_policy = ?5, enabled = ?6, next_run_time = ?7 WHERE id = ?8",
+            params![
+                task.name,
+                task.cron,
+                task.assistant_id,
+                task.prompt,
+                task.missed_policy,
+                task.enabled,
+                task.next_run_time,
+                task.id
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_task(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM scheduled_task_run WHERE task_id = ?1", [id])?;
+        self.conn
+            .execute("DELETE FROM scheduled_task WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn read_task(row: &rusqlite::Row) -> Result<ScheduledTask> {
+        Ok(ScheduledTask {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            cron: row.get(2)?,
+            assistant_id: row.get(3)?,
+            prompt: row.get(4)?,
+            missed_policy: row.get(5)?,
+            enabled: row.get(6)?,
+            last_run_time: row.get(7)?,
+            next_run_time: row.get(8)?,
+            created_time: row.get(9)?,
+        })
+    }
+
+    pub fn get_tasks(&self) -> Result<Vec<ScheduledTask>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, cron, assistant_id, prompt, missed_policy, enabled, last_run_time, next_run_time, created_time
+             FROM scheduled_task ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([], Self::read_task)?;
+        rows.collect()
+    }
+
+    pub fn get_task(&self, id: i64) -> Result<Option<ScheduledTask>> {
+        self.conn
+            .query_row(
+                "SELECT id, name, cron, assistant_id, prompt, missed_policy, enabled, last_run_time, next_run_time, created_time
+                 FROM scheduled_task WHERE id = ?1",
+                [id],
+                Self::read_task,
+            )
+            .optional()
+    }
+
+    pub fn update_next_run_time(
+        &self,
+        id: i64,
+        next_run_time: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        self.conn.execute(
+            "UPDATE scheduled_task SET next_run_time = ?1 WHERE id = ?2",
+            params![next_run_time, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn update_last_run_time(&self, id: i64, last_run_time: DateTime<Utc>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE scheduled_task SET last_run_time = ?1 WHERE id = ?2",
+            params![last_run_time, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn add_run(
+        &self,
+        task_id: i64,
+        scheduled_time: DateTime<Utc>,
+        missed: bool,
+    ) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO scheduled_task_run (task_id, scheduled_time, status, missed) VALUES (?1, ?2, 'running', ?3)",
+            params![task_id, scheduled_time, missed],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn finish_run(
+        &self,
+        id: i64,
+        status: &str,
+        conversation_id: Option<i64>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        self.conn.execute(
+            "UPDATE scheduled_task_run SET status = ?1, conversation_id = ?2, error = ?3, finished_time = CURRENT_TIMESTAMP WHERE id = ?4",
+            params![status, conversation_id, error, id],
+        )?;
+        Ok(())
+    }
+
+    // 程序退出时还在执行的任务不会继续，启动时标记为失败
+    pub fn fail_unfinished_runs(&self) -> Result<()> {
+        self.conn.execute(
+            "UPDATE scheduled_task_run SET status = 'failed', error = '程序退出，执行中断', finished_time = CURRENT_TIMESTAMP WHERE status = 'running'",
+            [],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_runs(&self, task_id: i64) -> Result<Vec<ScheduledTaskRun>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, task_id, scheduled_time, status, missed, conversation_id, error, created_time, finished_time
+             FROM scheduled_task_run WHERE task_id = ?1 ORDER BY id DESC LIMIT 50",
+        )?;
+        let rows = stmt.query_map([task_id], |row| {
+            Ok(ScheduledTaskRun {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                scheduled_time: row.get(2)?,
+                status: row.get(3)?,
+                missed: row.get(4)?,
+                conversation_id: row.get(5)?,
+                error: row.get(6)?,
+                created_time: row.get(7)?,
+                finished_time: row.get(8)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
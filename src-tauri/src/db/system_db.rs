@@ -13,6 +13,18 @@ pub struct FeatureConfig {
     pub description: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomBang {
+    pub id: i64,
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub description: String,
+    // text: 带有!占位符的文本片段, shell: 直接执行程序(不经过shell)取stdout, http: GET请求并按json_path提取, file: 读取允许目录下的文件
+    pub bang_kind: String,
+    pub body: String,
+    pub json_path: Option<String>,
+}
+
 pub struct SystemDatabase {
     pub conn: Connection,
 }
@@ -46,6 +58,19 @@ impl SystemDatabase {
             )",
             [],
         )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS custom_bang (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                aliases TEXT NOT NULL DEFAULT '',
+                description TEXT,
+                bang_kind TEXT NOT NULL,
+                body TEXT NOT NULL,
+                json_path TEXT,
+                created_time DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
         Ok(())
     }
 
@@ -144,7 +169,7 @@ impl SystemDatabase {
     }
 
     // 查询特定模块的所有配置
-    fn get_feature_config_by_module(&self, feature_code: &str) -> Result<Vec<FeatureConfig>> {
+    pub fn get_feature_config_by_module(&self, feature_code: &str) -> Result<Vec<FeatureConfig>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, feature_code, key, value, data_type, description
              FROM feature_config WHERE feature_code = ?1",
@@ -185,6 +210,67 @@ impl SystemDatabase {
         Ok(configs)
     }
 
+    pub fn get_custom_bangs(&self) -> Result<Vec<CustomBang>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, aliases, description, bang_kind, body, json_path
+             FROM custom_bang ORDER BY id",
+        )?;
+        let bangs = stmt
+            .query_map(params![], |row| {
+                let aliases: String = row.get(2)?;
+                Ok(CustomBang {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    aliases: split_aliases(&aliases),
+                    description: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                    bang_kind: row.get(4)?,
+                    body: row.get(5)?,
+                    json_path: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(bangs)
+    }
+
+    pub fn add_custom_bang(&self, bang: &CustomBang) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO custom_bang (name, aliases, description, bang_kind, body, json_path)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                bang.name,
+                bang.aliases.join(","),
+                bang.description,
+                bang.bang_kind,
+                bang.body,
+                bang.json_path
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_custom_bang(&self, bang: &CustomBang) -> Result<()> {
+        self.conn.execute(
+            "UPDATE custom_bang SET name = ?1, aliases = ?2, description = ?3, bang_kind = ?4, body = ?5, json_path = ?6
+             WHERE id = ?7",
+            params![
+                bang.name,
+                bang.aliases.join(","),
+                bang.description,
+                bang.bang_kind,
+                bang.body,
+                bang.json_path,
+                bang.id
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_custom_bang(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM custom_bang WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
     pub fn init_feature_config(&self) -> rusqlite::Result<()> {
         self.add_feature_config(&FeatureConfig {
             id: None,
@@ -210,3 +296,11 @@ impl SystemDatabase {
         Ok(())
     }
 }
+
+fn split_aliases(aliases: &str) -> Vec<String> {
+    aliases
+        .split(',')
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect()
+}
@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use rusqlite::Connection;
+
+use super::conversation_db::{
+    create_tables, ArtifactRunRepository, Conversation, ConversationRepository, FeedbackFilter,
+    Message, MessageFeedbackRepository, MessageRepository, Repository,
+};
+
+fn test_db(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("aipp_db_test_{}_{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    create_tables(&Connection::open(&path).unwrap()).unwrap();
+    path
+}
+
+// 在助手 assistant_id 的新对话中添加一条 model 的回答，返回消息 id
+fn add_answer(path: &PathBuf, assistant_id: i64, model: &str) -> i64 {
+    let conversation = ConversationRepository::new(Connection::open(path).unwrap())
+        .create(&Conversation {
+            id: 0,
+            name: "测试".to_string(),
+            assistant_id: Some(assistant_id),
+            created_time: Utc::now(),
+        })
+        .unwrap();
+    MessageRepository::new(Connection::open(path).unwrap())
+        .create(&Message {
+            id: 0,
+            parent_id: None,
+            conversation_id: conversation.id,
+            message_type: "assistant".to_string(),
+            content: "回答".to_string(),
+            llm_model_id: None,
+            llm_model_name: Some(model.to_string()),
+            created_time: Utc::now(),
+            start_time: None,
+            finish_time: None,
+            token_count: 0,
+            prompt_version_id: None,
+            compare_to_id: None,
+        })
+        .unwrap()
+        .id
+}
+
+#[test]
+fn test_feedback_list_filters_in_sql() {
+    let path = test_db("feedback");
+    let repo = MessageFeedbackRepository::new(Connection::open(&path).unwrap());
+    let first = add_answer(&path, 1, "gpt-4o");
+    let second = add_answer(&path, 1, "gpt-4o-mini");
+    let third = add_answer(&path, 2, "gpt-4o");
+    repo.upsert(first, 1, &["准确".to_string()], None).unwrap();
+    repo.upsert(
+        second,
+        -1,
+        &["啰嗦".to_string(), "不准确".to_string()],
+        Some("太长了"),
+    )
+    .unwrap();
+    repo.upsert(third, -1, &["不准确".to_string()], None)
+        .unwrap();
+
+    let ids = |filter: FeedbackFilter, limit: Option<usize>| {
+        let mut ids = repo
+            .list(&filter, limit)
+            .unwrap()
+            .iter()
+            .map(|r| r.feedback.message_id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+    assert_eq!(
+        ids(FeedbackFilter::default(), None),
+        vec![first, second, third]
+    );
+    assert_eq!(ids(FeedbackFilter::default(), Some(2)).len(), 2);
+    assert_eq!(
+        ids(
+            FeedbackFilter {
+                assistant_id: Some(1),
+                ..Default::default()
+            },
+            None
+        ),
+        vec![first, second]
+    );
+    assert_eq!(
+        ids(
+            FeedbackFilter {
+                rating: Some(-1),
+                llm_model_name: Some("gpt-4o".to_string()),
+                ..Default::default()
+            },
+            None
+        ),
+        vec![third]
+    );
+    // 标签需要完全一致，"准确" 不匹配 "不准确"
+    assert_eq!(
+        ids(
+            FeedbackFilter {
+                tag: Some("准确".to_string()),
+                ..Default::default()
+            },
+            None
+        ),
+        vec![first]
+    );
+    assert_eq!(
+        ids(
+            FeedbackFilter {
+                tag: Some("不准确".to_string()),
+                ..Default::default()
+            },
+            Some(1)
+        )
+        .len(),
+        1
+    );
+
+    // 再次反馈时覆盖之前的反馈
+    repo.upsert(first, -1, &[], None).unwrap();
+    let feedback = repo.read_by_message_id(first).unwrap().unwrap();
+    assert_eq!(feedback.rating, -1);
+    assert!(feedback.tags.is_empty());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_artifact_runs_deleted_with_message_and_conversation() {
+    let path = test_db("artifact_run");
+    let repo = ArtifactRunRepository::new(Connection::open(&path).unwrap());
+    let first = add_answer(&path, 1, "gpt-4o");
+    let second = add_answer(&path, 1, "gpt-4o");
+    let third = add_answer(&path, 1, "gpt-4o");
+    for message_id in [first, first, second, third] {
+        repo.create(Some(message_id), "bash", "echo 1", "hash")
+            .unwrap();
+    }
+    let conversation_id = MessageRepository::new(Connection::open(&path).unwrap())
+        .read(second)
+        .unwrap()
+        .unwrap()
+        .conversation_id;
+
+    MessageRepository::new(Connection::open(&path).unwrap())
+        .delete(first)
+        .unwrap();
+    assert!(repo.list(Some(first), None, 10).unwrap().is_empty());
+
+    repo.delete_by_conversation_id(conversation_id).unwrap();
+    assert!(repo.list(Some(second), None, 10).unwrap().is_empty());
+    // 其他对话的运行记录不受影响
+    assert_eq!(repo.list(None, None, 10).unwrap().len(), 1);
+
+    let _ = std::fs::remove_file(&path);
+}
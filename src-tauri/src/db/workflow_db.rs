@@ -0,0 +1,264 @@
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+
+use super::get_db_path;
+
+// 工作流，definition 为 YAML 或 JSON 格式的定义原文
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Workflow {
+    pub id: i64,
+    pub name: String,
+    pub definition: String,
+    pub created_time: String,
+    pub updated_time: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkflowRun {
+    pub id: i64,
+    pub workflow_id: i64,
+    // running、success、failed、cancelled
+    pub status: String,
+    pub inputs: String,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub created_time: String,
+    pub finished_time: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkflowStepRun {
+    pub id: i64,
+    pub run_id: i64,
+    pub step_id: String,
+    pub status: String,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub started_time: String,
+    pub finished_time: Option<String>,
+}
+
+pub struct WorkflowDatabase {
+    pub conn: Connection,
+}
+
+impl WorkflowDatabase {
+    pub fn new(app_handle: &tauri::AppHandle) -> rusqlite::Result<Self> {
+        let db_path = get_db_path(app_handle, "workflow.db");
+        let conn = Connection::open(db_path.unwrap())?;
+        Ok(WorkflowDatabase { conn })
+    }
+
+    pub fn create_tables(&self) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS workflow (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                definition TEXT NOT NULL,
+                created_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_time DATETIME DEFAULT CURRENT_TIMESTAMP
+            );",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS workflow_run (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                workflow_id INTEGER NOT NULL,
+                status TEXT NOT NULL,
+                inputs TEXT NOT NULL,
+                output TEXT,
+                error TEXT,
+                created_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+                finished_time DATETIME
+            );",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS workflow_step_run (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                run_id INTEGER NOT NULL,
+                step_id TEXT NOT NULL,
+                status TEXT NOT NULL,
+                output TEXT,
+                error TEXT,
+                started_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+                finished_time DATETIME,
+                UNIQUE(run_id, step_id)
+            );",
+            [],
+        )?;
+        Ok(())
+    }
+
+    pub fn add_workflow(&self, name: &str, definition: &str) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO workflow (name, definition) VALUES (?1, ?2)",
+            params![name, definition],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_workflow(&self, id: i64, name: &str, definition: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE workflow SET name = ?1, definition = ?2, updated_time = CURRENT_TIMESTAMP WHERE id = ?3",
+            params![name, definition, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_workflow(&self, id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM workflow_step_run WHERE run_id IN (SELECT id FROM workflow_run WHERE workflow_id = ?1)",
+            [id],
+        )?;
+        self.conn
+            .execute("DELETE FROM workflow_run WHERE workflow_id = ?1", [id])?;
+        self.conn
+            .execute("DELETE FROM workflow WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn read_workflow(row: &rusqlite::Row) -> Result<Workflow> {
+        Ok(Workflow {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            definition: row.get(2)?,
+            created_time: row.get(3)?,
+            updated_time: row.get(4)?,
+        })
+    }
+
+    pub fn get_workflows(&self) -> Result<Vec<Workflow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, definition, created_time, updated_time FROM workflow ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([], Self::read_workflow)?;
+        rows.collect()
+    }
+
+    pub fn get_workflow(&self, id: i64) -> Result<Option<Workflow>> {
+        self.conn
+            .query_row(
+                "SELECT id, name, definition, created_time, updated_time FROM workflow WHERE id = ?1",
+                [id],
+                Self::read_workflow,
+            )
+            .optional()
+    }
+
+    pub fn add_run(&self, workflow_id: i64, inputs: &str) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO workflow_run (workflow_id, status, inputs) VALUES (?1, 'running', ?2)",
+            params![workflow_id, inputs],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn finish_run(
+        &self,
+        id: i64,
+        status: &str,
+        output: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        self.conn.execute(
+            "UPDATE workflow_run SET status = ?1, output = ?2, error = ?3, finished_time = CURRENT_TIMESTAMP WHERE id = ?4",
+            params![status, output, error, id],
+        )?;
+        Ok(())
+    }
+
+    // 程序退出时还在运行的工作流不会继续执行，启动时标记为失败
+    pub fn fail_unfinished_runs(&self) -> Result<()> {
+        self.conn.execute(
+            "UPDATE workflow_run SET status = 'failed', error = '程序退出，执行中断', finished_time = CURRENT_TIMESTAMP WHERE status = 'running'",
+            [],
+        )?;
+        self.conn.execute(
+            "UPDATE workflow_step_run SET status = 'failed', finished_time = CURRENT_TIMESTAMP WHERE status = 'running'",
+            [],
+        )?;
+        Ok(())
+    }
+
+    fn read_run(row: &rusqlite::Row) -> Result<WorkflowRun> {
+        Ok(WorkflowRun {
+            id: row.get(0)?,
+            workflow_id: row.get(1)?,
+            status: row.get(2)?,
+            inputs: row.get(3)?,
+            output: row.get(4)?,
+            error: row.get(5)?,
+            created_time: row.get(6)?,
+            finished_time: row.get(7)?,
+        })
+    }
+
+    pub fn get_runs(&self, workflow_id: i64) -> Result<Vec<WorkflowRun>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, workflow_id, status, inputs, output, error, created_time, finished_time
+             FROM workflow_run WHERE workflow_id = ?1 ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([workflow_id], Self::read_run)?;
+        rows.collect()
+    }
+
+    pub fn get_run(&self, id: i64) -> Result<Option<WorkflowRun>> {
+        self.conn
+            .query_row(
+                "SELECT id, workflow_id, status, inputs, output, error, created_time, finished_time
+                 FROM workflow_run WHERE id = ?1",
+                [id],
+                Self::read_run,
+            )
+            .optional()
+    }
+
+    pub fn start_step(&self, run_id: i64, step_id: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO workflow_step_run (run_id, step_id, status) VALUES (?1, ?2, 'running')
+             ON CONFLICT(run_id, step_id) DO UPDATE SET status = 'running', started_time = CURRENT_TIMESTAMP",
+            params![run_id, step_id],
+        )?;
+        Ok(())
+    }
+
+    // 跳过的步骤没有开始记录，直接写入结束状态
+    pub fn finish_step(
+        &self,
+        run_id: i64,
+        step_id: &str,
+        status: &str,
+        output: &str,
+        error: Option<&str>,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO workflow_step_run (run_id, step_id, status, output, error, finished_time)
+             VALUES (?1, ?2, ?3, ?4, ?5, CURRENT_TIMESTAMP)
+             ON CONFLICT(run_id, step_id) DO UPDATE SET status = excluded.status, output = excluded.output,
+                error = excluded.error, finished_time = excluded.finished_time",
+            params![run_id, step_id, status, output, error],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_step_runs(&self, run_id: i64) -> Result<Vec<WorkflowStepRun>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, run_id, step_id, status, output, error, started_time, finished_time
+             FROM workflow_step_run WHERE run_id = ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map([run_id], |row| {
+            Ok(WorkflowStepRun {
+                id: row.get(0)?,
+                run_id: row.get(1)?,
+                step_id: row.get(2)?,
+                status: row.get(3)?,
+                output: row.get(4)?,
+                error: row.get(5)?,
+                started_time: row.get(6)?,
+                finished_time: row.get(7)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
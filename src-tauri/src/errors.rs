@@ -32,6 +32,9 @@ pub enum AppError {
     #[error("未进行配置: {0}")]
     NoConfigError(String),
 
+    #[error("提示词参数错误: {0}")]
+    PromptParamError(String),
+
     #[error("Anyhow错误: {0}")]
     Anyhow(String),
 }
@@ -0,0 +1,322 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use futures::future::join_all;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Mutex, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+use crate::api::llm::ModelProvider;
+use crate::db::assistant_db::AssistantModelConfig;
+use crate::db::conversation_db::MessageAttachment;
+
+const JUDGE_PROMPT: &str = "你是一名严格的评测员。请根据评分标准，对回答进行 0 到 10 分的评分。
+只输出 JSON，格式为 {\"score\": 分数, \"reason\": \"简短的评分理由\"}，不要输出其他内容。";
+// LLM 评分达到该分数视为通过，满分为 10
+const JUDGE_PASS_SCORE: f64 = 6.0;
+
+// 评分方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scorer {
+    // 去掉首尾空白后与期望答案完全一致
+    Exact,
+    // 回答能匹配期望答案中的正则表达式
+    Regex,
+    // 由评分模型按评分标准打分
+    LlmJudge,
+}
+
+impl Scorer {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "exact" => Ok(Scorer::Exact),
+            "regex" => Ok(Scorer::Regex),
+            "llm_judge" => Ok(Scorer::LlmJudge),
+            _ => Err(anyhow!("不支持的评分方式: {}", value)),
+        }
+    }
+}
+
+pub struct EvalCase {
+    pub question_id: i64,
+    pub prompt: String,
+    pub attachments: Vec<MessageAttachment>,
+    pub scorer: Scorer,
+    pub expected: String,
+}
+
+// 被评测的模型
+pub struct EvalTarget {
+    pub provider_id: i64,
+    pub model_code: String,
+    pub provider: Arc<dyn ModelProvider>,
+}
+
+pub struct EvalOptions {
+    // 同时进行的请求数
+    pub concurrency: usize,
+    // 每个模型每分钟的最大请求数，0 表示不限制
+    pub requests_per_minute: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EvalOutcome {
+    pub question_id: i64,
+    pub provider_id: i64,
+    pub model_code: String,
+    pub answer: String,
+    pub score: f64,
+    pub passed: bool,
+    pub reason: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Score {
+    pub score: f64,
+    pub passed: bool,
+    pub reason: Option<String>,
+}
+
+// 按固定间隔放行请求，用于限制单个模型的请求频率
+pub struct RateLimiter {
+    interval: Option<Duration>,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32) -> Self {
+        RateLimiter {
+            interval: (requests_per_minute > 0)
+                .then(|| Duration::from_secs(60) / requests_per_minute),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub async fn acquire(&self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let wait = {
+            let mut next = self.next.lock().await;
+            let now = Instant::now();
+            let slot = (*next).max(now);
+            *next = slot + interval;
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+fn model_config(model_code: &str) -> Vec<AssistantModelConfig> {
+    vec![AssistantModelConfig {
+        id: 0,
+        assistant_id: 0,
+        assistant_model_id: 0,
+        name: "model".to_string(),
+        value: Some(model_code.to_string()),
+        value_type: "string".to_string(),
+    }]
+}
+
+pub fn score_exact(expected: &str, answer: &str) -> Score {
+    let passed = expected.trim() == answer.trim();
+    Score {
+        score: if passed { 1.0 } else { 0.0 },
+        passed,
+        reason: None,
+    }
+}
+
+pub fn score_regex(pattern: &str, answer: &str) -> Result<Score> {
+    let regex = Regex::new(pattern).map_err(|e| anyhow!("正则表达式不正确: {}", e))?;
+    let passed = regex.is_match(answer);
+    Ok(Score {
+        score: if passed { 1.0 } else { 0.0 },
+        passed,
+        reason: None,
+    })
+}
+
+// 解析评分模型的输出，分数换算为 0 到 1
+pub fn parse_judge_response(response: &str) -> Result<Score> {
+    #[derive(Deserialize)]
+    struct JudgeResponse {
+        score: f64,
+        #[serde(default)]
+        reason: Option<String>,
+    }
+
+    let judge = match (response.find('{'), response.rfind('}')) {
+        (Some(start), Some(end)) if start < end => {
+            serde_json::from_str::<JudgeResponse>(&response[start..=end]).ok()
+        }
+        _ => None,
+    };
+    // 模型没有按 JSON 输出时，取第一个数字作为分数
+    let (score, reason) = match judge {
+        Some(judge) => (judge.score, judge.reason),
+        None => {
+            let number = Regex::new(r"\d+(\.\d+)?").unwrap();
+            let score = number
+                .find(response)
+                .and_then(|m| m.as_str().parse::<f64>().ok())
+                .ok_or_else(|| anyhow!("无法解析评分结果: {}", response))?;
+            (score, Some(response.trim().to_string()))
+        }
+    };
+    let score = score.clamp(0.0, 10.0);
+    Ok(Score {
+        score: score / 10.0,
+        passed: score >= JUDGE_PASS_SCORE,
+        reason,
+    })
+}
+
+fn build_judge_request(question: &str, rubric: &str, answer: &str) -> String {
+    format!(
+        "# 问题\n{}\n\n# 评分标准\n{}\n\n# 回答\n{}",
+        question, rubric, answer
+    )
+}
+
+async fn judge_answer(
+    judge: &EvalTarget,
+    limiter: &RateLimiter,
+    case: &EvalCase,
+    answer: &str,
+    cancel_token: CancellationToken,
+) -> Result<Score> {
+    limiter.acquire().await;
+    let response = judge
+        .provider
+        .chat(
+            -1,
+            vec![
+                ("system".to_string(), JUDGE_PROMPT.to_string(), vec![]),
+                (
+                    "user".to_string(),
+                    build_judge_request(&case.prompt, &case.expected, answer),
+                    vec![],
+                ),
+            ],
+            model_config(&judge.model_code),
+            cancel_token,
+        )
+        .await
+        .map_err(|e| anyhow!("评分模型调用失败: {}", e))?;
+    parse_judge_response(&response)
+}
+
+async fn run_case(
+    target: &EvalTarget,
+    limiter: &RateLimiter,
+    case: &EvalCase,
+    judge: Option<(&EvalTarget, &RateLimiter)>,
+    cancel_token: CancellationToken,
+) -> EvalOutcome {
+    let mut outcome = EvalOutcome {
+        question_id: case.question_id,
+        provider_id: target.provider_id,
+        model_code: target.model_code.clone(),
+        answer: String::new(),
+        score: 0.0,
+        passed: false,
+        reason: None,
+        error: None,
+        duration_ms: 0,
+    };
+    if cancel_token.is_cancelled() {
+        outcome.error = Some("评测已取消".to_string());
+        return outcome;
+    }
+
+    limiter.acquire().await;
+    let start = Instant::now();
+    let answer = target
+        .provider
+        .chat(
+            -1,
+            vec![(
+                "user".to_string(),
+                case.prompt.clone(),
+                case.attachments.clone(),
+            )],
+            model_config(&target.model_code),
+            cancel_token.clone(),
+        )
+        .await;
+    outcome.duration_ms = start.elapsed().as_millis() as i64;
+    outcome.answer = match answer {
+        Ok(answer) => answer,
+        Err(e) => {
+            outcome.error = Some(e.to_string());
+            return outcome;
+        }
+    };
+
+    let score = match case.scorer {
+        Scorer::Exact => Ok(score_exact(&case.expected, &outcome.answer)),
+        Scorer::Regex => score_regex(&case.expected, &outcome.answer),
+        Scorer::LlmJudge => match judge {
+            Some((judge, judge_limiter)) => {
+                judge_answer(judge, judge_limiter, case, &outcome.answer, cancel_token).await
+            }
+            None => Err(anyhow!("没有配置评分模型")),
+        },
+    };
+    match score {
+        Ok(score) => {
+            outcome.score = score.score;
+            outcome.passed = score.passed;
+            outcome.reason = score.reason;
+        }
+        Err(e) => outcome.error = Some(e.to_string()),
+    }
+    outcome
+}
+
+// 所有模型并发执行所有题目，每完成一道题通过 tx 推送结果，返回全部结果
+pub async fn run_eval(
+    cases: &[EvalCase],
+    targets: &[EvalTarget],
+    judge: Option<&EvalTarget>,
+    options: &EvalOptions,
+    tx: Option<mpsc::Sender<EvalOutcome>>,
+    cancel_token: CancellationToken,
+) -> Vec<EvalOutcome> {
+    let semaphore = Semaphore::new(options.concurrency.max(1));
+    let limiters = targets
+        .iter()
+        .map(|_| RateLimiter::new(options.requests_per_minute))
+        .collect::<Vec<_>>();
+    let judge_limiter = RateLimiter::new(options.requests_per_minute);
+
+    let tasks = targets
+        .iter()
+        .zip(limiters.iter())
+        .flat_map(|(target, limiter)| cases.iter().map(move |case| (target, limiter, case)))
+        .map(|(target, limiter, case)| {
+            let semaphore = &semaphore;
+            let judge = judge.map(|j| (j, &judge_limiter));
+            let tx = tx.clone();
+            let cancel_token = cancel_token.clone();
+            async move {
+                let _permit = semaphore.acquire().await;
+                let outcome = run_case(target, limiter, case, judge, cancel_token).await;
+                if let Some(tx) = tx {
+                    let _ = tx.send(outcome.clone()).await;
+                }
+                outcome
+            }
+        });
+    join_all(tasks).await
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,248 @@
+use super::*;
+use crate::api::llm_api::LlmModel;
+use crate::db::llm_db::LLMProviderConfig;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+type Answer = Arc<dyn Fn(&str) -> Result<String> + Send + Sync>;
+
+// 离线测试用的模型，根据最后一条消息返回固定的回答，并记录同时进行的请求数
+struct MockProvider {
+    answer: Answer,
+    delay: Duration,
+    running: Arc<AtomicUsize>,
+    max_running: Arc<AtomicUsize>,
+}
+
+impl MockProvider {
+    fn with_answer(answer: impl Fn(&str) -> Result<String> + Send + Sync + 'static) -> Self {
+        MockProvider {
+            answer: Arc::new(answer),
+            delay: Duration::ZERO,
+            running: Arc::new(AtomicUsize::new(0)),
+            max_running: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+impl ModelProvider for MockProvider {
+    fn new(_llm_provider_config: Vec<LLMProviderConfig>) -> Self {
+        MockProvider::with_answer(|prompt| Ok(prompt.to_string()))
+    }
+
+    fn chat(
+        &self,
+        _message_id: i64,
+        messages: Vec<(String, String, Vec<MessageAttachment>)>,
+        _model_config: Vec<AssistantModelConfig>,
+        _cancel_token: CancellationToken,
+    ) -> BoxFuture<'static, Result<String>> {
+        let prompt = messages.last().map(|m| m.1.clone()).unwrap_or_default();
+        let answer = self.answer.clone();
+        let delay = self.delay;
+        let running = self.running.clone();
+        let max_running = self.max_running.clone();
+        async move {
+            let current = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(delay).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+            answer(&prompt)
+        }
+        .boxed()
+    }
+
+    fn chat_stream(
+        &self,
+        _message_id: i64,
+        _messages: Vec<(String, String, Vec<MessageAttachment>)>,
+        _model_config: Vec<AssistantModelConfig>,
+        _tx: mpsc::Sender<(i64, String, bool)>,
+        _cancel_token: CancellationToken,
+    ) -> BoxFuture<'static, Result<()>> {
+        async { Ok(()) }.boxed()
+    }
+
+    fn models(&self) -> BoxFuture<'static, Result<Vec<LlmModel>>> {
+        async { Ok(vec![]) }.boxed()
+    }
+}
+
+fn target(provider_id: i64, provider: MockProvider) -> EvalTarget {
+    EvalTarget {
+        provider_id,
+        model_code: format!("mock-{}", provider_id),
+        provider: Arc::new(provider),
+    }
+}
+
+fn case(question_id: i64, prompt: &str, scorer: Scorer, expected: &str) -> EvalCase {
+    EvalCase {
+        question_id,
+        prompt: prompt.to_string(),
+        attachments: vec![],
+        scorer,
+        expected: expected.to_string(),
+    }
+}
+
+#[test]
+fn test_scorers() {
+    assert!(score_exact("4", " 4\n").passed);
+    assert!(!score_exact("4", "四").passed);
+
+    assert!(
+        score_regex(r"^\d{4}-\d{2}-\d{2}$", "2024-01-02")
+            .unwrap()
+            .passed
+    );
+    assert!(!score_regex(r"^\d+$", "abc").unwrap().passed);
+    assert!(score_regex(r"(", "abc").is_err());
+
+    let score = parse_judge_response("```json\n{\"score\": 8, \"reason\": \"正确\"}\n```").unwrap();
+    assert_eq!(score.score, 0.8);
+    assert!(score.passed);
+    assert_eq!(score.reason.as_deref(), Some("正确"));
+
+    let score = parse_judge_response("评分：3 分，回答不完整").unwrap();
+    assert_eq!(score.score, 0.3);
+    assert!(!score.passed);
+
+    assert!(parse_judge_response("无法评分").is_err());
+}
+
+#[tokio::test]
+async fn test_run_eval_with_mock_providers() {
+    let good = target(
+        1,
+        MockProvider::with_answer(|_| Ok("2024-01-02".to_string())),
+    );
+    let bad = target(
+        2,
+        MockProvider::with_answer(|prompt| {
+            if prompt.contains("日期") {
+                Err(anyhow!("请求超时"))
+            } else {
+                Ok("不知道".to_string())
+            }
+        }),
+    );
+    // 回答里包含日期的给高分
+    let judge = target(
+        3,
+        MockProvider::with_answer(|request| {
+            Ok(if request.contains("# 回答\n2024") {
+                "{\"score\": 9, \"reason\": \"正确\"}".to_string()
+            } else {
+                "{\"score\": 1, \"reason\": \"错误\"}".to_string()
+            })
+        }),
+    );
+    let cases = vec![
+        case(1, "今天是几号", Scorer::Exact, "2024-01-02"),
+        case(
+            2,
+            "用 yyyy-mm-dd 格式输出日期",
+            Scorer::Regex,
+            r"^\d{4}-\d{2}-\d{2}$",
+        ),
+        case(3, "今年是哪一年", Scorer::LlmJudge, "回答 2024 年"),
+    ];
+    let (tx, mut rx) = mpsc::channel(16);
+    let outcomes = run_eval(
+        &cases,
+        &[good, bad],
+        Some(&judge),
+        &EvalOptions {
+            concurrency: 4,
+            requests_per_minute: 0,
+        },
+        Some(tx),
+        CancellationToken::new(),
+    )
+    .await;
+
+    assert_eq!(outcomes.len(), 6);
+    let mut streamed = 0;
+    while rx.try_recv().is_ok() {
+        streamed += 1;
+    }
+    assert_eq!(streamed, 6);
+
+    let find = |provider_id: i64, question_id: i64| {
+        outcomes
+            .iter()
+            .find(|o| o.provider_id == provider_id && o.question_id == question_id)
+            .unwrap()
+    };
+    for question_id in 1..=3 {
+        assert!(find(1, question_id).passed);
+    }
+    assert_eq!(find(1, 3).score, 0.9);
+    assert!(!find(2, 1).passed);
+    assert_eq!(find(2, 2).error.as_deref(), Some("请求超时"));
+    assert_eq!(find(2, 3).score, 0.1);
+}
+
+#[tokio::test]
+async fn test_run_eval_limits() {
+    let mut provider = MockProvider::with_answer(|_| Ok("ok".to_string()));
+    provider.delay = Duration::from_millis(20);
+    let max_running = provider.max_running.clone();
+    let cases = (1..=6)
+        .map(|id| case(id, "hi", Scorer::Exact, "ok"))
+        .collect::<Vec<_>>();
+    let outcomes = run_eval(
+        &cases,
+        &[target(1, provider)],
+        None,
+        &EvalOptions {
+            concurrency: 2,
+            requests_per_minute: 0,
+        },
+        None,
+        CancellationToken::new(),
+    )
+    .await;
+    assert!(outcomes.iter().all(|o| o.passed));
+    assert_eq!(max_running.load(Ordering::SeqCst), 2);
+
+    // 每分钟 600 次即每 100ms 放行一次，3 个请求至少需要 200ms
+    let start = Instant::now();
+    run_eval(
+        &cases[..3],
+        &[target(
+            1,
+            MockProvider::with_answer(|_| Ok("ok".to_string())),
+        )],
+        None,
+        &EvalOptions {
+            concurrency: 3,
+            requests_per_minute: 600,
+        },
+        None,
+        CancellationToken::new(),
+    )
+    .await;
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    let cancel_token = CancellationToken::new();
+    cancel_token.cancel();
+    let outcomes = run_eval(
+        &cases[..1],
+        &[target(
+            1,
+            MockProvider::with_answer(|_| Ok("ok".to_string())),
+        )],
+        None,
+        &EvalOptions {
+            concurrency: 1,
+            requests_per_minute: 0,
+        },
+        None,
+        cancel_token,
+    )
+    .await;
+    assert_eq!(outcomes[0].error.as_deref(), Some("评测已取消"));
+}
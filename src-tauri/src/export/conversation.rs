@@ -0,0 +1,395 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+
+use crate::db::conversation_db::{AttachmentType, Conversation, Message, MessageAttachment};
+
+use super::markdown::{escape_html, markdown_to_html};
+use super::pdf::{build_text_pdf, markdown_lines, PdfLine};
+
+// 导出当前分支时每条消息只保留最后一次重新生成的结果，导出完整对话树时保留所有重新生成的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportScope {
+    Branch,
+    Tree,
+}
+
+impl ExportScope {
+    pub fn parse(scope: &str) -> Option<Self> {
+        match scope {
+            "branch" => Some(ExportScope::Branch),
+            "tree" => Some(ExportScope::Tree),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportScope::Branch => "branch",
+            ExportScope::Tree => "tree",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConversationExportFormat {
+    Markdown,
+    Html,
+    Json,
+    Pdf,
+}
+
+impl ConversationExportFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_lowercase().as_str() {
+            "md" | "markdown" => Some(ConversationExportFormat::Markdown),
+            "html" => Some(ConversationExportFormat::Html),
+            "json" => Some(ConversationExportFormat::Json),
+            "pdf" => Some(ConversationExportFormat::Pdf),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ExportedMessage {
+    pub id: i64,
+    pub message_type: String,
+    pub content: String,
+    pub llm_model_id: Option<i64>,
+    pub llm_model_name: Option<String>,
+    pub created_time: DateTime<Utc>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub finish_time: Option<DateTime<Utc>>,
+    pub token_count: i32,
+    pub prompt_version_id: Option<i64>,
+    pub attachments: Vec<MessageAttachment>,
+    // 只在导出完整对话树时包含，按生成顺序排列
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub regenerations: Vec<ExportedMessage>,
+    // 多模型对比助手中其他模型对这条回答的对比回答
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub compare_answers: Vec<ExportedMessage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedConversation {
+    pub id: i64,
+    pub name: String,
+    pub assistant_id: Option<i64>,
+    pub assistant_name: String,
+    pub created_time: DateTime<Utc>,
+    pub exported_time: DateTime<Utc>,
+    pub scope: &'static str,
+    pub messages: Vec<ExportedMessage>,
+}
+
+fn exported_message(message: Message, attachments: Vec<MessageAttachment>) -> ExportedMessage {
+    ExportedMessage {
+        id: message.id,
+        message_type: message.message_type,
+        content: message.content,
+        llm_model_id: message.llm_model_id,
+        llm_model_name: message.llm_model_name,
+        created_time: message.created_time,
+        start_time: message.start_time,
+        finish_time: message.finish_time,
+        token_count: message.token_count,
+        prompt_version_id: message.prompt_version_id,
+        attachments,
+        regenerations: vec![],
+        compare_answers: vec![],
+    }
+}
+
+// rows 为 list_by_conversation_id 的结果，有多个附件的消息会出现多次
+pub fn build_conversation_export(
+    conversation: &Conversation,
+    assistant_name: &str,
+    rows: Vec<(Message, Option<MessageAttachment>)>,
+    scope: ExportScope,
+) -> ExportedConversation {
+    let mut messages: BTreeMap<i64, (Message, Vec<MessageAttachment>)> = BTreeMap::new();
+    for (message, attachment) in rows {
+        let entry = messages
+            .entry(message.id)
+            .or_insert_with(|| (message, vec![]));
+        entry.1.extend(attachment);
+    }
+
+    // 对比回答的 compare_to_id 指向主模型的回答
+    let mut compare_answers: HashMap<i64, Vec<ExportedMessage>> = HashMap::new();
+    let mut answers = vec![];
+    for (message, attachments) in messages.into_values() {
+        match message.compare_to_id {
+            Some(compare_to_id) => compare_answers
+                .entry(compare_to_id)
+                .or_default()
+                .push(exported_message(message, attachments)),
+            None => answers.push((message, attachments)),
+        }
+    }
+
+    // 重新生成的消息的 parent_id 指向原消息
+    let mut regenerations: HashMap<i64, Vec<ExportedMessage>> = HashMap::new();
+    let mut roots = vec![];
+    for (message, attachments) in answers {
+        let parent_id = message.parent_id;
+        let mut exported = exported_message(message, attachments);
+        exported.compare_answers = compare_answers.remove(&exported.id).unwrap_or_default();
+        match parent_id {
+            Some(parent_id) => regenerations.entry(parent_id).or_default().push(exported),
+            None => roots.push(exported),
+        }
+    }
+
+    let messages = roots
+        .into_iter()
+        .map(|mut root| {
+            let children = regenerations.remove(&root.id).unwrap_or_default();
+            match scope {
+                ExportScope::Branch => children.into_iter().last().unwrap_or(root),
+                ExportScope::Tree => {
+                    root.regenerations = children;
+                    root
+                }
+            }
+        })
+        .collect();
+
+    ExportedConversation {
+        id: conversation.id,
+        name: conversation.name.clone(),
+        assistant_id: conversation.assistant_id,
+        assistant_name: assistant_name.to_string(),
+        created_time: conversation.created_time,
+        exported_time: Utc::now(),
+        scope: scope.name(),
+        messages,
+    }
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn role_label(message: &ExportedMessage) -> String {
+    match message.message_type.as_str() {
+        "system" => "系统".to_string(),
+        "user" => "用户".to_string(),
+        "assistant" => match &message.llm_model_name {
+            Some(model) if !model.is_empty() => format!("助手（{}）", model),
+            _ => "助手".to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+fn attachment_label(attachment: &MessageAttachment) -> String {
+    let kind = match attachment.attachment_type {
+        AttachmentType::Image => "图片",
+        AttachmentType::Text => "文本",
+        AttachmentType::PDF => "PDF",
+        AttachmentType::Word => "Word",
+        AttachmentType::PowerPoint => "PowerPoint",
+        AttachmentType::Excel => "Excel",
+        AttachmentType::Audio => "音频",
+    };
+    match &attachment.attachment_url {
+        Some(url) if !url.is_empty() => format!("[{}] {}", kind, url),
+        _ => format!("[{}]", kind),
+    }
+}
+
+fn message_title(message: &ExportedMessage) -> String {
+    format!(
+        "{} · {}",
+        role_label(message),
+        format_time(&message.created_time)
+    )
+}
+
+fn regeneration_title(index: usize, message: &ExportedMessage) -> String {
+    format!("重新生成 {} · {}", index + 1, message_title(message))
+}
+
+fn compare_title(message: &ExportedMessage) -> String {
+    match &message.llm_model_name {
+        Some(model) if !model.is_empty() => format!(
+            "对比回答（{}） · {}",
+            model,
+            format_time(&message.created_time)
+        ),
+        _ => format!("对比回答 · {}", format_time(&message.created_time)),
+    }
+}
+
+pub fn to_markdown(export: &ExportedConversation) -> String {
+    fn push_message(output: &mut String, heading: &str, title: String, message: &ExportedMessage) {
+        output.push_str(&format!("{} {}\n\n", heading, title));
+        output.push_str(message.content.trim_end());
+        output.push_str("\n\n");
+        if !message.attachments.is_empty() {
+            output.push_str("附件：\n\n");
+            for attachment in &message.attachments {
+                output.push_str(&format!("- {}\n", attachment_label(attachment)));
+            }
+            output.push('\n');
+        }
+        let compare_heading = format!("{}#", heading);
+        for compare in &message.compare_answers {
+            push_message(output, &compare_heading, compare_title(compare), compare);
+        }
+    }
+
+    let mut output = format!(
+        "# {}\n\n- 助手：{}\n- 创建时间：{}\n- 导出时间：{}\n\n",
+        export.name,
+        export.assistant_name,
+        format_time(&export.created_time),
+        format_time(&export.exported_time)
+    );
+    for message in &export.messages {
+        output.push_str("---\n\n");
+        push_message(&mut output, "##", message_title(message), message);
+        for (i, regeneration) in message.regenerations.iter().enumerate() {
+            push_message(
+                &mut output,
+                "###",
+                regeneration_title(i, regeneration),
+                regeneration,
+            );
+        }
+    }
+    output
+}
+
+const HTML_STYLE: &str = "body { max-width: 860px; margin: 0 auto; padding: 24px; font-family: -apple-system, 'Segoe UI', 'PingFang SC', 'Microsoft YaHei', sans-serif; line-height: 1.6; color: #1f2937; }
+.meta { color: #6b7280; font-size: 14px; }
+.message { border-top: 1px solid #e5e7eb; padding: 12px 0; }
+.message > header { font-weight: 600; margin-bottom: 8px; }
+.message.user > header { color: #2563eb; }
+.message.system > header { color: #6b7280; }
+.attachments { color: #6b7280; font-size: 14px; }
+details { margin: 8px 0 0 16px; }
+.compare { margin: 8px 0 0 16px; padding-left: 12px; border-left: 3px solid #e5e7eb; }
+summary { cursor: pointer; color: #6b7280; }
+.code-block { margin: 8px 0; border: 1px solid #e5e7eb; border-radius: 6px; overflow: hidden; }
+.code-lang { background: #f3f4f6; color: #6b7280; font-size: 12px; padding: 2px 8px; }
+pre { margin: 0; padding: 12px; background: #f9fafb; overflow-x: auto; }
+code { font-family: 'JetBrains Mono', Menlo, Consolas, monospace; font-size: 13px; }
+p code, li code { background: #f3f4f6; padding: 1px 4px; border-radius: 4px; }
+blockquote { margin: 8px 0; padding-left: 12px; border-left: 3px solid #e5e7eb; color: #6b7280; }";
+
+// 独立的 HTML 文件，样式内联，不依赖外部资源
+pub fn to_html(export: &ExportedConversation) -> String {
+    fn message_html(title: String, message: &ExportedMessage) -> String {
+        let mut html = format!(
+            "<header>{}</header>\n<div class=\"content\">\n{}</div>\n",
+            escape_html(&title),
+            markdown_to_html(&message.content)
+        );
+        if !message.attachments.is_empty() {
+            html.push_str("<ul class=\"attachments\">\n");
+            for attachment in &message.attachments {
+                html.push_str(&format!(
+                    "<li>{}</li>\n",
+                    escape_html(&attachment_label(attachment))
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+        for compare in &message.compare_answers {
+            html.push_str(&format!(
+                "<div class=\"compare\">\n{}</div>\n",
+                message_html(compare_title(compare), compare)
+            ));
+        }
+        html
+    }
+
+    let mut body = String::new();
+    for message in &export.messages {
+        body.push_str(&format!(
+            "<section class=\"message {}\">\n{}",
+            escape_html(&message.message_type),
+            message_html(message_title(message), message)
+        ));
+        for (i, regeneration) in message.regenerations.iter().enumerate() {
+            body.push_str(&format!(
+                "<details>\n<summary>{}</summary>\n{}</details>\n",
+                escape_html(&regeneration_title(i, regeneration)),
+                message_html(message_title(regeneration), regeneration)
+            ));
+        }
+        body.push_str("</section>\n");
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{style}\n</style>\n</head>\n<body>\n<h1>{title}</h1>\n<p class=\"meta\">助手：{assistant} · 创建时间：{created} · 导出时间：{exported}</p>\n{body}</body>\n</html>\n",
+        title = escape_html(&export.name),
+        style = HTML_STYLE,
+        assistant = escape_html(&export.assistant_name),
+        created = format_time(&export.created_time),
+        exported = format_time(&export.exported_time),
+        body = body
+    )
+}
+
+pub fn to_json(export: &ExportedConversation) -> Result<String, String> {
+    serde_json::to_string_pretty(export).map_err(|e| e.to_string())
+}
+
+// PDF 中按纯文本排版，代码块按原样输出
+pub fn to_pdf(export: &ExportedConversation) -> Vec<u8> {
+    fn push_message(lines: &mut Vec<PdfLine>, title: String, message: &ExportedMessage) {
+        lines.push(PdfLine::heading(title));
+        lines.extend(markdown_lines(&message.content));
+        for attachment in &message.attachments {
+            lines.push(PdfLine::note(format!(
+                "附件：{}",
+                attachment_label(attachment)
+            )));
+        }
+        lines.push(PdfLine::text(""));
+        for compare in &message.compare_answers {
+            push_message(lines, compare_title(compare), compare);
+        }
+    }
+
+    let mut lines = vec![
+        PdfLine::title(export.name.clone()),
+        PdfLine::note(format!(
+            "助手：{}  创建时间：{}  导出时间：{}",
+            export.assistant_name,
+            format_time(&export.created_time),
+            format_time(&export.exported_time)
+        )),
+        PdfLine::text(""),
+    ];
+    for message in &export.messages {
+        push_message(&mut lines, message_title(message), message);
+        for (i, regeneration) in message.regenerations.iter().enumerate() {
+            push_message(
+                &mut lines,
+                regeneration_title(i, regeneration),
+                regeneration,
+            );
+        }
+    }
+    build_text_pdf(&lines)
+}
+
+pub fn render_conversation(
+    export: &ExportedConversation,
+    format: ConversationExportFormat,
+) -> Result<Vec<u8>, String> {
+    Ok(match format {
+        ConversationExportFormat::Markdown => to_markdown(export).into_bytes(),
+        ConversationExportFormat::Html => to_html(export).into_bytes(),
+        ConversationExportFormat::Json => to_json(export)?.into_bytes(),
+        ConversationExportFormat::Pdf => to_pdf(export),
+    })
+}
@@ -0,0 +1,151 @@
+use regex::Regex;
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// 行内格式：代码、加粗和 http 链接，代码中的内容不再处理
+fn render_inline(text: &str) -> String {
+    let bold = Regex::new(r"\*\*(.+?)\*\*").unwrap();
+    let link = Regex::new(r"\[([^\]]+)\]\((https?://[^\s)]+)\)").unwrap();
+    text.split('`')
+        .enumerate()
+        .map(|(i, segment)| {
+            let segment = escape_html(segment);
+            if i % 2 == 1 {
+                format!("<code>{}</code>", segment)
+            } else {
+                let segment = bold.replace_all(&segment, "<strong>$1</strong>");
+                link.replace_all(&segment, "<a href=\"$2\">$1</a>")
+                    .into_owned()
+            }
+        })
+        .collect()
+}
+
+enum Block {
+    Paragraph(Vec<String>),
+    List(bool, Vec<String>),
+    Quote(Vec<String>),
+}
+
+fn flush(html: &mut String, block: &mut Option<Block>) {
+    match block.take() {
+        Some(Block::Paragraph(lines)) => {
+            html.push_str(&format!("<p>{}</p>\n", lines.join("<br>\n")));
+        }
+        Some(Block::List(ordered, items)) => {
+            let tag = if ordered { "ol" } else { "ul" };
+            html.push_str(&format!("<{}>\n", tag));
+            for item in items {
+                html.push_str(&format!("<li>{}</li>\n", item));
+            }
+            html.push_str(&format!("</{}>\n", tag));
+        }
+        Some(Block::Quote(lines)) => {
+            html.push_str(&format!(
+                "<blockquote>{}</blockquote>\n",
+                lines.join("<br>\n")
+            ));
+        }
+        None => {}
+    }
+}
+
+// 导出 HTML 时使用的简单 Markdown 渲染，支持代码块、标题、列表、引用和段落，其余内容按原文显示
+pub fn markdown_to_html(markdown: &str) -> String {
+    let fence_start = Regex::new(r"^\s*(`{3,}|~{3,})\s*([^\s`]*)").unwrap();
+    let heading = Regex::new(r"^(#{1,6})\s+(.*)$").unwrap();
+    let unordered = Regex::new(r"^\s*[-*+]\s+(.*)$").unwrap();
+    let ordered = Regex::new(r"^\s*\d+[.)]\s+(.*)$").unwrap();
+
+    let mut html = String::new();
+    let mut block: Option<Block> = None;
+    let mut lines = markdown.lines();
+    while let Some(line) = lines.next() {
+        if let Some(captures) = fence_start.captures(line) {
+            flush(&mut html, &mut block);
+            let fence = &captures[1];
+            let fence_char = fence.chars().next().unwrap_or('`');
+            let lang = captures[2].to_string();
+            let mut code = vec![];
+            for code_line in lines.by_ref() {
+                let trimmed = code_line.trim();
+                if trimmed.len() >= fence.len() && trimmed.chars().all(|c| c == fence_char) {
+                    break;
+                }
+                code.push(code_line);
+            }
+            let label = if lang.is_empty() {
+                String::new()
+            } else {
+                format!("<div class=\"code-lang\">{}</div>", escape_html(&lang))
+            };
+            let class = if lang.is_empty() {
+                String::new()
+            } else {
+                format!(" class=\"language-{}\"", escape_html(&lang))
+            };
+            html.push_str(&format!(
+                "<div class=\"code-block\">{}<pre><code{}>{}</code></pre></div>\n",
+                label,
+                class,
+                escape_html(&code.join("\n"))
+            ));
+            continue;
+        }
+        if line.trim().is_empty() {
+            flush(&mut html, &mut block);
+            continue;
+        }
+        if let Some(captures) = heading.captures(line) {
+            flush(&mut html, &mut block);
+            let level = captures[1].len();
+            html.push_str(&format!(
+                "<h{level}>{}</h{level}>\n",
+                render_inline(&captures[2]),
+                level = level
+            ));
+            continue;
+        }
+        let list_item = unordered
+            .captures(line)
+            .map(|c| (false, c[1].to_string()))
+            .or_else(|| ordered.captures(line).map(|c| (true, c[1].to_string())));
+        if let Some((is_ordered, item)) = list_item {
+            match &mut block {
+                Some(Block::List(current, items)) if *current == is_ordered => {
+                    items.push(render_inline(&item))
+                }
+                _ => {
+                    flush(&mut html, &mut block);
+                    block = Some(Block::List(is_ordered, vec![render_inline(&item)]));
+                }
+            }
+            continue;
+        }
+        if let Some(quote) = line.trim_start().strip_prefix('>') {
+            let quote = render_inline(quote.trim_start());
+            match &mut block {
+                Some(Block::Quote(lines)) => lines.push(quote),
+                _ => {
+                    flush(&mut html, &mut block);
+                    block = Some(Block::Quote(vec![quote]));
+                }
+            }
+            continue;
+        }
+        match &mut block {
+            Some(Block::Paragraph(lines)) => lines.push(render_inline(line)),
+            _ => {
+                flush(&mut html, &mut block);
+                block = Some(Block::Paragraph(vec![render_inline(line)]));
+            }
+        }
+    }
+    flush(&mut html, &mut block);
+    html
+}
@@ -0,0 +1,6 @@
+pub mod conversation;
+pub mod markdown;
+pub mod pdf;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,254 @@
+use crate::utils::pdf::{stream_object, write_pdf};
+
+// A4 纸，单位为点
+const PAGE_WIDTH: f64 = 595.28;
+const PAGE_HEIGHT: f64 = 841.89;
+const MARGIN: f64 = 56.0;
+
+// 使用 PDF 阅读器自带的宋体（Adobe-GB1 字符集），文件中没有嵌入字体：
+// 没有安装 Adobe 亚洲语言字体包的阅读器会用其他字体替代，部分阅读器中文显示为空白，
+// GB1 之外的字符（如 emoji）无法显示。
+// 字符编码为 UTF-16，半角字符宽度为 0.5，其余字符宽度为 1，半角字符因此等宽
+const FONT_OBJECTS: [&str; 3] = [
+    "<< /Type /Font /Subtype /Type0 /BaseFont /STSong-Light /Encoding /UniGB-UTF16-H /DescendantFonts [4 0 R] >>",
+    "<< /Type /Font /Subtype /CIDFontType0 /BaseFont /STSong-Light /CIDSystemInfo << /Registry (Adobe) /Ordering (GB1) /Supplement 4 >> /FontDescriptor 5 0 R /DW 1000 /W [1 95 500] >>",
+    "<< /Type /FontDescriptor /FontName /STSong-Light /Flags 6 /FontBBox [-25 -254 1000 880] /ItalicAngle 0 /Ascent 880 /Descent -120 /CapHeight 880 /StemV 93 >>",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfLine {
+    pub text: String,
+    pub size: f64,
+    // 灰度，0 为黑色
+    pub gray: f64,
+    // 代码块中的行，保留空白，按字符换行并带浅灰色背景
+    pub preformatted: bool,
+}
+
+impl PdfLine {
+    pub fn title(text: String) -> Self {
+        PdfLine {
+            text,
+            size: 16.0,
+            gray: 0.0,
+            preformatted: false,
+        }
+    }
+
+    pub fn heading(text: String) -> Self {
+        PdfLine {
+            text,
+            size: 12.0,
+            gray: 0.0,
+            preformatted: false,
+        }
+    }
+
+    pub fn note(text: String) -> Self {
+        PdfLine {
+            text,
+            size: 9.0,
+            gray: 0.45,
+            preformatted: false,
+        }
+    }
+
+    pub fn text(text: &str) -> Self {
+        PdfLine {
+            text: text.to_string(),
+            size: 10.0,
+            gray: 0.0,
+            preformatted: false,
+        }
+    }
+
+    pub fn code(text: &str) -> Self {
+        PdfLine {
+            text: text.to_string(),
+            size: 9.0,
+            gray: 0.0,
+            preformatted: true,
+        }
+    }
+}
+
+fn char_width(c: char) -> f64 {
+    if c.is_ascii() {
+        0.5
+    } else {
+        1.0
+    }
+}
+
+// 去掉控制字符，制表符替换成空格
+fn clean_text(text: &str) -> String {
+    text.replace('\t', "    ")
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect()
+}
+
+// 按宽度换行，英文单词尽量不从中间断开
+pub fn wrap_line(text: &str, size: f64, max_width: f64) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current: Vec<char> = vec![];
+    let mut width = 0.0;
+    for c in clean_text(text).chars() {
+        let w = char_width(c) * size;
+        if width + w > max_width && !current.is_empty() {
+            let break_at = if c.is_ascii_alphanumeric() {
+                current
+                    .iter()
+                    .rposition(|c| *c == ' ')
+                    .filter(|i| *i > 0)
+                    .map(|i| i + 1)
+            } else {
+                None
+            };
+            let rest = match break_at {
+                Some(i) => current.split_off(i),
+                None => vec![],
+            };
+            lines.push(current.iter().collect::<String>().trim_end().to_string());
+            current = rest;
+            width = current.iter().map(|c| char_width(*c) * size).sum();
+        }
+        current.push(c);
+        width += w;
+    }
+    lines.push(current.into_iter().collect());
+    lines
+}
+
+// 代码按字符宽度换行，不去掉空白
+pub fn wrap_code_line(text: &str, size: f64, max_width: f64) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current = String::new();
+    let mut width = 0.0;
+    for c in clean_text(text).chars() {
+        let w = char_width(c) * size;
+        if width + w > max_width && !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+            width = 0.0;
+        }
+        current.push(c);
+        width += w;
+    }
+    lines.push(current);
+    lines
+}
+
+// 以三个以上 ` 或 ~ 开头的行开始代码块，返回围栏和语言
+fn fence_start(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    let fence_char = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.len() - line.trim_start_matches(fence_char).len();
+    (len >= 3).then(|| (&line[..len], line[len..].trim()))
+}
+
+// 结束的围栏由同样的字符组成，长度不少于开始的围栏
+fn is_fence_end(line: &str, fence: &str) -> bool {
+    let line = line.trim();
+    let fence_char = fence.chars().next().unwrap_or('`');
+    line.len() >= fence.len() && line.chars().all(|c| c == fence_char)
+}
+
+// Markdown 中的代码块按原样输出，语言显示在代码块上方，其余内容按纯文本输出
+pub fn markdown_lines(markdown: &str) -> Vec<PdfLine> {
+    let mut lines = vec![];
+    let mut fence: Option<&str> = None;
+    for line in markdown.trim_end().lines() {
+        match fence {
+            Some(f) if is_fence_end(line, f) => fence = None,
+            Some(_) => lines.push(PdfLine::code(line)),
+            None => match fence_start(line) {
+                Some((f, lang)) => {
+                    fence = Some(f);
+                    if !lang.is_empty() {
+                        lines.push(PdfLine::note(lang.to_string()));
+                    }
+                }
+                None => lines.push(PdfLine::text(line)),
+            },
+        }
+    }
+    lines
+}
+
+fn encode_text(text: &str) -> String {
+    text.encode_utf16()
+        .map(|unit| format!("{:04X}", unit))
+        .collect()
+}
+
+// 纯文本 PDF，超出一页时自动分页
+pub fn build_text_pdf(lines: &[PdfLine]) -> Vec<u8> {
+    // 代码块背景左右的留白
+    const CODE_PADDING: f64 = 4.0;
+    let max_width = PAGE_WIDTH - MARGIN * 2.0;
+    let mut pages: Vec<String> = vec![];
+    let mut content = String::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+    for line in lines {
+        let leading = line.size * 1.5;
+        let (x, wrapped) = if line.preformatted {
+            (
+                MARGIN + CODE_PADDING,
+                wrap_code_line(&line.text, line.size, max_width - CODE_PADDING * 2.0),
+            )
+        } else {
+            (MARGIN, wrap_line(&line.text, line.size, max_width))
+        };
+        for text in wrapped {
+            if y - leading < MARGIN {
+                pages.push(std::mem::take(&mut content));
+                y = PAGE_HEIGHT - MARGIN;
+            }
+            y -= leading;
+            if line.preformatted {
+                content.push_str(&format!(
+                    "0.95 g {:.2} {:.2} {:.2} {:.2} re f\n",
+                    MARGIN,
+                    y - line.size * 0.5,
+                    max_width,
+                    leading
+                ));
+            }
+            if !text.trim().is_empty() {
+                content.push_str(&format!(
+                    "BT {:.2} g /F1 {:.1} Tf {:.2} {:.2} Td <{}> Tj ET\n",
+                    line.gray,
+                    line.size,
+                    x,
+                    y,
+                    encode_text(&text)
+                ));
+            }
+        }
+    }
+    pages.push(content);
+
+    let first_page = FONT_OBJECTS.len() + 3;
+    let kids = (0..pages.len())
+        .map(|i| format!("{} 0 R", first_page + i * 2))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids, pages.len()).into_bytes(),
+    ];
+    objects.extend(FONT_OBJECTS.iter().map(|o| o.as_bytes().to_vec()));
+    for (i, page) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                first_page + i * 2 + 1
+            )
+            .into_bytes(),
+        );
+        objects.push(stream_object("", page.as_bytes()));
+    }
+    write_pdf(&objects)
+}
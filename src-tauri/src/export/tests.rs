@@ -0,0 +1,199 @@
+use chrono::Utc;
+
+use super::conversation::{
+    build_conversation_export, to_html, to_json, to_markdown, ExportScope, ExportedConversation,
+};
+use super::markdown::markdown_to_html;
+use super::pdf::{build_text_pdf, markdown_lines, wrap_code_line, wrap_line, PdfLine};
+use crate::db::conversation_db::{AttachmentType, Conversation, Message, MessageAttachment};
+
+fn message(id: i64, parent_id: Option<i64>, message_type: &str, content: &str) -> Message {
+    Message {
+        id,
+        parent_id,
+        conversation_id: 1,
+        message_type: message_type.to_string(),
+        content: content.to_string(),
+        llm_model_id: Some(1),
+        llm_model_name: Some("gpt-4o".to_string()),
+        created_time: Utc::now(),
+        start_time: None,
+        finish_time: None,
+        token_count: 0,
+        prompt_version_id: None,
+        compare_to_id: None,
+    }
+}
+
+fn attachment(message_id: i64, url: &str) -> MessageAttachment {
+    MessageAttachment {
+        id: 0,
+        message_id,
+        attachment_type: AttachmentType::Image,
+        attachment_url: Some(url.to_string()),
+        attachment_content: None,
+        attachment_hash: None,
+        use_vector: false,
+        token_count: None,
+    }
+}
+
+fn export(scope: ExportScope) -> ExportedConversation {
+    let conversation = Conversation {
+        id: 1,
+        name: "测试对话".to_string(),
+        assistant_id: Some(1),
+        created_time: Utc::now(),
+    };
+    let mut compare = message(6, None, "assistant", "对比的回答");
+    compare.llm_model_name = Some("claude-3-5".to_string());
+    compare.compare_to_id = Some(5);
+    let rows = vec![
+        (
+            message(1, None, "user", "写一个函数"),
+            Some(attachment(1, "a.png")),
+        ),
+        (
+            message(1, None, "user", "写一个函数"),
+            Some(attachment(1, "b.png")),
+        ),
+        (message(2, None, "assistant", "第一次回答"), None),
+        (message(4, Some(2), "assistant", "第二次回答"), None),
+        (message(3, None, "user", "谢谢"), None),
+        (
+            message(5, Some(2), "assistant", "```rust\nfn main() {}\n```"),
+            None,
+        ),
+        (compare, None),
+    ];
+    build_conversation_export(&conversation, "默认助手", rows, scope)
+}
+
+#[test]
+fn test_build_conversation_export() {
+    let branch = export(ExportScope::Branch);
+    let ids: Vec<i64> = branch.messages.iter().map(|m| m.id).collect();
+    assert_eq!(ids, [1, 5, 3]);
+    assert_eq!(branch.messages[0].attachments.len(), 2);
+    assert!(branch.messages.iter().all(|m| m.regenerations.is_empty()));
+    // 对比回答跟在主模型的回答后面，不作为单独的消息
+    assert_eq!(branch.messages[1].compare_answers[0].id, 6);
+
+    let tree = export(ExportScope::Tree);
+    let ids: Vec<i64> = tree.messages.iter().map(|m| m.id).collect();
+    assert_eq!(ids, [1, 2, 3]);
+    let regenerated: Vec<i64> = tree.messages[1]
+        .regenerations
+        .iter()
+        .map(|m| m.id)
+        .collect();
+    assert_eq!(regenerated, [4, 5]);
+    assert!(tree.messages[1].compare_answers.is_empty());
+    assert_eq!(tree.messages[1].regenerations[1].compare_answers.len(), 1);
+
+    let json: serde_json::Value = serde_json::from_str(&to_json(&tree).unwrap()).unwrap();
+    assert_eq!(json["scope"], "tree");
+    assert_eq!(
+        json["messages"][0]["attachments"][1]["attachment_url"],
+        "b.png"
+    );
+    assert_eq!(json["messages"][1]["llm_model_name"], "gpt-4o");
+    assert_eq!(
+        json["messages"][1]["regenerations"][0]["content"],
+        "第二次回答"
+    );
+    assert!(json["messages"][0].get("regenerations").is_none());
+    assert!(json["messages"][1].get("compare_answers").is_none());
+    assert_eq!(
+        json["messages"][1]["regenerations"][1]["compare_answers"][0]["llm_model_name"],
+        "claude-3-5"
+    );
+}
+
+#[test]
+fn test_conversation_markdown_and_html() {
+    let markdown = to_markdown(&export(ExportScope::Tree));
+    assert!(markdown.starts_with("# 测试对话\n"));
+    assert!(markdown.contains("## 助手（gpt-4o） · "));
+    assert!(markdown.contains("### 重新生成 2 · 助手（gpt-4o）"));
+    assert!(markdown.contains("- [图片] a.png\n"));
+    assert!(markdown.contains("#### 对比回答（claude-3-5） · "));
+
+    let html = to_html(&export(ExportScope::Branch));
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains(
+        "<div class=\"code-block\"><div class=\"code-lang\">rust</div><pre><code class=\"language-rust\">fn main() {}</code></pre></div>"
+    ));
+    assert!(!html.contains("<details>"));
+    assert!(html.contains("<div class=\"compare\">\n<header>对比回答（claude-3-5） · "));
+}
+
+#[test]
+fn test_markdown_to_html() {
+    let html = markdown_to_html(
+        "## 标题\n\n第一行 **加粗**\n第二行 `<b>` [链接](https://example.com)\n\n- a\n- b\n1. c\n\n> 引用\n\n<script>alert(1)</script>",
+    );
+    assert_eq!(
+        html,
+        "<h2>标题</h2>\n\
+         <p>第一行 <strong>加粗</strong><br>\n第二行 <code>&lt;b&gt;</code> <a href=\"https://example.com\">链接</a></p>\n\
+         <ul>\n<li>a</li>\n<li>b</li>\n</ul>\n\
+         <ol>\n<li>c</li>\n</ol>\n\
+         <blockquote>引用</blockquote>\n\
+         <p>&lt;script&gt;alert(1)&lt;/script&gt;</p>\n"
+    );
+}
+
+#[test]
+fn test_text_pdf() {
+    // 10 号字，每行宽度 60，半角字符 12 个或者全角字符 6 个
+    assert_eq!(
+        wrap_line("hello world again", 10.0, 60.0),
+        ["hello world", "again"]
+    );
+    assert_eq!(
+        wrap_line("一二三四五六七八", 10.0, 60.0),
+        ["一二三四五六", "七八"]
+    );
+    assert_eq!(wrap_line("", 10.0, 60.0), [""]);
+
+    let lines: Vec<PdfLine> = (0..100)
+        .map(|i| PdfLine::text(&format!("中 {}", i)))
+        .collect();
+    let pdf = build_text_pdf(&lines);
+    let text = String::from_utf8(pdf).unwrap();
+    assert!(text.starts_with("%PDF-1.4\n"));
+    assert!(text.contains("/Encoding /UniGB-UTF16-H"));
+    assert!(text.contains("/Kids [6 0 R 8 0 R 10 0 R] /Count 3"));
+    // "中 0" 编码为 UTF-16
+    assert!(text.contains("<4E2D00200030> Tj"));
+    assert!(text.ends_with("%%EOF\n"));
+}
+
+#[test]
+fn test_pdf_code_blocks() {
+    let lines = markdown_lines("说明\n```rust\nfn main() {\n    println!();\n}\n```\n结束\n");
+    assert_eq!(
+        lines,
+        [
+            PdfLine::text("说明"),
+            PdfLine::note("rust".to_string()),
+            PdfLine::code("fn main() {"),
+            PdfLine::code("    println!();"),
+            PdfLine::code("}"),
+            PdfLine::text("结束"),
+        ]
+    );
+    // 代码按字符换行，保留空白
+    assert_eq!(wrap_code_line("  a b", 10.0, 20.0), ["  a ", "b"]);
+
+    let pdf = build_text_pdf(&lines);
+    let text = String::from_utf8(pdf).unwrap();
+    // 代码行带背景，缩进的空格原样输出
+    assert!(text.contains("0.95 g 56.00"));
+    let indented: String = "    println!();"
+        .encode_utf16()
+        .map(|unit| format!("{:04X}", unit))
+        .collect();
+    assert!(text.contains(&format!("<{}> Tj", indented)));
+}
@@ -7,44 +7,85 @@ mod api;
 mod artifacts;
 mod db;
 mod errors;
+mod eval;
+mod export;
 mod plugin;
+mod scheduler;
 mod state;
 mod template_engine;
+mod utils;
 mod window;
+mod workflow;
 
 use crate::api::ai_api::{ask_ai, cancel_ai, regenerate_ai};
-use crate::api::artifacts_api::run_artifacts;
+use crate::api::artifact_workspace_api::{
+    diff_artifact_versions, export_artifact, get_artifact_version, get_conversation_artifact,
+};
+use crate::api::artifacts_api::{
+    compare_artifact_runs, export_artifact_preview, get_artifact_fix_prompt, get_artifact_run,
+    get_preview_html, kill_artifact_run, list_artifact_runs, rerun_artifact, run_artifacts,
+    write_artifact_stdin,
+};
 use crate::api::assistant_api::{
-    add_assistant, copy_assistant, delete_assistant, get_assistant, get_assistant_field_value,
-    get_assistants, save_assistant,
+    add_assistant, copy_assistant, delete_assistant, diff_assistant_prompt_versions,
+    export_assistant, get_assistant, get_assistant_field_value, get_assistant_prompt_versions,
+    get_assistants, import_assistant, rollback_assistant_prompt_version, save_assistant,
 };
+use crate::api::assistant_pipeline::get_assistant_type_config_schema;
 use crate::api::attachment_api::{add_attachment, add_attachment_content};
 use crate::api::conversation_api::{
-    delete_conversation, get_conversation_with_messages, list_conversations, update_conversation,
+    delete_conversation, export_conversation, get_conversation_with_messages, list_conversations,
+    update_conversation,
+};
+use crate::api::eval_api::{
+    cancel_eval_run, delete_eval_question, delete_eval_question_set, get_eval_model_summaries,
+    get_eval_run_results, list_eval_question_sets, list_eval_questions, list_eval_runs,
+    save_eval_question, save_eval_question_set, start_eval_run,
+};
+use crate::api::feedback_api::{
+    get_feedback_stats, get_message_feedback, list_message_feedback, set_message_feedback,
 };
 use crate::api::llm_api::{
     add_llm_model, add_llm_provider, delete_llm_model, delete_llm_provider, fetch_model_list,
     get_llm_models, get_llm_provider_config, get_llm_providers, get_models_for_select,
     update_llm_provider, update_llm_provider_config,
 };
+use crate::api::prompt_optimize_api::{optimize_assistant_prompt, save_optimized_prompt};
+use crate::api::schedule_api::{
+    delete_scheduled_task, list_scheduled_task_runs, list_scheduled_tasks, open_schedule_notice,
+    open_scheduled_conversation, preview_cron_schedule, run_scheduled_task_now,
+    save_scheduled_task, start_scheduler, take_pending_conversation,
+};
 use crate::api::system_api::{
-    get_all_feature_config, get_bang_list, get_selected_text_api, open_data_folder,
+    build_template_engine, clear_bang_cache, delete_custom_bang, get_all_feature_config,
+    get_bang_list, get_selected_text_api, list_custom_bangs, open_data_folder, save_custom_bang,
     save_feature_config,
 };
+use crate::api::workflow_api::{
+    cancel_workflow_run, delete_workflow, get_workflow_step_runs, import_workflow_file,
+    list_workflow_runs, list_workflows, run_workflow, save_workflow,
+};
 use crate::db::assistant_db::AssistantDatabase;
 use crate::db::llm_db::LLMDatabase;
 use crate::db::system_db::SystemDatabase;
 use crate::window::{
     create_ask_window, open_chat_ui_window, open_config_window, open_plugin_window,
 };
+use artifacts::preview::{handle_preview_request, PREVIEW_PROTOCOL};
 use chrono::Local;
 use db::conversation_db::ConversationDatabase;
 use db::database_upgrade;
+use db::eval_db::EvalDatabase;
 use db::plugin_db::PluginDatabase;
+use db::schedule_db::ScheduleDatabase;
 use db::system_db::FeatureConfig;
+use db::workflow_db::WorkflowDatabase;
 use get_selected_text::get_selected_text;
+use scheduler::{ScheduleNotice, NOTICE_MENU_PREFIX};
 use serde::{Deserialize, Serialize};
+use state::artifact_run::ArtifactRunManager;
 use state::message_token::MessageTokenManager;
+use state::preview_store::PreviewStore;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::Emitter;
@@ -52,7 +93,9 @@ use tauri::{
     menu::{MenuBuilder, MenuItemBuilder},
     Manager, RunEvent,
 };
+use template_engine::TemplateEngine;
 use tokio::sync::Mutex as TokioMutex;
+use tokio_util::sync::CancellationToken;
 
 struct AppState {
     selected_text: TokioMutex<String>,
@@ -70,6 +113,24 @@ struct NameCacheState {
     model_names: Arc<TokioMutex<HashMap<i64, String>>>,
 }
 
+#[derive(Clone)]
+struct TemplateEngineState {
+    engine: Arc<TokioMutex<TemplateEngine>>,
+}
+
+// 正在执行的模型评测，用于取消评测
+#[derive(Clone)]
+struct EvalRunState {
+    tokens: Arc<TokioMutex<HashMap<i64, CancellationToken>>>,
+}
+
+// 定时任务的执行结果通知，以及等待对话窗口打开后显示的对话
+#[derive(Clone)]
+struct SchedulerState {
+    notices: Arc<TokioMutex<Vec<ScheduleNotice>>>,
+    pending_conversation: Arc<TokioMutex<Option<i64>>>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct Config {
     selected_text: String,
@@ -121,16 +182,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .register_uri_scheme_protocol(PREVIEW_PROTOCOL, |ctx, request| {
+            handle_preview_request(ctx.app_handle(), &request)
+        })
         .setup(|app| {
             let app_handle = app.handle();
 
             // 系统托盘菜单和图标初始化
-            let quit = MenuItemBuilder::with_id("quit", "退出").build(app)?;
-            let show = MenuItemBuilder::with_id("show", "显示").build(app)?;
-            let tray_menu = MenuBuilder::new(app).items(&[&show, &quit]).build()?;
-
+            refresh_tray_menu(app_handle, &[])?;
             let tray = app.tray_by_id("aipp").unwrap();
-            tray.set_menu(Some(tray_menu))?;
             tray.on_menu_event(move |app, event| match event.id().as_ref() {
                 "quit" => {
                     app.exit(0);
@@ -138,6 +199,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 "show" => {
                     handle_open_ask_window(&app);
                 }
+                id if id.starts_with(NOTICE_MENU_PREFIX) => {
+                    open_schedule_notice(app, id);
+                }
                 _ => {}
             });
             let _ = tray.set_show_menu_on_left_click(true);
@@ -218,11 +282,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             let assistant_db = AssistantDatabase::new(&app_handle)?;
             let conversation_db = ConversationDatabase::new(&app_handle)?;
             let plugin_db = PluginDatabase::new(&app_handle)?;
+            let eval_db = EvalDatabase::new(&app_handle)?;
+            let workflow_db = WorkflowDatabase::new(&app_handle)?;
+            let schedule_db = ScheduleDatabase::new(&app_handle)?;
             system_db.create_tables()?;
             llm_db.create_tables()?;
             assistant_db.create_tables()?;
             conversation_db.create_tables()?;
+            conversation_db.artifact_run_repo()?.fail_unfinished()?;
             plugin_db.create_tables()?;
+            eval_db.create_tables()?;
+            eval_db.fail_unfinished_runs()?;
+            workflow_db.create_tables()?;
+            workflow_db.fail_unfinished_runs()?;
+            schedule_db.create_tables()?;
+            schedule_db.fail_unfinished_runs()?;
 
             let _ = database_upgrade(
                 &app_handle,
@@ -234,6 +308,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             app.manage(initialize_state(&app_handle));
             app.manage(initialize_name_cache_state(&app_handle));
+            app.manage(initialize_template_engine_state(&app_handle));
+            start_scheduler(app_handle.clone());
 
             if app.get_webview_window("main").is_none() {
                 create_ask_window(&app_handle)
@@ -245,6 +321,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             selected_text: TokioMutex::new(String::new()),
         })
         .manage(MessageTokenManager::new())
+        .manage(ArtifactRunManager::new())
+        .manage(PreviewStore::new())
+        .manage(EvalRunState {
+            tokens: Arc::new(TokioMutex::new(HashMap::new())),
+        })
+        .manage(SchedulerState {
+            notices: Arc::new(TokioMutex::new(Vec::new())),
+            pending_conversation: Arc::new(TokioMutex::new(None)),
+        })
         .invoke_handler(tauri::generate_handler![
             ask_ai,
             regenerate_ai,
@@ -278,13 +363,70 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             add_assistant,
             delete_assistant,
             copy_assistant,
+            export_assistant,
+            import_assistant,
+            get_assistant_type_config_schema,
+            get_assistant_prompt_versions,
+            diff_assistant_prompt_versions,
+            rollback_assistant_prompt_version,
+            set_message_feedback,
+            get_message_feedback,
+            list_message_feedback,
+            get_feedback_stats,
+            list_eval_question_sets,
+            save_eval_question_set,
+            delete_eval_question_set,
+            list_eval_questions,
+            save_eval_question,
+            delete_eval_question,
+            start_eval_run,
+            cancel_eval_run,
+            list_eval_runs,
+            get_eval_run_results,
+            get_eval_model_summaries,
+            list_workflows,
+            save_workflow,
+            import_workflow_file,
+            delete_workflow,
+            run_workflow,
+            cancel_workflow_run,
+            list_workflow_runs,
+            get_workflow_step_runs,
+            list_scheduled_tasks,
+            save_scheduled_task,
+            delete_scheduled_task,
+            preview_cron_schedule,
+            run_scheduled_task_now,
+            list_scheduled_task_runs,
+            open_scheduled_conversation,
+            take_pending_conversation,
+            optimize_assistant_prompt,
+            save_optimized_prompt,
             list_conversations,
             get_conversation_with_messages,
             delete_conversation,
+            export_conversation,
             update_conversation,
             run_artifacts,
+            write_artifact_stdin,
+            kill_artifact_run,
+            get_artifact_run,
+            list_artifact_runs,
+            rerun_artifact,
+            compare_artifact_runs,
+            get_artifact_fix_prompt,
+            export_artifact_preview,
+            get_preview_html,
+            get_conversation_artifact,
+            get_artifact_version,
+            diff_artifact_versions,
+            export_artifact,
             get_bang_list,
-            get_selected_text_api
+            get_selected_text_api,
+            list_custom_bangs,
+            save_custom_bang,
+            delete_custom_bang,
+            clear_bang_cache
         ])
         .build(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -325,6 +467,37 @@ fn handle_open_ask_window(app_handle: &tauri::AppHandle) {
     }
 }
 
+// 托盘菜单，定时任务的执行结果显示在“显示”和“退出”之间，点击后查看结果
+fn refresh_tray_menu(
+    app_handle: &tauri::AppHandle,
+    notices: &[ScheduleNotice],
+) -> tauri::Result<()> {
+    let quit = MenuItemBuilder::with_id("quit", "退出").build(app_handle)?;
+    let show = MenuItemBuilder::with_id("show", "显示").build(app_handle)?;
+    let mut menu_builder = MenuBuilder::new(app_handle).item(&show);
+    if !notices.is_empty() {
+        menu_builder = menu_builder.separator();
+        for notice in notices {
+            let item =
+                MenuItemBuilder::with_id(notice.menu_id(), &notice.text).build(app_handle)?;
+            menu_builder = menu_builder.item(&item);
+        }
+        menu_builder = menu_builder.separator();
+    }
+    let tray_menu = menu_builder.item(&quit).build()?;
+
+    if let Some(tray) = app_handle.tray_by_id("aipp") {
+        tray.set_menu(Some(tray_menu))?;
+        let tooltip = if notices.is_empty() {
+            "Aipp".to_string()
+        } else {
+            format!("Aipp - {} 条定时任务结果", notices.len())
+        };
+        tray.set_tooltip(Some(tooltip))?;
+    }
+    Ok(())
+}
+
 fn initialize_state(app_handle: &tauri::AppHandle) -> FeatureConfigState {
     let db = SystemDatabase::new(app_handle).expect("Failed to connect to database");
     let configs = db
@@ -369,3 +542,18 @@ fn initialize_name_cache_state(app_handle: &tauri::AppHandle) -> NameCacheState
         model_names: Arc::new(TokioMutex::new(model_names)),
     }
 }
+
+fn initialize_template_engine_state(app_handle: &tauri::AppHandle) -> TemplateEngineState {
+    let db = SystemDatabase::new(app_handle).expect("Failed to connect to database");
+    // 配置有误（例如 user_agent 不合法）时不影响启动，使用默认配置，用户可以在 Bang 配置中修改后重新生效
+    let engine = build_template_engine(app_handle, &db).unwrap_or_else(|e| {
+        println!("Failed to load bang config: {}", e);
+        let mut engine = TemplateEngine::new();
+        engine.set_app_handle(app_handle.clone());
+        engine
+    });
+
+    TemplateEngineState {
+        engine: Arc::new(TokioMutex::new(engine)),
+    }
+}
@@ -0,0 +1,220 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike};
+
+#[cfg(test)]
+mod tests;
+
+// 错过的执行超过这个时间才算作错过，避免定时检查的间隔导致正常执行被当成错过
+pub const MISSED_GRACE_MINUTES: i64 = 5;
+
+// 标准的 5 段 cron 表达式：分 时 日 月 周，支持 *、列表、范围、步长以及 @daily 等别名
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpression {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    // 日和周都不是 * 时，按 cron 的约定满足其中一个即可
+    day_of_month_any: bool,
+    day_of_week_any: bool,
+}
+
+impl CronExpression {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "cron 表达式需要 5 段（分 时 日 月 周），当前为 {} 段",
+                fields.len()
+            ));
+        }
+        let mut days_of_week = parse_field(fields[4], 0, 7, "周")?;
+        // 0 和 7 都表示周日
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        days_of_week.truncate(7);
+        Ok(CronExpression {
+            minutes: parse_field(fields[0], 0, 59, "分")?,
+            hours: parse_field(fields[1], 0, 23, "时")?,
+            days_of_month: parse_field(fields[2], 1, 31, "日")?,
+            months: parse_field(fields[3], 1, 12, "月")?,
+            days_of_week,
+            day_of_month_any: fields[2] == "*",
+            day_of_week_any: fields[4] == "*",
+        })
+    }
+
+    fn matches_day(&self, time: &NaiveDateTime) -> bool {
+        let day_of_month = self.days_of_month[time.day() as usize];
+        let day_of_week = self.days_of_week[time.weekday().num_days_from_sunday() as usize];
+        match (self.day_of_month_any, self.day_of_week_any) {
+            (true, true) => true,
+            (true, false) => day_of_week,
+            (false, true) => day_of_month,
+            (false, false) => day_of_month || day_of_week,
+        }
+    }
+
+    // 严格晚于 after 的下一次执行时间，按本地时间计算
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut time = start;
+        // 最多向后查找 5 年，处理 2 月 30 日这类永远不会触发的表达式
+        let limit = start + Duration::days(366 * 5);
+        while time < limit {
+            if !self.months[time.month() as usize] {
+                let (year, month) = if time.month() == 12 {
+                    (time.year() + 1, 1)
+                } else {
+                    (time.year(), time.month() + 1)
+                };
+                time = chrono::NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.matches_day(&time) {
+                time = (time.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.hours[time.hour() as usize] {
+                time = time.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if !self.minutes[time.minute() as usize] {
+                time += Duration::minutes(1);
+                continue;
+            }
+            // 夏令时跳过的时间不存在，继续找下一个
+            if let Some(local) = Local.from_local_datetime(&time).earliest() {
+                return Some(local);
+            }
+            time += Duration::minutes(1);
+        }
+        None
+    }
+}
+
+fn parse_number(value: &str, min: u32, max: u32, field_name: &str) -> Result<u32, String> {
+    let number = value
+        .parse::<u32>()
+        .map_err(|_| format!("{}字段的值无效: {}", field_name, value))?;
+    if number < min || number > max {
+        return Err(format!(
+            "{}字段的值 {} 超出范围 {}-{}",
+            field_name, number, min, max
+        ));
+    }
+    Ok(number)
+}
+
+// 解析单个字段，返回下标为取值的布尔表
+fn parse_field(field: &str, min: u32, max: u32, field_name: &str) -> Result<Vec<bool>, String> {
+    let mut values = vec![false; max as usize + 1];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .map_err(|_| format!("{}字段的步长无效: {}", field_name, part))?;
+                if step == 0 {
+                    return Err(format!("{}字段的步长不能为 0", field_name));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            let start = parse_number(start, min, max, field_name)?;
+            let end = parse_number(end, min, max, field_name)?;
+            if start > end {
+                return Err(format!("{}字段的范围无效: {}", field_name, part));
+            }
+            (start, end)
+        } else {
+            let start = parse_number(range, min, max, field_name)?;
+            // 5/15 表示从 5 开始每 15 个单位执行一次
+            if part.contains('/') {
+                (start, max)
+            } else {
+                (start, start)
+            }
+        };
+        for value in (start..=end).step_by(step as usize) {
+            values[value as usize] = true;
+        }
+    }
+    Ok(values)
+}
+
+// 程序未运行期间错过的执行如何处理
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissedRunPolicy {
+    // 启动后补执行一次
+    RunOnce,
+    // 直接跳过，等待下一次执行
+    Skip,
+}
+
+impl MissedRunPolicy {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "skip" => MissedRunPolicy::Skip,
+            _ => MissedRunPolicy::RunOnce,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScheduleDecision {
+    // 还没到执行时间
+    Wait,
+    // 需要执行，missed 表示是错过后补执行的
+    Run { missed: bool },
+    // 错过且不补执行，只需要计算下一次执行时间
+    Skip,
+}
+
+pub fn decide(
+    next_run_time: DateTime<Local>,
+    now: DateTime<Local>,
+    policy: MissedRunPolicy,
+) -> ScheduleDecision {
+    if next_run_time > now {
+        return ScheduleDecision::Wait;
+    }
+    if now - next_run_time <= Duration::minutes(MISSED_GRACE_MINUTES) {
+        return ScheduleDecision::Run { missed: false };
+    }
+    match policy {
+        MissedRunPolicy::RunOnce => ScheduleDecision::Run { missed: true },
+        MissedRunPolicy::Skip => ScheduleDecision::Skip,
+    }
+}
+
+// 托盘菜单中定时任务执行结果的菜单项 id 前缀
+pub const NOTICE_MENU_PREFIX: &str = "schedule_result_";
+
+// 定时任务执行完成后显示在托盘菜单中的通知
+#[derive(Debug, Clone)]
+pub struct ScheduleNotice {
+    pub run_id: i64,
+    // 执行失败时没有对话
+    pub conversation_id: Option<i64>,
+    pub text: String,
+}
+
+impl ScheduleNotice {
+    pub fn menu_id(&self) -> String {
+        format!("{}{}", NOTICE_MENU_PREFIX, self.run_id)
+    }
+}
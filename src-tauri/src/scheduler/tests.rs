@@ -0,0 +1,69 @@
+use super::*;
+
+fn local(text: &str) -> DateTime<Local> {
+    let time = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap();
+    Local.from_local_datetime(&time).earliest().unwrap()
+}
+
+fn next(expression: &str, after: &str) -> String {
+    CronExpression::parse(expression)
+        .unwrap()
+        .next_after(local(after))
+        .unwrap()
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+#[test]
+fn test_parse_cron_expression() {
+    assert!(CronExpression::parse("*/15 8-18 * * 1-5").is_ok());
+    assert!(CronExpression::parse("0 9 1,15 * 7").is_ok());
+    assert!(CronExpression::parse("@daily").is_ok());
+    assert!(CronExpression::parse("0 9 * *").is_err());
+    assert!(CronExpression::parse("60 9 * * *").is_err());
+    assert!(CronExpression::parse("0 9 * * */0").is_err());
+    assert!(CronExpression::parse("0 18-9 * * *").is_err());
+}
+
+#[test]
+fn test_next_after() {
+    // 每天早上 8 点
+    assert_eq!(next("0 8 * * *", "2024-05-01 07:30"), "2024-05-01 08:00");
+    assert_eq!(next("0 8 * * *", "2024-05-01 08:00"), "2024-05-02 08:00");
+    // 工作日每 15 分钟，2024-05-04 是周六
+    assert_eq!(
+        next("*/15 9-17 * * 1-5", "2024-05-03 17:50"),
+        "2024-05-06 09:00"
+    );
+    assert_eq!(next("5/20 * * * *", "2024-05-01 10:06"), "2024-05-01 10:25");
+    // 日和周同时指定时满足任一即可，2024-05-05 是周日
+    assert_eq!(next("0 9 15 * 0", "2024-05-01 10:00"), "2024-05-05 09:00");
+    // 跨年和闰年
+    assert_eq!(next("@yearly", "2024-06-01 00:00"), "2025-01-01 00:00");
+    assert_eq!(next("0 0 29 2 *", "2024-03-01 00:00"), "2028-02-29 00:00");
+    assert!(CronExpression::parse("0 0 30 2 *")
+        .unwrap()
+        .next_after(local("2024-01-01 00:00"))
+        .is_none());
+}
+
+#[test]
+fn test_decide_missed_runs() {
+    let now = local("2024-05-01 08:02");
+    assert_eq!(
+        decide(local("2024-05-01 09:00"), now, MissedRunPolicy::RunOnce),
+        ScheduleDecision::Wait
+    );
+    assert_eq!(
+        decide(local("2024-05-01 08:00"), now, MissedRunPolicy::Skip),
+        ScheduleDecision::Run { missed: false }
+    );
+    assert_eq!(
+        decide(local("2024-04-30 08:00"), now, MissedRunPolicy::RunOnce),
+        ScheduleDecision::Run { missed: true }
+    );
+    assert_eq!(
+        decide(local("2024-04-30 08:00"), now, MissedRunPolicy::Skip),
+        ScheduleDecision::Skip
+    );
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+use crate::artifacts::sandbox::{OutputLine, SandboxResult};
+
+// 结束后保留输出的执行数量，窗口可以补齐开始监听之前的输出
+const KEEP_FINISHED_RUNS: usize = 20;
+
+// artifact_output_{id} 事件的内容，index 用于窗口去重
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtifactOutput {
+    pub index: usize,
+    #[serde(flatten)]
+    pub line: OutputLine,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtifactRunSnapshot {
+    pub output: Vec<ArtifactOutput>,
+    // 还在运行时为空
+    pub result: Option<SandboxResult>,
+}
+
+struct ArtifactRun {
+    stdin: Option<mpsc::Sender<String>>,
+    cancel_token: CancellationToken,
+    output: Vec<ArtifactOutput>,
+    result: Option<SandboxResult>,
+}
+
+// 正在运行和最近结束的代码执行
+pub struct ArtifactRunManager {
+    runs: Arc<Mutex<HashMap<i64, ArtifactRun>>>,
+    finished: Arc<Mutex<Vec<i64>>>,
+}
+
+impl ArtifactRunManager {
+    pub fn new() -> Self {
+        Self {
+            runs: Arc::new(Mutex::new(HashMap::new())),
+            finished: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub async fn register(
+        &self,
+        run_id: i64,
+        stdin: mpsc::Sender<String>,
+        cancel_token: CancellationToken,
+    ) {
+        let mut runs = self.runs.lock().await;
+        runs.insert(
+            run_id,
+            ArtifactRun {
+                stdin: Some(stdin),
+                cancel_token,
+                output: vec![],
+                result: None,
+            },
+        );
+    }
+
+    // 记录一行输出，返回带序号的事件内容
+    pub async fn push_output(&self, run_id: i64, line: OutputLine) -> ArtifactOutput {
+        let mut runs = self.runs.lock().await;
+        let output = runs.get_mut(&run_id).map(|run| &mut run.output);
+        let event = ArtifactOutput {
+            index: output.as_ref().map(|o| o.len()).unwrap_or_default(),
+            line,
+        };
+        if let Some(output) = output {
+            output.push(event.clone());
+        }
+        event
+    }
+
+    pub async fn finish(&self, run_id: i64, result: SandboxResult) {
+        let mut runs = self.runs.lock().await;
+        if let Some(run) = runs.get_mut(&run_id) {
+            run.stdin = None;
+            run.result = Some(result);
+        }
+        let mut finished = self.finished.lock().await;
+        finished.push(run_id);
+        if finished.len() > KEEP_FINISHED_RUNS {
+            let expired = finished.remove(0);
+            runs.remove(&expired);
+        }
+    }
+
+    pub async fn write_stdin(&self, run_id: i64, input: String) -> Result<(), String> {
+        let stdin = {
+            let runs = self.runs.lock().await;
+            runs.get(&run_id)
+                .and_then(|run| run.stdin.clone())
+                .ok_or("进程已结束或不接受输入".to_string())?
+        };
+        stdin
+            .send(input)
+            .await
+            .map_err(|_| "进程已结束".to_string())
+    }
+
+    // 关闭 stdin，读取输入的程序会收到 EOF
+    pub async fn close_stdin(&self, run_id: i64) {
+        let mut runs = self.runs.lock().await;
+        if let Some(run) = runs.get_mut(&run_id) {
+            run.stdin = None;
+        }
+    }
+
+    pub async fn kill(&self, run_id: i64) {
+        let runs = self.runs.lock().await;
+        if let Some(run) = runs.get(&run_id) {
+            run.cancel_token.cancel();
+        } else {
+            println!("未找到代码执行 {}", run_id);
+        }
+    }
+
+    pub async fn snapshot(&self, run_id: i64) -> Option<ArtifactRunSnapshot> {
+        let runs = self.runs.lock().await;
+        runs.get(&run_id).map(|run| ArtifactRunSnapshot {
+            output: run.output.clone(),
+            result: run.result.clone(),
+        })
+    }
+}
@@ -1 +1,3 @@
+pub mod artifact_run;
 pub mod message_token;
+pub mod preview_store;
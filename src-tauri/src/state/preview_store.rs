@@ -0,0 +1,104 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::artifacts::history::code_hash;
+use crate::artifacts::preview::PreviewKind;
+
+// 保留的预览组件数量，超出后丢弃最早的
+const KEEP_PREVIEWS: usize = 50;
+
+#[derive(Debug, Clone)]
+pub struct PreviewComponent {
+    pub kind: PreviewKind,
+    pub code: String,
+    // 页面渲染完成后回传的结果，导出时使用
+    pub rendered_svg: Option<String>,
+    pub rendered_png: Option<Vec<u8>>,
+}
+
+// 预览的组件代码，按 hash 保存，预览窗口通过自定义协议读取
+// 协议处理函数是同步调用的，这里使用标准库的 Mutex
+pub struct PreviewStore {
+    components: Mutex<HashMap<String, PreviewComponent>>,
+    order: Mutex<VecDeque<String>>,
+    // HTML 预览窗口的内容，按窗口 label 保存，窗口关闭时删除
+    html_pages: Mutex<HashMap<String, String>>,
+}
+
+impl PreviewStore {
+    pub fn new() -> Self {
+        Self {
+            components: Mutex::new(HashMap::new()),
+            order: Mutex::new(VecDeque::new()),
+            html_pages: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // 保存组件代码，返回用于预览地址的 hash
+    pub fn insert(&self, kind: PreviewKind, code: String) -> String {
+        let hash = code_hash(&code);
+        let mut components = self.components.lock().unwrap();
+        let mut order = self.order.lock().unwrap();
+        if components
+            .insert(
+                hash.clone(),
+                PreviewComponent {
+                    kind,
+                    code,
+                    rendered_svg: None,
+                    rendered_png: None,
+                },
+            )
+            .is_none()
+        {
+            order.push_back(hash.clone());
+            if order.len() > KEEP_PREVIEWS {
+                if let Some(expired) = order.pop_front() {
+                    components.remove(&expired);
+                }
+            }
+        }
+        hash
+    }
+
+    pub fn get(&self, hash: &str) -> Option<PreviewComponent> {
+        self.components.lock().unwrap().get(hash).cloned()
+    }
+
+    // 预览窗口关闭时删除组件代码和渲染结果
+    pub fn remove(&self, hash: &str) {
+        self.components.lock().unwrap().remove(hash);
+        self.order.lock().unwrap().retain(|h| h != hash);
+    }
+
+    pub fn set_rendered_svg(&self, hash: &str, svg: String) -> bool {
+        let mut components = self.components.lock().unwrap();
+        components
+            .get_mut(hash)
+            .map(|c| c.rendered_svg = Some(svg))
+            .is_some()
+    }
+
+    pub fn set_rendered_png(&self, hash: &str, png: Vec<u8>) -> bool {
+        let mut components = self.components.lock().unwrap();
+        components
+            .get_mut(hash)
+            .map(|c| c.rendered_png = Some(png))
+            .is_some()
+    }
+
+    pub fn set_html(&self, label: &str, html: String) {
+        self.html_pages
+            .lock()
+            .unwrap()
+            .insert(label.to_string(), html);
+    }
+
+    pub fn get_html(&self, label: &str) -> Option<String> {
+        self.html_pages.lock().unwrap().get(label).cloned()
+    }
+
+    pub fn remove_html(&self, label: &str) {
+        self.html_pages.lock().unwrap().remove(label);
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::future::{BoxFuture, Shared};
+use futures::FutureExt;
+
+use super::BangResult;
+
+type SharedResult = Shared<BoxFuture<'static, BangResult>>;
+
+// bang 结果的短期缓存，缓存的是共享的 future，所以同一个模板中并发的相同调用也只会执行一次
+#[derive(Clone)]
+pub struct BangCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, (Instant, SharedResult)>>>,
+}
+
+impl BangCache {
+    pub fn new(ttl: Duration) -> Self {
+        BangCache {
+            ttl,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn get_or_insert_with(
+        &self,
+        key: String,
+        create: impl FnOnce() -> BoxFuture<'static, BangResult>,
+    ) -> SharedResult {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        entries.retain(|_, (created, _)| now.duration_since(*created) < self.ttl);
+
+        entries
+            .entry(key)
+            .or_insert_with(|| (now, create().shared()))
+            .1
+            .clone()
+    }
+
+    pub fn remove(&self, key: &str) {
+        self.entries.lock().unwrap().remove(key);
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
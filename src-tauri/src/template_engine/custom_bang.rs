@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::FutureExt;
+use serde_json::Value;
+use tokio::process::Command;
+
+use crate::db::system_db::CustomBang;
+
+use super::system_bang::{check_path_allowed, read_file_lines, run_process, split_command};
+use super::{BangArgs, BangResult, CommandFn, TemplateEngine};
+
+// 自定义bang的body里可以再调用其他bang，用上下文记录嵌套层数，防止互相引用导致无限递归
+const DEPTH_KEY: &str = "__custom_bang_depth";
+const MAX_DEPTH: usize = 8;
+
+// 将用户自定义的bang包装为命令处理函数，调用时的参数可以在body中引用:
+// !input 为全部位置参数，!arg1、!arg2... 为单个位置参数，命名参数直接使用 !参数名
+pub fn build_command(bang: CustomBang) -> CommandFn {
+    Arc::new(move |engine, args, context| {
+        let bang = bang.clone();
+        async move {
+            match run_custom_bang(engine, bang, args, context).await {
+                Ok(text) => BangResult::Text(text),
+                Err(e) => BangResult::Error(e),
+            }
+        }
+        .boxed()
+    })
+}
+
+async fn run_custom_bang(
+    engine: TemplateEngine,
+    bang: CustomBang,
+    args: BangArgs,
+    mut context: HashMap<String, String>,
+) -> Result<String, String> {
+    let depth = context
+        .get(DEPTH_KEY)
+        .and_then(|d| d.parse::<usize>().ok())
+        .unwrap_or(0)
+        + 1;
+    if depth > MAX_DEPTH {
+        return Err(format!(
+            "自定义bang {} 嵌套超过 {} 层，可能存在循环引用",
+            bang.name, MAX_DEPTH
+        ));
+    }
+
+    context.insert("input".to_string(), args.positional.join(","));
+    for (index, value) in args.positional.iter().enumerate() {
+        context.insert(format!("arg{}", index + 1), value.clone());
+    }
+    context.extend(args.named);
+    context.insert(DEPTH_KEY.to_string(), depth.to_string());
+
+    match bang.bang_kind.as_str() {
+        "command" => run_command(&engine, &bang.body, &context).await,
+        kind => {
+            // body 本身也是模板，先展开其中的 ! 占位符
+            let body = engine.parse(&bang.body, &context).await;
+            match kind {
+                "text" => Ok(body),
+                "http" => fetch_http(&engine, &body, bang.json_path.as_deref()).await,
+                "file" => read_allowed_file(&engine, &body).await,
+                other => Err(format!("未知的bang类型: {}", other)),
+            }
+        }
+    }
+}
+
+// 先把 body 拆分为程序和参数，再分别展开每一项中的占位符，
+// 替换进来的内容只会作为一个参数直接传给程序，不经过 shell 解释，
+// 所以不支持管道、重定向、&& 等 shell 语法
+async fn run_command(
+    engine: &TemplateEngine,
+    body: &str,
+    context: &HashMap<String, String>,
+) -> Result<String, String> {
+    let mut argv = Vec::new();
+    for part in split_command(body) {
+        argv.push(engine.parse(&part, context).await);
+    }
+    let (program, args) = argv.split_first().ok_or("命令为空".to_string())?;
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    run_process(cmd, body).await
+}
+
+async fn read_allowed_file(engine: &TemplateEngine, path: &str) -> Result<String, String> {
+    let path = check_path_allowed(&engine.file_allowlist, path).await?;
+    read_file_lines(&path.to_string_lossy(), None, None).await
+}
+
+async fn fetch_http(
+    engine: &TemplateEngine,
+    url: &str,
+    json_path: Option<&str>,
+) -> Result<String, String> {
+    let response = engine
+        .fetcher
+        .fetch(url, &[])
+        .await
+        .map_err(|e| e.to_string())?;
+    let text = String::from_utf8_lossy(&response.bytes).to_string();
+
+    match json_path.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(path) => {
+            let json: Value =
+                serde_json::from_str(&text).map_err(|e| format!("响应不是合法的JSON: {}", e))?;
+            extract_json_path(&json, path)
+                .map(|v| match v {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .ok_or_else(|| format!("JSON中未找到路径: {}", path))
+        }
+        None => Ok(text),
+    }
+}
+
+// 支持 `data.items[0].name`、`$.data.items.0.name` 两种写法
+pub fn extract_json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.trim_start_matches('$').trim_start_matches('.');
+    let mut current = value;
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        let (key, indexes) = match segment.find('[') {
+            Some(i) => (&segment[..i], &segment[i..]),
+            None => (segment, ""),
+        };
+        if !key.is_empty() {
+            current = match current {
+                Value::Object(map) => map.get(key)?,
+                Value::Array(list) => list.get(key.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        for index in indexes
+            .split(|c| c == '[' || c == ']')
+            .filter(|s| !s.is_empty())
+        {
+            current = current.as_array()?.get(index.parse::<usize>().ok()?)?;
+        }
+    }
+    Some(current)
+}
@@ -0,0 +1,311 @@
+use std::error::Error as _;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::header::{CONTENT_TYPE, LOCATION};
+use reqwest::{redirect, RequestBuilder, Url};
+use serde_json::Value;
+use thiserror::Error;
+
+use super::readability;
+
+const DEFAULT_USER_AGENT: &str = concat!("Aipp/", env!("CARGO_PKG_VERSION"));
+
+// 网页类bang共用的请求配置，通过 feature_code 为 bang 的配置修改
+#[derive(Debug, Clone)]
+pub struct FetchConfig {
+    // 是否跳过证书校验，只在访问自签名证书的内网服务时打开
+    pub accept_invalid_certs: bool,
+    pub timeout: Duration,
+    pub max_bytes: usize,
+    pub max_redirects: usize,
+    // 是否禁止访问本机、内网等地址
+    pub block_private: bool,
+    pub user_agent: String,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        FetchConfig {
+            accept_invalid_certs: false,
+            timeout: Duration::from_secs(20),
+            max_bytes: 5 * 1024 * 1024,
+            max_redirects: 5,
+            block_private: true,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error("不支持的地址: {0}")]
+    InvalidUrl(String),
+    #[error("不允许访问本机或内网地址: {0}")]
+    BlockedAddress(String),
+    #[error("请求超时: {0}")]
+    Timeout(String),
+    #[error("请求失败: {0}")]
+    Request(String),
+    #[error("请求失败，HTTP状态码 {status}: {url}")]
+    Status { status: u16, url: String },
+    #[error("重定向次数超过 {0} 次")]
+    TooManyRedirects(usize),
+    #[error("内容类型 {content_type} 不是需要的 {expected}")]
+    ContentType {
+        content_type: String,
+        expected: String,
+    },
+    #[error("响应不是合法的JSON: {0}")]
+    Json(String),
+    #[error("创建请求客户端失败: {0}")]
+    Client(String),
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(err: reqwest::Error) -> Self {
+        let url = err.url().map(|u| u.to_string()).unwrap_or_default();
+        if err.is_timeout() {
+            return FetchError::Timeout(url);
+        }
+        // 解析域名时被 GuardedResolver 拦截的地址
+        let mut source = err.source();
+        while let Some(e) = source {
+            if let Some(FetchError::BlockedAddress(_)) = e.downcast_ref::<FetchError>() {
+                return FetchError::BlockedAddress(url);
+            }
+            source = e.source();
+        }
+        FetchError::Request(err.to_string())
+    }
+}
+
+// 禁止访问内网时使用的域名解析器，在真正建立连接时检查解析出的地址，
+// 避免先检查再由 reqwest 重新解析之间域名被改为指向内网地址(DNS rebinding)
+struct GuardedResolver;
+
+impl Resolve for GuardedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if addresses.iter().any(|addr| is_private_address(&addr.ip())) {
+                return Err(Box::new(FetchError::BlockedAddress(host)) as _);
+            }
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchResponse {
+    pub content_type: Option<String>,
+    pub bytes: Vec<u8>,
+    // 内容超过 max_bytes 被截断
+    pub truncated: bool,
+}
+
+impl FetchResponse {
+    // 不带参数的 mime 类型，如 text/html
+    pub fn mime_type(&self) -> Option<String> {
+        self.content_type
+            .as_deref()
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().to_ascii_lowercase())
+            .filter(|v| !v.is_empty())
+    }
+
+    // 按响应声明的编码解码为文本
+    pub fn text(&self) -> String {
+        readability::decode_html(&self.bytes, self.content_type.as_deref())
+    }
+}
+
+#[derive(Clone)]
+pub struct Fetcher {
+    config: FetchConfig,
+    client: reqwest::Client,
+}
+
+impl Fetcher {
+    pub fn new(config: FetchConfig) -> Result<Self, FetchError> {
+        // 重定向由 fetch 手动处理，每一跳都需要重新检查地址
+        let mut builder = reqwest::Client::builder()
+            .danger_accept_invalid_certs(config.accept_invalid_certs)
+            .timeout(config.timeout)
+            .connect_timeout(config.timeout.min(Duration::from_secs(10)))
+            .redirect(redirect::Policy::none())
+            .user_agent(config.user_agent.clone());
+        if config.block_private {
+            // 经过代理时解析的是代理的地址，无法检查目标地址，所以不使用系统代理
+            builder = builder.dns_resolver(Arc::new(GuardedResolver)).no_proxy();
+        }
+        let client = builder
+            .build()
+            .map_err(|e| FetchError::Client(e.to_string()))?;
+        Ok(Fetcher { config, client })
+    }
+
+    // GET 请求地址，accept 为允许的内容类型前缀，如 ["text/html"]，为空时不检查
+    pub async fn fetch(&self, url: &str, accept: &[&str]) -> Result<FetchResponse, FetchError> {
+        self.send(url, accept, |client, url| client.get(url)).await
+    }
+
+    // 请求 JSON 接口，body 不为空时使用 POST 发送，headers 用于传递 api key 等
+    pub async fn fetch_json(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Option<&Value>,
+    ) -> Result<Value, FetchError> {
+        let response = self
+            .send(url, &[], |client, url| {
+                let mut request = match body {
+                    Some(body) => client.post(url).json(body),
+                    None => client.get(url),
+                };
+                for (name, value) in headers {
+                    request = request.header(*name, *value);
+                }
+                request.header("accept", "application/json")
+            })
+            .await?;
+        serde_json::from_slice(&response.bytes).map_err(|e| FetchError::Json(e.to_string()))
+    }
+
+    // 发送请求并手动跟随重定向，build 根据地址构造每一跳的请求
+    async fn send(
+        &self,
+        url: &str,
+        accept: &[&str],
+        build: impl Fn(&reqwest::Client, Url) -> RequestBuilder,
+    ) -> Result<FetchResponse, FetchError> {
+        let mut url = parse_url(url)?;
+        let mut redirects = 0;
+
+        let mut response = loop {
+            self.check_address(&url)?;
+            let response = build(&self.client, url.clone()).send().await?;
+            if !response.status().is_redirection() {
+                break response;
+            }
+
+            redirects += 1;
+            if redirects > self.config.max_redirects {
+                return Err(FetchError::TooManyRedirects(self.config.max_redirects));
+            }
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| FetchError::Request("重定向缺少 Location".to_string()))?;
+            url = url
+                .join(location)
+                .map_err(|_| FetchError::InvalidUrl(location.to_string()))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(FetchError::InvalidUrl(url.to_string()));
+            }
+        };
+
+        if !response.status().is_success() {
+            return Err(FetchError::Status {
+                status: response.status().as_u16(),
+                url: url.to_string(),
+            });
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        if let Some(content_type) = &content_type {
+            let mime = content_type.to_ascii_lowercase();
+            if !accept.is_empty() && !accept.iter().any(|a| mime.starts_with(a)) {
+                return Err(FetchError::ContentType {
+                    content_type: content_type.clone(),
+                    expected: accept.join(" / "),
+                });
+            }
+        }
+
+        let mut bytes = Vec::new();
+        let mut truncated = false;
+        while let Some(chunk) = response.chunk().await? {
+            let remain = self.config.max_bytes - bytes.len();
+            if chunk.len() > remain {
+                bytes.extend_from_slice(&chunk[..remain]);
+                truncated = true;
+                break;
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        Ok(FetchResponse {
+            content_type,
+            bytes,
+            truncated,
+        })
+    }
+
+    // 地址直接是 IP 时不会经过域名解析，在这里检查，域名由 GuardedResolver 在连接时检查
+    fn check_address(&self, url: &Url) -> Result<(), FetchError> {
+        if !self.config.block_private {
+            return Ok(());
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| FetchError::InvalidUrl(url.to_string()))?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+
+        let blocked = match host.parse::<IpAddr>() {
+            Ok(ip) => is_private_address(&ip),
+            Err(_) => host.eq_ignore_ascii_case("localhost"),
+        };
+        if blocked {
+            return Err(FetchError::BlockedAddress(url.to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn parse_url(url: &str) -> Result<Url, FetchError> {
+    let url = Url::parse(url.trim()).map_err(|_| FetchError::InvalidUrl(url.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(FetchError::InvalidUrl(url.to_string()));
+    }
+    Ok(url)
+}
+
+// 本机、内网、链路本地等不应该从提示词中访问的地址
+pub fn is_private_address(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                // 100.64.0.0/10 运营商级 NAT
+                || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_private_address(&IpAddr::V4(v4));
+            }
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // fc00::/7 唯一本地地址
+                || (first & 0xfe00) == 0xfc00
+                // fe80::/10 链路本地地址
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
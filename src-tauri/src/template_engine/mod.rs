@@ -1,126 +1,306 @@
+use base64::Engine;
 use chrono::Local;
-use futures::future::BoxFuture;
+use futures::future::{join_all, BoxFuture};
 use futures::FutureExt;
 use htmd;
-use regex::Regex;
-use reqwest;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::db::system_db::{CustomBang, FeatureConfig};
+
+mod cache;
+mod custom_bang;
+mod fetch;
+pub mod parser;
+mod readability;
+mod search;
+mod system_bang;
+
+use cache::BangCache;
+use fetch::{FetchConfig, FetchResponse, Fetcher};
+use parser::{BangCall, Node, ParseError};
+use search::SearchConfig;
+
+// 单个bang的默认超时时间
+const DEFAULT_BANG_TIMEOUT: Duration = Duration::from_secs(30);
+// bang结果的默认缓存时间
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(120);
+// 网页类bang输出的默认最大字符数
+const DEFAULT_WEB_MAX_CHARS: usize = 20000;
 
 // 定义命令处理函数类型
-type CommandFn = fn(TemplateEngine, String, HashMap<String, String>) -> BoxFuture<'static, String>;
+pub type CommandFn = Arc<
+    dyn Fn(TemplateEngine, BangArgs, HashMap<String, String>) -> BoxFuture<'static, BangResult>
+        + Send
+        + Sync,
+>;
+
+// bang 的执行结果，图片和音频为 data url，不会替换到文本中而是作为附件发送，
+// Error 为执行失败的提示，和 Text 一样替换到文本中，但不会被缓存
+#[derive(Debug, Clone, PartialEq)]
+pub enum BangResult {
+    Text(String),
+    Image(String),
+    Audio(String),
+    Error(String),
+}
+
+impl From<String> for BangResult {
+    fn from(text: String) -> Self {
+        BangResult::Text(text)
+    }
+}
+
+// 模板渲染结果
+#[derive(Debug, Clone, Default)]
+pub struct RenderResult {
+    pub text: String,
+    // 图片、音频类bang产生的附件，按出现顺序排列
+    pub attachments: Vec<BangResult>,
+}
+
+// bang 调用时已经求值完成的参数
+#[derive(Debug, Clone, Default)]
+pub struct BangArgs {
+    pub positional: Vec<String>,
+    pub named: HashMap<String, String>,
+}
+
+impl BangArgs {
+    // 优先取命名参数，没有时取对应位置的参数
+    pub fn get(&self, index: usize, name: &str) -> Option<&String> {
+        self.named.get(name).or_else(|| self.positional.get(index))
+    }
+}
 
 // 获取当前日期的命令处理函数
 fn current_date(
     _: TemplateEngine,
-    _: String,
+    _: BangArgs,
     _: HashMap<String, String>,
-) -> BoxFuture<'static, String> {
-    async { Local::now().format("%Y-%m-%d").to_string() }.boxed()
+) -> BoxFuture<'static, BangResult> {
+    async { Local::now().format("%Y-%m-%d").to_string() }
+        .map(BangResult::Text)
+        .boxed()
 }
 
 // 获取当前时间的命令处理函数
 fn current_time(
     _: TemplateEngine,
-    _: String,
+    _: BangArgs,
     _: HashMap<String, String>,
-) -> BoxFuture<'static, String> {
-    async { Local::now().format("%H:%M:%S").to_string() }.boxed()
+) -> BoxFuture<'static, BangResult> {
+    async { Local::now().format("%H:%M:%S").to_string() }
+        .map(BangResult::Text)
+        .boxed()
 }
 
 // 截取指定长度字符的命令处理函数
 fn sub_start(
-    engine: TemplateEngine,
-    input: String,
-    context: HashMap<String, String>,
-) -> BoxFuture<'static, String> {
+    _: TemplateEngine,
+    args: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
     async move {
-        println!("input : {}", input);
-        let re = Regex::new(r"\((.*),(\d+)\)").unwrap();
-        for cap in re.captures_iter(&input) {
-            println!("cap : {:?}", &cap);
-            let text_origin = &cap[1];
-            let num = &cap[2];
-
-            let text = engine.parse(text_origin.trim(), &context).await;
-            if let Ok(count) = num.trim().parse::<usize>() {
-                return text.chars().take(count).collect();
-            }
+        let (text, count) = match (args.named.get("text"), args.named.get("length")) {
+            (Some(text), Some(count)) => (text.clone(), count.clone()),
+            _ => match args.positional.split_last() {
+                // 兼容未加引号且包含逗号的旧写法，最后一个参数为长度，其余参数按逗号拼接为文本
+                Some((count, text)) if !text.is_empty() => (text.join(","), count.clone()),
+                _ => return String::new(),
+            },
+        };
+        match count.trim().parse::<usize>() {
+            Ok(count) => text.chars().take(count).collect(),
+            Err(_) => String::new(),
         }
-        String::new()
     }
+    .map(BangResult::Text)
     .boxed()
 }
 
 fn selected_text(
     _: TemplateEngine,
-    _: String,
+    _: BangArgs,
     context: HashMap<String, String>,
-) -> BoxFuture<'static, String> {
+) -> BoxFuture<'static, BangResult> {
     async move {
         context
             .get("selected_text")
             .unwrap_or(&String::default())
             .to_string()
     }
+    .map(BangResult::Text)
     .boxed()
 }
 
 // 新增获取网页内容的函数
-fn web(_: TemplateEngine, url: String, _: HashMap<String, String>) -> BoxFuture<'static, String> {
+fn web(
+    engine: TemplateEngine,
+    args: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
     async move {
-        // 移除url中前后的括号
-        let url = url.trim_start_matches('(').trim_end_matches(')');
-
-        let client = reqwest::Client::builder()
-            .danger_accept_invalid_certs(true)
-            .build()
-            .unwrap();
-
-        match client.get(url).send().await {
-            Ok(response) => {
-                let html = response
-                    .text()
-                    .await
-                    .unwrap_or_else(|_| "Failed to get web content".to_string());
-                format!("\n<bangweb url=\"{}\">\n{}\n</bangweb>", url, html)
-            }
-            Err(err) => err.to_string(),
+        let url = args.get(0, "url").cloned().unwrap_or_default();
+
+        match engine.fetcher.fetch(&url, HTML_CONTENT_TYPES).await {
+            Ok(response) => BangResult::Text(format!(
+                "\n<bangweb url=\"{}\">\n{}\n</bangweb>",
+                url,
+                web_content(&engine, &response, &response.text())
+            )),
+            Err(err) => BangResult::Error(format!("获取网页失败: {}", err)),
         }
     }
     .boxed()
 }
 
-// 新增获取网页内容并转换为 Markdown 的函数
+// 新增获取网页内容并转换为 Markdown 的函数，只保留正文部分，并带上标题、作者、发布时间
 fn web_to_markdown(
-    _: TemplateEngine,
-    url: String,
+    engine: TemplateEngine,
+    args: BangArgs,
     _: HashMap<String, String>,
-) -> BoxFuture<'static, String> {
+) -> BoxFuture<'static, BangResult> {
     async move {
-        // 移除url中前后的括号
-        let url = url.trim_start_matches('(').trim_end_matches(')');
-
-        let client = reqwest::Client::new();
-        match client.get(url).send().await {
-            Ok(response) => {
-                let html = response.text().await.unwrap_or_default();
-                format!(
-                    "\n<bangwebtomarkdown url=\"{}\">\n{}\n</bangwebtomarkdown>",
-                    url,
-                    htmd::convert(&html).unwrap()
-                )
+        let url = args.get(0, "url").cloned().unwrap_or_default();
+
+        match fetch_markdown(&engine.fetcher, &url).await {
+            Ok((response, article, markdown)) => {
+                let mut attrs = format!(" url=\"{}\"", url);
+                for (key, value) in [
+                    ("title", &article.title),
+                    ("author", &article.author),
+                    ("published", &article.published),
+                ] {
+                    if let Some(value) = value {
+                        attrs.push_str(&format!(" {}=\"{}\"", key, value.replace('"', "'")));
+                    }
+                }
+                BangResult::Text(format!(
+                    "\n<bangwebtomarkdown{}>\n{}\n</bangwebtomarkdown>",
+                    attrs,
+                    web_content(&engine, &response, &markdown)
+                ))
             }
-            Err(_) => "".to_string(),
+            Err(err) => BangResult::Error(format!("获取网页失败: {}", err)),
+        }
+    }
+    .boxed()
+}
+
+// 获取网页并提取正文，转换为 markdown
+async fn fetch_markdown(
+    fetcher: &Fetcher,
+    url: &str,
+) -> Result<(FetchResponse, readability::Article, String), fetch::FetchError> {
+    let response = fetcher.fetch(url, HTML_CONTENT_TYPES).await?;
+    let article = readability::extract(&response.text());
+    let markdown = htmd::convert(&article.content).unwrap_or_default();
+    let markdown = markdown.trim().to_string();
+    Ok((response, article, markdown))
+}
+
+// 网页类bang接受的内容类型，没有返回类型的按网页处理
+const HTML_CONTENT_TYPES: &[&str] = &["text/", "application/xhtml", "application/xml"];
+
+// 按最大字符数截断网页内容，下载时超过字节上限的也加上提示
+fn web_content(engine: &TemplateEngine, response: &FetchResponse, content: &str) -> String {
+    let content = readability::truncate_chars(content, engine.web_max_chars);
+    if response.truncated {
+        format!(
+            "{}\n\n[网页过大，只读取了前 {} 字节]",
+            content,
+            response.bytes.len()
+        )
+    } else {
+        content
+    }
+}
+
+// 获取网络图片，作为图片附件发送
+fn web_image(
+    engine: TemplateEngine,
+    args: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
+    async move {
+        let url = args.get(0, "url").cloned().unwrap_or_default();
+        match fetch_media(&engine.fetcher, &url, "image/").await {
+            Ok(data_url) => BangResult::Image(data_url),
+            Err(e) => BangResult::Error(e),
         }
     }
     .boxed()
 }
 
+// 获取网络音频，作为音频附件发送
+fn web_audio(
+    engine: TemplateEngine,
+    args: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
+    async move {
+        let url = args.get(0, "url").cloned().unwrap_or_default();
+        match fetch_media(&engine.fetcher, &url, "audio/").await {
+            Ok(data_url) => BangResult::Audio(data_url),
+            Err(e) => BangResult::Error(e),
+        }
+    }
+    .boxed()
+}
+
+// 下载指定类型的资源并转换为 data url，mime_prefix 如 image/、audio/
+async fn fetch_media(fetcher: &Fetcher, url: &str, mime_prefix: &str) -> Result<String, String> {
+    // 部分服务端对媒体文件返回 application/octet-stream，这里先不限制类型，下载后再判断
+    let response = fetcher
+        .fetch(url, &[])
+        .await
+        .map_err(|e| format!("获取资源失败: {}", e))?;
+    if response.truncated {
+        return Err(format!("{} 的内容超过大小上限", url));
+    }
+    let content_type = response
+        .mime_type()
+        .filter(|v| v.starts_with(mime_prefix))
+        // 服务端没有返回对应类型时按扩展名猜测
+        .or_else(|| {
+            mime_guess::from_path(url.split(|c| c == '?' || c == '#').next().unwrap_or(""))
+                .first()
+                .map(|m| m.to_string())
+                .filter(|m| m.starts_with(mime_prefix))
+        })
+        .ok_or_else(|| format!("{} 的内容类型不是 {}*", url, mime_prefix))?;
+
+    Ok(format!(
+        "data:{};base64,{}",
+        content_type,
+        base64::engine::general_purpose::STANDARD.encode(response.bytes)
+    ))
+}
+
 // 模板解析器结构体
 #[derive(Clone)]
 pub struct TemplateEngine {
     commands: HashMap<String, Bang>,
+    // clone 之间共享，引擎保存在 state 中时多次请求可以复用结果
+    cache: BangCache,
+    bang_timeout: Duration,
+    // 剪贴板等需要访问应用的bang使用，未设置时这些bang返回空
+    app_handle: Option<tauri::AppHandle>,
+    // !shell 允许执行的程序
+    shell_allowlist: Vec<String>,
+    // 文件类bang允许读取的目录，未配置时不允许读取任何文件
+    file_allowlist: Vec<String>,
+    // !env 允许读取的环境变量
+    env_allowlist: Vec<String>,
+    // 网页类bang输出的最大字符数，超出部分截断
+    web_max_chars: usize,
+    // 网页类bang共用的请求客户端
+    fetcher: Fetcher,
+    // !search 使用的搜索服务
+    search: SearchConfig,
 }
 
 #[derive(Clone)]
@@ -129,9 +309,21 @@ pub struct Bang {
     pub complete: String,
     pub description: String,
     pub bang_type: BangType,
+    pub cache_policy: CachePolicy,
     pub command: CommandFn,
 }
 
+// bang结果的缓存方式
+#[derive(Clone, Copy, PartialEq)]
+pub enum CachePolicy {
+    // 不缓存，每次调用都重新执行
+    None,
+    // 按bang名称和参数缓存
+    Args,
+    // 按bang名称、参数和上下文缓存，用于结果依赖上下文变量的bang
+    ArgsAndContext,
+}
+
 #[derive(Clone, Serialize)]
 pub enum BangType {
     Text,
@@ -151,7 +343,8 @@ impl TemplateEngine {
                 complete: "current_date".to_string(),
                 description: "获取当前日期".to_string(),
                 bang_type: BangType::Text,
-                command: current_date as CommandFn,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(current_date),
             },
         );
         commands.insert(
@@ -161,7 +354,8 @@ impl TemplateEngine {
                 complete: "cd".to_string(),
                 description: "获取当前日期".to_string(),
                 bang_type: BangType::Text,
-                command: current_date as CommandFn,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(current_date),
             },
         );
 
@@ -172,7 +366,8 @@ impl TemplateEngine {
                 complete: "current_time".to_string(),
                 description: "获取当前时间".to_string(),
                 bang_type: BangType::Text,
-                command: current_time as CommandFn,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(current_time),
             },
         );
         commands.insert(
@@ -182,7 +377,8 @@ impl TemplateEngine {
                 complete: "ct".to_string(),
                 description: "获取当前时间".to_string(),
                 bang_type: BangType::Text,
-                command: current_time as CommandFn,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(current_time),
             },
         );
 
@@ -193,7 +389,8 @@ impl TemplateEngine {
                 complete: "sub_start(|)".to_string(),
                 description: "截取文本的前多少个字符".to_string(),
                 bang_type: BangType::Text,
-                command: sub_start as CommandFn,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(sub_start),
             },
         );
 
@@ -204,7 +401,8 @@ impl TemplateEngine {
                 complete: "selected_text".to_string(),
                 description: "获取当前选中的文本".to_string(),
                 bang_type: BangType::Text,
-                command: selected_text as CommandFn,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(selected_text),
             },
         );
         commands.insert(
@@ -214,7 +412,8 @@ impl TemplateEngine {
                 complete: "s".to_string(),
                 description: "获取当前选中的文本".to_string(),
                 bang_type: BangType::Text,
-                command: selected_text as CommandFn,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(selected_text),
             },
         );
 
@@ -225,7 +424,8 @@ impl TemplateEngine {
                 complete: "web(|)".to_string(),
                 description: "通过网络获取URL的网页信息".to_string(),
                 bang_type: BangType::Text,
-                command: web as CommandFn,
+                cache_policy: CachePolicy::Args,
+                command: Arc::new(web),
             },
         );
         commands.insert(
@@ -235,7 +435,8 @@ impl TemplateEngine {
                 complete: "w(|)".to_string(),
                 description: "通过网络获取URL的网页信息".to_string(),
                 bang_type: BangType::Text,
-                command: web as CommandFn,
+                cache_policy: CachePolicy::Args,
+                command: Arc::new(web),
             },
         );
 
@@ -246,7 +447,8 @@ impl TemplateEngine {
                 complete: "web_to_markdown(|)".to_string(),
                 description: "通过网络获取URL的网页信息并且转换为markdown格式".to_string(),
                 bang_type: BangType::Text,
-                command: web_to_markdown as CommandFn,
+                cache_policy: CachePolicy::Args,
+                command: Arc::new(web_to_markdown),
             },
         );
         commands.insert(
@@ -256,11 +458,229 @@ impl TemplateEngine {
                 complete: "wm(|)".to_string(),
                 description: "通过网络获取URL的网页信息并且转换为markdown格式".to_string(),
                 bang_type: BangType::Text,
-                command: web_to_markdown as CommandFn,
+                cache_policy: CachePolicy::Args,
+                command: Arc::new(web_to_markdown),
             },
         );
 
-        TemplateEngine { commands }
+        commands.insert(
+            "search".to_string(),
+            Bang {
+                name: "search".to_string(),
+                complete: "search(|)".to_string(),
+                description: "通过配置的搜索服务搜索，并获取前几条结果的网页正文".to_string(),
+                bang_type: BangType::Text,
+                cache_policy: CachePolicy::Args,
+                command: Arc::new(search::search),
+            },
+        );
+
+        commands.insert(
+            "clipboard".to_string(),
+            Bang {
+                name: "clipboard".to_string(),
+                complete: "clipboard".to_string(),
+                description: "获取剪贴板中的文本".to_string(),
+                bang_type: BangType::Text,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(system_bang::clipboard),
+            },
+        );
+        commands.insert(
+            "clipboard_image".to_string(),
+            Bang {
+                name: "clipboard_image".to_string(),
+                complete: "clipboard_image".to_string(),
+                description: "获取剪贴板中的图片作为图片发送".to_string(),
+                bang_type: BangType::Image,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(system_bang::clipboard_image),
+            },
+        );
+        commands.insert(
+            "file".to_string(),
+            Bang {
+                name: "file".to_string(),
+                complete: "file(|)".to_string(),
+                description: "读取文件内容，可以指定起止行号".to_string(),
+                bang_type: BangType::Text,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(system_bang::file),
+            },
+        );
+        commands.insert(
+            "dir".to_string(),
+            Bang {
+                name: "dir".to_string(),
+                complete: "dir(|)".to_string(),
+                description: "列出目录下的文件".to_string(),
+                bang_type: BangType::Text,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(system_bang::dir),
+            },
+        );
+        commands.insert(
+            "env".to_string(),
+            Bang {
+                name: "env".to_string(),
+                complete: "env(|)".to_string(),
+                description: "获取环境变量".to_string(),
+                bang_type: BangType::Text,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(system_bang::env),
+            },
+        );
+        commands.insert(
+            "shell".to_string(),
+            Bang {
+                name: "shell".to_string(),
+                complete: "shell(|)".to_string(),
+                description: "执行允许列表中的命令并获取输出".to_string(),
+                bang_type: BangType::Text,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(system_bang::shell),
+            },
+        );
+
+        commands.insert(
+            "screenshot".to_string(),
+            Bang {
+                name: "screenshot".to_string(),
+                complete: "screenshot".to_string(),
+                description: "截取屏幕作为图片发送".to_string(),
+                bang_type: BangType::Image,
+                cache_policy: CachePolicy::None,
+                command: Arc::new(system_bang::screenshot),
+            },
+        );
+        commands.insert(
+            "web_audio".to_string(),
+            Bang {
+                name: "web_audio".to_string(),
+                complete: "web_audio(|)".to_string(),
+                description: "获取网络音频作为音频发送".to_string(),
+                bang_type: BangType::Audio,
+                cache_policy: CachePolicy::Args,
+                command: Arc::new(web_audio),
+            },
+        );
+        commands.insert(
+            "web_image".to_string(),
+            Bang {
+                name: "web_image".to_string(),
+                complete: "web_image(|)".to_string(),
+                description: "获取网络图片作为图片发送".to_string(),
+                bang_type: BangType::Image,
+                cache_policy: CachePolicy::Args,
+                command: Arc::new(web_image),
+            },
+        );
+
+        TemplateEngine {
+            commands,
+            cache: BangCache::new(DEFAULT_CACHE_TTL),
+            bang_timeout: DEFAULT_BANG_TIMEOUT,
+            app_handle: None,
+            shell_allowlist: vec![],
+            file_allowlist: vec![],
+            env_allowlist: vec![],
+            web_max_chars: DEFAULT_WEB_MAX_CHARS,
+            // 和 reqwest::Client::new 一样，默认配置的客户端只有在 TLS 初始化失败时才会创建失败
+            fetcher: Fetcher::new(FetchConfig::default()).expect("创建请求客户端失败"),
+            search: SearchConfig::default(),
+        }
+    }
+
+    pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
+        self.app_handle = Some(app_handle);
+    }
+
+    // 设置单个bang的超时时间，超时的bang会输出提示文本而不会阻塞整个模板
+    pub fn set_bang_timeout(&mut self, timeout: Duration) {
+        self.bang_timeout = timeout;
+    }
+
+    // 设置缓存时间，会清空已有的缓存
+    pub fn set_cache_ttl(&mut self, ttl: Duration) {
+        self.cache = BangCache::new(ttl);
+    }
+
+    // 读取 feature_code 为 bang 的配置: timeout_secs 单个bang超时秒数，cache_ttl_secs 缓存秒数，
+    // shell_allowlist !shell 允许执行的程序，逗号分隔，file_allowlist 文件类bang允许读取的目录，逗号或换行分隔，
+    // env_allowlist !env 允许读取的环境变量，web_max_chars 网页类bang输出的最大字符数，
+    // web_ 开头的为网页请求配置: accept_invalid_certs 跳过证书校验，timeout_secs 超时秒数，
+    // max_bytes 最大下载字节数，max_redirects 最大重定向次数，block_private 禁止访问内网(默认开启)，user_agent，
+    // search_ 开头的为 !search 配置: engine 搜索服务(searxng、brave、bing、tavily)，url 接口地址，
+    // api_key，result_count 读取正文的结果条数
+    pub fn apply_feature_config(&mut self, configs: &[FeatureConfig]) -> Result<(), String> {
+        let mut fetch_config = FetchConfig::default();
+        let mut search_config = SearchConfig::default();
+        for config in configs {
+            let value = config.value.trim();
+            match config.key.as_str() {
+                "timeout_secs" => match value.parse::<u64>() {
+                    Ok(secs) if secs > 0 => self.set_bang_timeout(Duration::from_secs(secs)),
+                    _ => {}
+                },
+                "cache_ttl_secs" => {
+                    if let Ok(secs) = value.parse::<u64>() {
+                        self.set_cache_ttl(Duration::from_secs(secs));
+                    }
+                }
+                "web_max_chars" => match value.parse::<usize>() {
+                    Ok(max_chars) if max_chars > 0 => self.web_max_chars = max_chars,
+                    _ => {}
+                },
+                "shell_allowlist" => self.shell_allowlist = split_list(value),
+                "file_allowlist" => self.file_allowlist = split_list(value),
+                "env_allowlist" => self.env_allowlist = split_list(value),
+                "web_accept_invalid_certs" => {
+                    fetch_config.accept_invalid_certs = value == "true";
+                }
+                "web_timeout_secs" => match value.parse::<u64>() {
+                    Ok(secs) if secs > 0 => fetch_config.timeout = Duration::from_secs(secs),
+                    _ => {}
+                },
+                "web_max_bytes" => match value.parse::<usize>() {
+                    Ok(max_bytes) if max_bytes > 0 => fetch_config.max_bytes = max_bytes,
+                    _ => {}
+                },
+                "web_max_redirects" => {
+                    if let Ok(max_redirects) = value.parse::<usize>() {
+                        fetch_config.max_redirects = max_redirects;
+                    }
+                }
+                "web_block_private" => {
+                    fetch_config.block_private = value == "true";
+                }
+                "web_user_agent" => {
+                    if !value.is_empty() {
+                        fetch_config.user_agent = value.to_string();
+                    }
+                }
+                "search_engine" => {
+                    if !value.is_empty() {
+                        search_config.engine = value.to_lowercase();
+                    }
+                }
+                "search_url" => search_config.url = value.to_string(),
+                "search_api_key" => search_config.api_key = value.to_string(),
+                "search_result_count" => match value.parse::<usize>() {
+                    Ok(count) if count > 0 => search_config.result_count = count,
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        self.fetcher = Fetcher::new(fetch_config).map_err(|e| e.to_string())?;
+        self.search = search_config;
+        // 配置变化后之前的结果不再可靠
+        self.clear_cache();
+        Ok(())
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache.clear();
     }
 
     // 注册命令
@@ -272,40 +692,214 @@ impl TemplateEngine {
                 complete: name.to_string(),
                 description: "Custom command".to_string(),
                 bang_type: BangType::Text,
+                cache_policy: CachePolicy::None,
                 command: handler,
             },
         );
     }
 
-    // 解析并替换模板字符串
+    // 注册用户自定义的bang，名称与内置bang冲突时跳过
+    pub fn register_custom_bangs(&mut self, bangs: &[CustomBang]) {
+        for bang in bangs {
+            let command = custom_bang::build_command(bang.clone());
+            // 纯文本的bang执行很快且没有副作用，不需要缓存
+            let cache_policy = match bang.bang_kind.as_str() {
+                "text" => CachePolicy::None,
+                _ => CachePolicy::ArgsAndContext,
+            };
+            let complete = if bang.body.contains("!input") || bang.body.contains("!arg") {
+                "(|)"
+            } else {
+                ""
+            };
+            for name in std::iter::once(&bang.name).chain(bang.aliases.iter()) {
+                if self.commands.contains_key(name) {
+                    println!(
+                        "custom bang {} conflicts with an existing bang, skipped",
+                        name
+                    );
+                    continue;
+                }
+                self.commands.insert(
+                    name.clone(),
+                    Bang {
+                        name: name.clone(),
+                        complete: format!("{}{}", name, complete),
+                        description: bang.description.clone(),
+                        bang_type: BangType::Text,
+                        cache_policy,
+                        command: command.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    // 解析并替换模板字符串，模板有语法错误时原样返回
     pub async fn parse(&self, template: &str, context: &HashMap<String, String>) -> String {
-        let re = Regex::new(r"[!！](\w+)(\((?:[^()]|\((?:[^()]|\((?:[^()]|\((?:[^()]|\((?:[^()]|\((?:[^()]|\((?:[^()]|\((?:[^()]|\((?:[^()]|\([^()]*\))*\))*\))*\))*\))*\))*\))*\))*\))*\))?").unwrap();
-        let mut result = template.to_string();
-
-        for cap in re.captures_iter(template) {
-            println!("cap : {:?}", &cap);
-            let command = &cap[1];
-            let args = cap.get(2).map_or("", |m| m.as_str());
-            if let Some(bang) = self.commands.get(command) {
-                let replacement =
-                    (bang.command)(self.clone(), args.to_string(), context.clone()).await;
-                result = result.replace(&cap[0], &replacement);
+        match self.try_parse(template, context).await {
+            Ok(result) => result,
+            Err(e) => {
+                println!("{}", e);
+                template.to_string()
             }
         }
+    }
 
-        // 替换上下文变量
-        for (key, value) in context {
-            let placeholder = format!("!{}", key);
-            result = result.replace(&placeholder, value);
+    // 解析并渲染模板，模板有语法错误时原样返回文本，不带附件
+    pub async fn render(&self, template: &str, context: &HashMap<String, String>) -> RenderResult {
+        match self.try_render(template, context).await {
+            Ok(result) => result,
+            Err(e) => {
+                println!("{}", e);
+                RenderResult {
+                    text: template.to_string(),
+                    attachments: vec![],
+                }
+            }
         }
+    }
 
+    // 解析并替换模板字符串，语法错误会带上出错的位置返回，图片、音频类bang的结果会被忽略
+    pub async fn try_parse(
+        &self,
+        template: &str,
+        context: &HashMap<String, String>,
+    ) -> Result<String, ParseError> {
+        Ok(self.try_render(template, context).await?.text)
+    }
+
+    // 解析并渲染模板，图片、音频类bang的结果放在附件中返回
+    pub async fn try_render(
+        &self,
+        template: &str,
+        context: &HashMap<String, String>,
+    ) -> Result<RenderResult, ParseError> {
+        let is_known = |name: &str| self.commands.contains_key(name) || context.contains_key(name);
+        let nodes = parser::parse(template, &is_known)?;
+        Ok(self.render_nodes(&nodes, context).await)
+    }
+
+    // 同一层级的bang之间互不依赖，并发求值后按原顺序拼接
+    fn render_nodes<'a>(
+        &'a self,
+        nodes: &'a [Node],
+        context: &'a HashMap<String, String>,
+    ) -> BoxFuture<'a, RenderResult> {
+        async move {
+            let parts = join_all(nodes.iter().map(|node| async move {
+                match node {
+                    Node::Text(text) => RenderResult {
+                        text: text.clone(),
+                        attachments: vec![],
+                    },
+                    Node::Bang(call) => self.eval_bang(call, context).await,
+                }
+            }))
+            .await;
+
+            let mut result = RenderResult::default();
+            for part in parts {
+                result.text.push_str(&part.text);
+                result.attachments.extend(part.attachments);
+            }
+            result
+        }
+        .boxed()
+    }
+
+    async fn eval_bang(&self, call: &BangCall, context: &HashMap<String, String>) -> RenderResult {
+        let values = join_all(
+            call.args
+                .iter()
+                .flatten()
+                .map(|arg| self.render_nodes(&arg.value, context)),
+        )
+        .await;
+        // 参数中产生的附件同样需要发送
+        let mut result = RenderResult::default();
+        let mut args = BangArgs::default();
+        for (arg, value) in call.args.iter().flatten().zip(values) {
+            result.attachments.extend(value.attachments);
+            match &arg.name {
+                Some(name) => {
+                    args.named.insert(name.clone(), value.text);
+                }
+                None => args.positional.push(value.text),
+            }
+        }
+
+        let bang = match self.commands.get(&call.name) {
+            Some(bang) => bang,
+            // 上下文变量
+            None => {
+                result.text = context.get(&call.name).cloned().unwrap_or_default();
+                return result;
+            }
+        };
+
+        let cache_key = cache_key(&call.name, bang.cache_policy, &args, context);
+        let future = match &cache_key {
+            Some(key) => self
+                .cache
+                .get_or_insert_with(key.clone(), || {
+                    (bang.command)(self.clone(), args, context.clone())
+                })
+                .boxed(),
+            None => (bang.command)(self.clone(), args, context.clone()),
+        };
+
+        let value = tokio::time::timeout(self.bang_timeout, future)
+            .await
+            .unwrap_or_else(|_| BangResult::Error(format!("[!{} 执行超时]", call.name)));
+        // 失败和超时的结果不保留，下次调用重新执行
+        if let (BangResult::Error(_), Some(key)) = (&value, &cache_key) {
+            self.cache.remove(key);
+        }
+        match value {
+            BangResult::Text(text) | BangResult::Error(text) => result.text = text,
+            attachment => result.attachments.push(attachment),
+        }
         result
     }
 
+    pub fn has_command(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
     pub fn get_commands(&self) -> Vec<Bang> {
         self.commands.values().cloned().collect()
     }
 }
 
+// 按逗号或换行拆分配置中的列表
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(|c| c == ',' || c == '\n')
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+fn cache_key(
+    name: &str,
+    policy: CachePolicy,
+    args: &BangArgs,
+    context: &HashMap<String, String>,
+) -> Option<String> {
+    let named: BTreeMap<_, _> = args.named.iter().collect();
+    match policy {
+        CachePolicy::None => None,
+        CachePolicy::Args => Some(format!("{}\0{:?}\0{:?}", name, args.positional, named)),
+        CachePolicy::ArgsAndContext => {
+            let context: BTreeMap<_, _> = context.iter().collect();
+            Some(format!(
+                "{}\0{:?}\0{:?}\0{:?}",
+                name, args.positional, named, context
+            ))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests;
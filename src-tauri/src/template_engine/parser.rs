@@ -0,0 +1,279 @@
+use thiserror::Error;
+
+// 模板语法:
+//   !name / ！name                       不带参数的bang或上下文变量
+//   !name(a, b, key=value)               位置参数与命名参数，参数中可以嵌套 bang
+//   !name("a, b", 'c')                   引号字符串中的内容按字面量处理，支持 \" \' \\ \n \t \! 转义
+//   \!                                   输出字面量的 !
+// 只有已注册的 bang 和上下文变量会被识别，其余的 ! 按普通文本处理
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Text(String),
+    Bang(BangCall),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BangCall {
+    pub name: String,
+    // 没有括号时为 None，`!name()` 为 Some(vec![])
+    pub args: Option<Vec<Arg>>,
+    // 在模板中的字符位置
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arg {
+    pub name: Option<String>,
+    pub value: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("模板解析错误(第{line}行第{column}列): {message}")]
+pub struct ParseError {
+    pub position: usize,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+pub fn parse(template: &str, is_known: &dyn Fn(&str) -> bool) -> Result<Vec<Node>, ParseError> {
+    let mut parser = Parser {
+        chars: template.chars().collect(),
+        pos: 0,
+        is_known,
+    };
+    parser.parse_nodes(false)
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    is_known: &'a dyn Fn(&str) -> bool,
+}
+
+fn is_bang_char(c: char) -> bool {
+    c == '!' || c == '！'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn error(&self, position: usize, message: impl Into<String>) -> ParseError {
+        let mut line = 1;
+        let mut column = 1;
+        for c in self.chars.iter().take(position) {
+            if *c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        ParseError {
+            position,
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    // 读取 ! 之后的标识符，不移动位置
+    fn ident_len_at(&self, start: usize) -> usize {
+        self.chars[start.min(self.chars.len())..]
+            .iter()
+            .take_while(|c| is_ident_char(**c))
+            .count()
+    }
+
+    // in_arg 为 true 时解析未加引号的参数，遇到同层级的 , 或 ) 结束
+    fn parse_nodes(&mut self, in_arg: bool) -> Result<Vec<Node>, ParseError> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+        let mut depth = 0;
+
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                let escaped = self.peek_at(1);
+                let escapable = match escaped {
+                    Some(e) if is_bang_char(e) => true,
+                    Some('\\' | ',' | '(' | ')' | '"' | '\'') => in_arg,
+                    _ => false,
+                };
+                if escapable {
+                    text.push(escaped.unwrap());
+                    self.pos += 2;
+                } else {
+                    text.push(c);
+                    self.pos += 1;
+                }
+                continue;
+            }
+
+            if is_bang_char(c) {
+                let len = self.ident_len_at(self.pos + 1);
+                let name: String = self.chars[self.pos + 1..self.pos + 1 + len]
+                    .iter()
+                    .collect();
+                if len > 0 && (self.is_known)(&name) {
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(std::mem::take(&mut text)));
+                    }
+                    nodes.push(Node::Bang(self.parse_bang(name, len)?));
+                    continue;
+                }
+                text.push(c);
+                self.pos += 1;
+                continue;
+            }
+
+            if in_arg {
+                match c {
+                    '(' => depth += 1,
+                    ')' if depth == 0 => break,
+                    ')' => depth -= 1,
+                    ',' if depth == 0 => break,
+                    _ => {}
+                }
+            }
+            text.push(c);
+            self.pos += 1;
+        }
+
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+        Ok(nodes)
+    }
+
+    fn parse_bang(&mut self, name: String, len: usize) -> Result<BangCall, ParseError> {
+        let position = self.pos;
+        self.pos += 1 + len;
+
+        let args = if self.peek() == Some('(') {
+            Some(self.parse_args()?)
+        } else {
+            None
+        };
+
+        Ok(BangCall {
+            name,
+            args,
+            position,
+        })
+    }
+
+    fn parse_args(&mut self) -> Result<Vec<Arg>, ParseError> {
+        let open_position = self.pos;
+        self.pos += 1;
+        let mut args = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(')') {
+            self.pos += 1;
+            return Ok(args);
+        }
+
+        loop {
+            args.push(self.parse_arg()?);
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(')') => {
+                    self.pos += 1;
+                    return Ok(args);
+                }
+                _ => return Err(self.error(open_position, "参数列表缺少右括号 )")),
+            }
+        }
+    }
+
+    fn parse_arg(&mut self) -> Result<Arg, ParseError> {
+        self.skip_whitespace();
+
+        // 命名参数: key=value
+        let mut name = None;
+        let len = self.ident_len_at(self.pos);
+        if len > 0 {
+            let mut lookahead = self.pos + len;
+            while matches!(self.chars.get(lookahead), Some(c) if c.is_whitespace()) {
+                lookahead += 1;
+            }
+            if self.chars.get(lookahead) == Some(&'=') {
+                name = Some(self.chars[self.pos..self.pos + len].iter().collect());
+                self.pos = lookahead + 1;
+                self.skip_whitespace();
+            }
+        }
+
+        let value = match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                let literal = self.parse_quoted(quote)?;
+                self.skip_whitespace();
+                if !matches!(self.peek(), Some(',' | ')')) {
+                    return Err(self.error(self.pos, "字符串参数之后应为 , 或 )"));
+                }
+                vec![Node::Text(literal)]
+            }
+            _ => trim_nodes(self.parse_nodes(true)?),
+        };
+
+        Ok(Arg { name, value })
+    }
+
+    fn parse_quoted(&mut self, quote: char) -> Result<String, ParseError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut literal = String::new();
+
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == quote {
+                return Ok(literal);
+            }
+            if c == '\\' {
+                let escaped = self
+                    .peek()
+                    .ok_or_else(|| self.error(self.pos - 1, "转义字符 \\ 之后缺少内容"))?;
+                self.pos += 1;
+                literal.push(match escaped {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    other => other,
+                });
+                continue;
+            }
+            literal.push(c);
+        }
+
+        Err(self.error(start, format!("字符串缺少结束引号 {}", quote)))
+    }
+}
+
+// 去掉未加引号参数首尾的空白
+fn trim_nodes(mut nodes: Vec<Node>) -> Vec<Node> {
+    if let Some(Node::Text(text)) = nodes.first_mut() {
+        *text = text.trim_start().to_string();
+    }
+    if let Some(Node::Text(text)) = nodes.last_mut() {
+        *text = text.trim_end().to_string();
+    }
+    nodes.retain(|n| !matches!(n, Node::Text(t) if t.is_empty()));
+    nodes
+}
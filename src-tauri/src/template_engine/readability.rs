@@ -0,0 +1,613 @@
+use encoding_rs::{Encoding, GB18030, UTF_8};
+use html5ever::tendril::TendrilSink;
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use regex::Regex;
+
+// 网页正文提取，参考 Mozilla Readability 的打分方式:
+// 用 html5ever 解析后，先去掉脚本、导航、页脚、cookie 提示等明显不是正文的节点，再按段落文本长度、逗号数量给父节点打分，
+// 分数乘以(1 - 链接密度)后最高的节点作为正文。
+// 遍历都使用显式的栈，每个节点的文本长度等统计只计算一次，嵌套很深的网页也不会栈溢出
+
+// 不需要保留的标签
+const REMOVED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "object", "embed", "nav",
+    "header", "footer", "aside", "form", "button", "input", "select", "textarea", "dialog", "link",
+    "meta",
+];
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dl",
+    "div",
+    "fieldset",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+// 转换为 markdown 时保留的属性
+const KEPT_ATTRS: &[&str] = &["href", "src", "alt", "title"];
+
+const UNLIKELY_CANDIDATES: &str = r"(?i)-ad-|ai2html|banner|breadcrumbs|combx|comment|community|cookie|consent|cover-wrap|disqus|extra|footer|gdpr|header|legends|menu|modal|newsletter|pagination|pager|popup|related|remark|replies|rss|share|shoutbox|sidebar|skyscraper|social|sponsor|subscribe|supplemental|yom-remote";
+const MAYBE_CANDIDATE: &str = r"(?i)and|article|body|column|content|main|shadow";
+const POSITIVE: &str =
+    r"(?i)article|body|content|entry|hentry|h-entry|main|page|post|text|blog|story";
+const NEGATIVE: &str = r"(?i)-ad-|hidden|banner|combx|comment|com-|contact|foot|footnote|gdpr|masthead|media|meta|outbrain|promo|related|scroll|share|shoutbox|sidebar|skyscraper|sponsor|shopping|tags|tool|widget";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Article {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub published: Option<String>,
+    // 正文部分的 html
+    pub content: String,
+}
+
+// 按 BOM、Content-Type、<meta charset> 的顺序确定编码，都没有时按 utf-8 解码，失败再尝试 gb18030
+pub fn decode_html(bytes: &[u8], content_type: Option<&str>) -> String {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        return encoding
+            .decode_without_bom_handling(&bytes[bom_length..])
+            .0
+            .into_owned();
+    }
+
+    let label = content_type
+        .and_then(charset_from_content_type)
+        .or_else(|| sniff_meta_charset(&bytes[..bytes.len().min(4096)]));
+    if let Some(encoding) = label.and_then(|l| Encoding::for_label(l.trim().as_bytes())) {
+        return encoding.decode_without_bom_handling(bytes).0.into_owned();
+    }
+
+    let (text, had_errors) = UTF_8.decode_without_bom_handling(bytes);
+    if had_errors {
+        return GB18030.decode_without_bom_handling(bytes).0.into_owned();
+    }
+    text.into_owned()
+}
+
+fn charset_from_content_type(content_type: &str) -> Option<String> {
+    content_type.split(';').find_map(|part| {
+        let (key, value) = part.split_once('=')?;
+        if key.trim().eq_ignore_ascii_case("charset") {
+            Some(
+                value
+                    .trim()
+                    .trim_matches(|c| c == '"' || c == '\'')
+                    .to_string(),
+            )
+        } else {
+            None
+        }
+    })
+}
+
+fn sniff_meta_charset(head: &[u8]) -> Option<String> {
+    let head = String::from_utf8_lossy(head);
+    let re = Regex::new(r#"(?i)<meta[^>]+charset\s*=\s*["']?\s*([a-z0-9_\-:.]+)"#).unwrap();
+    re.captures(&head).map(|caps| caps[1].to_string())
+}
+
+// 超过 max_chars 个字符时截断并在末尾加上提示
+pub fn truncate_chars(text: &str, max_chars: usize) -> String {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return text.to_string();
+    }
+    let truncated: String = text.chars().take(max_chars).collect();
+    format!(
+        "{}\n\n[内容过长已截断: 共 {} 个字符，保留前 {} 个字符]",
+        truncated.trim_end(),
+        total,
+        max_chars
+    )
+}
+
+pub fn extract(html: &str) -> Article {
+    let dom = Dom::parse(html);
+    let mut article = Article::default();
+    dom.read_metadata(&mut article);
+
+    let regexes = Regexes::new();
+    let body = dom.find_first(0, "body").unwrap_or(0);
+    let removed = dom.mark_removed(body, &regexes);
+    let counts = dom.count_text(&removed);
+    let content_node = dom
+        .top_candidate(body, &removed, &counts, &regexes)
+        .unwrap_or(body);
+
+    article.content = dom.serialize(content_node, &removed, &counts, &regexes);
+    article
+}
+
+struct Regexes {
+    unlikely: Regex,
+    maybe: Regex,
+    positive: Regex,
+    negative: Regex,
+}
+
+impl Regexes {
+    fn new() -> Self {
+        Regexes {
+            unlikely: Regex::new(UNLIKELY_CANDIDATES).unwrap(),
+            maybe: Regex::new(MAYBE_CANDIDATE).unwrap(),
+            positive: Regex::new(POSITIVE).unwrap(),
+            negative: Regex::new(NEGATIVE).unwrap(),
+        }
+    }
+}
+
+enum Node {
+    Element {
+        name: String,
+        attrs: Vec<(String, String)>,
+        children: Vec<usize>,
+        parent: usize,
+    },
+    Text(String),
+}
+
+// 每个节点(不含被去掉的子节点)的文本统计
+struct TextCounts {
+    // 去掉首尾空白、连续空白算一个字符后的长度
+    text: Vec<usize>,
+    // 其中链接文本的长度
+    links: Vec<usize>,
+    commas: Vec<usize>,
+}
+
+enum Step {
+    Enter(usize),
+    Exit(usize),
+}
+
+// html5ever 解析结果转换成的扁平节点树，节点 0 为根节点，节点按文档顺序编号，子节点的编号总是大于父节点
+struct Dom {
+    nodes: Vec<Node>,
+}
+
+impl Dom {
+    fn parse(html: &str) -> Dom {
+        let document = html5ever::parse_document(RcDom::default(), Default::default()).one(html);
+        let mut dom = Dom {
+            nodes: vec![Node::Element {
+                name: "#root".to_string(),
+                attrs: vec![],
+                children: vec![],
+                parent: 0,
+            }],
+        };
+
+        let mut stack: Vec<(Handle, usize)> = document
+            .document
+            .children
+            .borrow()
+            .iter()
+            .rev()
+            .map(|child| (child.clone(), 0))
+            .collect();
+        while let Some((handle, parent)) = stack.pop() {
+            let index = match &handle.data {
+                NodeData::Text { contents } => {
+                    dom.push(parent, Node::Text(contents.borrow().to_string()));
+                    continue;
+                }
+                NodeData::Element { name, attrs, .. } => {
+                    let attrs = attrs
+                        .borrow()
+                        .iter()
+                        .map(|a| (a.name.local.to_string(), a.value.to_string()))
+                        .collect();
+                    dom.push(
+                        parent,
+                        Node::Element {
+                            name: name.local.to_string(),
+                            attrs,
+                            children: vec![],
+                            parent,
+                        },
+                    )
+                }
+                // 注释、doctype 等
+                _ => continue,
+            };
+            stack.extend(
+                handle
+                    .children
+                    .borrow()
+                    .iter()
+                    .rev()
+                    .map(|child| (child.clone(), index)),
+            );
+        }
+        dom
+    }
+
+    fn push(&mut self, parent: usize, node: Node) -> usize {
+        self.nodes.push(node);
+        let index = self.nodes.len() - 1;
+        if let Node::Element { children, .. } = &mut self.nodes[parent] {
+            children.push(index);
+        }
+        index
+    }
+
+    fn name(&self, node: usize) -> &str {
+        match &self.nodes[node] {
+            Node::Element { name, .. } => name,
+            Node::Text(_) => "#text",
+        }
+    }
+
+    fn children(&self, node: usize) -> &[usize] {
+        match &self.nodes[node] {
+            Node::Element { children, .. } => children,
+            Node::Text(_) => &[],
+        }
+    }
+
+    fn parent(&self, node: usize) -> Option<usize> {
+        match &self.nodes[node] {
+            Node::Element { parent, .. } if node != 0 => Some(*parent),
+            _ => None,
+        }
+    }
+
+    fn attr(&self, node: usize, key: &str) -> Option<&str> {
+        match &self.nodes[node] {
+            Node::Element { attrs, .. } => attrs
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str()),
+            Node::Text(_) => None,
+        }
+    }
+
+    fn class_and_id(&self, node: usize) -> String {
+        format!(
+            "{} {}",
+            self.attr(node, "class").unwrap_or(""),
+            self.attr(node, "id").unwrap_or("")
+        )
+    }
+
+    // 按文档顺序返回所有后代节点
+    fn descendants(&self, node: usize) -> Vec<usize> {
+        let mut result = vec![];
+        let mut stack: Vec<usize> = self.children(node).iter().rev().copied().collect();
+        while let Some(node) = stack.pop() {
+            result.push(node);
+            stack.extend(self.children(node).iter().rev());
+        }
+        result
+    }
+
+    fn find_first(&self, node: usize, name: &str) -> Option<usize> {
+        self.descendants(node)
+            .into_iter()
+            .find(|&n| self.name(n) == name)
+    }
+
+    fn text(&self, node: usize) -> String {
+        let mut result = String::new();
+        for node in std::iter::once(node).chain(self.descendants(node)) {
+            if let Node::Text(text) = &self.nodes[node] {
+                result.push_str(text);
+            }
+        }
+        result
+    }
+
+    // 倒序遍历时子节点总是先于父节点，一次遍历就能得到所有节点的统计
+    fn count_text(&self, removed: &[bool]) -> TextCounts {
+        let mut counts = TextCounts {
+            text: vec![0; self.nodes.len()],
+            links: vec![0; self.nodes.len()],
+            commas: vec![0; self.nodes.len()],
+        };
+        for node in (0..self.nodes.len()).rev() {
+            if removed[node] {
+                continue;
+            }
+            match &self.nodes[node] {
+                Node::Text(text) => {
+                    counts.text[node] = text
+                        .split_whitespace()
+                        .map(|w| w.chars().count() + 1)
+                        .sum::<usize>()
+                        .saturating_sub(1);
+                    counts.commas[node] =
+                        text.matches(|c| c == ',' || c == '，' || c == '、').count();
+                }
+                Node::Element { name, children, .. } => {
+                    for &child in children.iter().filter(|&&c| !removed[c]) {
+                        counts.text[node] += counts.text[child];
+                        counts.links[node] += counts.links[child];
+                        counts.commas[node] += counts.commas[child];
+                    }
+                    if name == "a" {
+                        counts.links[node] = counts.text[node];
+                    }
+                }
+            }
+        }
+        counts
+    }
+
+    fn link_density(&self, node: usize, counts: &TextCounts) -> f64 {
+        if counts.text[node] == 0 {
+            return 0.0;
+        }
+        counts.links[node] as f64 / counts.text[node] as f64
+    }
+
+    fn class_weight(&self, node: usize, regexes: &Regexes) -> f64 {
+        let mut weight = 0.0;
+        for value in [self.attr(node, "class"), self.attr(node, "id")]
+            .into_iter()
+            .flatten()
+        {
+            if regexes.negative.is_match(value) {
+                weight -= 25.0;
+            }
+            if regexes.positive.is_match(value) {
+                weight += 25.0;
+            }
+        }
+        weight
+    }
+
+    // 标记明显不是正文的节点，被标记节点的子元素也一起标记
+    fn mark_removed(&self, root: usize, regexes: &Regexes) -> Vec<bool> {
+        let mut removed = vec![false; self.nodes.len()];
+        for node in self.descendants(root) {
+            if let Some(parent) = self.parent(node) {
+                if removed[parent] {
+                    removed[node] = true;
+                    continue;
+                }
+            }
+            let name = self.name(node);
+            if name == "#text" || matches!(name, "body" | "article" | "main") {
+                continue;
+            }
+            let class_and_id = self.class_and_id(node);
+            let hidden = self.attr(node, "hidden").is_some()
+                || self.attr(node, "aria-hidden") == Some("true")
+                || self
+                    .attr(node, "style")
+                    .map(|s| s.replace(' ', "").contains("display:none"))
+                    .unwrap_or(false);
+            removed[node] = REMOVED_TAGS.contains(&name)
+                || hidden
+                || self.attr(node, "role").map_or(false, |r| {
+                    matches!(r, "navigation" | "banner" | "complementary" | "dialog")
+                })
+                || (regexes.unlikely.is_match(&class_and_id)
+                    && !regexes.maybe.is_match(&class_and_id)
+                    && !matches!(name, "table" | "tbody" | "tr" | "td" | "th" | "a"));
+        }
+        removed
+    }
+
+    fn top_candidate(
+        &self,
+        root: usize,
+        removed: &[bool],
+        counts: &TextCounts,
+        regexes: &Regexes,
+    ) -> Option<usize> {
+        let mut scores: Vec<Option<f64>> = vec![None; self.nodes.len()];
+
+        for node in self.descendants(root) {
+            if removed[node] {
+                continue;
+            }
+            let name = self.name(node);
+            // 没有块级子元素的 div 也当作段落
+            let is_paragraph = matches!(name, "p" | "pre" | "td" | "blockquote")
+                || (name == "div"
+                    && !self
+                        .children(node)
+                        .iter()
+                        .any(|&c| BLOCK_TAGS.contains(&self.name(c))));
+            if !is_paragraph {
+                continue;
+            }
+            let text_length = counts.text[node];
+            if text_length < 25 {
+                continue;
+            }
+
+            let score = 1.0 + counts.commas[node] as f64 + (text_length as f64 / 100.0).min(3.0);
+            let ancestors = [
+                self.parent(node),
+                self.parent(node).and_then(|p| self.parent(p)),
+            ];
+            for (level, ancestor) in ancestors.into_iter().enumerate() {
+                let ancestor = match ancestor {
+                    Some(a) if a != 0 => a,
+                    _ => break,
+                };
+                let current =
+                    scores[ancestor].unwrap_or_else(|| self.initial_score(ancestor, regexes));
+                let divider = if level == 0 { 1.0 } else { 2.0 };
+                scores[ancestor] = Some(current + score / divider);
+            }
+        }
+
+        let best = scores
+            .iter()
+            .enumerate()
+            .filter_map(|(node, score)| score.map(|s| (node, s)))
+            .map(|(node, score)| (node, score * (1.0 - self.link_density(node, counts))))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(node, _)| node)?;
+
+        // 正文只占页面很小一部分时说明打分不可靠，退回使用整个页面
+        let best_length = counts.text[best];
+        let root_length = counts.text[root];
+        if best_length < 500 && best_length * 4 < root_length {
+            return None;
+        }
+        Some(best)
+    }
+
+    fn initial_score(&self, node: usize, regexes: &Regexes) -> f64 {
+        let base = match self.name(node) {
+            "div" | "article" | "main" => 5.0,
+            "pre" | "td" | "blockquote" => 3.0,
+            "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+            _ => 0.0,
+        };
+        base + self.class_weight(node, regexes)
+    }
+
+    fn serialize(
+        &self,
+        node: usize,
+        removed: &[bool],
+        counts: &TextCounts,
+        regexes: &Regexes,
+    ) -> String {
+        let mut out = String::new();
+        let mut stack = vec![Step::Enter(node)];
+        while let Some(step) = stack.pop() {
+            let node = match step {
+                Step::Enter(node) => node,
+                Step::Exit(node) => {
+                    out.push_str(&format!("</{}>", self.name(node)));
+                    continue;
+                }
+            };
+            let (name, attrs, children) = match &self.nodes[node] {
+                Node::Text(text) => {
+                    push_escaped(&mut out, text, false);
+                    continue;
+                }
+                Node::Element {
+                    name,
+                    attrs,
+                    children,
+                    ..
+                } => (name, attrs, children),
+            };
+
+            // 正文中链接占比很高的列表、表格一般是相关推荐或目录
+            let link_heavy = matches!(name.as_str(), "ul" | "ol" | "div" | "table" | "section")
+                && self.link_density(node, counts) > 0.5
+                && self.class_weight(node, regexes) <= 0.0;
+            if link_heavy || self.class_weight(node, regexes) < 0.0 {
+                continue;
+            }
+            let is_root = name == "#root";
+            if !is_root {
+                out.push('<');
+                out.push_str(name);
+                for (key, value) in attrs {
+                    if KEPT_ATTRS.contains(&key.as_str()) {
+                        out.push_str(&format!(" {}=\"", key));
+                        push_escaped(&mut out, value, true);
+                        out.push('"');
+                    }
+                }
+                out.push('>');
+            }
+            if VOID_TAGS.contains(&name.as_str()) {
+                continue;
+            }
+            if !is_root {
+                stack.push(Step::Exit(node));
+            }
+            stack.extend(
+                children
+                    .iter()
+                    .rev()
+                    .filter(|&&c| !removed[c])
+                    .map(|&c| Step::Enter(c)),
+            );
+        }
+        out
+    }
+
+    fn read_metadata(&self, article: &mut Article) {
+        let mut metas: Vec<(String, String)> = vec![];
+        let all = self.descendants(0);
+        for &node in all.iter() {
+            if self.name(node) != "meta" {
+                continue;
+            }
+            let key = self
+                .attr(node, "property")
+                .or_else(|| self.attr(node, "name"))
+                .or_else(|| self.attr(node, "itemprop"));
+            if let (Some(key), Some(content)) = (key, self.attr(node, "content")) {
+                let content = content.trim().to_string();
+                if !content.is_empty() {
+                    metas.push((key.to_ascii_lowercase(), content));
+                }
+            }
+        }
+        let meta = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| metas.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone()))
+        };
+
+        article.title = meta(&["og:title", "twitter:title", "dc.title"]).or_else(|| {
+            let title = self.find_first(0, "title")?;
+            Some(self.text(title).trim().to_string()).filter(|t| !t.is_empty())
+        });
+        article.author = meta(&["author", "article:author", "dc.creator", "byl"])
+            .filter(|a| !a.starts_with("http"));
+        article.published = meta(&[
+            "article:published_time",
+            "og:published_time",
+            "datepublished",
+            "pubdate",
+            "publishdate",
+            "dc.date.issued",
+            "date",
+        ])
+        .or_else(|| {
+            all.iter()
+                .filter(|&&n| self.name(n) == "time")
+                .find_map(|&n| self.attr(n, "datetime").map(|d| d.to_string()))
+        });
+    }
+}
+
+// html5ever 解析出的文本已经解码了实体，重新输出为 html 时需要转义
+fn push_escaped(out: &mut String, text: &str, attribute: bool) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' if !attribute => out.push_str("&lt;"),
+            '>' if !attribute => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+}
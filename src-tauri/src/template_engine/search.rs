@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use futures::future::{join_all, BoxFuture};
+use futures::FutureExt;
+use reqwest::Url;
+use serde_json::{json, Value};
+
+use super::custom_bang::extract_json_path;
+use super::fetch::Fetcher;
+use super::{fetch_markdown, readability, BangArgs, BangResult, TemplateEngine};
+
+const DEFAULT_RESULT_COUNT: usize = 3;
+
+// !search 使用的搜索服务配置，通过 feature_code 为 bang 的配置修改
+#[derive(Debug, Clone)]
+pub struct SearchConfig {
+    // searxng、brave、bing、tavily
+    pub engine: String,
+    // 搜索接口地址，searxng 必填，其他服务不填时使用官方地址
+    pub url: String,
+    pub api_key: String,
+    // 读取正文的结果条数
+    pub result_count: usize,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            engine: "searxng".to_string(),
+            url: String::new(),
+            api_key: String::new(),
+            result_count: DEFAULT_RESULT_COUNT,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+// 搜索并读取前几条结果的正文，带上来源地址方便回答时引用
+pub fn search(
+    engine: TemplateEngine,
+    args: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
+    async move {
+        let query = args.get(0, "query").cloned().unwrap_or_default();
+        let query = query.trim();
+        if query.is_empty() {
+            return BangResult::Text(String::new());
+        }
+
+        let config = &engine.search;
+        let results = match search_results(&engine.fetcher, config, query).await {
+            Ok(results) => results,
+            Err(e) => return BangResult::Error(format!("搜索失败: {}", e)),
+        };
+        if results.is_empty() {
+            return BangResult::Text(format!(
+                "\n<bangsearch query=\"{}\">\n没有找到相关结果\n</bangsearch>",
+                query
+            ));
+        }
+
+        // 正文按结果条数平分最大字符数
+        let max_chars = (engine.web_max_chars / results.len()).max(1);
+        let pages = join_all(results.iter().map(|result| async {
+            match fetch_markdown(&engine.fetcher, &result.url).await {
+                Ok((_, _, markdown)) if !markdown.is_empty() => {
+                    readability::truncate_chars(&markdown, max_chars)
+                }
+                // 读取失败时退回使用搜索结果的摘要
+                _ => result.snippet.clone(),
+            }
+        }))
+        .await;
+
+        let content = results
+            .iter()
+            .zip(pages)
+            .enumerate()
+            .map(|(index, (result, page))| {
+                format!(
+                    "<result index=\"{}\" title=\"{}\" url=\"{}\">\n{}\n</result>",
+                    index + 1,
+                    result.title.replace('"', "'"),
+                    result.url,
+                    page
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        BangResult::Text(format!(
+            "\n<bangsearch query=\"{}\">\n{}\n</bangsearch>",
+            query.replace('"', "'"),
+            content
+        ))
+    }
+    .boxed()
+}
+
+pub async fn search_results(
+    fetcher: &Fetcher,
+    config: &SearchConfig,
+    query: &str,
+) -> Result<Vec<SearchResult>, String> {
+    let count = config.result_count.to_string();
+    let api_key = config.api_key.as_str();
+    let endpoint = |default: &str| {
+        if config.url.trim().is_empty() {
+            default.to_string()
+        } else {
+            config.url.trim().to_string()
+        }
+    };
+    let build_url = |base: String, params: &[(&str, &str)]| {
+        Url::parse_with_params(&base, params)
+            .map(|u| u.to_string())
+            .map_err(|_| format!("搜索地址不正确: {}", base))
+    };
+
+    // 每种服务的结果列表路径以及标题、地址、摘要字段名
+    let (response, list_path, fields) = match config.engine.as_str() {
+        "searxng" => {
+            if config.url.trim().is_empty() {
+                return Err("没有配置 SearxNG 的地址".to_string());
+            }
+            let base = format!("{}/search", config.url.trim().trim_end_matches('/'));
+            let url = build_url(base, &[("q", query), ("format", "json")])?;
+            let response = fetcher.fetch_json(&url, &[], None).await;
+            (response, "results", ("title", "url", "content"))
+        }
+        "brave" => {
+            let base = endpoint("https://api.search.brave.com/res/v1/web/search");
+            let url = build_url(base, &[("q", query), ("count", &count)])?;
+            let response = fetcher
+                .fetch_json(&url, &[("X-Subscription-Token", api_key)], None)
+                .await;
+            (response, "web.results", ("title", "url", "description"))
+        }
+        "bing" => {
+            let base = endpoint("https://api.bing.microsoft.com/v7.0/search");
+            let url = build_url(base, &[("q", query), ("count", &count)])?;
+            let response = fetcher
+                .fetch_json(&url, &[("Ocp-Apim-Subscription-Key", api_key)], None)
+                .await;
+            (response, "webPages.value", ("name", "url", "snippet"))
+        }
+        "tavily" => {
+            let url = endpoint("https://api.tavily.com/search");
+            let body = json!({
+                "api_key": api_key,
+                "query": query,
+                "max_results": config.result_count,
+            });
+            let response = fetcher.fetch_json(&url, &[], Some(&body)).await;
+            (response, "results", ("title", "url", "content"))
+        }
+        other => return Err(format!("未知的搜索服务: {}", other)),
+    };
+    let response = response.map_err(|e| e.to_string())?;
+
+    let (title, url, snippet) = fields;
+    let text = |item: &Value, key: &str| {
+        item.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let results = extract_json_path(&response, list_path)
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .map(|item| SearchResult {
+                    title: text(item, title),
+                    url: text(item, url),
+                    snippet: text(item, snippet),
+                })
+                .filter(|r| !r.url.is_empty())
+                .take(config.result_count)
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(results)
+}
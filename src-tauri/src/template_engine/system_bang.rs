@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use base64::Engine;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use image::{ImageFormat, RgbaImage};
+use screenshots::Screen;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tokio::io::AsyncReadExt;
+use tokio::process::{Child, Command};
+use tokio::time::timeout;
+
+use super::{BangArgs, BangResult, TemplateEngine};
+
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+// 读取文件的大小上限，避免把大文件整个塞进提示词
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+// 命令输出的大小上限，超过时结束进程
+const MAX_OUTPUT_BYTES: u64 = 1024 * 1024;
+
+// 失败的结果作为 Error 返回，不会被缓存
+fn into_bang_result(result: Result<String, String>) -> BangResult {
+    result.map_or_else(BangResult::Error, BangResult::Text)
+}
+
+// 读取剪贴板中的文本
+pub fn clipboard(
+    engine: TemplateEngine,
+    _: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
+    async move {
+        match &engine.app_handle {
+            Some(app_handle) => app_handle.clipboard().read_text().unwrap_or_default(),
+            None => String::new(),
+        }
+    }
+    .map(BangResult::Text)
+    .boxed()
+}
+
+// 读取剪贴板中的图片，作为图片附件发送
+pub fn clipboard_image(
+    engine: TemplateEngine,
+    _: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
+    async move {
+        let app_handle = match &engine.app_handle {
+            Some(app_handle) => app_handle,
+            None => return BangResult::Text(String::new()),
+        };
+        let image = match app_handle.clipboard().read_image() {
+            Ok(image) => image,
+            Err(_) => return BangResult::Text(String::new()),
+        };
+        match encode_png_data_url(image.width(), image.height(), image.rgba().to_vec()) {
+            Ok(data_url) => BangResult::Image(data_url),
+            Err(e) => BangResult::Error(e),
+        }
+    }
+    .boxed()
+}
+
+// 截取主屏幕，作为图片附件发送
+pub fn screenshot(
+    _: TemplateEngine,
+    _: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
+    async move {
+        let result = tokio::task::spawn_blocking(|| {
+            let screens = Screen::all().map_err(|e| format!("截图失败: {}", e))?;
+            let screen = screens
+                .iter()
+                .find(|s| s.display_info.is_primary)
+                .or_else(|| screens.first())
+                .ok_or("截图失败: 没有找到屏幕".to_string())?;
+            let image = screen.capture().map_err(|e| format!("截图失败: {}", e))?;
+            encode_png_data_url(image.width(), image.height(), image.into_raw())
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("截图失败: {}", e)));
+
+        match result {
+            Ok(data_url) => BangResult::Image(data_url),
+            Err(e) => BangResult::Error(e),
+        }
+    }
+    .boxed()
+}
+
+pub fn encode_png_data_url(width: u32, height: u32, rgba: Vec<u8>) -> Result<String, String> {
+    let image = RgbaImage::from_raw(width, height, rgba).ok_or("图片数据不完整".to_string())?;
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|e| format!("图片编码失败: {}", e))?;
+    Ok(format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(png.into_inner())
+    ))
+}
+
+// 读取文件内容，可以指定起止行号(从1开始，包含结束行): !file(path, 10, 20) 或 !file(path, start=10, end=20)，
+// 只能读取 file_allowlist 中的目录
+pub fn file(
+    engine: TemplateEngine,
+    args: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
+    async move {
+        let path = args.get(0, "path").cloned().unwrap_or_default();
+        let start = args
+            .get(1, "start")
+            .and_then(|s| s.trim().parse::<usize>().ok());
+        let end = args
+            .get(2, "end")
+            .and_then(|s| s.trim().parse::<usize>().ok());
+
+        let allowed = check_path_allowed(&engine.file_allowlist, &path).await?;
+        let content = read_file_lines(&allowed.to_string_lossy(), start, end).await?;
+        let lines = match (start, end) {
+            (None, None) => String::new(),
+            _ => format!(
+                " lines=\"{}-{}\"",
+                start.unwrap_or(1),
+                end.map(|e| e.to_string()).unwrap_or_default()
+            ),
+        };
+        Ok(format!(
+            "\n<bangfile path=\"{}\"{}>\n{}\n</bangfile>",
+            path, lines, content
+        ))
+    }
+    .map(into_bang_result)
+    .boxed()
+}
+
+// 检查路径是否位于 file_allowlist 配置的目录下，先规范化路径，避免通过 .. 或符号链接绕过
+pub async fn check_path_allowed(allowlist: &[String], path: &str) -> Result<PathBuf, String> {
+    let path = tokio::fs::canonicalize(path.trim())
+        .await
+        .map_err(|e| format!("无法访问 {}: {}", path.trim(), e))?;
+    for allowed in allowlist {
+        if let Ok(allowed) = tokio::fs::canonicalize(allowed).await {
+            if path.starts_with(&allowed) {
+                return Ok(path);
+            }
+        }
+    }
+    Err(format!(
+        "{} 不在允许读取的目录中，可以在 Bang 配置中添加",
+        path.display()
+    ))
+}
+
+pub async fn read_file_lines(
+    path: &str,
+    start: Option<usize>,
+    end: Option<usize>,
+) -> Result<String, String> {
+    let metadata = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| format!("读取文件失败: {}", e))?;
+    if metadata.len() > MAX_FILE_BYTES {
+        return Err(format!(
+            "文件过大({} 字节)，超过上限 {} 字节",
+            metadata.len(),
+            MAX_FILE_BYTES
+        ));
+    }
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("读取文件失败: {}", e))?;
+    if start.is_none() && end.is_none() {
+        return Ok(content);
+    }
+
+    let start = start.unwrap_or(1).max(1);
+    let end = end.unwrap_or(usize::MAX);
+    Ok(content
+        .lines()
+        .skip(start - 1)
+        .take(end.saturating_sub(start - 1))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+// 列出目录下的文件，目录以 / 结尾，只能列出 file_allowlist 中的目录
+pub fn dir(
+    engine: TemplateEngine,
+    args: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
+    async move {
+        let path = args.get(0, "path").cloned().unwrap_or_default();
+        let allowed = check_path_allowed(&engine.file_allowlist, &path).await?;
+        let entries = list_dir(&allowed)
+            .await
+            .map_err(|e| format!("读取目录失败: {}", e))?;
+        Ok(format!(
+            "\n<bangdir path=\"{}\">\n{}\n</bangdir>",
+            path,
+            entries.join("\n")
+        ))
+    }
+    .map(into_bang_result)
+    .boxed()
+}
+
+async fn list_dir(path: &Path) -> std::io::Result<Vec<String>> {
+    let mut read_dir = tokio::fs::read_dir(path).await?;
+    let mut entries = Vec::new();
+    while let Some(entry) = read_dir.next_entry().await? {
+        let mut name = entry.file_name().to_string_lossy().to_string();
+        if entry.file_type().await?.is_dir() {
+            name.push('/');
+        }
+        entries.push(name);
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+// 读取环境变量，只能读取 env_allowlist 中的变量，避免 API Key 等被发送给大模型
+pub fn env(
+    engine: TemplateEngine,
+    args: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
+    async move {
+        let name = args.get(0, "name").cloned().unwrap_or_default();
+        let name = name.trim();
+        if !engine.env_allowlist.iter().any(|n| n == name) {
+            return Err(format!("环境变量 {} 不在允许读取的列表中", name));
+        }
+        Ok(std::env::var(name).unwrap_or_default())
+    }
+    .map(into_bang_result)
+    .boxed()
+}
+
+// 执行命令并返回输出，只允许执行配置在 shell_allowlist 中的程序，且不经过 shell 解释
+pub fn shell(
+    engine: TemplateEngine,
+    args: BangArgs,
+    _: HashMap<String, String>,
+) -> BoxFuture<'static, BangResult> {
+    async move {
+        let command_line = args.get(0, "cmd").cloned().unwrap_or_default();
+        let parts = split_command(&command_line);
+        let (program, program_args) = match parts.split_first() {
+            Some(parts) => parts,
+            None => return Ok(String::new()),
+        };
+        if !engine.shell_allowlist.iter().any(|p| p == program) {
+            return Err(format!("命令 {} 不在允许执行的列表中", program));
+        }
+
+        let mut cmd = Command::new(program);
+        cmd.args(program_args);
+        run_process(cmd, &command_line).await
+    }
+    .map(into_bang_result)
+    .boxed()
+}
+
+// 按空白拆分命令行，支持单引号和双引号包裹含空格的参数
+pub fn split_command(command_line: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut has_part = false;
+    let mut quote = None;
+
+    for c in command_line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                has_part = true;
+            }
+            None if c.is_whitespace() => {
+                if has_part {
+                    parts.push(std::mem::take(&mut current));
+                    has_part = false;
+                }
+            }
+            None => {
+                current.push(c);
+                has_part = true;
+            }
+        }
+    }
+    if has_part {
+        parts.push(current);
+    }
+    parts
+}
+
+// 执行进程并返回去掉末尾空白的 stdout，失败时返回带 stderr 的错误信息
+pub async fn run_process(mut cmd: Command, command_line: &str) -> Result<String, String> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = cmd.spawn().map_err(|e| format!("命令执行失败: {}", e))?;
+
+    // 超时后 child 被丢弃，进程随之结束
+    timeout(COMMAND_TIMEOUT, collect_output(&mut child))
+        .await
+        .map_err(|_| format!("命令执行超时: {}", command_line))?
+}
+
+// 同时读取 stdout 和 stderr，stdout 超过上限时结束进程，不把全部输出读进内存
+async fn collect_output(child: &mut Child) -> Result<String, String> {
+    let (Some(mut stdout), Some(mut stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        return Err("命令执行失败: 无法读取输出".to_string());
+    };
+    let read_stdout = async {
+        let mut buf = Vec::new();
+        let result = (&mut stdout)
+            .take(MAX_OUTPUT_BYTES + 1)
+            .read_to_end(&mut buf)
+            .await;
+        if buf.len() as u64 > MAX_OUTPUT_BYTES {
+            let _ = child.start_kill();
+        }
+        result.map(|_| buf)
+    };
+    // stderr 只保留开头部分，其余读出后丢弃，避免进程写满管道后阻塞
+    let read_stderr = async {
+        let mut buf = Vec::new();
+        (&mut stderr)
+            .take(MAX_OUTPUT_BYTES)
+            .read_to_end(&mut buf)
+            .await?;
+        tokio::io::copy(&mut stderr, &mut tokio::io::sink()).await?;
+        Ok::<_, std::io::Error>(buf)
+    };
+    let (stdout, stderr) = tokio::join!(read_stdout, read_stderr);
+    let stdout = stdout.map_err(|e| format!("命令执行失败: {}", e))?;
+    let stderr = stderr.map_err(|e| format!("命令执行失败: {}", e))?;
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("命令执行失败: {}", e))?;
+
+    if stdout.len() as u64 > MAX_OUTPUT_BYTES {
+        return Err(format!("命令输出超过上限 {} 字节", MAX_OUTPUT_BYTES));
+    }
+    if !status.success() {
+        return Err(format!(
+            "命令执行失败({}): {}",
+            status,
+            String::from_utf8_lossy(&stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&stdout).trim_end().to_string())
+}
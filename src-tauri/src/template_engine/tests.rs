@@ -1,4 +1,5 @@
 use super::*;
+use crate::db::system_db::CustomBang;
 use chrono::Local;
 use mockito::mock;
 
@@ -121,7 +122,7 @@ async fn test_web_command() {
         }
     }
 
-    let engine = TemplateEngine::new();
+    let engine = local_engine();
     let context = HashMap::new();
 
     let except_result = format!(
@@ -169,7 +170,7 @@ async fn test_web_to_markdown_command() {
         }
     }
 
-    let engine = TemplateEngine::new();
+    let engine = local_engine();
     let context = HashMap::new();
 
     let except_result = format!(
@@ -193,3 +194,805 @@ async fn test_web_to_markdown_command() {
 
     mock.assert();
 }
+
+// mock server 在本机，测试中需要允许访问内网地址
+fn allow_private_config() -> FeatureConfig {
+    FeatureConfig {
+        id: None,
+        feature_code: "bang".to_string(),
+        key: "web_block_private".to_string(),
+        value: "false".to_string(),
+        data_type: "string".to_string(),
+        description: None,
+    }
+}
+
+fn local_engine() -> TemplateEngine {
+    let mut template_engine = TemplateEngine::new();
+    template_engine
+        .apply_feature_config(&[allow_private_config()])
+        .unwrap();
+    template_engine
+}
+
+fn custom_bang(name: &str, kind: &str, body: &str, json_path: Option<&str>) -> CustomBang {
+    CustomBang {
+        id: 1,
+        name: name.to_string(),
+        aliases: vec![],
+        description: "test".to_string(),
+        bang_kind: kind.to_string(),
+        body: body.to_string(),
+        json_path: json_path.map(|p| p.to_string()),
+    }
+}
+
+#[tokio::test]
+async fn test_custom_text_bang() {
+    let mut template_engine = TemplateEngine::new();
+    let mut bang = custom_bang("translate", "text", "请将以下内容翻译成英文: !input", None);
+    bang.aliases = vec!["tr".to_string()];
+    template_engine.register_custom_bangs(&[bang]);
+
+    let mut context = HashMap::new();
+    context.insert("selected_text".to_string(), "你好".to_string());
+    let result = template_engine.parse("!translate(!s)", &context).await;
+    println!("result : {}", result);
+    assert_eq!(result, "请将以下内容翻译成英文: 你好");
+
+    let result2 = template_engine.parse("!tr(世界)", &context).await;
+    println!("result2 : {}", result2);
+    assert_eq!(result2, "请将以下内容翻译成英文: 世界");
+}
+
+#[tokio::test]
+async fn test_custom_bang_does_not_override_builtin() {
+    let mut template_engine = TemplateEngine::new();
+    template_engine.register_custom_bangs(&[custom_bang("s", "text", "custom", None)]);
+
+    let mut context = HashMap::new();
+    context.insert("selected_text".to_string(), "test".to_string());
+    let result = template_engine.parse("!s", &context).await;
+    assert_eq!(result, "test");
+}
+
+#[tokio::test]
+async fn test_custom_bang_cycle_is_limited() {
+    let mut template_engine = TemplateEngine::new();
+    template_engine.register_custom_bangs(&[
+        custom_bang("ping", "text", "!pong", None),
+        custom_bang("pong", "text", "!ping", None),
+    ]);
+
+    let result = template_engine.parse("!ping", &HashMap::new()).await;
+    println!("result : {}", result);
+    assert!(result.contains("嵌套超过"));
+}
+
+#[tokio::test]
+async fn test_custom_http_bang_with_json_path() {
+    let mock = mock("GET", "/custom_bang")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"data":{"items":[{"name":"first"},{"name":"second"}]}}"#)
+        .create();
+
+    let mut template_engine = local_engine();
+    template_engine.register_custom_bangs(&[custom_bang(
+        "item",
+        "http",
+        &format!("{}/custom_bang", mockito::server_url()),
+        Some("data.items[1].name"),
+    )]);
+
+    let result = template_engine.parse("!item", &HashMap::new()).await;
+    println!("result : {}", result);
+    assert_eq!(result, "second");
+
+    mock.assert();
+}
+
+#[cfg(not(target_os = "windows"))]
+#[tokio::test]
+async fn test_custom_command_bang_does_not_use_shell() {
+    let mut template_engine = TemplateEngine::new();
+    template_engine.register_custom_bangs(&[custom_bang("say", "command", "echo !input", None)]);
+
+    let mut context = HashMap::new();
+    context.insert("selected_text".to_string(), "$(echo pwned); ls".to_string());
+    let result = template_engine.parse("!say(!s)", &context).await;
+    assert_eq!(result, "$(echo pwned); ls");
+}
+
+#[tokio::test]
+async fn test_custom_file_bang_allowlist() {
+    let dir = std::env::temp_dir().join(format!("aipp_custom_bang_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file_path = dir.join("notes.txt");
+    std::fs::write(&file_path, "custom file").unwrap();
+
+    let mut template_engine = TemplateEngine::new();
+    template_engine.register_custom_bangs(&[custom_bang("notes", "file", "!input", None)]);
+    let template = format!("!notes(\"{}\")", file_path.to_string_lossy());
+
+    let result = template_engine.parse(&template, &HashMap::new()).await;
+    assert!(result.contains("不在允许读取的目录中"));
+
+    template_engine
+        .apply_feature_config(&[FeatureConfig {
+            id: None,
+            feature_code: "bang".to_string(),
+            key: "file_allowlist".to_string(),
+            value: dir.to_string_lossy().to_string(),
+            data_type: "string".to_string(),
+            description: None,
+        }])
+        .unwrap();
+    let result = template_engine.parse(&template, &HashMap::new()).await;
+    assert_eq!(result, "custom file");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_parse_quoted_and_named_args() {
+    let template_engine = TemplateEngine::new();
+    let result = template_engine
+        .parse(r#"!sub_start("a,b,c,d", 3)"#, &HashMap::new())
+        .await;
+    println!("result : {}", result);
+    assert_eq!(result, "a,b");
+
+    let result2 = template_engine
+        .parse("!sub_start(length=2, text='hello')", &HashMap::new())
+        .await;
+    println!("result2 : {}", result2);
+    assert_eq!(result2, "he");
+
+    let result3 = template_engine
+        .parse(r#"!sub_start("say \"hi\"", 6)"#, &HashMap::new())
+        .await;
+    println!("result3 : {}", result3);
+    assert_eq!(result3, "say \"h");
+}
+
+#[tokio::test]
+async fn test_parse_escape_and_unknown_bang() {
+    let template_engine = TemplateEngine::new();
+    let mut context = HashMap::new();
+    context.insert("selected_text".to_string(), "test".to_string());
+
+    let result = template_engine
+        .parse(r"\!s is !s, hello! !unknown(1", &context)
+        .await;
+    println!("result : {}", result);
+    assert_eq!(result, "!s is test, hello! !unknown(1");
+}
+
+#[tokio::test]
+async fn test_parse_deep_nesting() {
+    let template_engine = TemplateEngine::new();
+    let mut template = "abcdefghijklmnop".to_string();
+    for _ in 0..15 {
+        template = format!("!sub_start({},15)", template);
+    }
+    let result = template_engine.parse(&template, &HashMap::new()).await;
+    assert_eq!(result, "abcdefghijklmno");
+}
+
+#[tokio::test]
+async fn test_parse_identical_bangs_evaluated_separately() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let mut template_engine = TemplateEngine::new();
+    template_engine.register_command(
+        "counter",
+        Arc::new(|_, _, _| {
+            async { COUNTER.fetch_add(1, Ordering::SeqCst).to_string().into() }.boxed()
+        }),
+    );
+    let result = template_engine
+        .parse("!counter !counter !counter", &HashMap::new())
+        .await;
+    println!("result : {}", result);
+    assert_eq!(result, "0 1 2");
+}
+
+#[tokio::test]
+async fn test_try_parse_reports_position() {
+    let template_engine = TemplateEngine::new();
+    let error = template_engine
+        .try_parse("line1\nsee !sub_start(abc, 2", &HashMap::new())
+        .await
+        .unwrap_err();
+    println!("error : {}", error);
+    assert_eq!(error.line, 2);
+    assert_eq!(error.column, 15);
+
+    let error = template_engine
+        .try_parse(r#"!sub_start("abc, 2)"#, &HashMap::new())
+        .await
+        .unwrap_err();
+    println!("error : {}", error);
+    assert_eq!(error.position, 11);
+}
+
+#[tokio::test]
+async fn test_render_falls_back_to_raw_text() {
+    let template_engine = TemplateEngine::new();
+    let template = "用法是 !sub_start(abc, 2";
+    let result = template_engine.render(template, &HashMap::new()).await;
+    assert_eq!(result.text, template);
+    assert!(result.attachments.is_empty());
+}
+
+#[tokio::test]
+async fn test_bangs_evaluated_concurrently() {
+    let mut template_engine = TemplateEngine::new();
+    template_engine.register_command(
+        "slow",
+        Arc::new(|_, args, _| {
+            async move {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                args.positional.join(",").into()
+            }
+            .boxed()
+        }),
+    );
+
+    let start = std::time::Instant::now();
+    let result = template_engine
+        .parse("!slow(a) !slow(b) !slow(!slow(c))", &HashMap::new())
+        .await;
+    assert_eq!(result, "a b c");
+    // 同层级并发执行，嵌套的bang需要等参数求值，总耗时约为两层
+    assert!(start.elapsed() < std::time::Duration::from_millis(550));
+}
+
+#[tokio::test]
+async fn test_bang_timeout() {
+    let mut template_engine = TemplateEngine::new();
+    template_engine.set_bang_timeout(std::time::Duration::from_millis(50));
+    template_engine.register_command(
+        "hang",
+        Arc::new(|_, _, _| {
+            async {
+                tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                "never".to_string().into()
+            }
+            .boxed()
+        }),
+    );
+
+    let result = template_engine
+        .parse("before !hang after !cd", &HashMap::new())
+        .await;
+    let current_date = Local::now().format("%Y-%m-%d").to_string();
+    assert_eq!(
+        result,
+        format!("before [!hang 执行超时] after {}", current_date)
+    );
+}
+
+#[tokio::test]
+async fn test_web_result_cached() {
+    let _m = mock("GET", "/cached_page")
+        .with_status(200)
+        .with_body("cached body")
+        .expect(1)
+        .create();
+
+    let template_engine = local_engine();
+    let url = format!("{}/cached_page", mockito::server_url());
+    let template = format!("!web({}) !web({})", url, url);
+
+    let first = template_engine.parse(&template, &HashMap::new()).await;
+    // clone 出来的引擎共享缓存
+    let second = template_engine
+        .clone()
+        .parse(&template, &HashMap::new())
+        .await;
+    assert_eq!(first, second);
+    assert!(first.contains("cached body"));
+    _m.assert();
+
+    template_engine.clear_cache();
+    let _m2 = mock("GET", "/cached_page")
+        .with_status(200)
+        .with_body("fresh body")
+        .expect(1)
+        .create();
+    let third = template_engine.parse(&template, &HashMap::new()).await;
+    assert!(third.contains("fresh body"));
+    _m2.assert();
+}
+
+#[tokio::test]
+async fn test_web_failure_not_cached() {
+    let _m = mock("GET", "/flaky_page")
+        .with_status(500)
+        .expect(1)
+        .create();
+
+    let template_engine = local_engine();
+    let template = format!("!web({}/flaky_page)", mockito::server_url());
+
+    let first = template_engine.parse(&template, &HashMap::new()).await;
+    assert!(first.starts_with("获取网页失败"));
+    _m.assert();
+
+    let _m2 = mock("GET", "/flaky_page")
+        .with_status(200)
+        .with_body("recovered body")
+        .expect(1)
+        .create();
+    let second = template_engine.parse(&template, &HashMap::new()).await;
+    assert!(second.contains("recovered body"));
+    _m2.assert();
+}
+
+#[tokio::test]
+async fn test_file_and_dir_bangs() {
+    let dir = std::env::temp_dir().join(format!("aipp_bang_test_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    let file_path = dir.join("notes.txt");
+    std::fs::write(&file_path, "line1\nline2\nline3\nline4\n").unwrap();
+    let file_path = file_path.to_string_lossy().to_string();
+    let dir_path = dir.to_string_lossy().to_string();
+
+    let mut template_engine = TemplateEngine::new();
+    let context = HashMap::new();
+
+    // 没有配置允许的目录时不能读取
+    let result = template_engine
+        .parse(&format!("!file(\"{}\")", file_path), &context)
+        .await;
+    assert!(result.contains("不在允许读取的目录中"));
+    let result = template_engine
+        .parse(&format!("!dir(\"{}\")", dir_path), &context)
+        .await;
+    assert!(result.contains("不在允许读取的目录中"));
+
+    template_engine
+        .apply_feature_config(&[FeatureConfig {
+            id: None,
+            feature_code: "bang".to_string(),
+            key: "file_allowlist".to_string(),
+            value: dir_path.clone(),
+            data_type: "string".to_string(),
+            description: None,
+        }])
+        .unwrap();
+    let result = template_engine
+        .parse(&format!("!file(\"{}\", 2, 3)", file_path), &context)
+        .await;
+    assert_eq!(
+        result,
+        format!(
+            "\n<bangfile path=\"{}\" lines=\"2-3\">\nline2\nline3\n</bangfile>",
+            file_path
+        )
+    );
+
+    let result = template_engine
+        .parse(&format!("!file(path=\"{}\", start=4)", file_path), &context)
+        .await;
+    assert!(result.contains("lines=\"4-\">\nline4\n</bangfile>"));
+
+    let result = template_engine
+        .parse(&format!("!dir(\"{}\")", dir_path), &context)
+        .await;
+    assert_eq!(
+        result,
+        format!(
+            "\n<bangdir path=\"{}\">\nnotes.txt\nsub/\n</bangdir>",
+            dir_path
+        )
+    );
+    // 通过 .. 跳出允许的目录
+    let result = template_engine
+        .parse(&format!("!dir(\"{}/sub/../..\")", dir_path), &context)
+        .await;
+    assert!(result.contains("不在允许读取的目录中"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_env_bang() {
+    std::env::set_var("AIPP_BANG_TEST_ENV", "hello env");
+    let mut template_engine = TemplateEngine::new();
+    let result = template_engine
+        .parse("!env(AIPP_BANG_TEST_ENV)", &HashMap::new())
+        .await;
+    assert_eq!(result, "环境变量 AIPP_BANG_TEST_ENV 不在允许读取的列表中");
+
+    template_engine
+        .apply_feature_config(&[FeatureConfig {
+            id: None,
+            feature_code: "bang".to_string(),
+            key: "env_allowlist".to_string(),
+            value: "AIPP_BANG_TEST_ENV, AIPP_BANG_TEST_MISSING".to_string(),
+            data_type: "string".to_string(),
+            description: None,
+        }])
+        .unwrap();
+    let result = template_engine
+        .parse(
+            "!env(AIPP_BANG_TEST_ENV)|!env(AIPP_BANG_TEST_MISSING)",
+            &HashMap::new(),
+        )
+        .await;
+    assert_eq!(result, "hello env|");
+}
+
+#[cfg(not(target_os = "windows"))]
+#[tokio::test]
+async fn test_shell_bang_allowlist() {
+    let mut template_engine = TemplateEngine::new();
+    let context = HashMap::new();
+
+    let result = template_engine.parse("!shell(echo hi)", &context).await;
+    assert_eq!(result, "命令 echo 不在允许执行的列表中");
+
+    template_engine
+        .apply_feature_config(&[FeatureConfig {
+            id: None,
+            feature_code: "bang".to_string(),
+            key: "shell_allowlist".to_string(),
+            value: "echo, ls".to_string(),
+            data_type: "string".to_string(),
+            description: None,
+        }])
+        .unwrap();
+    // 不经过 shell，; 只是 echo 的普通参数
+    let result = template_engine
+        .parse("!shell(\"echo 'a  b'; rm -rf x\")", &context)
+        .await;
+    assert_eq!(result, "a  b; rm -rf x");
+}
+
+#[cfg(not(target_os = "windows"))]
+#[tokio::test]
+async fn test_shell_bang_output_limit() {
+    let mut template_engine = TemplateEngine::new();
+    template_engine
+        .apply_feature_config(&[FeatureConfig {
+            id: None,
+            feature_code: "bang".to_string(),
+            key: "shell_allowlist".to_string(),
+            value: "yes".to_string(),
+            data_type: "string".to_string(),
+            description: None,
+        }])
+        .unwrap();
+    let result = template_engine.parse("!shell(yes)", &HashMap::new()).await;
+    assert!(result.starts_with("命令输出超过上限"));
+}
+
+#[tokio::test]
+async fn test_web_image_becomes_attachment() {
+    let _m = mock("GET", "/image.png")
+        .with_status(200)
+        .with_header("content-type", "image/png")
+        .with_body(vec![0x89, b'P', b'N', b'G'])
+        .create();
+    let _html = mock("GET", "/page.html")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body("<html></html>")
+        .create();
+
+    let template_engine = local_engine();
+    let context = HashMap::new();
+    let result = template_engine
+        .try_render(
+            &format!("看看这张图 !web_image({}/image.png)", mockito::server_url()),
+            &context,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.text, "看看这张图 ");
+    assert_eq!(
+        result.attachments,
+        vec![BangResult::Image(
+            "data:image/png;base64,iVBORw==".to_string()
+        )]
+    );
+
+    // 非图片内容不会作为附件，返回错误文本
+    let result = template_engine
+        .try_render(
+            &format!("!web_image({}/page.html)", mockito::server_url()),
+            &context,
+        )
+        .await
+        .unwrap();
+    assert!(result.attachments.is_empty());
+    assert!(result.text.contains("不是 image/*"));
+}
+
+#[tokio::test]
+async fn test_attachments_in_nested_args() {
+    let mut template_engine = TemplateEngine::new();
+    template_engine.register_command(
+        "fake_audio",
+        Arc::new(|_, _, _| {
+            async { BangResult::Audio("data:audio/wav;base64,AA==".to_string()) }.boxed()
+        }),
+    );
+
+    let result = template_engine
+        .try_render("!sub_start(abc!fake_audio, 2)", &HashMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.text, "ab");
+    assert_eq!(
+        result.attachments,
+        vec![BangResult::Audio("data:audio/wav;base64,AA==".to_string())]
+    );
+    // parse 只返回文本
+    assert_eq!(
+        template_engine
+            .parse("!fake_audio!cd", &HashMap::new())
+            .await,
+        Local::now().format("%Y-%m-%d").to_string()
+    );
+}
+
+#[tokio::test]
+async fn test_web_to_markdown_extracts_main_content() {
+    let paragraph = "正文段落的内容比较长，包含很多逗号，用来模拟真实文章，阅读模式应该保留这些内容，而不是导航和页脚。";
+    let html_content = format!(
+        r#"<!DOCTYPE html>
+<html><head>
+<meta charset="utf-8">
+<title>备用标题</title>
+<meta property="og:title" content="测试文章 &amp; 标题">
+<meta name="author" content="张三">
+<meta property="article:published_time" content="2024-05-01T08:00:00Z">
+<script>var nav = "<p>script text</p>";</script>
+<style>.x {{ color: red; }}</style>
+</head><body>
+<nav><a href="/">首页</a><a href="/about">关于</a></nav>
+<div class="cookie-banner">我们使用 cookie 来改善体验，点击接受。</div>
+<div id="main"><article class="post-content">
+<h2>小标题</h2>
+<p>{p}</p><p>{p}</p><p>{p}</p>
+<ul class="related-links"><li><a href="/1">相关推荐一</a></li><li><a href="/2">相关推荐二</a></li></ul>
+</article></div>
+<footer>版权所有 footer text</footer>
+</body></html>"#,
+        p = paragraph
+    );
+    let _m = mock("GET", "/article")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(html_content)
+        .create();
+
+    let template_engine = local_engine();
+    let url = format!("{}/article", mockito::server_url());
+    let result = template_engine
+        .parse(&format!("!wm({})", url), &HashMap::new())
+        .await;
+    println!("result : {}", result);
+
+    assert!(result.starts_with(&format!(
+        "\n<bangwebtomarkdown url=\"{}\" title=\"测试文章 & 标题\" author=\"张三\" published=\"2024-05-01T08:00:00Z\">\n",
+        url
+    )));
+    assert!(result.contains("小标题"));
+    assert_eq!(result.matches(paragraph).count(), 3);
+    for noise in [
+        "首页",
+        "cookie",
+        "相关推荐",
+        "footer text",
+        "script text",
+        "color: red",
+    ] {
+        assert!(!result.contains(noise), "should not contain {}", noise);
+    }
+}
+
+#[test]
+fn test_readability_deeply_nested_page() {
+    let depth = 2000;
+    let paragraph =
+        "嵌套很深的页面，也应该能提取出正文，而不会栈溢出，这里的逗号用来提高段落得分。";
+    let html = format!(
+        "<html><body>{}<p>{}</p><p>{}</p>{}</body></html>",
+        "<div>".repeat(depth),
+        paragraph,
+        paragraph,
+        "</div>".repeat(depth)
+    );
+    let article = readability::extract(&html);
+    assert_eq!(article.content.matches(paragraph).count(), 2);
+    assert!(article.content.starts_with("<div><p>"));
+}
+
+#[tokio::test]
+async fn test_web_charset_and_truncation() {
+    let (gbk_body, _, _) = encoding_rs::GBK.encode("<html><body><p>中文编码测试</p></body></html>");
+    let _m = mock("GET", "/gbk")
+        .with_status(200)
+        .with_header("content-type", "text/html; charset=GBK")
+        .with_body(gbk_body.to_vec())
+        .create();
+    let _long = mock("GET", "/long")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(format!(
+            "<html><body><p>{}</p></body></html>",
+            "字".repeat(50)
+        ))
+        .create();
+
+    let mut template_engine = local_engine();
+    let context = HashMap::new();
+    let result = template_engine
+        .parse(&format!("!wm({}/gbk)", mockito::server_url()), &context)
+        .await;
+    assert!(result.contains("中文编码测试"));
+
+    template_engine
+        .apply_feature_config(&[
+            FeatureConfig {
+                id: None,
+                feature_code: "bang".to_string(),
+                key: "web_max_chars".to_string(),
+                value: "10".to_string(),
+                data_type: "string".to_string(),
+                description: None,
+            },
+            allow_private_config(),
+        ])
+        .unwrap();
+    let result = template_engine
+        .parse(&format!("!wm({}/long)", mockito::server_url()), &context)
+        .await;
+    assert!(result.contains(&format!(
+        "{}\n\n[内容过长已截断: 共 50 个字符，保留前 10 个字符]",
+        "字".repeat(10)
+    )));
+}
+
+#[tokio::test]
+async fn test_web_fetch_errors_surfaced() {
+    let _missing = mock("GET", "/missing").with_status(404).create();
+    let _json = mock("GET", "/data.json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body("{}")
+        .create();
+    let _loop = mock("GET", "/loop")
+        .with_status(302)
+        .with_header("location", "/loop")
+        .create();
+
+    let template_engine = local_engine();
+    let context = HashMap::new();
+    let result = template_engine
+        .parse(&format!("!wm({}/missing)", mockito::server_url()), &context)
+        .await;
+    assert!(result.contains("HTTP状态码 404"));
+
+    let result = template_engine
+        .parse(
+            &format!("!web({}/data.json)", mockito::server_url()),
+            &context,
+        )
+        .await;
+    assert!(result.contains("内容类型 application/json"));
+
+    let result = template_engine
+        .parse(&format!("!web({}/loop)", mockito::server_url()), &context)
+        .await;
+    assert!(result.contains("重定向次数超过 5 次"));
+
+    let result = template_engine
+        .parse("!web(file:///etc/passwd)", &context)
+        .await;
+    assert!(result.contains("不支持的地址"));
+
+    // 默认禁止访问本机和内网地址
+    let result = TemplateEngine::new()
+        .parse(
+            &format!("!web({}/missing)", mockito::server_url()),
+            &context,
+        )
+        .await;
+    assert!(result.contains("不允许访问本机或内网地址"));
+}
+
+#[test]
+fn test_is_private_address() {
+    let is_private = |ip: &str| fetch::is_private_address(&ip.parse().unwrap());
+    for ip in [
+        "127.0.0.1",
+        "10.1.2.3",
+        "192.168.1.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "::1",
+        "fc00::1",
+        "fd12:3456::1",
+        "fe80::1",
+        "febf::1",
+        "::ffff:127.0.0.1",
+        "::ffff:192.168.0.1",
+    ] {
+        assert!(is_private(ip), "{} 应该是内网地址", ip);
+    }
+    for ip in [
+        "8.8.8.8",
+        "100.128.0.1",
+        "2001:4860::8888",
+        "fec0::1",
+        "::ffff:8.8.8.8",
+    ] {
+        assert!(!is_private(ip), "{} 不应该是内网地址", ip);
+    }
+}
+
+#[tokio::test]
+async fn test_search_bang_with_searxng() {
+    let server = mockito::server_url();
+    let _search = mock("GET", "/searx/search")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("q".into(), "rust 异步".into()),
+            mockito::Matcher::UrlEncoded("format".into(), "json".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            serde_json::json!({
+                "results": [
+                    {"title": "Async Book", "url": format!("{}/search_page", server), "content": "摘要一"},
+                    {"title": "Gone", "url": format!("{}/search_gone", server), "content": "摘要二"},
+                    {"title": "Third", "url": format!("{}/search_third", server), "content": "摘要三"}
+                ]
+            })
+            .to_string(),
+        )
+        .create();
+    let _page = mock("GET", "/search_page")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body("<html><body><p>async 正文</p></body></html>")
+        .create();
+    let _gone = mock("GET", "/search_gone").with_status(404).create();
+
+    let mut template_engine = local_engine();
+    let context = HashMap::new();
+    let result = template_engine.parse("!search(rust 异步)", &context).await;
+    assert_eq!(result, "搜索失败: 没有配置 SearxNG 的地址");
+
+    let config = |key: &str, value: String| FeatureConfig {
+        id: None,
+        feature_code: "bang".to_string(),
+        key: key.to_string(),
+        value,
+        data_type: "string".to_string(),
+        description: None,
+    };
+    template_engine
+        .apply_feature_config(&[
+            config("search_engine", "searxng".to_string()),
+            config("search_url", format!("{}/searx/", server)),
+            config("search_result_count", "2".to_string()),
+            allow_private_config(),
+        ])
+        .unwrap();
+    let result = template_engine.parse("!search(rust 异步)", &context).await;
+    assert_eq!(
+        result,
+        format!(
+            "\n<bangsearch query=\"rust 异步\">\n<result index=\"1\" title=\"Async Book\" url=\"{0}/search_page\">\nasync 正文\n</result>\n<result index=\"2\" title=\"Gone\" url=\"{0}/search_gone\">\n摘要二\n</result>\n</bangsearch>",
+            server
+        )
+    );
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffTag {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DiffLine {
+    pub tag: DiffTag,
+    pub text: String,
+}
+
+// LCS 表的最大单元格数，超过时不再逐行比较，避免长文本（如程序输出）占用过多内存和时间
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+// 按行比较两段文本，基于最长公共子序列，适用于提示词这类不太长的文本。
+// 相同的开头和结尾不参与比较，中间部分过长时整体作为删除和插入
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let prefix = old_lines
+        .iter()
+        .zip(new_lines.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old_lines[prefix..old_lines.len() - suffix];
+    let new_middle = &new_lines[prefix..new_lines.len() - suffix];
+
+    let line = |tag: DiffTag, text: &str| DiffLine {
+        tag,
+        text: text.to_string(),
+    };
+    let mut result: Vec<DiffLine> = old_lines[..prefix]
+        .iter()
+        .map(|l| line(DiffTag::Equal, l))
+        .collect();
+    if (old_middle.len() + 1).saturating_mul(new_middle.len() + 1) > MAX_LCS_CELLS {
+        result.extend(old_middle.iter().map(|l| line(DiffTag::Delete, l)));
+        result.extend(new_middle.iter().map(|l| line(DiffTag::Insert, l)));
+    } else {
+        result.extend(diff_middle(old_middle, new_middle));
+    }
+    result.extend(
+        old_lines[old_lines.len() - suffix..]
+            .iter()
+            .map(|l| line(DiffTag::Equal, l)),
+    );
+    result
+}
+
+fn diff_middle(old_lines: &[&str], new_lines: &[&str]) -> Vec<DiffLine> {
+    let (n, m) = (old_lines.len(), new_lines.len());
+
+    // lcs[i][j] 为 old_lines[i..] 与 new_lines[j..] 的最长公共子序列长度
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_lines[i] == new_lines[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let line = |tag: DiffTag, text: &str| DiffLine {
+        tag,
+        text: text.to_string(),
+    };
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_lines[i] == new_lines[j] {
+            result.push(line(DiffTag::Equal, old_lines[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            result.push(line(DiffTag::Delete, old_lines[i]));
+            i += 1;
+        } else {
+            result.push(line(DiffTag::Insert, new_lines[j]));
+            j += 1;
+        }
+    }
+    result.extend(old_lines[i..].iter().map(|l| line(DiffTag::Delete, l)));
+    result.extend(new_lines[j..].iter().map(|l| line(DiffTag::Insert, l)));
+    result
+}
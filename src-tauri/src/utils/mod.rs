@@ -0,0 +1,5 @@
+pub mod diff;
+pub mod pdf;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,35 @@
+use std::io::Write;
+
+// 按顺序写出 PDF 对象和交叉引用表，第 1 个对象为 Catalog
+pub fn write_pdf(objects: &[Vec<u8>]) -> Vec<u8> {
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n".to_vec();
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        let _ = writeln!(pdf, "{} 0 obj", i + 1);
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+    pdf
+}
+
+// 带长度的流对象
+pub fn stream_object(dictionary: &str, data: &[u8]) -> Vec<u8> {
+    [
+        format!("<< {} /Length {} >>\nstream\n", dictionary, data.len()).as_bytes(),
+        data,
+        b"\nendstream",
+    ]
+    .concat()
+}
@@ -0,0 +1,82 @@
+use super::diff::{diff_lines, DiffLine, DiffTag};
+
+fn tags(lines: &[DiffLine]) -> Vec<(DiffTag, &str)> {
+    lines.iter().map(|l| (l.tag, l.text.as_str())).collect()
+}
+
+#[test]
+fn test_diff_lines_insert() {
+    let result = diff_lines("a\nc", "a\nb\nc");
+    assert_eq!(
+        tags(&result),
+        vec![
+            (DiffTag::Equal, "a"),
+            (DiffTag::Insert, "b"),
+            (DiffTag::Equal, "c")
+        ]
+    );
+}
+
+#[test]
+fn test_diff_lines_delete() {
+    let result = diff_lines("a\nb\nc", "a\nc");
+    assert_eq!(
+        tags(&result),
+        vec![
+            (DiffTag::Equal, "a"),
+            (DiffTag::Delete, "b"),
+            (DiffTag::Equal, "c")
+        ]
+    );
+}
+
+#[test]
+fn test_diff_lines_replace() {
+    let result = diff_lines("a\nb\nc\nd", "a\nx\nc\ny");
+    assert_eq!(
+        tags(&result),
+        vec![
+            (DiffTag::Equal, "a"),
+            (DiffTag::Delete, "b"),
+            (DiffTag::Insert, "x"),
+            (DiffTag::Equal, "c"),
+            (DiffTag::Delete, "d"),
+            (DiffTag::Insert, "y")
+        ]
+    );
+}
+
+#[test]
+fn test_diff_lines_empty() {
+    assert!(diff_lines("", "").is_empty());
+    assert_eq!(
+        tags(&diff_lines("", "a\nb")),
+        vec![(DiffTag::Insert, "a"), (DiffTag::Insert, "b")]
+    );
+    assert_eq!(
+        tags(&diff_lines("a\nb", "")),
+        vec![(DiffTag::Delete, "a"), (DiffTag::Delete, "b")]
+    );
+    assert_eq!(
+        tags(&diff_lines("a\nb", "a\nb")),
+        vec![(DiffTag::Equal, "a"), (DiffTag::Equal, "b")]
+    );
+}
+
+#[test]
+fn test_diff_lines_large_input() {
+    // 中间部分超过 LCS 表的上限时整体作为删除和插入，相同的开头和结尾仍然保留
+    let old: Vec<String> = (0..5000).map(|i| format!("old {}", i)).collect();
+    let new: Vec<String> = (0..5000).map(|i| format!("new {}", i)).collect();
+    let old = format!("head\n{}\ntail", old.join("\n"));
+    let new = format!("head\n{}\ntail", new.join("\n"));
+
+    let result = diff_lines(&old, &new);
+    assert_eq!(result.len(), 10002);
+    assert_eq!(
+        tags(&result[..2]),
+        vec![(DiffTag::Equal, "head"), (DiffTag::Delete, "old 0")]
+    );
+    assert_eq!(result[5001].tag, DiffTag::Insert);
+    assert_eq!(tags(&result[10001..]), vec![(DiffTag::Equal, "tail")]);
+}
@@ -1,8 +1,9 @@
-use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
 use tauri::Emitter;
-use tauri::Listener;
-use tauri::{AppHandle, Manager, Url, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+use crate::artifacts::history::code_hash;
+use crate::artifacts::preview::{preview_url, preview_window_label, PreviewKind};
+use crate::state::preview_store::PreviewStore;
 
 pub fn create_ask_window(app: &AppHandle) {
     let window_builder =
@@ -171,10 +172,25 @@ pub async fn open_plugin_window(app_handle: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+// HTML 预览，每个内容一个窗口，窗口加载后通过 get_preview_html 读取内容，再次预览同样的内容时刷新原窗口
 pub async fn open_preview_html_window(app_handle: AppHandle, html: String) -> Result<(), String> {
+    let window_id = preview_window_label("html", &code_hash(&html));
+
+    if let Some(window) = app_handle.get_webview_window(&window_id) {
+        window
+            .emit_to(window_id.as_str(), "preview_html", html)
+            .map_err(|e| e.to_string())?;
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    app_handle
+        .state::<PreviewStore>()
+        .set_html(&window_id, html);
     let window_builder = WebviewWindowBuilder::new(
         &app_handle,
-        "preview_html",
+        &window_id,
         WebviewUrl::App("index.html".into()),
     )
     .title("Aipp")
@@ -189,140 +205,68 @@ pub async fn open_preview_html_window(app_handle: AppHandle, html: String) -> Re
 
     match window_builder.build() {
         Ok(window) => {
-            let window_clone = window.clone();
+            // 预览窗口关闭时直接销毁，同时删除保存的内容
+            let app_handle = app_handle.clone();
             window.on_window_event(move |event| {
-                if let WindowEvent::CloseRequested { .. } = event {
-                    window_clone.hide().unwrap();
+                if let WindowEvent::Destroyed = event {
+                    app_handle.state::<PreviewStore>().remove_html(&window_id);
                 }
             });
-
-            let window = app_handle.get_webview_window("preview_html").unwrap();
-
-            window.clone().once("preview-window-load", move |_| {
-                window.emit("preview_html", html.clone()).unwrap();
-            });
         }
-        Err(e) => eprintln!("Failed to build window: {}", e),
+        Err(e) => {
+            app_handle.state::<PreviewStore>().remove_html(&window_id);
+            eprintln!("Failed to build window: {}", e)
+        }
     }
 
     Ok(())
 }
 
-#[derive(Serialize, Deserialize)]
-struct ReactComponentPayload {
-    code: String,
-    css: String,
-}
-
-async fn open_preview_window(
+// 组件和图表预览，每个内容一个窗口，再次预览同样的内容时重新加载原窗口，返回预览的 hash
+pub async fn open_preview_component_window(
     app_handle: AppHandle,
-    html: String,
-    port: u16,
-    window_id: &str,
-    file_extension: &str,
-    event_name: String,
-) -> Result<(), String> {
-    let mut hasher = Sha256::new();
-    hasher.update(html.clone());
-    let result = hasher.finalize();
-    let html_hash = format!("{:x}", result);
-    let file_name = format!("{}.{}", html_hash, file_extension);
-
-    let file_content = html.clone();
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!(
-            "http://preview.teafakedomain.com:{}/api/saveFile",
-            port
-        ))
-        .json(&serde_json::json!({
-            "fileName": file_name,
-            "fileContent": file_content
-        }))
-        .send()
-        .await;
-
-    if let Ok(response) = response {
-        if response.status().is_success() {
-            let url = Url::parse(&format!(
-                "http://preview.teafakedomain.com:{}/previews/{}",
-                port, html_hash
-            ))
-            .map_err(|e| e.to_string())?;
-
-            let window_builder =
-                WebviewWindowBuilder::new(&app_handle, window_id, WebviewUrl::External(url))
-                    .title("Aipp")
-                    .inner_size(1000.0, 800.0)
-                    .fullscreen(false)
-                    .resizable(true)
-                    .decorations(true)
-                    .center();
-
-            #[cfg(not(target_os = "macos"))]
-            let window_builder = window_builder.transparent(false);
+    kind: PreviewKind,
+    code: String,
+) -> Result<String, String> {
+    let hash = app_handle.state::<PreviewStore>().insert(kind, code);
+    let url = preview_url(&hash)?;
+    let window_id = kind.window_label(&hash);
+
+    if let Some(window) = app_handle.get_webview_window(&window_id) {
+        window.navigate(url).map_err(|e| e.to_string())?;
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(hash);
+    }
 
-            match window_builder.build() {
-                Ok(window) => {
-                    let window_clone = window.clone();
-                    window.on_window_event(move |event| {
-                        if let WindowEvent::CloseRequested { .. } = event {
-                            window_clone.hide().unwrap();
-                        }
-                    });
+    let window_builder =
+        WebviewWindowBuilder::new(&app_handle, &window_id, WebviewUrl::CustomProtocol(url))
+            .title("Aipp")
+            .inner_size(1000.0, 800.0)
+            .fullscreen(false)
+            .resizable(true)
+            .decorations(true)
+            .center();
 
-                    let window = app_handle.get_webview_window(window_id).unwrap();
+    #[cfg(not(target_os = "macos"))]
+    let window_builder = window_builder.transparent(false);
 
-                    window.clone().once("preview-window-load", move |_| {
-                        let payload = ReactComponentPayload {
-                            code: html.clone(),
-                            css: "".to_string(),
-                        };
-                        let json_payload = serde_json::to_string(&payload).unwrap();
-                        window.emit(&event_name, json_payload).unwrap();
-                    });
+    match window_builder.build() {
+        Ok(window) => {
+            // 预览窗口关闭时直接销毁，同时删除保存的组件代码和渲染结果
+            let app_handle = app_handle.clone();
+            let hash = hash.clone();
+            window.on_window_event(move |event| {
+                if let WindowEvent::Destroyed = event {
+                    app_handle.state::<PreviewStore>().remove(&hash);
                 }
-                Err(e) => eprintln!("Failed to build window: {}", e),
-            }
-        } else {
-            eprintln!("Failed to save file: {}", response.status());
+            });
+        }
+        Err(e) => {
+            app_handle.state::<PreviewStore>().remove(&hash);
+            eprintln!("Failed to build window: {}", e)
         }
-    } else {
-        eprintln!("Failed to send request: {:?}", response);
     }
 
-    Ok(())
-}
-
-pub async fn open_preview_react_window(
-    app_handle: AppHandle,
-    html: String,
-    port: u16,
-) -> Result<(), String> {
-    open_preview_window(
-        app_handle,
-        html,
-        port,
-        "preview_react",
-        "js",
-        "preview_react".to_string(),
-    )
-    .await
-}
-
-pub async fn open_preview_vue_window(
-    app_handle: AppHandle,
-    html: String,
-    port: u16,
-) -> Result<(), String> {
-    open_preview_window(
-        app_handle,
-        html,
-        port,
-        "preview_vue",
-        "vue",
-        "preview_vue".to_string(),
-    )
-    .await
+    Ok(hash)
 }
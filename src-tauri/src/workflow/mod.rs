@@ -0,0 +1,466 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use futures::future::{join_all, BoxFuture};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::template_engine::TemplateEngine;
+
+// 工作流定义，步骤之间通过 depends_on 组成有向无环图，
+// 步骤的输出以步骤 id 为名称放入上下文，后续步骤通过 !步骤id 引用
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkflowDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub inputs: Vec<WorkflowInput>,
+    pub steps: Vec<WorkflowStep>,
+    // 工作流的最终输出模板，为空时使用最后一个执行的步骤的输出
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkflowInput {
+    pub name: String,
+    #[serde(default)]
+    pub default: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkflowStep {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(flatten)]
+    pub action: StepAction,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StepAction {
+    // 使用指定助手的模型和系统提示词回答 prompt
+    Llm {
+        assistant_id: i64,
+        prompt: String,
+    },
+    // 渲染模板，模板中的 bang 会被求值
+    Bang {
+        template: String,
+    },
+    // 执行脚本，脚本内容同样会先渲染模板
+    Script {
+        language: String,
+        code: String,
+    },
+    // 判断 value 是否满足条件，then 中的步骤只在满足时执行，else 中的步骤只在不满足时执行
+    Condition {
+        value: String,
+        #[serde(default)]
+        operator: ConditionOperator,
+        #[serde(default)]
+        operand: String,
+        #[serde(default)]
+        then: Vec<String>,
+        #[serde(default, rename = "else")]
+        otherwise: Vec<String>,
+    },
+    // 对 items 中的每一项执行 step，!item 为当前项，!index 为序号，输出为所有结果组成的 JSON 数组
+    Loop {
+        items: String,
+        step: Box<StepAction>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOperator {
+    #[default]
+    NotEmpty,
+    Equals,
+    Contains,
+    Regex,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Running,
+    Success,
+    Failed,
+    Skipped,
+}
+
+impl StepStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StepStatus::Running => "running",
+            StepStatus::Success => "success",
+            StepStatus::Failed => "failed",
+            StepStatus::Skipped => "skipped",
+        }
+    }
+}
+
+// 执行过程中推送的事件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkflowEvent {
+    StepStarted {
+        step_id: String,
+    },
+    LoopProgress {
+        step_id: String,
+        index: usize,
+        total: usize,
+    },
+    StepFinished {
+        step_id: String,
+        status: StepStatus,
+        output: String,
+        error: Option<String>,
+    },
+}
+
+// 需要访问模型和本机环境的步骤由调用方实现，便于离线测试
+pub trait StepExecutor: Send + Sync {
+    fn llm(
+        &self,
+        assistant_id: i64,
+        prompt: String,
+        cancel_token: CancellationToken,
+    ) -> BoxFuture<'static, Result<String>>;
+
+    fn script(
+        &self,
+        language: String,
+        code: String,
+        cancel_token: CancellationToken,
+    ) -> BoxFuture<'static, Result<String>>;
+}
+
+// 以 { 开头的按 JSON 解析，否则按 YAML 解析，步骤 id 不能与 template_engine 中的 bang 重名
+pub fn parse_definition(
+    text: &str,
+    template_engine: &TemplateEngine,
+) -> Result<WorkflowDefinition> {
+    let definition: WorkflowDefinition = if text.trim_start().starts_with('{') {
+        serde_json::from_str(text).map_err(|e| anyhow!("工作流定义格式不正确: {}", e))?
+    } else {
+        serde_yaml::from_str(text).map_err(|e| anyhow!("工作流定义格式不正确: {}", e))?
+    };
+    validate_definition(&definition, template_engine)?;
+    Ok(definition)
+}
+
+// 步骤 id 会作为 !步骤id 引用，只能由字母、数字和下划线组成且不能以数字开头
+fn is_valid_step_id(id: &str) -> bool {
+    let mut chars = id.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// 条件分支中的步骤隐式依赖条件步骤
+fn dependencies(definition: &WorkflowDefinition) -> HashMap<&str, HashSet<&str>> {
+    let mut deps: HashMap<&str, HashSet<&str>> = definition
+        .steps
+        .iter()
+        .map(|s| {
+            (
+                s.id.as_str(),
+                s.depends_on.iter().map(|d| d.as_str()).collect(),
+            )
+        })
+        .collect();
+    for step in &definition.steps {
+        if let StepAction::Condition {
+            then, otherwise, ..
+        } = &step.action
+        {
+            for target in then.iter().chain(otherwise.iter()) {
+                if let Some(d) = deps.get_mut(target.as_str()) {
+                    d.insert(step.id.as_str());
+                }
+            }
+        }
+    }
+    deps
+}
+
+pub fn validate_definition(
+    definition: &WorkflowDefinition,
+    template_engine: &TemplateEngine,
+) -> Result<()> {
+    if definition.steps.is_empty() {
+        return Err(anyhow!("工作流没有配置步骤"));
+    }
+    let mut ids = HashSet::new();
+    for step in &definition.steps {
+        if !is_valid_step_id(&step.id) {
+            return Err(anyhow!(
+                "步骤 id {} 只能包含字母、数字和下划线，且不能以数字开头",
+                step.id
+            ));
+        }
+        if template_engine.has_command(&step.id) {
+            return Err(anyhow!("步骤 id {} 与 bang !{} 重名", step.id, step.id));
+        }
+        if !ids.insert(step.id.as_str()) {
+            return Err(anyhow!("步骤 id 重复: {}", step.id));
+        }
+    }
+    for step in &definition.steps {
+        let mut targets = step.depends_on.iter().collect::<Vec<_>>();
+        if let StepAction::Condition {
+            then, otherwise, ..
+        } = &step.action
+        {
+            targets.extend(then.iter().chain(otherwise.iter()));
+        }
+        if let Some(unknown) = targets.iter().find(|t| !ids.contains(t.as_str())) {
+            return Err(anyhow!("步骤 {} 引用了不存在的步骤 {}", step.id, unknown));
+        }
+        if let StepAction::Loop { step: inner, .. } = &step.action {
+            if matches!(
+                inner.as_ref(),
+                StepAction::Condition { .. } | StepAction::Loop { .. }
+            ) {
+                return Err(anyhow!("循环步骤 {} 中不支持条件和循环", step.id));
+            }
+        }
+    }
+    execution_levels(definition).map(|_| ())
+}
+
+// 按依赖关系分层，同一层的步骤之间没有依赖，可以并发执行
+pub fn execution_levels(definition: &WorkflowDefinition) -> Result<Vec<Vec<&WorkflowStep>>> {
+    let deps = dependencies(definition);
+    let mut done: HashSet<&str> = HashSet::new();
+    let mut levels = vec![];
+    while done.len() < definition.steps.len() {
+        let level = definition
+            .steps
+            .iter()
+            .filter(|s| !done.contains(s.id.as_str()))
+            .filter(|s| deps[s.id.as_str()].iter().all(|d| done.contains(d)))
+            .collect::<Vec<_>>();
+        if level.is_empty() {
+            return Err(anyhow!("工作流步骤之间存在循环依赖"));
+        }
+        done.extend(level.iter().map(|s| s.id.as_str()));
+        levels.push(level);
+    }
+    Ok(levels)
+}
+
+pub fn check_condition(value: &str, operator: ConditionOperator, operand: &str) -> Result<bool> {
+    Ok(match operator {
+        ConditionOperator::NotEmpty => !value.trim().is_empty(),
+        ConditionOperator::Equals => value.trim() == operand.trim(),
+        ConditionOperator::Contains => value.contains(operand),
+        ConditionOperator::Regex => Regex::new(operand)
+            .map_err(|e| anyhow!("正则表达式不正确: {}", e))?
+            .is_match(value),
+    })
+}
+
+// 循环的列表可以是 JSON 数组，否则按行拆分
+pub fn parse_loop_items(value: &str) -> Vec<String> {
+    match serde_json::from_str::<Vec<serde_json::Value>>(value.trim()) {
+        Ok(items) => items
+            .into_iter()
+            .map(|item| match item {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            })
+            .collect(),
+        Err(_) => value
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect(),
+    }
+}
+
+pub struct WorkflowRunner<'a> {
+    pub template_engine: &'a TemplateEngine,
+    pub executor: &'a dyn StepExecutor,
+    pub events: mpsc::Sender<WorkflowEvent>,
+    pub cancel_token: CancellationToken,
+}
+
+impl<'a> WorkflowRunner<'a> {
+    async fn render(&self, template: &str, context: &HashMap<String, String>) -> Result<String> {
+        self.template_engine
+            .try_parse(template, context)
+            .await
+            .map_err(|e| anyhow!("模板解析失败: {}", e))
+    }
+
+    async fn run_action(
+        &self,
+        step_id: &str,
+        action: &StepAction,
+        context: &HashMap<String, String>,
+    ) -> Result<String> {
+        if self.cancel_token.is_cancelled() {
+            return Err(anyhow!("工作流已取消"));
+        }
+        match action {
+            StepAction::Llm {
+                assistant_id,
+                prompt,
+            } => {
+                let prompt = self.render(prompt, context).await?;
+                self.executor
+                    .llm(*assistant_id, prompt, self.cancel_token.clone())
+                    .await
+            }
+            StepAction::Bang { template } => self.render(template, context).await,
+            StepAction::Script { language, code } => {
+                let code = self.render(code, context).await?;
+                self.executor
+                    .script(language.clone(), code, self.cancel_token.clone())
+                    .await
+            }
+            StepAction::Condition {
+                value,
+                operator,
+                operand,
+                ..
+            } => {
+                let value = self.render(value, context).await?;
+                let operand = self.render(operand, context).await?;
+                check_condition(&value, *operator, &operand).map(|passed| passed.to_string())
+            }
+            StepAction::Loop { items, step } => {
+                let items = parse_loop_items(&self.render(items, context).await?);
+                let mut outputs = vec![];
+                for (index, item) in items.iter().enumerate() {
+                    let _ = self
+                        .events
+                        .send(WorkflowEvent::LoopProgress {
+                            step_id: step_id.to_string(),
+                            index,
+                            total: items.len(),
+                        })
+                        .await;
+                    let mut item_context = context.clone();
+                    item_context.insert("item".to_string(), item.clone());
+                    item_context.insert("index".to_string(), index.to_string());
+                    outputs.push(Box::pin(self.run_action(step_id, step, &item_context)).await?);
+                }
+                Ok(serde_json::to_string(&outputs)?)
+            }
+        }
+    }
+
+    // 执行整个工作流，返回最终输出，任意步骤失败时停止执行
+    pub async fn run(
+        &self,
+        definition: &WorkflowDefinition,
+        inputs: &HashMap<String, String>,
+    ) -> Result<String> {
+        let levels = execution_levels(definition)?;
+        let deps = dependencies(definition);
+        let mut context: HashMap<String, String> = definition
+            .inputs
+            .iter()
+            .map(|input| {
+                let value = inputs
+                    .get(&input.name)
+                    .cloned()
+                    .unwrap_or_else(|| input.default.clone());
+                (input.name.clone(), value)
+            })
+            .collect();
+        context.extend(inputs.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+        // 没有选中的分支中的步骤
+        let mut excluded: HashSet<String> = HashSet::new();
+        let mut skipped: HashSet<String> = HashSet::new();
+        let mut last_output = String::new();
+
+        for level in levels {
+            let mut runnable = vec![];
+            for step in level {
+                let step_deps = &deps[step.id.as_str()];
+                // 依赖全部被跳过时跳过，汇合多个分支的步骤只要有一个分支执行就会执行
+                let all_deps_skipped =
+                    !step_deps.is_empty() && step_deps.iter().all(|d| skipped.contains(*d));
+                if excluded.contains(&step.id) || all_deps_skipped {
+                    skipped.insert(step.id.clone());
+                    context.insert(step.id.clone(), String::new());
+                    let _ = self
+                        .events
+                        .send(WorkflowEvent::StepFinished {
+                            step_id: step.id.clone(),
+                            status: StepStatus::Skipped,
+                            output: String::new(),
+                            error: None,
+                        })
+                        .await;
+                } else {
+                    runnable.push(step);
+                }
+            }
+
+            let results = join_all(runnable.iter().map(|step| {
+                let context = &context;
+                async move {
+                    let _ = self
+                        .events
+                        .send(WorkflowEvent::StepStarted {
+                            step_id: step.id.clone(),
+                        })
+                        .await;
+                    let result = self.run_action(&step.id, &step.action, context).await;
+                    let _ = self
+                        .events
+                        .send(WorkflowEvent::StepFinished {
+                            step_id: step.id.clone(),
+                            status: if result.is_ok() {
+                                StepStatus::Success
+                            } else {
+                                StepStatus::Failed
+                            },
+                            output: result.as_ref().cloned().unwrap_or_default(),
+                            error: result.as_ref().err().map(|e| e.to_string()),
+                        })
+                        .await;
+                    result
+                }
+            }))
+            .await;
+
+            for (step, result) in runnable.into_iter().zip(results) {
+                let output = result.map_err(|e| anyhow!("步骤 {} 执行失败: {}", step.id, e))?;
+                if let StepAction::Condition {
+                    then, otherwise, ..
+                } = &step.action
+                {
+                    let not_taken = if output == "true" { otherwise } else { then };
+                    excluded.extend(not_taken.iter().cloned());
+                }
+                context.insert(step.id.clone(), output.clone());
+                last_output = output;
+            }
+        }
+
+        match &definition.output {
+            Some(output) => self.render(output, &context).await,
+            None => Ok(last_output),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,219 @@
+use super::*;
+use futures::FutureExt;
+
+// 离线测试用的执行器，llm 步骤返回 “助手id:提示词”
+struct MockExecutor;
+
+impl StepExecutor for MockExecutor {
+    fn llm(
+        &self,
+        assistant_id: i64,
+        prompt: String,
+        _cancel_token: CancellationToken,
+    ) -> BoxFuture<'static, Result<String>> {
+        async move { Ok(format!("{}:{}", assistant_id, prompt)) }.boxed()
+    }
+
+    fn script(
+        &self,
+        language: String,
+        code: String,
+        _cancel_token: CancellationToken,
+    ) -> BoxFuture<'static, Result<String>> {
+        async move {
+            match language.as_str() {
+                "echo" => Ok(code),
+                _ => Err(anyhow!("暂不支持该语言的代码执行")),
+            }
+        }
+        .boxed()
+    }
+}
+
+async fn run(
+    text: &str,
+    inputs: &[(&str, &str)],
+    cancel_token: CancellationToken,
+) -> (Result<String>, Vec<WorkflowEvent>) {
+    let template_engine = TemplateEngine::new();
+    let definition = parse_definition(text, &template_engine).unwrap();
+    let executor = MockExecutor;
+    let (tx, mut rx) = mpsc::channel(100);
+    let runner = WorkflowRunner {
+        template_engine: &template_engine,
+        executor: &executor,
+        events: tx,
+        cancel_token,
+    };
+    let inputs = inputs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let result = runner.run(&definition, &inputs).await;
+    drop(runner);
+    let mut events = vec![];
+    while let Some(event) = rx.recv().await {
+        events.push(event);
+    }
+    (result, events)
+}
+
+fn status_of(events: &[WorkflowEvent], id: &str) -> Option<StepStatus> {
+    events.iter().rev().find_map(|event| match event {
+        WorkflowEvent::StepFinished {
+            step_id, status, ..
+        } if step_id == id => Some(*status),
+        _ => None,
+    })
+}
+
+#[test]
+fn test_parse_definition() {
+    let yaml = r#"
+name: 翻译
+inputs:
+  - name: Text
+    default: hello
+steps:
+  - id: Translate
+    type: llm
+    assistant_id: 1
+    prompt: "翻译 !Text"
+  - id: check
+    type: condition
+    value: "!Translate"
+    operator: contains
+    operand: 错误
+    depends_on: [Translate]
+    then: [fix]
+  - id: fix
+    type: bang
+    template: "修正 !Translate"
+"#;
+    let engine = TemplateEngine::new();
+    let definition = parse_definition(yaml, &engine).unwrap();
+    assert_eq!(definition.inputs[0].name, "Text");
+    assert_eq!(definition.steps[0].id, "Translate");
+    let levels = execution_levels(&definition).unwrap();
+    assert_eq!(levels.len(), 3);
+
+    let json = r#"{"name": "json", "steps": [{"id": "a", "type": "bang", "template": "x"}]}"#;
+    assert_eq!(parse_definition(json, &engine).unwrap().steps.len(), 1);
+
+    let cycle = r#"{"name": "c", "steps": [
+        {"id": "a", "type": "bang", "template": "x", "depends_on": ["b"]},
+        {"id": "b", "type": "bang", "template": "y", "depends_on": ["a"]}]}"#;
+    assert!(parse_definition(cycle, &engine).is_err());
+    let unknown = r#"{"name": "u", "steps": [{"id": "a", "type": "bang", "template": "x", "depends_on": ["b"]}]}"#;
+    assert!(parse_definition(unknown, &engine).is_err());
+}
+
+#[test]
+fn test_parse_definition_rejects_invalid_step_ids() {
+    let engine = TemplateEngine::new();
+    let with_id = |id: &str| {
+        format!(
+            r#"{{"name": "ids", "steps": [{{"id": "{}", "type": "bang", "template": "x"}}]}}"#,
+            id
+        )
+    };
+    assert!(parse_definition(&with_id("_step_1"), &engine).is_ok());
+    for id in ["", "1step", "step-1", "step 1", "步骤"] {
+        assert!(parse_definition(&with_id(id), &engine).is_err(), "{}", id);
+    }
+    // 步骤 id 与 bang 重名时 !步骤id 的含义不明确
+    let err = parse_definition(&with_id("web"), &engine).unwrap_err();
+    assert!(err.to_string().contains("重名"));
+}
+
+#[tokio::test]
+async fn test_run_workflow_with_condition_and_loop() {
+    let yaml = r#"
+name: 测试
+inputs:
+  - name: topic
+    default: 天气
+steps:
+  - id: list
+    type: bang
+    template: '["北京", "上海"]'
+  - id: each
+    type: loop
+    items: "!list"
+    depends_on: [list]
+    step:
+      type: llm
+      assistant_id: 2
+      prompt: "!index !item !topic"
+  - id: check
+    type: condition
+    value: "!each"
+    operator: contains
+    operand: 上海
+    depends_on: [each]
+    then: [yes]
+    else: [no]
+  - id: "yes"
+    type: script
+    language: echo
+    code: "包含上海"
+  - id: "no"
+    type: script
+    language: echo
+    code: "不包含上海"
+  - id: after_no
+    type: bang
+    template: "只在 no 之后"
+    depends_on: ["no"]
+  - id: done
+    type: bang
+    template: "结果 !yes!no"
+    depends_on: ["yes", "no"]
+"#;
+    let (result, events) = run(yaml, &[("topic", "气温")], CancellationToken::new()).await;
+    assert_eq!(result.unwrap(), "结果 包含上海");
+    assert_eq!(status_of(&events, "each"), Some(StepStatus::Success));
+    assert_eq!(status_of(&events, "yes"), Some(StepStatus::Success));
+    assert_eq!(status_of(&events, "no"), Some(StepStatus::Skipped));
+    assert_eq!(status_of(&events, "after_no"), Some(StepStatus::Skipped));
+    let each_output = events
+        .iter()
+        .find_map(|event| match event {
+            WorkflowEvent::StepFinished {
+                step_id, output, ..
+            } if step_id == "each" => Some(output.clone()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(each_output, r#"["2:0 北京 气温","2:1 上海 气温"]"#);
+}
+
+#[tokio::test]
+async fn test_run_workflow_failure_and_cancel() {
+    let yaml = r#"
+name: 失败
+steps:
+  - id: run
+    type: script
+    language: cobol
+    code: x
+  - id: next
+    type: bang
+    template: y
+    depends_on: [run]
+"#;
+    let (result, events) = run(yaml, &[], CancellationToken::new()).await;
+    assert!(result.unwrap_err().to_string().contains("暂不支持"));
+    assert_eq!(status_of(&events, "run"), Some(StepStatus::Failed));
+    assert_eq!(status_of(&events, "next"), None);
+
+    let cancel_token = CancellationToken::new();
+    cancel_token.cancel();
+    let (result, _) = run(
+        "name: c\nsteps:\n  - id: a\n    type: bang\n    template: x\n",
+        &[],
+        cancel_token,
+    )
+    .await;
+    assert!(result.unwrap_err().to_string().contains("已取消"));
+}
@@ -4,7 +4,6 @@ import AskWindow from "./AskWindow.tsx";
 import ConfigWindow from "./ConfigWindow.tsx";
 import ChatUIWindow from './ChatUIWindow.tsx';
 import PreviewHTMLWindow from './PreviewHTMLWindow.tsx';
-import PreviewReactWindow from './PreviewReactWindow.tsx';
 import PluginWindow from './PluginWindow.tsx';
 import { Toaster } from './components/ui/sonner.tsx';
 
@@ -13,15 +12,16 @@ const windowsMap: Record<string, typeof AskWindow> = {
     config: ConfigWindow,
     chat_ui: ChatUIWindow,
     preview_html: PreviewHTMLWindow,
-    preview_react: PreviewReactWindow,
     plugin: PluginWindow
 }
 
 function App() {
     let win = getCurrentWebviewWindow();
+    // HTML 预览窗口按内容区分，label 为 preview_html_{hash}
+    const windowName = win.label.startsWith("preview_html_") ? "preview_html" : win.label;
 
     return <>
-        {windowsMap[win.label]()}
+        {windowsMap[windowName]()}
         <Toaster richColors />
     </>
 }
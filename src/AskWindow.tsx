@@ -21,6 +21,8 @@ import IconButton from "./components/IconButton";
 import { throttle } from "lodash";
 import { writeText } from "@tauri-apps/plugin-clipboard-manager";
 import CodeBlock from "./components/CodeBlock";
+import PromptParamsForm from "./components/PromptParamsForm";
+import { AssistantDetail, AssistantPromptParam } from "./data/Assistant";
 import { getCaretCoordinates } from "./utils/caretCoordinates";
 const appWindow = getCurrentWebviewWindow()
 
@@ -51,6 +53,9 @@ function AskWindow() {
     const [bangList, setBangList] = useState<string[]>([]);
     const [originalBangList, setOriginalBangList] = useState<string[]>([]);
     const [selectedText, setSelectedText] = useState<string>("");
+    // 快速提问助手的提示词参数
+    const [promptParams, setPromptParams] = useState<AssistantPromptParam[]>([]);
+    const [promptParamValues, setPromptParamValues] = useState<Record<string, string>>({});
 
     const [cursorPosition, setCursorPosition] = useState<{
         top: number;
@@ -325,6 +330,7 @@ function AskWindow() {
                     prompt: query,
                     conversation_id: "",
                     assistant_id: 1,
+                    prompt_params: promptParamValues,
                 },
             }).then((res) => {
                 setMessageId(res.add_message_id);
@@ -350,6 +356,9 @@ function AskWindow() {
                         }
                     },
                 );
+            }).catch((error) => {
+                setAiIsResponsing(false);
+                setResponse("" + error);
             });
         } catch (error) {
             console.error("Error:", error);
@@ -408,6 +417,12 @@ function AskWindow() {
         });
     }, []);
 
+    useEffect(() => {
+        invoke<AssistantDetail>("get_assistant", { assistantId: 1 }).then((assistant) => {
+            setPromptParams(assistant.prompt_params);
+        });
+    }, []);
+
     useEffect(() => {
         invoke<string[]>("get_bang_list").then((bangList) => {
             setBangList(bangList);
@@ -505,7 +520,16 @@ function AskWindow() {
                             />
                         )
                     ) : (
-                        <AskWindowPrepare selectedText={selectedText} />
+                        <>
+                            <PromptParamsForm
+                                params={promptParams}
+                                values={promptParamValues}
+                                onChange={(name, value) =>
+                                    setPromptParamValues((prev) => ({ ...prev, [name]: value }))
+                                }
+                            />
+                            <AskWindowPrepare selectedText={selectedText} />
+                        </>
                     )}
                 </div>
                 <div className="tools" data-tauri-drag-region>
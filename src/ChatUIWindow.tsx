@@ -6,7 +6,8 @@ import ConversationUI from "./components/ConversationUI";
 
 import "./styles/ChatUIWindow.css";
 import { appDataDir } from "@tauri-apps/api/path";
-import { convertFileSrc } from "@tauri-apps/api/core";
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 function ChatUIWindow() {
     const [pluginList, setPluginList] = useState<any[]>([]);
@@ -54,6 +55,21 @@ function ChatUIWindow() {
         initPlugin();
     }, []);
 
+    // 从托盘菜单或定时任务页面打开定时任务生成的对话
+    useEffect(() => {
+        invoke<number | null>("take_pending_conversation").then((conversationId) => {
+            if (conversationId !== null) {
+                setSelectedConversation(conversationId.toString());
+            }
+        });
+        const unlisten = listen<number>("open_conversation", (event) => {
+            setSelectedConversation(event.payload.toString());
+        });
+        return () => {
+            unlisten.then((f) => f());
+        };
+    }, []);
+
     return (
         <div className="chat-ui-window">
             <div className="left-side">
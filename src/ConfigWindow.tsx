@@ -3,9 +3,17 @@ import SideMenu from "./components/SideMenu";
 import LLMProviderConfig from "./components/config/LLMProviderConfig";
 import AssistantConfig from "./components/config/AssistantConfig";
 import FeatureAssistantConfig from "./components/config/FeatureAssistantConfig";
+import EvalConfig from "./components/config/EvalConfig";
+import WorkflowConfig from "./components/config/WorkflowConfig";
+import ScheduleConfig from "./components/config/ScheduleConfig";
+import BangConfig from "./components/config/BangConfig";
 import Model from "./assets/model.svg?react";
 import Assistant from "./assets/assistant.svg?react";
 import Program from "./assets/program.svg?react";
+import Experiment from "./assets/experiment.svg?react";
+import Run from "./assets/run.svg?react";
+import Clock from "./assets/clock.svg?react";
+import Text from "./assets/text.svg?react";
 import { appDataDir } from "@tauri-apps/api/path";
 import { convertFileSrc } from "@tauri-apps/api/core";
 
@@ -21,6 +29,10 @@ const contentMap: Record<string, React.ComponentType<any>> = {
     'llm-provider-config': LLMProviderConfig,
     'assistant-config': AssistantConfig,
     'feature-assistant-config': FeatureAssistantConfig,
+    'eval-config': EvalConfig,
+    'workflow-config': WorkflowConfig,
+    'schedule-config': ScheduleConfig,
+    'bang-config': BangConfig,
 }
 
 function ConfigWindow() {
@@ -28,6 +40,10 @@ function ConfigWindow() {
         { id: 'llm-provider-config', name: '大模型配置', icon: <Model fill="gray" />, iconSelected: <Model fill="black" /> },
         { id: 'assistant-config', name: '个人助手配置', icon: <Assistant fill="gray" />, iconSelected: <Assistant fill="black" /> },
         { id: 'feature-assistant-config', name: '程序助手配置', icon: <Program fill="gray" />, iconSelected: <Program fill="black" /> },
+        { id: 'eval-config', name: '大模型评测', icon: <Experiment fill="gray" />, iconSelected: <Experiment fill="black" /> },
+        { id: 'workflow-config', name: '工作流', icon: <Run fill="gray" />, iconSelected: <Run fill="black" /> },
+        { id: 'schedule-config', name: '定时任务', icon: <Clock fill="gray" />, iconSelected: <Clock fill="black" /> },
+        { id: 'bang-config', name: 'Bang 配置', icon: <Text fill="gray" />, iconSelected: <Text fill="black" /> },
     ];
 
     const [selectedMenu, setSelectedMenu] = useState<string>('llm-provider-config');
@@ -1,19 +1,26 @@
-import { emit, listen } from "@tauri-apps/api/event";
+import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 import { useEffect, useState } from "react";
 
 
 function PreviewHTMLWindow() {
     const [html, setHtml] = useState<string>("");
+    // 再次预览同样的内容时重新加载 iframe
+    const [reloadKey, setReloadKey] = useState<number>(0);
     useEffect(() => {
-        listen<string>("preview_html", (e) => {
-            console.log(e);
+        invoke<string>("get_preview_html").then(setHtml).catch(console.error);
+        // 只接收发送给当前窗口的事件，同时打开多个预览窗口时互不影响
+        const unlisten = getCurrentWebviewWindow().listen<string>("preview_html", (e) => {
             setHtml(e.payload);
+            setReloadKey((key) => key + 1);
         });
-        emit("preview-window-load");
+        return () => {
+            unlisten.then((f) => f());
+        };
     }, []);
     return (
-        // <div dangerouslySetInnerHTML={{__html: html}}></div>
         <iframe 
+            key={reloadKey}
             style={{
                 width: "100vw",
                 height: "100vh",
@@ -27,4 +34,4 @@ function PreviewHTMLWindow() {
     );
 }
 
-export default PreviewHTMLWindow;
\ No newline at end of file
+export default PreviewHTMLWindow;
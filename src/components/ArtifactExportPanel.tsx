@@ -0,0 +1,43 @@
+import React from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { save } from "@tauri-apps/plugin-dialog";
+import { toast } from "sonner";
+import { Button } from "./ui/button";
+
+interface ArtifactExportPanelProps {
+    hash: string;
+    kind: string;
+    onClose: () => void;
+}
+
+const FORMATS = ["png", "svg", "pdf"];
+
+// 把预览窗口中渲染的图表导出成文件
+const ArtifactExportPanel: React.FC<ArtifactExportPanelProps> = ({ hash, kind, onClose }) => {
+    const handleExport = async (format: string) => {
+        const path = await save({
+            defaultPath: `${kind}.${format}`,
+            filters: [{ name: format.toUpperCase(), extensions: [format] }],
+        });
+        if (!path) return;
+        invoke("export_artifact_preview", { hash, format, path })
+            .then(() => toast.success("导出成功"))
+            .catch((e) => toast.error("导出失败: " + e));
+    };
+
+    return (
+        <div className="mx-4 mb-2 p-2 border rounded flex gap-2 items-center">
+            <span className="flex-1 text-sm text-gray-500">{kind} 预览已打开</span>
+            {FORMATS.map((format) => (
+                <Button key={format} variant="outline" onClick={() => handleExport(format)}>
+                    导出 {format.toUpperCase()}
+                </Button>
+            ))}
+            <Button variant="outline" onClick={onClose}>
+                关闭
+            </Button>
+        </div>
+    );
+};
+
+export default ArtifactExportPanel;
@@ -0,0 +1,217 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import {
+    ArtifactDiffLine,
+    ArtifactOutput,
+    ArtifactRunComparison,
+    ArtifactRunRecord,
+    ArtifactRunResult,
+    ArtifactRunSnapshot,
+} from "../data/Artifact";
+import { Button } from "./ui/button";
+import { Input } from "./ui/input";
+
+interface ArtifactRunPanelProps {
+    runId: number;
+    // 代码所在的消息，有消息时可以查看这条消息的运行历史
+    messageId: number | null;
+    // 重新运行后切换到新的执行
+    onRunIdChange: (runId: number) => void;
+    // 把修复错误的追问作为新消息发送
+    onFixError: (prompt: string) => void;
+    onClose: () => void;
+}
+
+const describeResult = (result: ArtifactRunResult) => {
+    const duration = `耗时 ${(result.duration_ms / 1000).toFixed(2)}s`;
+    if (result.killed) return `进程已被结束，${duration}`;
+    if (result.timed_out) return `执行超时，${duration}`;
+    if (result.truncated) return `输出超过限制，${duration}`;
+    if (result.exit_code === null) return `进程没有正常退出，${duration}`;
+    return `退出码 ${result.exit_code}，${duration}`;
+};
+
+export const DiffBlock: React.FC<{ title: string; lines: ArtifactDiffLine[] }> = ({ title, lines }) => (
+    <div>
+        <div className="text-sm font-medium">{title}</div>
+        <pre className="whitespace-pre-wrap text-sm max-h-48 overflow-auto">
+            {lines.map((line, i) => (
+                <div
+                    key={i}
+                    className={
+                        line.tag === "insert" ? "text-green-600" : line.tag === "delete" ? "text-red-600" : ""
+                    }
+                >
+                    {line.tag === "insert" ? "+ " : line.tag === "delete" ? "- " : "  "}
+                    {line.text}
+                </div>
+            ))}
+        </pre>
+    </div>
+);
+
+// 代码运行的实时输出，运行中可以输入 stdin 或者结束进程，结束后可以重新运行、对比历史输出或者让模型修复错误
+const ArtifactRunPanel: React.FC<ArtifactRunPanelProps> = ({ runId, messageId, onRunIdChange, onFixError, onClose }) => {
+    const [output, setOutput] = useState<ArtifactOutput[]>([]);
+    const [result, setResult] = useState<ArtifactRunResult | null>(null);
+    const [input, setInput] = useState<string>("");
+    const [history, setHistory] = useState<ArtifactRunRecord[] | null>(null);
+    const [comparison, setComparison] = useState<{ runId: number; diff: ArtifactRunComparison } | null>(null);
+
+    useEffect(() => {
+        setOutput([]);
+        setResult(null);
+        setHistory(null);
+        setComparison(null);
+        // 按 index 合并，避免监听前已获取的输出重复显示
+        const merge = (lines: ArtifactOutput[]) =>
+            setOutput((current) => {
+                const known = new Set(current.map((l) => l.index));
+                return [...current, ...lines.filter((l) => !known.has(l.index))].sort((a, b) => a.index - b.index);
+            });
+        const unlistenOutput = listen<ArtifactOutput>(`artifact_output_${runId}`, (event) => merge([event.payload]));
+        const unlistenFinished = listen<ArtifactRunResult>(`artifact_finished_${runId}`, (event) =>
+            setResult(event.payload),
+        );
+        Promise.all([unlistenOutput, unlistenFinished]).then(() =>
+            invoke<ArtifactRunSnapshot | null>("get_artifact_run", { runId }).then((snapshot) => {
+                if (!snapshot) return;
+                merge(snapshot.output);
+                if (snapshot.result) setResult(snapshot.result);
+            }),
+        );
+        return () => {
+            unlistenOutput.then((f) => f());
+            unlistenFinished.then((f) => f());
+        };
+    }, [runId]);
+
+    const sendInput = (close: boolean) => {
+        invoke("write_artifact_stdin", { runId, input: input ? input + "\n" : "", close })
+            .then(() => setInput(""))
+            .catch((e) => toast.error("输入失败: " + e));
+    };
+
+    const handleKill = () => {
+        invoke("kill_artifact_run", { runId }).catch((e) => toast.error("结束进程失败: " + e));
+    };
+
+    const handleRerun = () => {
+        invoke<number>("rerun_artifact", { runId })
+            .then(onRunIdChange)
+            .catch((e) => toast.error("重新运行失败: " + e));
+    };
+
+    const handleFixError = () => {
+        invoke<string>("get_artifact_fix_prompt", { runId })
+            .then(onFixError)
+            .catch((e) => toast.error("生成追问失败: " + e));
+    };
+
+    const toggleHistory = () => {
+        if (history) {
+            setHistory(null);
+            setComparison(null);
+            return;
+        }
+        invoke<ArtifactRunRecord[]>("list_artifact_runs", { messageId, limit: 20 })
+            .then((runs) => setHistory(runs.filter((r) => r.id !== runId)))
+            .catch((e) => toast.error("获取运行历史失败: " + e));
+    };
+
+    // 与历史中的某次运行对比，旧的一次作为基准
+    const handleCompare = (otherRunId: number) => {
+        const [oldRunId, newRunId] = otherRunId < runId ? [otherRunId, runId] : [runId, otherRunId];
+        invoke<ArtifactRunComparison>("compare_artifact_runs", { oldRunId, newRunId })
+            .then((diff) => setComparison({ runId: otherRunId, diff }))
+            .catch((e) => toast.error("对比失败: " + e));
+    };
+
+    const failed = result !== null && (result.exit_code !== 0 || result.timed_out || result.stderr.trim() !== "");
+
+    return (
+        <div className="mx-4 mb-2 p-2 border rounded flex flex-col gap-2">
+            <div className="flex gap-2 items-center">
+                <span className="flex-1 text-sm text-gray-500">
+                    {result ? describeResult(result) : "运行中..."}
+                </span>
+                {!result && (
+                    <Button variant="outline" onClick={handleKill}>
+                        结束
+                    </Button>
+                )}
+                {failed && (
+                    <Button variant="outline" onClick={handleFixError}>
+                        修复这个错误
+                    </Button>
+                )}
+                {result && (
+                    <Button variant="outline" onClick={handleRerun}>
+                        重新运行
+                    </Button>
+                )}
+                {messageId !== null && (
+                    <Button variant="outline" onClick={toggleHistory}>
+                        {history ? "隐藏历史" : "运行历史"}
+                    </Button>
+                )}
+                <Button variant="outline" onClick={onClose}>
+                    关闭
+                </Button>
+            </div>
+            <pre className="whitespace-pre-wrap text-sm max-h-48 overflow-auto">
+                {output.map((l) => (
+                    <div key={l.index} className={l.stream === "stderr" ? "text-red-500" : undefined}>
+                        {l.line}
+                    </div>
+                ))}
+                {/* 没有启动成功时只有错误信息 */}
+                {result && output.length === 0 && result.stderr}
+            </pre>
+            {history && (
+                <div className="flex flex-col gap-1 text-sm">
+                    {history.length === 0 && <span className="text-gray-500">这条消息没有其他运行记录</span>}
+                    {history.map((run) => (
+                        <div key={run.id} className="flex gap-2 items-center">
+                            <span className="flex-1">
+                                #{run.id} {new Date(run.created_time).toLocaleString()}{" "}
+                                {run.finished_time ? describeResult(run) : "未结束"}
+                            </span>
+                            <Button variant="ghost" onClick={() => handleCompare(run.id)}>
+                                对比输出
+                            </Button>
+                        </div>
+                    ))}
+                </div>
+            )}
+            {comparison && (
+                <div className="flex flex-col gap-1">
+                    <span className="text-sm text-gray-500">
+                        与 #{comparison.runId} 对比{comparison.diff.same_code ? "" : "（代码不同）"}
+                    </span>
+                    <DiffBlock title="stdout" lines={comparison.diff.stdout} />
+                    <DiffBlock title="stderr" lines={comparison.diff.stderr} />
+                </div>
+            )}
+            {!result && (
+                <div className="flex gap-2">
+                    <Input
+                        placeholder="输入内容，回车发送到 stdin"
+                        value={input}
+                        onChange={(e) => setInput(e.target.value)}
+                        onKeyDown={(e) => {
+                            if (e.key === "Enter") sendInput(false);
+                        }}
+                    />
+                    <Button variant="outline" onClick={() => sendInput(true)}>
+                        结束输入
+                    </Button>
+                </div>
+            )}
+        </div>
+    );
+};
+
+export default ArtifactRunPanel;
@@ -0,0 +1,174 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { open, save } from "@tauri-apps/plugin-dialog";
+import { toast } from "sonner";
+import { ArtifactDetail, ArtifactFileDiff, ArtifactVersion } from "../data/Artifact";
+import { Button } from "./ui/button";
+import { DiffBlock } from "./ArtifactRunPanel";
+
+interface ArtifactWorkspacePanelProps {
+    conversationId: number;
+    onClose: () => void;
+}
+
+const CHANGE_LABELS: Record<ArtifactFileDiff["change"], string> = {
+    added: "新增",
+    removed: "删除",
+    modified: "修改",
+    unchanged: "未变",
+};
+
+// 对话的多文件产物，可以切换版本、查看与上一版本的差异，导出为目录或者 zip
+const ArtifactWorkspacePanel: React.FC<ArtifactWorkspacePanelProps> = ({ conversationId, onClose }) => {
+    const [detail, setDetail] = useState<ArtifactDetail | null>(null);
+    const [version, setVersion] = useState<ArtifactVersion | null>(null);
+    const [selectedPath, setSelectedPath] = useState<string | null>(null);
+    const [diffs, setDiffs] = useState<ArtifactFileDiff[] | null>(null);
+
+    const loadArtifact = () => {
+        invoke<ArtifactDetail | null>("get_conversation_artifact", { conversationId })
+            .then((detail) => {
+                setDetail(detail);
+                setVersion(detail?.latest ?? null);
+                setDiffs(null);
+            })
+            .catch((e) => toast.error("读取产物失败: " + e));
+    };
+
+    useEffect(() => {
+        loadArtifact();
+        const unlisten = listen<{ conversation_id: number }>("artifact_updated", (event) => {
+            if (event.payload.conversation_id === conversationId) {
+                loadArtifact();
+            }
+        });
+        return () => {
+            unlisten.then((f) => f());
+        };
+    }, [conversationId]);
+
+    useEffect(() => {
+        if (version && !version.files.some((f) => f.path === selectedPath)) {
+            setSelectedPath(version.files[0]?.path ?? null);
+        }
+    }, [version]);
+
+    const selectVersion = (value: number) => {
+        if (!detail) return;
+        invoke<ArtifactVersion>("get_artifact_version", { artifactId: detail.artifact.id, version: value })
+            .then((version) => {
+                setVersion(version);
+                setDiffs(null);
+            })
+            .catch((e) => toast.error("读取版本失败: " + e));
+    };
+
+    const toggleDiff = () => {
+        if (diffs) {
+            setDiffs(null);
+            return;
+        }
+        if (!detail || !version) return;
+        invoke<ArtifactFileDiff[]>("diff_artifact_versions", {
+            artifactId: detail.artifact.id,
+            fromVersion: version.version - 1,
+            toVersion: version.version,
+        })
+            .then(setDiffs)
+            .catch((e) => toast.error("比较版本失败: " + e));
+    };
+
+    const handleExport = async (format: "folder" | "zip") => {
+        if (!detail || !version) return;
+        const path =
+            format === "folder"
+                ? await open({ directory: true })
+                : await save({
+                      defaultPath: `${detail.artifact.name}-v${version.version}.zip`,
+                      filters: [{ name: "ZIP", extensions: ["zip"] }],
+                  });
+        if (!path || Array.isArray(path)) return;
+        invoke("export_artifact", { artifactId: detail.artifact.id, version: version.version, format, path })
+            .then(() => toast.success("导出成功"))
+            .catch((e) => toast.error("导出失败: " + e));
+    };
+
+    const selectedFile = version?.files.find((f) => f.path === selectedPath);
+    const selectedDiff = diffs?.find((d) => d.path === selectedPath);
+
+    return (
+        <div className="mx-4 mb-2 p-2 border rounded flex flex-col gap-2">
+            <div className="flex gap-2 items-center">
+                <span className="flex-1 text-sm font-medium">{detail ? detail.artifact.name : "产物"}</span>
+                {detail && version && (
+                    <>
+                        <select
+                            className="border rounded px-2 py-1 text-sm"
+                            value={version.version}
+                            onChange={(e) => selectVersion(Number(e.target.value))}
+                        >
+                            {detail.versions.map((v) => (
+                                <option key={v.version} value={v.version}>
+                                    v{v.version} · {v.paths.length} 个文件
+                                </option>
+                            ))}
+                        </select>
+                        <Button variant="outline" onClick={toggleDiff}>
+                            {diffs ? "查看文件" : "对比上一版本"}
+                        </Button>
+                        <Button variant="outline" onClick={() => handleExport("folder")}>
+                            导出到目录
+                        </Button>
+                        <Button variant="outline" onClick={() => handleExport("zip")}>
+                            导出 ZIP
+                        </Button>
+                    </>
+                )}
+                <Button variant="outline" onClick={onClose}>
+                    关闭
+                </Button>
+            </div>
+            {!version ? (
+                <div className="text-sm text-gray-500">
+                    回答中带文件路径的代码块会自动保存为产物，如 ```java src/Main.java
+                </div>
+            ) : (
+                <div className="flex gap-2 max-h-72">
+                    <div className="w-56 shrink-0 overflow-auto text-sm">
+                        {(diffs ?? version.files).map((item) => (
+                            <div
+                                key={item.path}
+                                className={
+                                    "px-2 py-1 rounded cursor-pointer truncate " +
+                                    (item.path === selectedPath ? "bg-gray-200" : "hover:bg-gray-100")
+                                }
+                                title={item.path}
+                                onClick={() => setSelectedPath(item.path)}
+                            >
+                                {"change" in item && (
+                                    <span className="text-gray-500 mr-1">[{CHANGE_LABELS[item.change]}]</span>
+                                )}
+                                {item.path}
+                            </div>
+                        ))}
+                    </div>
+                    <div className="flex-1 min-w-0 overflow-auto">
+                        {diffs ? (
+                            selectedDiff &&
+                            (selectedDiff.change === "unchanged" ? (
+                                <div className="text-sm text-gray-500">与上一版本相同</div>
+                            ) : (
+                                <DiffBlock title={selectedDiff.path} lines={selectedDiff.lines} />
+                            ))
+                        ) : (
+                            <pre className="whitespace-pre text-sm">{selectedFile?.content}</pre>
+                        )}
+                    </div>
+                </div>
+            )}
+        </div>
+    );
+};
+
+export default ArtifactWorkspacePanel;
@@ -0,0 +1,68 @@
+import React, { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { save } from "@tauri-apps/plugin-dialog";
+import { toast } from "sonner";
+import { Button } from "./ui/button";
+
+interface ConversationExportPanelProps {
+    conversationId: number;
+    conversationName: string;
+    onClose: () => void;
+}
+
+const FORMATS = [
+    { format: "markdown", name: "Markdown", extension: "md" },
+    { format: "html", name: "HTML", extension: "html" },
+    { format: "json", name: "JSON", extension: "json" },
+    {
+        format: "pdf",
+        name: "PDF",
+        extension: "pdf",
+        hint: "PDF 中没有嵌入字体，需要阅读器支持中文宋体，emoji 等字符无法显示",
+    },
+];
+
+// 导出对话，可以只导出当前分支，也可以导出包含所有重新生成回答的完整对话
+const ConversationExportPanel: React.FC<ConversationExportPanelProps> = ({
+    conversationId,
+    conversationName,
+    onClose,
+}) => {
+    const [scope, setScope] = useState<"branch" | "tree">("branch");
+
+    const handleExport = async ({ format, name, extension }: (typeof FORMATS)[number]) => {
+        const path = await save({
+            defaultPath: `${conversationName || "conversation"}.${extension}`,
+            filters: [{ name, extensions: [extension] }],
+        });
+        if (!path) return;
+        invoke("export_conversation", { conversationId, format, scope, path })
+            .then(() => toast.success("导出成功"))
+            .catch((e) => toast.error("导出失败: " + e));
+    };
+
+    return (
+        <div className="mx-4 mb-2 p-2 border rounded flex gap-2 items-center">
+            <span className="text-sm text-gray-500">导出对话</span>
+            <select
+                className="border rounded px-2 py-1 text-sm"
+                value={scope}
+                onChange={(e) => setScope(e.target.value as "branch" | "tree")}
+            >
+                <option value="branch">当前分支</option>
+                <option value="tree">完整对话（含重新生成）</option>
+            </select>
+            <span className="flex-1" />
+            {FORMATS.map((item) => (
+                <Button key={item.format} variant="outline" title={item.hint} onClick={() => handleExport(item)}>
+                    {item.name}
+                </Button>
+            ))}
+            <Button variant="outline" onClick={onClose}>
+                关闭
+            </Button>
+        </div>
+    );
+};
+
+export default ConversationExportPanel;
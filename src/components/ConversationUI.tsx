@@ -17,16 +17,23 @@ import {
     FileInfo,
     Message,
 } from "../data/Conversation";
+import { AssistantDetail, AssistantPromptParam } from "../data/Assistant";
 import "katex/dist/katex.min.css";
 import { listen } from "@tauri-apps/api/event";
 import { throttle } from "lodash";
 import NewChatComponent from "./NewChatComponent";
+import PromptParamsForm from "./PromptParamsForm";
 import FileDropArea from "./FileDropArea";
 import MessageItem from "./MessageItem";
 import ConversationTitle from "./conversation/ConversationTitle";
 import useFileDropHandler from "../hooks/useFileDropHandler";
 import InputArea from "./conversation/InputArea";
 import FormDialog from "./FormDialog";
+import ArtifactRunPanel from "./ArtifactRunPanel";
+import ArtifactExportPanel from "./ArtifactExportPanel";
+import ArtifactWorkspacePanel from "./ArtifactWorkspacePanel";
+import ConversationExportPanel from "./ConversationExportPanel";
+import { ArtifactStarted } from "../data/Artifact";
 import useConversationManager from "../hooks/useConversationManager";
 
 interface AssistantListItem {
@@ -464,8 +471,10 @@ function ConversationUI({
             }
 
             const assistantData = assistants.find(a => a.id === +assistantId);
-            if (assistantData?.assistant_type !== 0) {
-                assistantTypePluginMap.get(assistantData?.assistant_type ?? 0)?.onAssistantTypeRun(assistantRunApi);
+            // 插件注册的助手类型由插件处理，内置的助手类型由后端按类型执行
+            const assistantTypePlugin = assistantTypePluginMap.get(assistantData?.assistant_type ?? 0);
+            if (assistantData?.assistant_type !== 0 && assistantTypePlugin) {
+                assistantTypePlugin.onAssistantTypeRun(assistantRunApi);
             } else {
                 try {
                     const userMessage = {
@@ -487,6 +496,7 @@ function ConversationUI({
                             conversation_id: conversationId,
                             assistant_id: +assistantId,
                             attachment_list: fileInfoList?.map((i) => i.id),
+                            prompt_params: promptParamValues,
                         },
                     }).then((res) => {
                         console.log("ask ai response", res);
@@ -566,6 +576,47 @@ function ConversationUI({
                                 }
                             },
                         );
+
+                        // 多模型对比助手其他模型的回答展示在该消息下方
+                        (res.compare_message_ids ?? []).forEach((compareMessageId) => {
+                            const unlisten = listen(
+                                `message_${compareMessageId}`,
+                                (event) => {
+                                    const payload = event.payload as string;
+                                    if (payload === "Tea::Event::MessageFinish") {
+                                        unlisten.then((f) => f());
+                                        return;
+                                    }
+                                    setMessages((prevMessages) =>
+                                        prevMessages.map((msg) => {
+                                            if (msg.id !== res.add_message_id) {
+                                                return msg;
+                                            }
+                                            const compare = [...(msg.compare ?? [])];
+                                            const index = compare.findIndex(
+                                                (r) => r.id === compareMessageId,
+                                            );
+                                            if (index === -1) {
+                                                compare.push({
+                                                    ...msg,
+                                                    id: compareMessageId,
+                                                    content: payload,
+                                                    llm_model_name: null,
+                                                    regenerate: null,
+                                                    compare: null,
+                                                });
+                                            } else {
+                                                compare[index] = {
+                                                    ...compare[index],
+                                                    content: payload,
+                                                };
+                                            }
+                                            return { ...msg, compare };
+                                        }),
+                                    );
+                                },
+                            );
+                        });
                     });
                 } catch (error) {
                     toast.error("发送消息失败: " + error);
@@ -577,12 +628,55 @@ function ConversationUI({
         }
     }, 200);
 
+    // 修复代码错误的追问填入输入框后自动发送
+    const [pendingFixPrompt, setPendingFixPrompt] = useState<string | null>(null);
+    const handleFixError = (prompt: string) => {
+        if (aiIsResponsing) {
+            toast.error("请等待当前回答完成");
+            return;
+        }
+        setInputText(prompt);
+        setPendingFixPrompt(prompt);
+    };
+    useEffect(() => {
+        if (pendingFixPrompt !== null && inputText === pendingFixPrompt) {
+            setPendingFixPrompt(null);
+            handleSend();
+        }
+    }, [inputText, pendingFixPrompt]);
+
     const [selectedAssistant, setSelectedAssistant] = useState(-1);
 
-    const handleArtifact = useCallback((lang: string, inputStr: string) => {
-        invoke("run_artifacts", { lang, inputStr })
-            .then((res) => {
-                console.log(res);
+    // 新对话选择的助手的提示词参数
+    const [promptParams, setPromptParams] = useState<AssistantPromptParam[]>([]);
+    const [promptParamValues, setPromptParamValues] = useState<Record<string, string>>({});
+    useEffect(() => {
+        setPromptParamValues({});
+        if (selectedAssistant === -1) {
+            setPromptParams([]);
+            return;
+        }
+        invoke<AssistantDetail>("get_assistant", { assistantId: selectedAssistant })
+            .then((assistant) => setPromptParams(assistant.prompt_params))
+            .catch(() => setPromptParams([]));
+    }, [selectedAssistant]);
+
+    // 正在显示输出的代码运行以及代码所在的消息
+    const [artifactRunId, setArtifactRunId] = useState<number | null>(null);
+    const [artifactMessageId, setArtifactMessageId] = useState<number | null>(null);
+    // 可以导出的图表预览
+    const [showArtifactWorkspace, setShowArtifactWorkspace] = useState<boolean>(false);
+    const [showConversationExport, setShowConversationExport] = useState<boolean>(false);
+    const [artifactPreview, setArtifactPreview] = useState<{ hash: string; kind: string } | null>(null);
+    const handleArtifact = useCallback((lang: string, inputStr: string, messageId?: number) => {
+        invoke<ArtifactStarted | null>("run_artifacts", { lang, inputStr, messageId: messageId ?? null })
+            .then((started) => {
+                if (started?.type === "process") {
+                    setArtifactRunId(started.run_id);
+                    setArtifactMessageId(messageId ?? null);
+                } else if (started?.type === "preview" && started.exportable) {
+                    setArtifactPreview({ hash: started.hash, kind: started.kind });
+                }
             })
             .catch((error) => {
                 toast.error("运行失败: " + JSON.stringify(error));
@@ -884,6 +978,8 @@ function ConversationUI({
                 <ConversationTitle
                     onEdit={openFormDialog}
                     onDelete={handleDeleteConversation}
+                    onToggleArtifact={() => setShowArtifactWorkspace((show) => !show)}
+                    onExport={() => setShowConversationExport((show) => !show)}
                     conversation={conversation}
                 />
             ) : null}
@@ -899,6 +995,17 @@ function ConversationUI({
                         setSelectedAssistant={setSelectedAssistant}
                     />
                 )}
+                {!conversationId && (
+                    <div className="w-60 m-auto mt-4">
+                        <PromptParamsForm
+                            params={promptParams}
+                            values={promptParamValues}
+                            onChange={(name, value) =>
+                                setPromptParamValues((prev) => ({ ...prev, [name]: value }))
+                            }
+                        />
+                    </div>
+                )}
                 <div className="message-anchor"></div>
                 <div ref={messagesEndRef} />
             </div>
@@ -909,6 +1016,39 @@ function ConversationUI({
                 />
             ) : null}
 
+            {conversationId && showConversationExport ? (
+                <ConversationExportPanel
+                    conversationId={+conversationId}
+                    conversationName={conversation?.name ?? ""}
+                    onClose={() => setShowConversationExport(false)}
+                />
+            ) : null}
+
+            {conversationId && showArtifactWorkspace ? (
+                <ArtifactWorkspacePanel
+                    conversationId={+conversationId}
+                    onClose={() => setShowArtifactWorkspace(false)}
+                />
+            ) : null}
+
+            {artifactPreview !== null ? (
+                <ArtifactExportPanel
+                    hash={artifactPreview.hash}
+                    kind={artifactPreview.kind}
+                    onClose={() => setArtifactPreview(null)}
+                />
+            ) : null}
+
+            {artifactRunId !== null ? (
+                <ArtifactRunPanel
+                    runId={artifactRunId}
+                    messageId={artifactMessageId}
+                    onRunIdChange={setArtifactRunId}
+                    onFixError={handleFixError}
+                    onClose={() => setArtifactRunId(null)}
+                />
+            ) : null}
+
             <InputArea
                 inputText={inputText}
                 setInputText={setInputText}
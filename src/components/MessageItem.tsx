@@ -14,6 +14,7 @@ import Refresh from "../assets/refresh.svg?react";
 import CodeBlock from "./CodeBlock";
 import MessageFileAttachment from "./MessageFileAttachment";
 import MessageWebContent from "./conversation/MessageWebContent";
+import MessageFeedback from "./conversation/MessageFeedback";
 
 interface CustomComponents extends Components {
     thinking: React.ElementType;
@@ -186,7 +187,9 @@ const MessageItem = React.memo(
                                 return match ? (
                                     <CodeBlock
                                         language={match[1]}
-                                        onCodeRun={onCodeRun}
+                                        onCodeRun={(lang: string, code: string) =>
+                                            onCodeRun(lang, code, message.id)
+                                        }
                                     >
                                         {String(children).replace(/\n$/, "")}
                                     </CodeBlock>
@@ -244,8 +247,18 @@ const MessageItem = React.memo(
                     </div>
                 ) : null}
 
+                {message.message_type === "assistant" && message.id > 0 ? (
+                    <MessageFeedback
+                        messageId={
+                            currentMessageIndex > 1
+                                ? message.regenerate[currentMessageIndex - 2].id
+                                : message.id
+                        }
+                    />
+                ) : null}
+
                 <div className="message-item-button-container">
-                    {message.message_type === "assistant" ? (
+                    {message.message_type === "assistant" && onMessageRegenerate ? (
                         <IconButton
                             icon={<Refresh fill="black" />}
                             onClick={onMessageRegenerate}
@@ -262,6 +275,19 @@ const MessageItem = React.memo(
                         onClick={handleCopy}
                     />
                 </div>
+
+                {message.compare?.length > 0 ? (
+                    <div className="message-compare-list">
+                        {message.compare.map((compareMessage: any) => (
+                            <div key={compareMessage.id}>
+                                <div className="message-compare-title">
+                                    {compareMessage.llm_model_name ?? "对比回答"}
+                                </div>
+                                <MessageItem message={compareMessage} onCodeRun={onCodeRun} />
+                            </div>
+                        ))}
+                    </div>
+                ) : null}
             </div>
         );
     },
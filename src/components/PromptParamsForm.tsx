@@ -0,0 +1,84 @@
+import React from "react";
+import { AssistantPromptParam } from "../data/Assistant";
+import { Input } from "./ui/input";
+import { Textarea } from "./ui/textarea";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "./ui/select";
+
+interface PromptParamsFormProps {
+    params: AssistantPromptParam[];
+    values: Record<string, string>;
+    onChange: (name: string, value: string) => void;
+}
+
+// select 类型的 param_value 为逗号分隔的选项
+export const getSelectOptions = (param: AssistantPromptParam) =>
+    (param.param_value ?? "").split(",").map((o) => o.trim()).filter((o) => o !== "");
+
+// 提问时填写助手的提示词参数，未填写的参数由后端使用默认值
+const PromptParamsForm: React.FC<PromptParamsFormProps> = ({ params, values, onChange }) => {
+    if (params.length === 0) {
+        return null;
+    }
+
+    return (
+        <div className="prompt-params-form flex flex-col gap-2 w-full">
+            {params.map((param) => {
+                const value = values[param.param_name] ?? "";
+                const placeholder = param.param_value ?? "";
+                switch (param.param_type) {
+                    case "multiline":
+                        return (
+                            <Textarea
+                                key={param.param_name}
+                                value={value}
+                                placeholder={`${param.param_name}: ${placeholder}`}
+                                onChange={(e) => onChange(param.param_name, e.target.value)}
+                            />
+                        );
+                    case "number":
+                        return (
+                            <Input
+                                key={param.param_name}
+                                type="number"
+                                value={value}
+                                placeholder={`${param.param_name}: ${placeholder}`}
+                                onChange={(e) => onChange(param.param_name, e.target.value)}
+                            />
+                        );
+                    case "select": {
+                        const options = getSelectOptions(param);
+                        return (
+                            <Select
+                                key={param.param_name}
+                                value={value || options[0]}
+                                onValueChange={(v) => onChange(param.param_name, v)}
+                            >
+                                <SelectTrigger>
+                                    <SelectValue placeholder={param.param_name} />
+                                </SelectTrigger>
+                                <SelectContent>
+                                    {options.map((option) => (
+                                        <SelectItem key={option} value={option}>
+                                            {option}
+                                        </SelectItem>
+                                    ))}
+                                </SelectContent>
+                            </Select>
+                        );
+                    }
+                    default:
+                        return (
+                            <Input
+                                key={param.param_name}
+                                value={value}
+                                placeholder={`${param.param_name}: ${placeholder}`}
+                                onChange={(e) => onChange(param.param_name, e.target.value)}
+                            />
+                        );
+                }
+            })}
+        </div>
+    );
+};
+
+export default PromptParamsForm;
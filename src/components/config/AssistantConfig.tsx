@@ -1,13 +1,18 @@
 import React, { useCallback, useEffect, useState, useMemo } from "react";
 import { toast } from "sonner";
 import { invoke } from "@tauri-apps/api/core";
-import { AssistantDetail, AssistantListItem } from "../../data/Assistant";
+import { open, save, ask } from "@tauri-apps/plugin-dialog";
+import { AssistantDetail, AssistantListItem, AssistantPromptParam } from "../../data/Assistant";
 import { Button } from "../ui/button";
 import ConfigForm from "../ConfigForm";
 import ConfirmDialog from "../ConfirmDialog";
 import AddAssistantDialog from "./AddAssistantDialog";
 import EditAssistantDialog from "./EditAssistantDialog";
-import { AssistantType } from "../../types/assistant";
+import PromptParamsEditor from "./PromptParamsEditor";
+import PromptVersionHistory from "./PromptVersionHistory";
+import PromptOptimizer from "./PromptOptimizer";
+import FeedbackStatsCard from "./FeedbackStatsCard";
+import { AssistantConfigField, AssistantType, BUILTIN_ASSISTANT_TYPES } from "../../types/assistant";
 import { validateConfig } from "../../utils/validate";
 
 import "../../styles/AssistantConfig.css";
@@ -32,7 +37,7 @@ const AssistantConfig: React.FC<AssistantConfigProps> = ({ pluginList }) => {
     // 插件名称
     const [assistantTypeNameMap, setAssistantTypeNameMap] = useState<
         Map<number, string>
-    >(new Map<number, string>());
+    >(new Map<number, string>(BUILTIN_ASSISTANT_TYPES.map((type) => [type.code, type.name])));
     // 插件自定义字段
     const [assistantTypeCustomField, setAssistantTypeCustomField] = useState<
         Map<string, Record<string, any>>
@@ -110,9 +115,7 @@ const AssistantConfig: React.FC<AssistantConfigProps> = ({ pluginList }) => {
     );
 
     // 助手类型
-    const [assistantTypes, setAssistantTypes] = useState<AssistantType[]>([
-        { code: 0, name: "普通对话助手" },
-    ]);
+    const [assistantTypes, setAssistantTypes] = useState<AssistantType[]>(BUILTIN_ASSISTANT_TYPES);
     useEffect(() => {
         // 加载助手类型的插件
         pluginList
@@ -155,6 +158,17 @@ const AssistantConfig: React.FC<AssistantConfigProps> = ({ pluginList }) => {
                 toast.error("获取助手列表失败: " + error);
             });
     }, []);
+    // 当前助手类型的配置项名称
+    const [configSchemaLabel, setConfigSchemaLabel] = useState<Map<string, string>>(new Map());
+    useEffect(() => {
+        if (!currentAssistant) return;
+        invoke<AssistantConfigField[]>("get_assistant_type_config_schema", {
+            assistantType: currentAssistant.assistant.assistant_type,
+        })
+            .then((fields) => setConfigSchemaLabel(new Map(fields.map((f) => [f.name, f.label]))))
+            .catch(() => setConfigSchemaLabel(new Map()));
+    }, [currentAssistant?.assistant.id]);
+
     // 使用 useCallback 缓存回调函数
     const onSave = useCallback((assistant: AssistantDetail) => {
         return invoke<void>("save_assistant", { assistantDetail: assistant });
@@ -314,6 +328,35 @@ const AssistantConfig: React.FC<AssistantConfigProps> = ({ pluginList }) => {
         [currentAssistant],
     );
 
+    // 修改提示词参数
+    const handlePromptParamsChange = useCallback(
+        (params: AssistantPromptParam[]) => {
+            setCurrentAssistant((prev) => {
+                if (!prev) return prev;
+                return { ...prev, prompt_params: params };
+            });
+        },
+        [],
+    );
+
+    // 保存后刷新提示词版本列表
+    const [promptVersionRefreshKey, setPromptVersionRefreshKey] = useState<number>(0);
+
+    // 回滚或优化提示词后同步表单内容
+    const syncAssistantPromptForm = useCallback(
+        (assistant: AssistantDetail) => {
+            setCurrentAssistant(assistant);
+            form.setValue("prompt", assistant.prompts[0]?.prompt ?? "");
+            assistant.model_configs.forEach((config) => {
+                form.setValue(
+                    config.name,
+                    config.value_type === "boolean" ? config.value == "true" : config.value,
+                );
+            });
+        },
+        [form],
+    );
+
     // 保存助手
     const handleAssistantFormSave = useCallback(() => {
         if (!currentAssistant) return;
@@ -363,7 +406,10 @@ const AssistantConfig: React.FC<AssistantConfigProps> = ({ pluginList }) => {
                 },
             ],
         })
-            .then(() => toast.success("保存成功"))
+            .then(() => {
+                toast.success("保存成功");
+                setPromptVersionRefreshKey((key) => key + 1);
+            })
             .catch((error) => toast.error("保存失败: " + error));
     }, [currentAssistant, form, onSave]);
 
@@ -503,6 +549,7 @@ const AssistantConfig: React.FC<AssistantConfigProps> = ({ pluginList }) => {
                                 : ("input" as const),
                         label:
                             assistantTypeCustomLabel.get(config.name) ??
+                            configSchemaLabel.get(config.name) ??
                             config.name,
                         value:
                             config.value_type === "boolean"
@@ -572,8 +619,54 @@ const AssistantConfig: React.FC<AssistantConfigProps> = ({ pluginList }) => {
         assistantTypeNameMap,
         assistantTypeCustomField,
         assistantTypeCustomLabel,
+        configSchemaLabel,
     ]);
 
+    // 导出当前助手
+    const handleExport = useCallback(async () => {
+        if (!currentAssistant) return;
+        const path = await save({
+            defaultPath: `${currentAssistant.assistant.name}.json`,
+            filters: [{ name: "Assistant", extensions: ["json"] }],
+        });
+        if (!path) return;
+        invoke("export_assistant", {
+            assistantId: currentAssistant.assistant.id,
+            path,
+        })
+            .then(() => toast.success("导出成功"))
+            .catch((error) => toast.error("导出失败: " + error));
+    }, [currentAssistant]);
+
+    // 导入助手，存在同名助手时询问覆盖还是重命名
+    const handleImport = useCallback(async () => {
+        const path = await open({
+            multiple: false,
+            filters: [{ name: "Assistant", extensions: ["json"] }],
+        });
+        if (!path) return;
+        const overwrite = await ask("如果存在同名助手，是否覆盖它的配置？选择否将以新名称导入。", {
+            title: "导入助手",
+            okLabel: "覆盖",
+            cancelLabel: "重命名",
+        });
+        invoke<{ assistant: AssistantDetail; warnings: string[] }>("import_assistant", {
+            path,
+            conflictStrategy: overwrite ? "overwrite" : "rename",
+        })
+            .then(({ assistant, warnings }) => {
+                setAssistants((prev) => [
+                    ...prev.filter((a) => a.id !== assistant.assistant.id),
+                    { id: assistant.assistant.id, name: assistant.assistant.name },
+                ]);
+                // 覆盖的可能是当前正在编辑的助手，需要重新选择以加载新的配置
+                setCurrentAssistant(null);
+                toast.success("导入成功");
+                warnings.forEach((warning) => toast.warning(warning));
+            })
+            .catch((error) => toast.error("导入失败: " + error));
+    }, []);
+
     // 添加新的处理函数
     const handleAssistantAdded = (assistantDetail: AssistantDetail) => {
         setAssistants((prev) => [
@@ -614,6 +707,14 @@ const AssistantConfig: React.FC<AssistantConfigProps> = ({ pluginList }) => {
                     assistantTypes={assistantTypes}
                     onAssistantAdded={handleAssistantAdded}
                 />
+                <Button variant="outline" onClick={handleImport}>
+                    导入助手
+                </Button>
+                {currentAssistant && (
+                    <Button variant="outline" onClick={handleExport}>
+                        导出助手
+                    </Button>
+                )}
             </div>
             {currentAssistant && (
                 <ConfigForm
@@ -633,6 +734,31 @@ const AssistantConfig: React.FC<AssistantConfigProps> = ({ pluginList }) => {
                     useFormReturn={form}
                 />
             )}
+            {currentAssistant && (
+                <PromptParamsEditor
+                    assistantId={currentAssistant.assistant.id}
+                    assistantPromptId={currentAssistant.prompts[0]?.id ?? 0}
+                    params={currentAssistant.prompt_params}
+                    onChange={handlePromptParamsChange}
+                />
+            )}
+            {currentAssistant && (
+                <PromptOptimizer
+                    assistantId={currentAssistant.assistant.id}
+                    onSaved={(assistant) => {
+                        syncAssistantPromptForm(assistant);
+                        setPromptVersionRefreshKey((key) => key + 1);
+                    }}
+                />
+            )}
+            {currentAssistant && <FeedbackStatsCard assistantId={currentAssistant.assistant.id} />}
+            {currentAssistant && (
+                <PromptVersionHistory
+                    assistantId={currentAssistant.assistant.id}
+                    refreshKey={promptVersionRefreshKey}
+                    onRollback={syncAssistantPromptForm}
+                />
+            )}
             <ConfirmDialog
                 title="确认操作"
                 confirmText="该操作不可逆，确认执行删除助手操作吗？删除后，配置将会删除，并且该助手的对话将转移到 快速使用助手 ，且不可恢复。"
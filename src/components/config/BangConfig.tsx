@@ -0,0 +1,257 @@
+import React, { useCallback, useEffect, useMemo, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { useForm } from "react-hook-form";
+import { CustomBang, CustomBangKind, FeatureConfigListItem } from "../../data/Bang";
+import ConfigForm from "../ConfigForm";
+import CustomSelect from "../CustomSelect";
+import ConfirmDialog from "../ConfirmDialog";
+import { Badge } from "../ui/badge";
+import { Button } from "../ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "../ui/card";
+import { Input } from "../ui/input";
+import { Textarea } from "../ui/textarea";
+
+const emptyBang = (): CustomBang => ({
+    id: 0,
+    name: "",
+    aliases: [],
+    description: "",
+    bang_kind: "text",
+    body: "",
+    json_path: null,
+});
+
+const KIND_HINTS: Record<CustomBangKind, string> = {
+    text: "文本片段，例如：请将以下内容翻译成英文: !input",
+    command: "程序和参数，例如：git log -n 5 !arg1，直接执行程序不经过 shell，不支持管道、重定向、&& 等 shell 语法，每个参数单独替换",
+    http: "请求地址，例如：https://api.example.com/items?q=!input，可以填写 JSON 路径提取结果",
+    file: "文件路径，例如：/path/to/notes/!arg1.md，只能读取 Bang 配置中允许的目录",
+};
+
+// bang 相关配置和用户自定义的 bang，自定义 bang 可以通过 !input、!arg1、!参数名 引用调用时的参数
+const BangConfig: React.FC = () => {
+    const [bangConfig, setBangConfig] = useState<Map<string, string>>(new Map());
+    const [customBangs, setCustomBangs] = useState<CustomBang[]>([]);
+    const [editing, setEditing] = useState<CustomBang | null>(null);
+    const [confirmDeleteOpen, setConfirmDeleteOpen] = useState<boolean>(false);
+
+    const settingsFormReturnData = useForm({
+        defaultValues: {
+            timeout_secs: "30",
+            cache_ttl_secs: "120",
+            file_allowlist: "",
+            env_allowlist: "",
+            shell_allowlist: "",
+            web_block_private: "true",
+        },
+    });
+
+    const loadCustomBangs = useCallback(() => {
+        invoke<CustomBang[]>("list_custom_bangs")
+            .then(setCustomBangs)
+            .catch((e) => toast.error("获取自定义 bang 失败: " + e));
+    }, []);
+
+    useEffect(() => {
+        loadCustomBangs();
+        invoke<FeatureConfigListItem[]>("get_all_feature_config")
+            .then((list) => {
+                const config = new Map(
+                    list.filter((c) => c.feature_code === "bang").map((c) => [c.key, c.value]),
+                );
+                setBangConfig(config);
+                settingsFormReturnData.reset({
+                    timeout_secs: config.get("timeout_secs") || "30",
+                    cache_ttl_secs: config.get("cache_ttl_secs") || "120",
+                    file_allowlist: config.get("file_allowlist") || "",
+                    env_allowlist: config.get("env_allowlist") || "",
+                    shell_allowlist: config.get("shell_allowlist") || "",
+                    web_block_private: config.get("web_block_private") || "true",
+                });
+            })
+            .catch((e) => toast.error("获取配置失败: " + e));
+    }, []);
+
+    const handleSaveSettings = useCallback(() => {
+        // 搜索等其他 bang 配置一起保存，避免被覆盖
+        const config = { ...Object.fromEntries(bangConfig), ...settingsFormReturnData.getValues() };
+        invoke("save_feature_config", { featureCode: "bang", config })
+            .then(() => {
+                setBangConfig(new Map(Object.entries(config)));
+                toast.success("保存成功");
+            })
+            .catch((e) => toast.error("保存失败: " + e));
+    }, [bangConfig, settingsFormReturnData]);
+
+    const settingsFormConfig = useMemo(
+        () => ({
+            timeout_secs: {
+                type: "input" as const,
+                label: "超时时间（秒）",
+                tooltip: "单个 bang 的最长执行时间，超时后输出提示文本",
+            },
+            cache_ttl_secs: {
+                type: "input" as const,
+                label: "缓存时间（秒）",
+                tooltip: "网页、搜索等 bang 的结果缓存时间，填 0 不缓存，执行失败的结果不会缓存",
+            },
+            file_allowlist: {
+                type: "textarea" as const,
+                label: "允许读取的目录",
+                tooltip: "每行一个目录，!file、!dir 和文件类自定义 bang 只能读取这些目录，不填时不允许读取任何文件",
+            },
+            env_allowlist: {
+                type: "input" as const,
+                label: "允许读取的环境变量",
+                tooltip: "逗号分隔，!env 只能读取这些环境变量",
+            },
+            shell_allowlist: {
+                type: "input" as const,
+                label: "允许执行的程序",
+                tooltip: "逗号分隔，例如 git, ls，!shell 只能执行这些程序",
+            },
+            web_block_private: {
+                type: "select" as const,
+                label: "禁止访问内网地址",
+                tooltip: "网页、搜索和 HTTP 类自定义 bang 不能访问本机和内网地址，使用本机部署的 SearxNG 时需要关闭",
+                options: [
+                    { value: "true", label: "禁止" },
+                    { value: "false", label: "允许" },
+                ],
+            },
+        }),
+        [],
+    );
+
+    const handleSave = () => {
+        if (!editing) return;
+        const bang = { ...editing, aliases: editing.aliases.map((a) => a.trim()).filter((a) => a) };
+        invoke<number>("save_custom_bang", { bang })
+            .then((id) => {
+                setEditing({ ...bang, id });
+                loadCustomBangs();
+                toast.success("保存成功");
+            })
+            .catch((e) => toast.error("保存失败: " + e));
+    };
+
+    const handleDelete = () => {
+        if (!editing) return;
+        invoke("delete_custom_bang", { id: editing.id })
+            .then(() => {
+                setConfirmDeleteOpen(false);
+                setEditing(null);
+                loadCustomBangs();
+            })
+            .catch((e) => toast.error("删除失败: " + e));
+    };
+
+    return (
+        <div className="p-4 flex flex-col gap-4">
+            <ConfigForm
+                title="Bang 配置"
+                description="读取本地数据的 bang 只在这里允许的范围内生效"
+                config={settingsFormConfig}
+                layout="default"
+                onSave={handleSaveSettings}
+                useFormReturn={settingsFormReturnData}
+            />
+
+            <Card>
+                <CardHeader>
+                    <CardTitle>自定义 Bang</CardTitle>
+                    <CardDescription>名称与内置 bang 相同时不会生效</CardDescription>
+                </CardHeader>
+                <CardContent className="flex flex-col gap-2">
+                    {customBangs.map((bang) => (
+                        <div key={bang.id} className="flex gap-2 items-center">
+                            <span className="flex-1 text-sm">
+                                !{bang.name} <Badge variant="outline">{bang.bang_kind}</Badge>{" "}
+                                <span className="text-gray-500">{bang.description}</span>
+                            </span>
+                            <Button variant="outline" onClick={() => setEditing(bang)}>
+                                编辑
+                            </Button>
+                        </div>
+                    ))}
+                    <div>
+                        <Button onClick={() => setEditing(emptyBang())}>新建 Bang</Button>
+                    </div>
+                </CardContent>
+            </Card>
+
+            {editing && (
+                <Card>
+                    <CardHeader>
+                        <CardTitle>{editing.id === 0 ? "新建 Bang" : `!${editing.name}`}</CardTitle>
+                        <CardDescription>{KIND_HINTS[editing.bang_kind]}</CardDescription>
+                    </CardHeader>
+                    <CardContent className="flex flex-col gap-2">
+                        <Input
+                            placeholder="名称，只能包含字母、数字和下划线"
+                            value={editing.name}
+                            onChange={(e) => setEditing({ ...editing, name: e.target.value })}
+                        />
+                        <Input
+                            placeholder="别名，逗号分隔"
+                            value={editing.aliases.join(",")}
+                            onChange={(e) =>
+                                setEditing({
+                                    ...editing,
+                                    aliases: e.target.value.split(","),
+                                })
+                            }
+                        />
+                        <Input
+                            placeholder="描述"
+                            value={editing.description}
+                            onChange={(e) => setEditing({ ...editing, description: e.target.value })}
+                        />
+                        <CustomSelect
+                            options={[
+                                { value: "text", label: "文本" },
+                                { value: "command", label: "命令" },
+                                { value: "http", label: "HTTP 请求" },
+                                { value: "file", label: "文件" },
+                            ]}
+                            value={editing.bang_kind}
+                            onChange={(value) => setEditing({ ...editing, bang_kind: value as CustomBangKind })}
+                        />
+                        <Textarea
+                            className="font-mono min-h-[120px]"
+                            value={editing.body}
+                            onChange={(e) => setEditing({ ...editing, body: e.target.value })}
+                        />
+                        {editing.bang_kind === "http" && (
+                            <Input
+                                placeholder="JSON 路径，例如 data.items[0].name，不填时返回完整响应"
+                                className="font-mono"
+                                value={editing.json_path ?? ""}
+                                onChange={(e) => setEditing({ ...editing, json_path: e.target.value || null })}
+                            />
+                        )}
+                        <div className="flex gap-2">
+                            <Button onClick={handleSave}>保存</Button>
+                            {editing.id !== 0 && (
+                                <Button variant="outline" onClick={() => setConfirmDeleteOpen(true)}>
+                                    删除
+                                </Button>
+                            )}
+                        </div>
+                    </CardContent>
+                </Card>
+            )}
+
+            <ConfirmDialog
+                title="确认操作"
+                confirmText="确认删除这个自定义 bang 吗？"
+                onConfirm={handleDelete}
+                onCancel={() => setConfirmDeleteOpen(false)}
+                isOpen={confirmDeleteOpen}
+            />
+        </div>
+    );
+};
+
+export default BangConfig;
@@ -0,0 +1,448 @@
+import React, { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { open } from "@tauri-apps/plugin-dialog";
+import { toast } from "sonner";
+import {
+    EvalModel,
+    EvalModelSummary,
+    EvalQuestion,
+    EvalQuestionSet,
+    EvalResult,
+    EvalRun,
+    EvalScorer,
+} from "../../data/Eval";
+import CustomSelect from "../CustomSelect";
+import ConfirmDialog from "../ConfirmDialog";
+import { Badge } from "../ui/badge";
+import { Button } from "../ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "../ui/card";
+import { Checkbox } from "../ui/checkbox";
+import { Input } from "../ui/input";
+import { Textarea } from "../ui/textarea";
+
+interface ModelForSelect {
+    name: string;
+    code: string;
+    id: number;
+    llm_provider_id: number;
+}
+
+const scorerOptions: { value: EvalScorer; label: string }[] = [
+    { value: "exact", label: "完全匹配" },
+    { value: "regex", label: "正则匹配" },
+    { value: "llm_judge", label: "模型评分" },
+];
+
+const expectedPlaceholder: Record<EvalScorer, string> = {
+    exact: "期望答案",
+    regex: "正则表达式，例如 ^\\d{4}-\\d{2}-\\d{2}$",
+    llm_judge: "评分标准，例如：回答需要指出 xxx，并给出示例代码",
+};
+
+const modelKey = (model: EvalModel) => `${model.provider_id}%%${model.model_code}`;
+
+const parseModelKey = (key: string): EvalModel => {
+    const [provider_id, model_code] = key.split("%%");
+    return { provider_id: parseInt(provider_id), model_code };
+};
+
+// 大模型评测：维护题集，选择多个模型并发回答，按完全匹配、正则或模型评分打分，并保存每次评测的结果
+const EvalConfig: React.FC = () => {
+    const [models, setModels] = useState<ModelForSelect[]>([]);
+    const [sets, setSets] = useState<EvalQuestionSet[]>([]);
+    const [currentSetId, setCurrentSetId] = useState<number | null>(null);
+    const [newSetName, setNewSetName] = useState<string>("");
+    const [confirmDeleteSetOpen, setConfirmDeleteSetOpen] = useState<boolean>(false);
+
+    const [questions, setQuestions] = useState<EvalQuestion[]>([]);
+    const [prompt, setPrompt] = useState<string>("");
+    const [scorer, setScorer] = useState<EvalScorer>("exact");
+    const [expected, setExpected] = useState<string>("");
+    const [attachmentIds, setAttachmentIds] = useState<number[]>([]);
+
+    const [selectedModels, setSelectedModels] = useState<string[]>([]);
+    const [judgeModel, setJudgeModel] = useState<string>("");
+    const [concurrency, setConcurrency] = useState<string>("4");
+    const [requestsPerMinute, setRequestsPerMinute] = useState<string>("60");
+    const [runningRunId, setRunningRunId] = useState<number | null>(null);
+    const [progress, setProgress] = useState<number>(0);
+
+    const [runs, setRuns] = useState<EvalRun[]>([]);
+    const [summaries, setSummaries] = useState<EvalModelSummary[]>([]);
+    const [viewRunId, setViewRunId] = useState<number | null>(null);
+    const [results, setResults] = useState<EvalResult[]>([]);
+
+    const modelName = useCallback(
+        (providerId: number, modelCode: string) =>
+            models.find((m) => m.llm_provider_id === providerId && m.code === modelCode)?.name ?? modelCode,
+        [models],
+    );
+
+    const loadSets = useCallback(() => {
+        invoke<EvalQuestionSet[]>("list_eval_question_sets")
+            .then((list) => {
+                setSets(list);
+                setCurrentSetId((id) => id ?? list[0]?.id ?? null);
+            })
+            .catch((e) => toast.error("获取题集失败: " + e));
+    }, []);
+
+    const loadSetDetail = useCallback((setId: number) => {
+        invoke<EvalQuestion[]>("list_eval_questions", { setId })
+            .then(setQuestions)
+            .catch((e) => toast.error("获取题目失败: " + e));
+        invoke<EvalRun[]>("list_eval_runs", { setId })
+            .then(setRuns)
+            .catch((e) => toast.error("获取评测记录失败: " + e));
+        invoke<EvalModelSummary[]>("get_eval_model_summaries", { setId })
+            .then(setSummaries)
+            .catch((e) => toast.error("获取评测汇总失败: " + e));
+    }, []);
+
+    useEffect(() => {
+        invoke<ModelForSelect[]>("get_models_for_select").then(setModels);
+        loadSets();
+    }, []);
+
+    useEffect(() => {
+        setViewRunId(null);
+        setResults([]);
+        if (currentSetId !== null) {
+            loadSetDetail(currentSetId);
+        } else {
+            setQuestions([]);
+            setRuns([]);
+            setSummaries([]);
+        }
+    }, [currentSetId]);
+
+    useEffect(() => {
+        if (viewRunId === null) return;
+        invoke<EvalResult[]>("get_eval_run_results", { runId: viewRunId })
+            .then(setResults)
+            .catch((e) => toast.error("获取评测结果失败: " + e));
+    }, [viewRunId]);
+
+    // 评测在后台执行，监听每道题的结果和评测结束
+    useEffect(() => {
+        if (runningRunId === null) return;
+        const unlistenResult = listen<EvalResult>(`eval_result_${runningRunId}`, (event) => {
+            setProgress((p) => p + 1);
+            if (viewRunId === runningRunId) {
+                setResults((list) => [...list, event.payload]);
+            }
+        });
+        const unlistenRun = listen<EvalRun>(`eval_run_${runningRunId}`, (event) => {
+            setRunningRunId(null);
+            toast.success(`评测结束：${event.payload.finished}/${event.payload.total}`);
+            if (currentSetId !== null) {
+                loadSetDetail(currentSetId);
+            }
+        });
+        return () => {
+            unlistenResult.then((f) => f());
+            unlistenRun.then((f) => f());
+        };
+    }, [runningRunId, viewRunId, currentSetId]);
+
+    const handleAddSet = () => {
+        invoke<number>("save_eval_question_set", { name: newSetName })
+            .then((id) => {
+                setNewSetName("");
+                setCurrentSetId(id);
+                loadSets();
+            })
+            .catch((e) => toast.error("新建题集失败: " + e));
+    };
+
+    const handleDeleteSet = () => {
+        if (currentSetId === null) return;
+        invoke("delete_eval_question_set", { id: currentSetId })
+            .then(() => {
+                setCurrentSetId(null);
+                setConfirmDeleteSetOpen(false);
+                loadSets();
+            })
+            .catch((e) => toast.error("删除题集失败: " + e));
+    };
+
+    const handleChooseAttachment = async () => {
+        const selected = await open({ multiple: false });
+        if (!selected) return;
+        invoke<{ attachment_id: number }>("add_attachment", { fileUrl: selected as string })
+            .then((res) => setAttachmentIds((ids) => [...ids, res.attachment_id]))
+            .catch((e) => toast.error("添加附件失败: " + e));
+    };
+
+    const handleAddQuestion = () => {
+        if (currentSetId === null) return;
+        invoke<number>("save_eval_question", {
+            setId: currentSetId,
+            prompt,
+            attachmentIds,
+            scorer,
+            expected,
+        })
+            .then(() => {
+                setPrompt("");
+                setExpected("");
+                setAttachmentIds([]);
+                loadSetDetail(currentSetId);
+                loadSets();
+            })
+            .catch((e) => toast.error("保存题目失败: " + e));
+    };
+
+    const handleDeleteQuestion = (id: number) => {
+        if (currentSetId === null) return;
+        invoke("delete_eval_question", { id })
+            .then(() => {
+                loadSetDetail(currentSetId);
+                loadSets();
+            })
+            .catch((e) => toast.error("删除题目失败: " + e));
+    };
+
+    const toggleModel = (key: string, checked: boolean) => {
+        setSelectedModels((list) => (checked ? [...list, key] : list.filter((k) => k !== key)));
+    };
+
+    const handleStartRun = () => {
+        if (currentSetId === null) return;
+        invoke<number>("start_eval_run", {
+            setId: currentSetId,
+            models: selectedModels.map(parseModelKey),
+            judgeModel: judgeModel ? parseModelKey(judgeModel) : null,
+            concurrency: parseInt(concurrency) || null,
+            requestsPerMinute: parseInt(requestsPerMinute) || 0,
+        })
+            .then((runId) => {
+                setProgress(0);
+                setRunningRunId(runId);
+                setViewRunId(runId);
+                setResults([]);
+                loadSetDetail(currentSetId);
+            })
+            .catch((e) => toast.error("开始评测失败: " + e));
+    };
+
+    const handleCancelRun = () => {
+        if (runningRunId === null) return;
+        invoke("cancel_eval_run", { runId: runningRunId }).catch((e) => toast.error("取消评测失败: " + e));
+    };
+
+    const modelOptions = models.map((m) => ({ value: `${m.llm_provider_id}%%${m.code}`, label: m.name }));
+    const total = selectedModels.length * questions.length;
+
+    return (
+        <div className="p-4 flex flex-col gap-4">
+            <Card>
+                <CardHeader>
+                    <CardTitle>题集</CardTitle>
+                    <CardDescription>每个题集包含若干题目，可以反复用来评测不同的模型</CardDescription>
+                </CardHeader>
+                <CardContent className="flex flex-col gap-2">
+                    <div className="flex gap-2 items-center">
+                        {sets.length > 0 && (
+                            <CustomSelect
+                                options={sets.map((s) => ({
+                                    value: s.id.toString(),
+                                    label: `${s.name}（${s.question_count} 题）`,
+                                }))}
+                                value={currentSetId?.toString() ?? ""}
+                                onChange={(value) => setCurrentSetId(parseInt(value))}
+                            />
+                        )}
+                        {currentSetId !== null && (
+                            <Button variant="outline" onClick={() => setConfirmDeleteSetOpen(true)}>
+                                删除题集
+                            </Button>
+                        )}
+                    </div>
+                    <div className="flex gap-2 items-center">
+                        <Input
+                            value={newSetName}
+                            placeholder="新题集名称"
+                            onChange={(e) => setNewSetName(e.target.value)}
+                        />
+                        <Button onClick={handleAddSet}>新建题集</Button>
+                    </div>
+                </CardContent>
+            </Card>
+
+            {currentSetId !== null && (
+                <Card>
+                    <CardHeader>
+                        <CardTitle>题目</CardTitle>
+                    </CardHeader>
+                    <CardContent className="flex flex-col gap-2">
+                        {questions.map((question, index) => (
+                            <div key={question.id} className="flex gap-2 items-start">
+                                <span className="w-8">{index + 1}.</span>
+                                <div className="flex-1 flex flex-col gap-1">
+                                    <span className="whitespace-pre-wrap">{question.prompt}</span>
+                                    <span className="text-sm text-muted-foreground">
+                                        <Badge variant="outline">
+                                            {scorerOptions.find((s) => s.value === question.scorer)?.label}
+                                        </Badge>{" "}
+                                        {question.expected}
+                                        {question.attachment_ids.length > 0 &&
+                                            `（${question.attachment_ids.length} 个附件）`}
+                                    </span>
+                                </div>
+                                <Button variant="outline" onClick={() => handleDeleteQuestion(question.id)}>
+                                    删除
+                                </Button>
+                            </div>
+                        ))}
+                        <Textarea value={prompt} placeholder="题目" onChange={(e) => setPrompt(e.target.value)} />
+                        <div className="flex gap-2 items-center">
+                            <CustomSelect
+                                options={scorerOptions}
+                                value={scorer}
+                                onChange={(value) => setScorer(value as EvalScorer)}
+                            />
+                            <Input
+                                value={expected}
+                                placeholder={expectedPlaceholder[scorer]}
+                                onChange={(e) => setExpected(e.target.value)}
+                            />
+                        </div>
+                        <div className="flex gap-2 items-center">
+                            <Button variant="outline" onClick={handleChooseAttachment}>
+                                添加附件{attachmentIds.length > 0 ? `（${attachmentIds.length}）` : ""}
+                            </Button>
+                            <Button onClick={handleAddQuestion}>添加题目</Button>
+                        </div>
+                    </CardContent>
+                </Card>
+            )}
+
+            {currentSetId !== null && questions.length > 0 && (
+                <Card>
+                    <CardHeader>
+                        <CardTitle>开始评测</CardTitle>
+                        <CardDescription>所选模型会并发回答所有题目，每个模型按每分钟请求数限流</CardDescription>
+                    </CardHeader>
+                    <CardContent className="flex flex-col gap-2">
+                        <div className="flex flex-wrap gap-4">
+                            {modelOptions.map((option) => (
+                                <label key={option.value} className="flex gap-1 items-center">
+                                    <Checkbox
+                                        checked={selectedModels.includes(option.value)}
+                                        onCheckedChange={(checked) => toggleModel(option.value, checked === true)}
+                                    />
+                                    {option.label}
+                                </label>
+                            ))}
+                        </div>
+                        <div className="flex gap-2 items-center">
+                            <span className="w-24">评分模型</span>
+                            <CustomSelect
+                                options={[{ value: "", label: "不使用" }, ...modelOptions]}
+                                value={judgeModel}
+                                onChange={setJudgeModel}
+                            />
+                        </div>
+                        <div className="flex gap-2 items-center">
+                            <span className="w-24">并发数</span>
+                            <Input value={concurrency} onChange={(e) => setConcurrency(e.target.value)} />
+                            <span className="w-40">每分钟请求数</span>
+                            <Input value={requestsPerMinute} onChange={(e) => setRequestsPerMinute(e.target.value)} />
+                        </div>
+                        <div className="flex gap-2 items-center">
+                            {runningRunId === null ? (
+                                <Button disabled={selectedModels.length === 0} onClick={handleStartRun}>
+                                    开始评测
+                                </Button>
+                            ) : (
+                                <>
+                                    <Button variant="outline" onClick={handleCancelRun}>
+                                        取消评测
+                                    </Button>
+                                    <span className="text-sm text-muted-foreground">
+                                        进度 {progress}/{total}
+                                    </span>
+                                </>
+                            )}
+                        </div>
+                    </CardContent>
+                </Card>
+            )}
+
+            {summaries.length > 0 && (
+                <Card>
+                    <CardHeader>
+                        <CardTitle>历次评测</CardTitle>
+                        <CardDescription>每次评测各模型的通过数和平均得分，点击查看详细结果</CardDescription>
+                    </CardHeader>
+                    <CardContent className="flex flex-col gap-2">
+                        {runs.map((run) => (
+                            <div key={run.id} className="flex flex-col gap-1">
+                                <div className="flex gap-2 items-center">
+                                    <span className="flex-1">
+                                        {run.created_time} <Badge variant="outline">{run.status}</Badge>
+                                    </span>
+                                    <Button variant="outline" onClick={() => setViewRunId(run.id)}>
+                                        查看结果
+                                    </Button>
+                                </div>
+                                {summaries
+                                    .filter((s) => s.run_id === run.id)
+                                    .map((s) => (
+                                        <div
+                                            key={`${s.provider_id}%%${s.model_code}`}
+                                            className="text-sm text-muted-foreground"
+                                        >
+                                            {modelName(s.provider_id, s.model_code)}：通过 {s.passed}/{s.count}，平均得分{" "}
+                                            {(s.avg_score * 100).toFixed(0)}，平均耗时 {Math.round(s.avg_duration_ms)}ms
+                                            {s.errors > 0 && `，失败 ${s.errors}`}
+                                        </div>
+                                    ))}
+                            </div>
+                        ))}
+                    </CardContent>
+                </Card>
+            )}
+
+            {viewRunId !== null && results.length > 0 && (
+                <Card>
+                    <CardHeader>
+                        <CardTitle>评测结果</CardTitle>
+                    </CardHeader>
+                    <CardContent className="flex flex-col gap-3">
+                        {results.map((result) => (
+                            <div key={result.id} className="flex flex-col gap-1">
+                                <div className="flex gap-2 items-center">
+                                    <span className="flex-1">
+                                        第 {questions.findIndex((q) => q.id === result.question_id) + 1} 题 ·{" "}
+                                        {modelName(result.provider_id, result.model_code)}
+                                    </span>
+                                    <Badge variant={result.passed ? "default" : "outline"}>
+                                        {result.error ? "失败" : result.passed ? "通过" : "未通过"}{" "}
+                                        {(result.score * 100).toFixed(0)}
+                                    </Badge>
+                                </div>
+                                <pre className="whitespace-pre-wrap text-sm">{result.error ?? result.answer}</pre>
+                                {result.reason && (
+                                    <span className="text-sm text-muted-foreground">{result.reason}</span>
+                                )}
+                            </div>
+                        ))}
+                    </CardContent>
+                </Card>
+            )}
+
+            <ConfirmDialog
+                title="确认操作"
+                confirmText="删除题集会同时删除题集中的题目和评测记录，确认删除吗？"
+                onConfirm={handleDeleteSet}
+                onCancel={() => setConfirmDeleteSetOpen(false)}
+                isOpen={confirmDeleteSetOpen}
+            />
+        </div>
+    );
+};
+
+export default EvalConfig;
@@ -60,9 +60,27 @@ const FeatureAssistantConfig: React.FC = () => {
 
                 previewFormReturnData.reset({
                     preview_type: featureConfig.get("preview")?.get("preview_type") || "service",
-                    nextjs_port: featureConfig.get("preview")?.get("nextjs_port") || "3001",
-                    nuxtjs_port: featureConfig.get("preview")?.get("nuxtjs_port") || "3002",
                     auth_token: featureConfig.get("preview")?.get("auth_token") || "",
+                    python_path: featureConfig.get("preview")?.get("python_path") || "",
+                    node_path: featureConfig.get("preview")?.get("node_path") || "",
+                    bash_path: featureConfig.get("preview")?.get("bash_path") || "",
+                    deno_path: featureConfig.get("preview")?.get("deno_path") || "",
+                    powershell_path: featureConfig.get("preview")?.get("powershell_path") || "",
+                    osascript_path: featureConfig.get("preview")?.get("osascript_path") || "",
+                    run_max_output_kb: featureConfig.get("preview")?.get("run_max_output_kb") || "256",
+                    run_network_isolation: featureConfig.get("preview")?.get("run_network_isolation") || "false",
+                });
+
+                optimizeFormReturnData.reset({
+                    model: `${featureConfig.get("prompt_optimize")?.get("provider_id")}%%${featureConfig.get("prompt_optimize")?.get("model_code")}`,
+                    prompt: featureConfig.get("prompt_optimize")?.get("prompt") || "",
+                });
+
+                searchFormReturnData.reset({
+                    search_engine: featureConfig.get("bang")?.get("search_engine") || "searxng",
+                    search_url: featureConfig.get("bang")?.get("search_url") || "",
+                    search_api_key: featureConfig.get("bang")?.get("search_api_key") || "",
+                    search_result_count: featureConfig.get("bang")?.get("search_result_count") || "3",
                 });
             },
         ).catch((e) => {
@@ -103,12 +121,47 @@ const FeatureAssistantConfig: React.FC = () => {
         });
     }, [featureConfig, summaryFormReturnData]);
 
+    const optimizeFormReturnData = useForm({
+        defaultValues: {
+            model: "",
+            prompt: "",
+        },
+    });
+
+    const handleSaveOptimize = useCallback(() => {
+        const optimizeFormValues = optimizeFormReturnData.getValues();
+        const [provider_id, model_code] = (optimizeFormValues.model as string).split("%%");
+        if (!provider_id || !model_code || model_code === "undefined") {
+            toast.error("请选择一个模型");
+            return;
+        }
+
+        invoke("save_feature_config", {
+            featureCode: "prompt_optimize",
+            config: {
+                provider_id,
+                model_code,
+                prompt: optimizeFormValues.prompt,
+            }
+        }).then(() => {
+            toast.success('保存成功');
+        }).catch((e) => {
+            toast.error('保存失败: ' + e);
+        });
+    }, [optimizeFormReturnData]);
+
     const previewFormReturnData = useForm({
         defaultValues: {
             preview_type: featureConfig.get("preview")?.get("preview_type") || "service",
-            nextjs_port: featureConfig.get("preview")?.get("nextjs_port") || "3001",
-            nuxtjs_port: featureConfig.get("preview")?.get("nuxtjs_port") || "3002",
             auth_token: featureConfig.get("preview")?.get("auth_token") || "",
+            python_path: featureConfig.get("preview")?.get("python_path") || "",
+            node_path: featureConfig.get("preview")?.get("node_path") || "",
+            bash_path: featureConfig.get("preview")?.get("bash_path") || "",
+            deno_path: featureConfig.get("preview")?.get("deno_path") || "",
+            powershell_path: featureConfig.get("preview")?.get("powershell_path") || "",
+            osascript_path: featureConfig.get("preview")?.get("osascript_path") || "",
+            run_max_output_kb: featureConfig.get("preview")?.get("run_max_output_kb") || "256",
+            run_network_isolation: featureConfig.get("preview")?.get("run_network_isolation") || "false",
         },
     });
 
@@ -126,6 +179,32 @@ const FeatureAssistantConfig: React.FC = () => {
         });
     }, [featureConfig, previewFormReturnData]);
 
+    const searchFormReturnData = useForm({
+        defaultValues: {
+            search_engine: "searxng",
+            search_url: "",
+            search_api_key: "",
+            search_result_count: "3",
+        },
+    });
+
+    const handleSaveSearch = useCallback(() => {
+        // bang 的其他配置（超时、网页请求等）一起保存，避免被覆盖
+        const bangConfig = Object.fromEntries(featureConfig.get("bang") || new Map());
+        const config = { ...bangConfig, ...searchFormReturnData.getValues() };
+
+        invoke("save_feature_config", {
+            featureCode: "bang",
+            config,
+        }).then(() => {
+            featureConfig.set("bang", new Map(Object.entries(config)));
+            setFeatureConfig(new Map(featureConfig));
+            toast.success('保存成功');
+        }).catch((e) => {
+            toast.error('保存失败: ' + e);
+        });
+    }, [featureConfig, searchFormReturnData]);
+
     const summaryFormConfig = useMemo(() => ({
         model: {
             type: "select" as const,
@@ -149,6 +228,21 @@ const FeatureAssistantConfig: React.FC = () => {
         },
     }), [models]);
 
+    const optimizeFormConfig = useMemo(() => ({
+        model: {
+            type: "select" as const,
+            label: "Model",
+            options: models.map((m) => ({
+                value: `${m.llm_provider_id}%%${m.code}`,
+                label: m.name,
+            })),
+        },
+        prompt: {
+            type: "textarea" as const,
+            label: "Prompt",
+        },
+    }), [models]);
+
     const previewFormConfig = useMemo(() => {
         return {
             preview_type: {
@@ -160,17 +254,86 @@ const FeatureAssistantConfig: React.FC = () => {
                     { value: "service", label: "使用服务" },
                 ],
             },
-            nextjs_port: {
+            auth_token: {
                 type: "input" as const,
-                label: "Next.js端口",
+                label: "Auth token",
             },
-            nuxtjs_port: {
+            python_path: {
                 type: "input" as const,
-                label: "Nuxt.js端口",
+                label: "Python 路径",
+                tooltip: "留空使用 PATH 中的 python3",
             },
-            auth_token: {
+            node_path: {
                 type: "input" as const,
-                label: "Auth token",
+                label: "Node 路径",
+                tooltip: "留空使用 PATH 中的 node",
+            },
+            bash_path: {
+                type: "input" as const,
+                label: "Bash 路径",
+                tooltip: "留空使用 PATH 中的 bash",
+            },
+            deno_path: {
+                type: "input" as const,
+                label: "Deno 路径",
+                tooltip: "留空使用 PATH 中的 deno",
+            },
+            powershell_path: {
+                type: "input" as const,
+                label: "PowerShell 路径",
+                tooltip: "留空使用 PATH 中的 powershell",
+            },
+            osascript_path: {
+                type: "input" as const,
+                label: "osascript 路径",
+                tooltip: "留空使用 PATH 中的 osascript",
+            },
+            run_timeout_seconds: {
+                type: "input" as const,
+                label: "运行超时（秒）",
+            },
+            run_max_output_kb: {
+                type: "input" as const,
+                label: "最大输出（KB）",
+            },
+            run_network_isolation: {
+                type: "radio" as const,
+                label: "禁止网络访问",
+                options: [
+                    { value: "false", label: "否" },
+                    { value: "true", label: "是" },
+                ],
+            },
+        };
+    }, []);
+
+    const searchFormConfig = useMemo(() => {
+        return {
+            search_engine: {
+                type: "select" as const,
+                label: "搜索服务",
+                options: [
+                    { value: "searxng", label: "SearxNG" },
+                    { value: "brave", label: "Brave" },
+                    { value: "bing", label: "Bing" },
+                    { value: "tavily", label: "Tavily" },
+                ],
+            },
+            search_url: {
+                type: "input" as const,
+                label: "接口地址",
+            },
+            search_api_key: {
+                type: "password" as const,
+                label: "API Key",
+            },
+            search_result_count: {
+                type: "select" as const,
+                label: "读取结果数",
+                options: [1, 3, 5, 10].map((m) => ({
+                    value: m.toString(),
+                    label: m.toString(),
+                })),
             },
         };
     }, []);
@@ -214,6 +377,16 @@ const FeatureAssistantConfig: React.FC = () => {
                 useFormReturn={summaryFormReturnData}
             />
 
+            <ConfigForm
+                title="提示词自优化"
+                description="根据用户评价不好的回答优化助手的提示词，Prompt 不填时使用内置的优化提示词"
+                config={optimizeFormConfig}
+                layout="prompt"
+                classNames="bottom-space"
+                onSave={handleSaveOptimize}
+                useFormReturn={optimizeFormReturnData}
+            />
+
             <ConfigForm
                 title="预览配置"
                 description="在大模型编写完react或者vue组件之后，能够快速预览"
@@ -224,6 +397,16 @@ const FeatureAssistantConfig: React.FC = () => {
                 useFormReturn={previewFormReturnData}
             />
 
+            <ConfigForm
+                title="网络搜索"
+                description="!search 使用的搜索服务，SearxNG 需要填写实例地址，其他服务不填时使用官方接口"
+                config={searchFormConfig}
+                layout="default"
+                classNames="bottom-space"
+                onSave={handleSaveSearch}
+                useFormReturn={searchFormReturnData}
+            />
+
             <ConfigForm
                 title="数据目录"
                 description="管理和同步数据文件夹"
@@ -0,0 +1,57 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { FeedbackStats } from "../../data/Assistant";
+import { Badge } from "../ui/badge";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "../ui/card";
+
+interface FeedbackStatsCardProps {
+    assistantId: number;
+}
+
+// 助手在各个模型下的好评率，作为选择默认模型的参考
+const FeedbackStatsCard: React.FC<FeedbackStatsCardProps> = ({ assistantId }) => {
+    const [stats, setStats] = useState<FeedbackStats[]>([]);
+
+    useEffect(() => {
+        invoke<FeedbackStats[]>("get_feedback_stats", { assistantId })
+            .then(setStats)
+            .catch((error) => toast.error("获取反馈统计失败: " + error));
+    }, [assistantId]);
+
+    if (stats.length === 0) {
+        return null;
+    }
+
+    return (
+        <Card className="bottom-space">
+            <CardHeader>
+                <CardTitle>反馈统计</CardTitle>
+                <CardDescription>按模型汇总该助手回答收到的评价，好评率高的排在前面</CardDescription>
+            </CardHeader>
+            <CardContent className="flex flex-col gap-2">
+                {stats.map((item) => (
+                    <div key={item.llm_model_name ?? "unknown"} className="flex flex-col gap-1">
+                        <div className="flex gap-2 items-center">
+                            <span className="flex-1">{item.llm_model_name ?? "未知模型"}</span>
+                            <span className="text-sm text-muted-foreground">
+                                👍 {item.positive} / 👎 {item.negative}，好评率 {(item.positive_rate * 100).toFixed(0)}%
+                            </span>
+                        </div>
+                        {Object.keys(item.tag_counts).length > 0 && (
+                            <div className="flex flex-wrap gap-1">
+                                {Object.entries(item.tag_counts).map(([tag, count]) => (
+                                    <Badge key={tag} variant="outline">
+                                        {tag} × {count}
+                                    </Badge>
+                                ))}
+                            </div>
+                        )}
+                    </div>
+                ))}
+            </CardContent>
+        </Card>
+    );
+};
+
+export default FeedbackStatsCard;
@@ -0,0 +1,93 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { AssistantDetail, PromptOptimization } from "../../data/Assistant";
+import { Button } from "../ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "../ui/card";
+
+interface PromptOptimizerProps {
+    assistantId: number;
+    onSaved: (assistant: AssistantDetail) => void;
+}
+
+// 根据用户评价不好的回答生成改进后的提示词，确认差异后保存为新版本
+const PromptOptimizer: React.FC<PromptOptimizerProps> = ({ assistantId, onSaved }) => {
+    const [optimizing, setOptimizing] = useState<boolean>(false);
+    const [optimization, setOptimization] = useState<PromptOptimization | null>(null);
+
+    useEffect(() => {
+        setOptimization(null);
+    }, [assistantId]);
+
+    const handleOptimize = () => {
+        setOptimizing(true);
+        invoke<PromptOptimization>("optimize_assistant_prompt", { assistantId })
+            .then(setOptimization)
+            .catch((error) => toast.error("优化提示词失败: " + error))
+            .finally(() => setOptimizing(false));
+    };
+
+    const handleSave = () => {
+        if (!optimization) return;
+        invoke<AssistantDetail>("save_optimized_prompt", {
+            assistantId,
+            prompt: optimization.proposed_prompt,
+        })
+            .then((assistant) => {
+                onSaved(assistant);
+                setOptimization(null);
+                toast.success("已保存为新的提示词版本");
+            })
+            .catch((error) => toast.error("保存失败: " + error));
+    };
+
+    return (
+        <Card className="bottom-space">
+            <CardHeader>
+                <CardTitle>提示词优化</CardTitle>
+                <CardDescription>使用功能配置中的模型，根据评价不好的回答改进提示词</CardDescription>
+            </CardHeader>
+            <CardContent className="flex flex-col gap-2">
+                <div className="flex gap-2">
+                    <Button type="button" variant="outline" disabled={optimizing} onClick={handleOptimize}>
+                        {optimizing ? "优化中..." : "根据反馈优化"}
+                    </Button>
+                    {optimization && (
+                        <>
+                            <Button type="button" onClick={handleSave}>
+                                保存为新版本
+                            </Button>
+                            <Button type="button" variant="outline" onClick={() => setOptimization(null)}>
+                                放弃
+                            </Button>
+                        </>
+                    )}
+                </div>
+                {optimization && (
+                    <div className="text-sm">
+                        <div>参考了 {optimization.example_count} 条评价不好的回答</div>
+                        <pre className="whitespace-pre-wrap">
+                            {optimization.diff.map((line, i) => (
+                                <div
+                                    key={i}
+                                    className={
+                                        line.tag === "insert"
+                                            ? "text-green-600"
+                                            : line.tag === "delete"
+                                              ? "text-red-600 line-through"
+                                              : ""
+                                    }
+                                >
+                                    {line.tag === "insert" ? "+ " : line.tag === "delete" ? "- " : "  "}
+                                    {line.text}
+                                </div>
+                            ))}
+                        </pre>
+                    </div>
+                )}
+            </CardContent>
+        </Card>
+    );
+};
+
+export default PromptOptimizer;
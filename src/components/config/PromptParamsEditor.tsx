@@ -0,0 +1,103 @@
+import React from "react";
+import { AssistantPromptParam } from "../../data/Assistant";
+import { Button } from "../ui/button";
+import { Input } from "../ui/input";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "../ui/card";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "../ui/select";
+
+interface PromptParamsEditorProps {
+    assistantId: number;
+    assistantPromptId: number;
+    params: AssistantPromptParam[];
+    onChange: (params: AssistantPromptParam[]) => void;
+}
+
+const PARAM_TYPES = [
+    { value: "text", label: "单行文本" },
+    { value: "multiline", label: "多行文本" },
+    { value: "number", label: "数字" },
+    { value: "select", label: "选项" },
+];
+
+// 编辑助手的提示词参数，参数在 Prompt 中通过 !参数名 引用，随助手一起保存
+const PromptParamsEditor: React.FC<PromptParamsEditorProps> = ({
+    assistantId,
+    assistantPromptId,
+    params,
+    onChange,
+}) => {
+    const updateParam = (index: number, patch: Partial<AssistantPromptParam>) => {
+        onChange(params.map((param, i) => (i === index ? { ...param, ...patch } : param)));
+    };
+
+    const addParam = () => {
+        onChange([
+            ...params,
+            {
+                id: 0,
+                assistant_id: assistantId,
+                assistant_prompt_id: assistantPromptId,
+                param_name: `param${params.length + 1}`,
+                param_type: "text",
+                param_value: "",
+            },
+        ]);
+    };
+
+    return (
+        <Card className="bottom-space">
+            <CardHeader>
+                <CardTitle>提示词参数</CardTitle>
+                <CardDescription>
+                    在 Prompt 中使用 !参数名 引用，提问时填写，参数名不能与 bang 重名；选项类型的默认值填写逗号分隔的选项
+                </CardDescription>
+            </CardHeader>
+            <CardContent className="flex flex-col gap-2">
+                {params.map((param, index) => (
+                    <div key={index} className="flex gap-2 items-center">
+                        <Input
+                            className="w-40"
+                            value={param.param_name}
+                            placeholder="参数名"
+                            onChange={(e) => updateParam(index, { param_name: e.target.value })}
+                        />
+                        <Select
+                            value={param.param_type || "text"}
+                            onValueChange={(value) => updateParam(index, { param_type: value })}
+                        >
+                            <SelectTrigger className="w-32">
+                                <SelectValue />
+                            </SelectTrigger>
+                            <SelectContent>
+                                {PARAM_TYPES.map((type) => (
+                                    <SelectItem key={type.value} value={type.value}>
+                                        {type.label}
+                                    </SelectItem>
+                                ))}
+                            </SelectContent>
+                        </Select>
+                        <Input
+                            value={param.param_value ?? ""}
+                            placeholder={param.param_type === "select" ? "选项1,选项2" : "默认值"}
+                            onChange={(e) => updateParam(index, { param_value: e.target.value })}
+                        />
+                        <Button
+                            type="button"
+                            variant="outline"
+                            onClick={() => onChange(params.filter((_, i) => i !== index))}
+                        >
+                            删除
+                        </Button>
+                    </div>
+                ))}
+                <div>
+                    <Button type="button" variant="outline" onClick={addParam}>
+                        添加参数
+                    </Button>
+                </div>
+            </CardContent>
+        </Card>
+    );
+};
+
+export default PromptParamsEditor;
@@ -0,0 +1,123 @@
+import React, { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { ask } from "@tauri-apps/plugin-dialog";
+import { toast } from "sonner";
+import { AssistantDetail, AssistantPromptVersion, PromptVersionDiff } from "../../data/Assistant";
+import { Button } from "../ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "../ui/card";
+
+interface PromptVersionHistoryProps {
+    assistantId: number;
+    // 保存助手后变化，用于刷新版本列表
+    refreshKey: number;
+    onRollback: (assistant: AssistantDetail) => void;
+}
+
+// 助手提示词和模型配置的历史版本，可以与最新版本对比以及回滚
+const PromptVersionHistory: React.FC<PromptVersionHistoryProps> = ({
+    assistantId,
+    refreshKey,
+    onRollback,
+}) => {
+    const [versions, setVersions] = useState<AssistantPromptVersion[]>([]);
+    const [diff, setDiff] = useState<PromptVersionDiff | null>(null);
+
+    const loadVersions = useCallback(() => {
+        invoke<AssistantPromptVersion[]>("get_assistant_prompt_versions", { assistantId })
+            .then(setVersions)
+            .catch((error) => toast.error("获取提示词版本失败: " + error));
+    }, [assistantId]);
+
+    useEffect(() => {
+        setDiff(null);
+        loadVersions();
+    }, [loadVersions, refreshKey]);
+
+    const handleDiff = (version: AssistantPromptVersion) => {
+        invoke<PromptVersionDiff>("diff_assistant_prompt_versions", {
+            fromVersionId: version.id,
+            toVersionId: versions[0].id,
+        })
+            .then(setDiff)
+            .catch((error) => toast.error("对比版本失败: " + error));
+    };
+
+    const handleRollback = async (version: AssistantPromptVersion) => {
+        const confirmed = await ask(`确认回滚到版本 v${version.version} 吗？当前内容会保留在历史版本中。`, {
+            title: "回滚提示词",
+        });
+        if (!confirmed) return;
+        invoke<AssistantDetail>("rollback_assistant_prompt_version", { versionId: version.id })
+            .then((assistant) => {
+                onRollback(assistant);
+                loadVersions();
+                toast.success(`已回滚到版本 v${version.version}`);
+            })
+            .catch((error) => toast.error("回滚失败: " + error));
+    };
+
+    if (versions.length === 0) {
+        return null;
+    }
+
+    return (
+        <Card className="bottom-space">
+            <CardHeader>
+                <CardTitle>提示词版本</CardTitle>
+                <CardDescription>每次保存有变化时记录一个版本，消息会记录生成时使用的版本</CardDescription>
+            </CardHeader>
+            <CardContent className="flex flex-col gap-2">
+                {versions.map((version, index) => (
+                    <div key={version.id} className="flex gap-2 items-center">
+                        <span className="w-16">v{version.version}</span>
+                        <span className="flex-1 text-sm text-muted-foreground">
+                            {version.created_time}
+                            {index === 0 ? "（当前）" : ""}
+                        </span>
+                        {index !== 0 && (
+                            <>
+                                <Button type="button" variant="outline" onClick={() => handleDiff(version)}>
+                                    与当前对比
+                                </Button>
+                                <Button type="button" variant="outline" onClick={() => handleRollback(version)}>
+                                    回滚
+                                </Button>
+                            </>
+                        )}
+                    </div>
+                ))}
+                {diff && (
+                    <div className="prompt-version-diff text-sm">
+                        <div className="font-medium">
+                            v{diff.from_version} → v{diff.to_version}
+                        </div>
+                        <pre className="whitespace-pre-wrap">
+                            {diff.prompt.map((line, i) => (
+                                <div
+                                    key={i}
+                                    className={
+                                        line.tag === "insert"
+                                            ? "text-green-600"
+                                            : line.tag === "delete"
+                                              ? "text-red-600 line-through"
+                                              : ""
+                                    }
+                                >
+                                    {line.tag === "insert" ? "+ " : line.tag === "delete" ? "- " : "  "}
+                                    {line.text}
+                                </div>
+                            ))}
+                        </pre>
+                        {diff.model_configs.map((change) => (
+                            <div key={change.name}>
+                                {change.name}: {change.old_value ?? "（无）"} → {change.new_value ?? "（无）"}
+                            </div>
+                        ))}
+                    </div>
+                )}
+            </CardContent>
+        </Card>
+    );
+};
+
+export default PromptVersionHistory;
@@ -0,0 +1,258 @@
+import React, { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { AssistantListItem } from "../../data/Assistant";
+import { MissedPolicy, ScheduledTask, ScheduledTaskRun } from "../../data/Schedule";
+import CustomSelect from "../CustomSelect";
+import ConfirmDialog from "../ConfirmDialog";
+import { Badge } from "../ui/badge";
+import { Button } from "../ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "../ui/card";
+import { Checkbox } from "../ui/checkbox";
+import { Input } from "../ui/input";
+import { Textarea } from "../ui/textarea";
+
+const emptyTask = (assistantId: number): ScheduledTask => ({
+    id: 0,
+    name: "",
+    cron: "0 8 * * *",
+    assistant_id: assistantId,
+    prompt: "总结下面的内容：!web_to_markdown(https://example.com)",
+    missed_policy: "run_once",
+    enabled: true,
+    last_run_time: null,
+    next_run_time: null,
+    created_time: "",
+});
+
+const formatTime = (time: string | null) => (time ? new Date(time).toLocaleString() : "-");
+
+// 定时任务：按 cron 表达式在后台用助手执行提示词，结果保存为新对话
+const ScheduleConfig: React.FC = () => {
+    const [tasks, setTasks] = useState<ScheduledTask[]>([]);
+    const [assistants, setAssistants] = useState<AssistantListItem[]>([]);
+    const [editing, setEditing] = useState<ScheduledTask | null>(null);
+    const [preview, setPreview] = useState<string[]>([]);
+    const [previewError, setPreviewError] = useState<string>("");
+    const [confirmDeleteOpen, setConfirmDeleteOpen] = useState<boolean>(false);
+    const [runs, setRuns] = useState<ScheduledTaskRun[]>([]);
+
+    const loadTasks = useCallback(() => {
+        invoke<ScheduledTask[]>("list_scheduled_tasks")
+            .then(setTasks)
+            .catch((e) => toast.error("获取定时任务失败: " + e));
+    }, []);
+
+    const loadRuns = useCallback((taskId: number) => {
+        invoke<ScheduledTaskRun[]>("list_scheduled_task_runs", { taskId })
+            .then(setRuns)
+            .catch((e) => toast.error("获取执行记录失败: " + e));
+    }, []);
+
+    useEffect(() => {
+        loadTasks();
+        invoke<AssistantListItem[]>("get_assistants")
+            .then(setAssistants)
+            .catch((e) => toast.error("获取助手列表失败: " + e));
+    }, []);
+
+    useEffect(() => {
+        const unlisten = listen<number>("scheduled_task_run_finished", (event) => {
+            loadTasks();
+            if (editing && editing.id === event.payload) {
+                loadRuns(editing.id);
+            }
+        });
+        return () => {
+            unlisten.then((f) => f());
+        };
+    }, [editing?.id]);
+
+    useEffect(() => {
+        if (!editing) return;
+        invoke<string[]>("preview_cron_schedule", { cron: editing.cron })
+            .then((times) => {
+                setPreview(times);
+                setPreviewError("");
+            })
+            .catch((e) => {
+                setPreview([]);
+                setPreviewError(String(e));
+            });
+    }, [editing?.cron]);
+
+    const handleEdit = (task: ScheduledTask) => {
+        setEditing(task);
+        if (task.id !== 0) {
+            loadRuns(task.id);
+        } else {
+            setRuns([]);
+        }
+    };
+
+    const handleSave = () => {
+        if (!editing) return;
+        invoke<ScheduledTask>("save_scheduled_task", { task: editing })
+            .then((task) => {
+                handleEdit(task);
+                loadTasks();
+                toast.success("保存成功");
+            })
+            .catch((e) => toast.error("保存失败: " + e));
+    };
+
+    const handleDelete = () => {
+        if (!editing) return;
+        invoke("delete_scheduled_task", { id: editing.id })
+            .then(() => {
+                setConfirmDeleteOpen(false);
+                setEditing(null);
+                loadTasks();
+            })
+            .catch((e) => toast.error("删除失败: " + e));
+    };
+
+    const handleRunNow = () => {
+        if (!editing) return;
+        invoke("run_scheduled_task_now", { id: editing.id })
+            .then(() => toast.success("已开始执行，完成后会在托盘菜单中提示"))
+            .catch((e) => toast.error("执行失败: " + e));
+    };
+
+    const openConversation = (conversationId: number) => {
+        invoke("open_scheduled_conversation", { conversationId }).catch((e) => toast.error("打开对话失败: " + e));
+    };
+
+    return (
+        <div className="p-4 flex flex-col gap-4">
+            <Card>
+                <CardHeader>
+                    <CardTitle>定时任务</CardTitle>
+                    <CardDescription>程序在托盘中运行时按计划执行，错过的任务在下次启动时处理</CardDescription>
+                </CardHeader>
+                <CardContent className="flex flex-col gap-2">
+                    {tasks.map((task) => (
+                        <div key={task.id} className="flex gap-2 items-center">
+                            <span className="flex-1 text-sm">
+                                {task.name} <Badge variant="outline">{task.cron}</Badge>{" "}
+                                {!task.enabled && <Badge variant="destructive">已停用</Badge>}
+                            </span>
+                            <span className="text-sm text-gray-500">下次执行：{formatTime(task.next_run_time)}</span>
+                            <Button variant="outline" onClick={() => handleEdit(task)}>
+                                编辑
+                            </Button>
+                        </div>
+                    ))}
+                    <div>
+                        <Button onClick={() => handleEdit(emptyTask(assistants[0]?.id ?? 0))}>新建定时任务</Button>
+                    </div>
+                </CardContent>
+            </Card>
+
+            {editing && (
+                <Card>
+                    <CardHeader>
+                        <CardTitle>{editing.id === 0 ? "新建定时任务" : editing.name}</CardTitle>
+                        <CardDescription>
+                            提示词支持 bang 模板，可以用 !task_name、!scheduled_time 引用任务名称和计划执行时间
+                        </CardDescription>
+                    </CardHeader>
+                    <CardContent className="flex flex-col gap-2">
+                        <Input
+                            placeholder="任务名称"
+                            value={editing.name}
+                            onChange={(e) => setEditing({ ...editing, name: e.target.value })}
+                        />
+                        <Input
+                            placeholder="cron 表达式：分 时 日 月 周"
+                            className="font-mono"
+                            value={editing.cron}
+                            onChange={(e) => setEditing({ ...editing, cron: e.target.value })}
+                        />
+                        <div className="text-sm text-gray-500">
+                            {previewError ? previewError : `接下来执行：${preview.join("，")}`}
+                        </div>
+                        <CustomSelect
+                            options={assistants.map((a) => ({ value: a.id.toString(), label: a.name }))}
+                            value={editing.assistant_id.toString()}
+                            onChange={(value) => setEditing({ ...editing, assistant_id: parseInt(value) })}
+                        />
+                        <Textarea
+                            className="min-h-[120px]"
+                            value={editing.prompt}
+                            onChange={(e) => setEditing({ ...editing, prompt: e.target.value })}
+                        />
+                        <div className="flex gap-4 items-center">
+                            <CustomSelect
+                                options={[
+                                    { value: "run_once", label: "错过后启动时补执行一次" },
+                                    { value: "skip", label: "错过后跳过" },
+                                ]}
+                                value={editing.missed_policy}
+                                onChange={(value) => setEditing({ ...editing, missed_policy: value as MissedPolicy })}
+                            />
+                            <label className="flex gap-1 items-center">
+                                <Checkbox
+                                    checked={editing.enabled}
+                                    onCheckedChange={(checked) => setEditing({ ...editing, enabled: checked === true })}
+                                />
+                                启用
+                            </label>
+                        </div>
+                        <div className="flex gap-2">
+                            <Button onClick={handleSave}>保存</Button>
+                            {editing.id !== 0 && (
+                                <>
+                                    <Button variant="outline" onClick={handleRunNow}>
+                                        立即执行
+                                    </Button>
+                                    <Button variant="outline" onClick={() => setConfirmDeleteOpen(true)}>
+                                        删除
+                                    </Button>
+                                </>
+                            )}
+                        </div>
+                    </CardContent>
+                </Card>
+            )}
+
+            {editing && runs.length > 0 && (
+                <Card>
+                    <CardHeader>
+                        <CardTitle>执行记录</CardTitle>
+                    </CardHeader>
+                    <CardContent className="flex flex-col gap-2">
+                        {runs.map((run) => (
+                            <div key={run.id} className="flex gap-2 items-center">
+                                <span className="flex-1 text-sm">
+                                    {formatTime(run.scheduled_time)}{" "}
+                                    <Badge variant={run.status === "failed" ? "destructive" : "outline"}>
+                                        {run.status}
+                                    </Badge>{" "}
+                                    {run.missed && <Badge variant="outline">补执行</Badge>}
+                                    {run.error && <span className="text-red-500"> {run.error}</span>}
+                                </span>
+                                {run.conversation_id !== null && (
+                                    <Button variant="outline" onClick={() => openConversation(run.conversation_id!)}>
+                                        查看对话
+                                    </Button>
+                                )}
+                            </div>
+                        ))}
+                    </CardContent>
+                </Card>
+            )}
+
+            <ConfirmDialog
+                title="确认操作"
+                confirmText="删除定时任务会同时删除执行记录，确认删除吗？"
+                onConfirm={handleDelete}
+                onCancel={() => setConfirmDeleteOpen(false)}
+                isOpen={confirmDeleteOpen}
+            />
+        </div>
+    );
+};
+
+export default ScheduleConfig;
@@ -0,0 +1,285 @@
+import React, { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { open } from "@tauri-apps/plugin-dialog";
+import { toast } from "sonner";
+import { Workflow, WorkflowEvent, WorkflowRun, WorkflowStepRun } from "../../data/Workflow";
+import CustomSelect from "../CustomSelect";
+import ConfirmDialog from "../ConfirmDialog";
+import { Badge } from "../ui/badge";
+import { Button } from "../ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "../ui/card";
+import { Textarea } from "../ui/textarea";
+
+const EXAMPLE_DEFINITION = `name: 网页摘要
+inputs:
+  - name: url
+    default: https://example.com
+steps:
+  - id: page
+    type: bang
+    template: "!web_to_markdown(!url)"
+  - id: summary
+    type: llm
+    assistant_id: 1
+    prompt: "总结下面的内容：!page"
+    depends_on: [page]
+`;
+
+// 用 YAML 或 JSON 定义的工作流，可以导入文件、编辑、运行并查看每一步的输出
+const WorkflowConfig: React.FC = () => {
+    const [workflows, setWorkflows] = useState<Workflow[]>([]);
+    const [currentId, setCurrentId] = useState<number | null>(null);
+    const [definition, setDefinition] = useState<string>(EXAMPLE_DEFINITION);
+    const [inputs, setInputs] = useState<string>("{}");
+    const [confirmDeleteOpen, setConfirmDeleteOpen] = useState<boolean>(false);
+
+    const [runs, setRuns] = useState<WorkflowRun[]>([]);
+    const [runningRunId, setRunningRunId] = useState<number | null>(null);
+    const [viewRunId, setViewRunId] = useState<number | null>(null);
+    const [steps, setSteps] = useState<WorkflowStepRun[]>([]);
+
+    const loadWorkflows = useCallback(() => {
+        invoke<Workflow[]>("list_workflows")
+            .then(setWorkflows)
+            .catch((e) => toast.error("获取工作流失败: " + e));
+    }, []);
+
+    const loadRuns = useCallback((workflowId: number) => {
+        invoke<WorkflowRun[]>("list_workflow_runs", { workflowId })
+            .then(setRuns)
+            .catch((e) => toast.error("获取运行记录失败: " + e));
+    }, []);
+
+    useEffect(() => {
+        loadWorkflows();
+    }, []);
+
+    useEffect(() => {
+        setViewRunId(null);
+        setSteps([]);
+        if (currentId === null) {
+            setDefinition(EXAMPLE_DEFINITION);
+            setRuns([]);
+            return;
+        }
+        const workflow = workflows.find((w) => w.id === currentId);
+        if (workflow) {
+            setDefinition(workflow.definition);
+        }
+        loadRuns(currentId);
+    }, [currentId]);
+
+    useEffect(() => {
+        if (viewRunId === null) return;
+        invoke<WorkflowStepRun[]>("get_workflow_step_runs", { runId: viewRunId })
+            .then(setSteps)
+            .catch((e) => toast.error("获取步骤输出失败: " + e));
+    }, [viewRunId]);
+
+    // 运行中的工作流按事件更新步骤状态
+    useEffect(() => {
+        if (runningRunId === null) return;
+        const unlistenStep = listen<WorkflowEvent>(`workflow_run_${runningRunId}`, (event) => {
+            const payload = event.payload;
+            if (payload.type === "loop_progress") return;
+            setSteps((list) => {
+                const rest = list.filter((s) => s.step_id !== payload.step_id);
+                const previous = list.find((s) => s.step_id === payload.step_id);
+                return [
+                    ...rest,
+                    {
+                        id: previous?.id ?? list.length,
+                        run_id: runningRunId,
+                        step_id: payload.step_id,
+                        status: payload.type === "step_started" ? "running" : payload.status,
+                        output: payload.type === "step_finished" ? payload.output : null,
+                        error: payload.type === "step_finished" ? payload.error : null,
+                        started_time: previous?.started_time ?? "",
+                        finished_time: null,
+                    },
+                ];
+            });
+        });
+        const unlistenFinished = listen<WorkflowRun>(`workflow_run_finished_${runningRunId}`, (event) => {
+            setRunningRunId(null);
+            if (event.payload.status === "success") {
+                toast.success("工作流运行完成");
+            } else {
+                toast.error(`工作流运行结束：${event.payload.error ?? event.payload.status}`);
+            }
+            if (currentId !== null) {
+                loadRuns(currentId);
+            }
+        });
+        return () => {
+            unlistenStep.then((f) => f());
+            unlistenFinished.then((f) => f());
+        };
+    }, [runningRunId, currentId]);
+
+    const handleSave = () => {
+        invoke<Workflow>("save_workflow", { id: currentId, definition })
+            .then((workflow) => {
+                setCurrentId(workflow.id);
+                loadWorkflows();
+                toast.success("保存成功");
+            })
+            .catch((e) => toast.error("保存失败: " + e));
+    };
+
+    const handleImport = async () => {
+        const selected = await open({
+            multiple: false,
+            filters: [{ name: "Workflow", extensions: ["yaml", "yml", "json"] }],
+        });
+        if (!selected) return;
+        invoke<Workflow>("import_workflow_file", { filePath: selected as string })
+            .then((workflow) => {
+                setWorkflows((list) => [workflow, ...list]);
+                setCurrentId(workflow.id);
+                toast.success("导入成功");
+            })
+            .catch((e) => toast.error("导入失败: " + e));
+    };
+
+    const handleDelete = () => {
+        if (currentId === null) return;
+        invoke("delete_workflow", { id: currentId })
+            .then(() => {
+                setConfirmDeleteOpen(false);
+                setCurrentId(null);
+                loadWorkflows();
+            })
+            .catch((e) => toast.error("删除失败: " + e));
+    };
+
+    const handleRun = () => {
+        if (currentId === null) return;
+        let parsedInputs: Record<string, string>;
+        try {
+            parsedInputs = JSON.parse(inputs || "{}");
+        } catch (e) {
+            toast.error("输入参数需要是 JSON 对象");
+            return;
+        }
+        invoke<number>("run_workflow", { workflowId: currentId, inputs: parsedInputs })
+            .then((runId) => {
+                setSteps([]);
+                setRunningRunId(runId);
+                setViewRunId(runId);
+                loadRuns(currentId);
+            })
+            .catch((e) => toast.error("运行失败: " + e));
+    };
+
+    const handleCancel = () => {
+        if (runningRunId === null) return;
+        invoke("cancel_workflow_run", { runId: runningRunId }).catch((e) => toast.error("取消失败: " + e));
+    };
+
+    return (
+        <div className="p-4 flex flex-col gap-4">
+            <Card>
+                <CardHeader>
+                    <CardTitle>工作流</CardTitle>
+                    <CardDescription>
+                        步骤类型：llm、bang、script、condition、loop，步骤输出通过 !步骤id 在后续步骤中引用，步骤 id 只能包含字母、数字和下划线且不能与 bang 重名
+                    </CardDescription>
+                </CardHeader>
+                <CardContent className="flex flex-col gap-2">
+                    <div className="flex gap-2 items-center">
+                        <CustomSelect
+                            options={[
+                                { value: "", label: "新建工作流" },
+                                ...workflows.map((w) => ({ value: w.id.toString(), label: w.name })),
+                            ]}
+                            value={currentId?.toString() ?? ""}
+                            onChange={(value) => setCurrentId(value ? parseInt(value) : null)}
+                        />
+                        <Button variant="outline" onClick={handleImport}>
+                            导入文件
+                        </Button>
+                        {currentId !== null && (
+                            <Button variant="outline" onClick={() => setConfirmDeleteOpen(true)}>
+                                删除
+                            </Button>
+                        )}
+                    </div>
+                    <Textarea
+                        className="font-mono min-h-[300px]"
+                        value={definition}
+                        onChange={(e) => setDefinition(e.target.value)}
+                    />
+                    <div className="flex gap-2">
+                        <Button onClick={handleSave}>保存</Button>
+                    </div>
+                </CardContent>
+            </Card>
+
+            {currentId !== null && (
+                <Card>
+                    <CardHeader>
+                        <CardTitle>运行</CardTitle>
+                        <CardDescription>输入参数为 JSON 对象，未填写的参数使用定义中的默认值</CardDescription>
+                    </CardHeader>
+                    <CardContent className="flex flex-col gap-2">
+                        <Textarea className="font-mono" value={inputs} onChange={(e) => setInputs(e.target.value)} />
+                        <div className="flex gap-2">
+                            {runningRunId === null ? (
+                                <Button onClick={handleRun}>运行</Button>
+                            ) : (
+                                <Button variant="outline" onClick={handleCancel}>
+                                    取消运行
+                                </Button>
+                            )}
+                        </div>
+                        {runs.map((run) => (
+                            <div key={run.id} className="flex gap-2 items-center">
+                                <span className="flex-1 text-sm">
+                                    {run.created_time} <Badge variant="outline">{run.status}</Badge>
+                                </span>
+                                <Button variant="outline" onClick={() => setViewRunId(run.id)}>
+                                    查看步骤
+                                </Button>
+                            </div>
+                        ))}
+                    </CardContent>
+                </Card>
+            )}
+
+            {viewRunId !== null && steps.length > 0 && (
+                <Card>
+                    <CardHeader>
+                        <CardTitle>步骤输出</CardTitle>
+                    </CardHeader>
+                    <CardContent className="flex flex-col gap-3">
+                        {steps.map((step) => (
+                            <div key={step.step_id} className="flex flex-col gap-1">
+                                <div className="flex gap-2 items-center">
+                                    <span className="flex-1 font-medium">{step.step_id}</span>
+                                    <Badge variant={step.status === "failed" ? "destructive" : "outline"}>
+                                        {step.status}
+                                    </Badge>
+                                </div>
+                                {(step.error || step.output) && (
+                                    <pre className="whitespace-pre-wrap text-sm">{step.error ?? step.output}</pre>
+                                )}
+                            </div>
+                        ))}
+                    </CardContent>
+                </Card>
+            )}
+
+            <ConfirmDialog
+                title="确认操作"
+                confirmText="删除工作流会同时删除运行记录，确认删除吗？"
+                onConfirm={handleDelete}
+                onCancel={() => setConfirmDeleteOpen(false)}
+                isOpen={confirmDeleteOpen}
+            />
+        </div>
+    );
+};
+
+export default WorkflowConfig;
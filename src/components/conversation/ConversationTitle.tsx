@@ -2,19 +2,25 @@ import React from "react";
 import IconButton from "../IconButton";
 import Edit from "../../assets/edit.svg?react";
 import Delete from "../../assets/delete.svg?react";
+import Program from "../../assets/program.svg?react";
+import Download from "../../assets/download.svg?react";
 import { Conversation } from "../../data/Conversation";
 
 const ConversationTitle: React.FC<{
     conversation: Conversation | undefined;
     onEdit: () => void;
     onDelete: () => void;
-}> = React.memo(({ conversation, onEdit, onDelete }) => (
+    onToggleArtifact?: () => void;
+    onExport?: () => void;
+}> = React.memo(({ conversation, onEdit, onDelete, onToggleArtifact, onExport }) => (
     <div className="conversation-title-panel">
         <div className="conversation-title-panel-text-group">
             <div className="conversation-title-panel-title">{conversation?.name}</div>
             <div className="conversation-title-panel-assistant-name">{conversation?.assistant_name}</div>
         </div>
         <div className="conversation-title-panel-button-group">
+            {onToggleArtifact && <IconButton icon={<Program fill="black" />} onClick={onToggleArtifact} border />}
+            {onExport && <IconButton icon={<Download fill="black" />} onClick={onExport} border />}
             <IconButton icon={<Edit fill="black" />} onClick={onEdit} border />
             <IconButton icon={<Delete fill="black" />} onClick={onDelete} border />
         </div>
@@ -0,0 +1,98 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import IconButton from "../IconButton";
+import { Input } from "../ui/input";
+import { Button } from "../ui/button";
+import TagInput from "../TagInput";
+import ThumbUp from "../../assets/thumb-up.svg?react";
+import ThumbDown from "../../assets/thumb-down.svg?react";
+
+interface MessageFeedbackData {
+    rating: number;
+    tags: string[];
+    comment: string | null;
+}
+
+interface MessageFeedbackProps {
+    messageId: number;
+}
+
+// 回答的好评、差评，差评时可以填写意见，用于之后优化助手的提示词
+const MessageFeedback: React.FC<MessageFeedbackProps> = ({ messageId }) => {
+    const [rating, setRating] = useState<number>(0);
+    const [tags, setTags] = useState<string[]>([]);
+    const [comment, setComment] = useState<string>("");
+    const [commentVisible, setCommentVisible] = useState<boolean>(false);
+
+    useEffect(() => {
+        setRating(0);
+        setTags([]);
+        setComment("");
+        setCommentVisible(false);
+        invoke<MessageFeedbackData | null>("get_message_feedback", { messageId })
+            .then((feedback) => {
+                setRating(feedback?.rating ?? 0);
+                setTags(feedback?.tags ?? []);
+                setComment(feedback?.comment ?? "");
+            })
+            .catch(() => {});
+    }, [messageId]);
+
+    const saveFeedback = (newRating: number, newTags: string[], newComment: string) => {
+        invoke("set_message_feedback", { messageId, rating: newRating, tags: newTags, comment: newComment })
+            .then(() => setRating(newRating))
+            .catch((error) => toast.error("保存反馈失败: " + error));
+    };
+
+    const handleRate = (value: number) => {
+        // 再次点击同一个评价时取消
+        const newRating = rating === value ? 0 : value;
+        saveFeedback(newRating, newRating === -1 ? tags : [], newRating === -1 ? comment : "");
+        setCommentVisible(newRating === -1);
+    };
+
+    return (
+        <div className="message-feedback">
+            <IconButton
+                className={rating === 1 ? "message-feedback-active" : ""}
+                icon={<ThumbUp fill={rating === 1 ? "hsl(var(--primary))" : "gray"} />}
+                onClick={() => handleRate(1)}
+            />
+            <IconButton
+                className={rating === -1 ? "message-feedback-active" : ""}
+                icon={<ThumbDown fill={rating === -1 ? "hsl(var(--primary))" : "gray"} />}
+                onClick={() => handleRate(-1)}
+            />
+            {commentVisible && (
+                <div className="message-feedback-comment flex flex-col gap-2">
+                    <TagInput
+                        tags={tags}
+                        placeholder="问题标签，回车添加，例如：不准确、太啰嗦"
+                        onAddTag={(tag) => setTags((prev) => (prev.includes(tag) ? prev : [...prev, tag]))}
+                        onRemoveTag={(index) => setTags((prev) => prev.filter((_, i) => i !== index))}
+                    />
+                    <div className="flex gap-2 items-center">
+                        <Input
+                            value={comment}
+                            placeholder="哪里回答得不好（可选）"
+                            onChange={(e) => setComment(e.target.value)}
+                        />
+                        <Button
+                            type="button"
+                            variant="outline"
+                            onClick={() => {
+                                saveFeedback(-1, tags, comment);
+                                setCommentVisible(false);
+                            }}
+                        >
+                            提交
+                        </Button>
+                    </div>
+                </div>
+            )}
+        </div>
+    );
+};
+
+export default MessageFeedback;
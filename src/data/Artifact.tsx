@@ -0,0 +1,86 @@
+export interface ArtifactOutput {
+    index: number;
+    stream: "stdout" | "stderr";
+    line: string;
+}
+
+export interface ArtifactRunResult {
+    exit_code: number | null;
+    stdout: string;
+    stderr: string;
+    timed_out: boolean;
+    truncated: boolean;
+    killed: boolean;
+    duration_ms: number;
+}
+
+export interface ArtifactRunSnapshot {
+    output: ArtifactOutput[];
+    result: ArtifactRunResult | null;
+}
+
+export interface ArtifactRunRecord extends ArtifactRunResult {
+    id: number;
+    message_id: number | null;
+    language: string;
+    code: string;
+    code_hash: string;
+    created_time: string;
+    finished_time: string | null;
+}
+
+export interface ArtifactDiffLine {
+    tag: "equal" | "insert" | "delete";
+    text: string;
+}
+
+export interface ArtifactRunComparison {
+    stdout: ArtifactDiffLine[];
+    stderr: ArtifactDiffLine[];
+    same_code: boolean;
+}
+
+// run_artifacts 的返回值，脚本返回执行 id，组件和图表返回预览的 hash
+export type ArtifactStarted =
+    | { type: "process"; run_id: number }
+    | { type: "preview"; hash: string; kind: string; exportable: boolean };
+
+export interface ArtifactFile {
+    path: string;
+    content: string;
+}
+
+export interface ArtifactVersion {
+    id: number;
+    artifact_id: number;
+    version: number;
+    message_id: number | null;
+    files: ArtifactFile[];
+    created_time: string;
+}
+
+export interface ArtifactVersionSummary {
+    version: number;
+    message_id: number | null;
+    paths: string[];
+    created_time: string;
+}
+
+// 对话中的多文件产物，latest 为最新版本
+export interface ArtifactDetail {
+    artifact: {
+        id: number;
+        conversation_id: number;
+        name: string;
+        created_time: string;
+        updated_time: string;
+    };
+    versions: ArtifactVersionSummary[];
+    latest: ArtifactVersion | null;
+}
+
+export interface ArtifactFileDiff {
+    path: string;
+    change: "added" | "removed" | "modified" | "unchanged";
+    lines: ArtifactDiffLine[];
+}
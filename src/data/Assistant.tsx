@@ -51,4 +51,38 @@ export interface AssistantDetail {
     model: AssistantModel[];
     model_configs: AssistantModelConfig[];
     prompt_params: AssistantPromptParam[];
-}
\ No newline at end of file
+}
+
+export interface AssistantPromptVersion {
+    id: number;
+    assistant_id: number;
+    version: number;
+    prompt: string;
+    model_configs: AssistantModelConfig[];
+    created_time: string;
+}
+
+export interface PromptVersionDiff {
+    from_version: number;
+    to_version: number;
+    prompt: { tag: "equal" | "insert" | "delete"; text: string }[];
+    model_configs: { name: string; old_value: string | null; new_value: string | null }[];
+}
+
+export interface PromptOptimization {
+    assistant_id: number;
+    current_prompt: string;
+    proposed_prompt: string;
+    diff: { tag: "equal" | "insert" | "delete"; text: string }[];
+    example_count: number;
+}
+
+export interface FeedbackStats {
+    assistant_id: number | null;
+    llm_model_name: string | null;
+    total: number;
+    positive: number;
+    negative: number;
+    positive_rate: number;
+    tag_counts: Record<string, number>;
+}
@@ -0,0 +1,18 @@
+export type CustomBangKind = "text" | "command" | "http" | "file";
+
+export interface CustomBang {
+    id: number;
+    name: string;
+    aliases: string[];
+    description: string;
+    bang_kind: CustomBangKind;
+    body: string;
+    json_path: string | null;
+}
+
+export interface FeatureConfigListItem {
+    id: number;
+    feature_code: string;
+    key: string;
+    value: string;
+}
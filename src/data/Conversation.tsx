@@ -14,7 +14,10 @@ export interface Message {
     llm_model_id: number | null;
     created_time: Date;
     token_count: number;
+    llm_model_name?: string | null;
     regenerate: Array<Message> | null;
+    // 多模型对比助手其他模型的回答
+    compare?: Array<Message> | null;
 }
 
 export interface AddAttachmentResponse {
@@ -36,4 +39,5 @@ export enum AttachmentType { // 添加AttachmentType枚举
     Word = 4,
     PowerPoint = 5,
     Excel = 6,
+    Audio = 7,
 }
\ No newline at end of file
@@ -0,0 +1,63 @@
+export interface EvalQuestionSet {
+    id: number;
+    name: string;
+    description: string | null;
+    question_count: number;
+    created_time: string;
+}
+
+export type EvalScorer = "exact" | "regex" | "llm_judge";
+
+export interface EvalQuestion {
+    id: number;
+    set_id: number;
+    prompt: string;
+    attachment_ids: number[];
+    scorer: EvalScorer;
+    expected: string;
+    created_time: string;
+}
+
+export interface EvalModel {
+    provider_id: number;
+    model_code: string;
+}
+
+export interface EvalRun {
+    id: number;
+    set_id: number;
+    models: EvalModel[];
+    judge_model: EvalModel | null;
+    status: "running" | "finished" | "cancelled" | "failed";
+    total: number;
+    finished: number;
+    created_time: string;
+    finished_time: string | null;
+}
+
+export interface EvalResult {
+    id: number;
+    run_id: number;
+    question_id: number;
+    provider_id: number;
+    model_code: string;
+    answer: string;
+    score: number;
+    passed: boolean;
+    reason: string | null;
+    error: string | null;
+    duration_ms: number;
+    created_time: string;
+}
+
+export interface EvalModelSummary {
+    run_id: number;
+    run_time: string;
+    provider_id: number;
+    model_code: string;
+    count: number;
+    passed: number;
+    errors: number;
+    avg_score: number;
+    avg_duration_ms: number;
+}
@@ -0,0 +1,26 @@
+export type MissedPolicy = "run_once" | "skip";
+
+export interface ScheduledTask {
+    id: number;
+    name: string;
+    cron: string;
+    assistant_id: number;
+    prompt: string;
+    missed_policy: MissedPolicy;
+    enabled: boolean;
+    last_run_time: string | null;
+    next_run_time: string | null;
+    created_time: string;
+}
+
+export interface ScheduledTaskRun {
+    id: number;
+    task_id: number;
+    scheduled_time: string;
+    status: "running" | "success" | "failed" | "skipped";
+    missed: boolean;
+    conversation_id: number | null;
+    error: string | null;
+    created_time: string;
+    finished_time: string | null;
+}
@@ -0,0 +1,42 @@
+export interface Workflow {
+    id: number;
+    name: string;
+    definition: string;
+    created_time: string;
+    updated_time: string;
+}
+
+export interface WorkflowRun {
+    id: number;
+    workflow_id: number;
+    status: "running" | "success" | "failed" | "cancelled";
+    inputs: string;
+    output: string | null;
+    error: string | null;
+    created_time: string;
+    finished_time: string | null;
+}
+
+export type WorkflowStepStatus = "running" | "success" | "failed" | "skipped";
+
+export interface WorkflowStepRun {
+    id: number;
+    run_id: number;
+    step_id: string;
+    status: WorkflowStepStatus;
+    output: string | null;
+    error: string | null;
+    started_time: string;
+    finished_time: string | null;
+}
+
+export type WorkflowEvent =
+    | { type: "step_started"; step_id: string }
+    | { type: "loop_progress"; step_id: string; index: number; total: number }
+    | {
+          type: "step_finished";
+          step_id: string;
+          status: WorkflowStepStatus;
+          output: string;
+          error: string | null;
+      };
@@ -2,3 +2,18 @@ export interface AssistantType {
     code: number;
     name: string;
 }
+
+// 后端内置的助手类型，插件可以注册更多的类型
+export const BUILTIN_ASSISTANT_TYPES: AssistantType[] = [
+    { code: 0, name: "普通对话助手" },
+    { code: 1, name: "多模型对比助手" },
+    { code: 2, name: "工作流助手" },
+    { code: 4, name: "选中文本处理助手" },
+];
+
+export interface AssistantConfigField {
+    name: string;
+    label: string;
+    value_type: string;
+    default_value: string;
+}
@@ -38,6 +38,7 @@ interface AiResponse {
     conversation_id: number;
     add_message_id: number;
     request_prompt_result_with_context: string;
+    compare_message_ids?: number[];
 }
 
 declare class AskAiResponse {